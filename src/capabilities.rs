@@ -0,0 +1,193 @@
+use std::ffi::CStr;
+use std::fmt;
+use std::io;
+use std::mem;
+
+use crate::{IoUring, Probe, SetupFeatures};
+
+/// The version of the running Linux kernel, as reported by `uname`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KernelVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl KernelVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> KernelVersion {
+        KernelVersion { major, minor, patch }
+    }
+
+    /// The version of the running kernel.
+    pub fn current() -> io::Result<KernelVersion> {
+        let release = unsafe {
+            let mut uts: libc::utsname = mem::zeroed();
+            if libc::uname(&mut uts) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            CStr::from_ptr(uts.release.as_ptr()).to_string_lossy().into_owned()
+        };
+        KernelVersion::parse(&release).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("unknown kernel release {:?}", release))
+        })
+    }
+
+    /// Parse a kernel release such as `"6.1.0-18-amd64"`, ignoring everything after the numeric
+    /// version. A missing patch level is read as zero.
+    pub fn parse(release: &str) -> Option<KernelVersion> {
+        let end = release.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(release.len());
+        let mut parts = release[..end].split('.').map(|part| part.parse::<u32>());
+        let major = parts.next()?.ok()?;
+        let minor = parts.next()?.ok()?;
+        let patch = match parts.next() {
+            Some(patch) => patch.ok()?,
+            None        => 0,
+        };
+        Some(KernelVersion::new(major, minor, patch))
+    }
+}
+
+impl fmt::Display for KernelVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// What the running kernel's io-uring interface can do, combining its version, the operations it
+/// reports through a [`Probe`] and the setup features it grants.
+///
+/// Some capabilities, like multishot accept, are flags of operations which older kernels
+/// already support, so they can't be probed; these are decided by the kernel version.
+///
+/// ```
+/// # use std::io;
+/// # fn main() -> io::Result<()> {
+/// let caps = iou::Capabilities::detect()?;
+/// if caps.supports_multishot_accept() {
+///     // one accept event can accept every connection
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Capabilities {
+    version: KernelVersion,
+    probe: Probe,
+    features: SetupFeatures,
+}
+
+impl Capabilities {
+    /// Detect the capabilities of the running kernel, setting up a short-lived ring to learn
+    /// which features it grants.
+    pub fn detect() -> io::Result<Capabilities> {
+        Capabilities::for_ring(&IoUring::new(1)?)
+    }
+
+    /// Detect the capabilities of the running kernel, with the features granted to `ring`.
+    pub fn for_ring(ring: &IoUring) -> io::Result<Capabilities> {
+        Ok(Capabilities::new(KernelVersion::current()?, ring.registrar().probe()?, ring.features()))
+    }
+
+    /// Build the capabilities from their parts.
+    pub fn new(version: KernelVersion, probe: Probe, features: SetupFeatures) -> Capabilities {
+        Capabilities { version, probe, features }
+    }
+
+    pub fn kernel_version(&self) -> KernelVersion {
+        self.version
+    }
+
+    pub fn probe(&self) -> &Probe {
+        &self.probe
+    }
+
+    pub fn features(&self) -> SetupFeatures {
+        self.features
+    }
+
+    /// Whether the kernel is at least version `major.minor`.
+    pub fn kernel_at_least(&self, major: u32, minor: u32) -> bool {
+        self.version >= KernelVersion::new(major, minor, 0)
+    }
+
+    /// Whether an accept event can post a CQE for every incoming connection.
+    pub fn supports_multishot_accept(&self) -> bool {
+        self.probe.supports_name("ACCEPT") && self.kernel_at_least(5, 19)
+    }
+
+    /// Whether a receive event can post a CQE for every received message.
+    pub fn supports_multishot_recv(&self) -> bool {
+        self.probe.supports_name("RECV") && self.kernel_at_least(6, 0)
+    }
+
+    /// Whether sends can avoid copying their buffers.
+    pub fn supports_send_zc(&self) -> bool {
+        self.probe.supports_name("SEND_ZC")
+    }
+
+    /// Whether events can be sent to other rings.
+    pub fn supports_msg_ring(&self) -> bool {
+        self.probe.supports_name("MSG_RING")
+    }
+
+    /// Whether sockets can be created, including as direct descriptors.
+    pub fn supports_socket(&self) -> bool {
+        self.probe.supports_name("SOCKET")
+    }
+
+    /// Whether buffer rings can be registered to provide buffers.
+    pub fn supports_buf_ring(&self) -> bool {
+        self.kernel_at_least(5, 19)
+    }
+
+    /// Whether the ring fd can be registered.
+    pub fn supports_registered_ring_fd(&self) -> bool {
+        self.kernel_at_least(5, 18)
+    }
+
+    /// Whether rings can use 128 byte SQEs and 32 byte CQEs.
+    pub fn supports_big_entries(&self) -> bool {
+        self.kernel_at_least(5, 19)
+    }
+
+    /// Whether rings can live in application allocated memory.
+    pub fn supports_no_mmap(&self) -> bool {
+        self.kernel_at_least(6, 5)
+    }
+
+    /// Whether events can skip their CQE on success.
+    pub fn supports_cqe_skip(&self) -> bool {
+        self.features.contains(SetupFeatures::CQE_SKIP)
+    }
+
+    /// Whether waits can take a timeout without submitting a timeout event.
+    pub fn supports_ext_arg(&self) -> bool {
+        self.features.contains(SetupFeatures::EXT_ARG)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for KernelVersion {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut version = serializer.serialize_struct("KernelVersion", 3)?;
+        version.serialize_field("major", &self.major)?;
+        version.serialize_field("minor", &self.minor)?;
+        version.serialize_field("patch", &self.patch)?;
+        version.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Capabilities {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut caps = serializer.serialize_struct("Capabilities", 3)?;
+        caps.serialize_field("kernel_version", &self.version)?;
+        caps.serialize_field("features", &self.features)?;
+        caps.serialize_field("probe", &self.probe)?;
+        caps.end()
+    }
+}
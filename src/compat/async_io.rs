@@ -0,0 +1,113 @@
+use std::fmt;
+use std::io;
+
+use ::async_io::Async;
+
+use crate::{CQE, IoUring};
+use crate::registrar::EventFd;
+
+/// An [`IoUring`] whose completions can be awaited with the reactor of the `async-io` crate,
+/// which drives smol and async-std, available with the `async-io` feature.
+///
+/// The adapter registers an [`EventFd`] with the ring and watches it with the reactor, so
+/// awaiting a completion does not block a thread. Events are prepared and submitted through the
+/// ring as usual, with [`get_mut`](SmolIoUring::get_mut).
+///
+/// ```
+/// # use std::io;
+/// # use iou::{IoUring, SmolIoUring};
+/// # fn main() -> io::Result<()> {
+/// # async_io::block_on(async {
+/// let mut ring = SmolIoUring::new(IoUring::new(8)?)?;
+/// for user_data in 0..2 {
+///     unsafe {
+///         let mut sqe = ring.get_mut().prepare_sqe().unwrap();
+///         sqe.prep_nop();
+///         sqe.set_user_data(user_data);
+///     }
+/// }
+/// ring.get_mut().submit_sqes()?;
+///
+/// let mut reaped = vec![];
+/// while reaped.len() < 2 {
+///     ring.drain(|cqe| reaped.push(cqe.user_data())).await?;
+/// }
+/// assert_eq!(reaped, [0, 1]);
+/// # Ok(())
+/// # })
+/// # }
+/// ```
+pub struct SmolIoUring {
+    ring: IoUring,
+    eventfd: Async<EventFd>,
+}
+
+impl SmolIoUring {
+    /// Register a new eventfd with `ring` and watch it with the reactor, replacing any eventfd
+    /// already registered with the ring.
+    pub fn new(ring: IoUring) -> io::Result<SmolIoUring> {
+        let eventfd = EventFd::new()?;
+        let registrar = ring.registrar();
+        if registrar.register_owned_eventfd(&eventfd).is_err() {
+            // only one eventfd can be registered at a time
+            registrar.unregister_eventfd()?;
+            registrar.register_owned_eventfd(&eventfd)?;
+        }
+        Ok(SmolIoUring { eventfd: Async::new(eventfd)?, ring })
+    }
+
+    pub fn get_ref(&self) -> &IoUring {
+        &self.ring
+    }
+
+    pub fn get_mut(&mut self) -> &mut IoUring {
+        &mut self.ring
+    }
+
+    /// Wait for the next [`CQE`] without blocking the thread.
+    pub async fn next_cqe(&mut self) -> io::Result<CQE> {
+        loop {
+            if let Some(cqe) = self.ring.peek_for_cqe() {
+                return Ok(cqe);
+            }
+            self.ready().await?;
+        }
+    }
+
+    /// Wait until there are completions, then reap every ready [`CQE`], passing each to `f`.
+    /// Returns the number of CQEs reaped, which is never zero.
+    pub async fn drain(&mut self, mut f: impl FnMut(CQE)) -> io::Result<usize> {
+        loop {
+            let mut count = 0;
+            while let Some(cqe) = self.ring.peek_for_cqe() {
+                f(cqe);
+                count += 1;
+            }
+            if count > 0 {
+                return Ok(count);
+            }
+            self.ready().await?;
+        }
+    }
+
+    /// Unregister the eventfd and return the ring.
+    pub fn into_inner(self) -> io::Result<IoUring> {
+        self.ring.registrar().unregister_eventfd()?;
+        Ok(self.ring)
+    }
+
+    async fn ready(&self) -> io::Result<()> {
+        self.eventfd.readable().await?;
+        self.eventfd.get_ref().read_events()?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for SmolIoUring {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SmolIoUring")
+            .field("ring", &self.ring)
+            .field("eventfd", self.eventfd.get_ref())
+            .finish()
+    }
+}
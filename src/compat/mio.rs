@@ -0,0 +1,32 @@
+use std::io;
+
+use ::mio::event::Source;
+use ::mio::unix::SourceFd;
+use ::mio::{Interest, Registry, Token};
+
+use crate::IoUring;
+
+/// With the `mio` feature enabled, an `IoUring` can be registered with a `mio::Poll`, which
+/// reports it readable when there are completions to reap.
+///
+/// The ring is watched through its [`notify_fd`](IoUring::notify_fd): the owned eventfd if one is
+/// registered, and the file descriptor of the ring otherwise. An eventfd must not be registered or
+/// unregistered while the ring is registered with mio, or the ring is deregistered through a
+/// different file descriptor than the one mio watches.
+///
+/// mio's events are edge-triggered: after an event, reap every ready CQE (and drain the eventfd,
+/// if there is one, with [`read_events`](crate::registrar::EventFd::read_events)), or no further
+/// event may be reported for them.
+impl Source for IoUring {
+    fn register(&mut self, registry: &Registry, token: Token, interests: Interest) -> io::Result<()> {
+        SourceFd(&self.notify_fd()?).register(registry, token, interests)
+    }
+
+    fn reregister(&mut self, registry: &Registry, token: Token, interests: Interest) -> io::Result<()> {
+        SourceFd(&self.notify_fd()?).reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        SourceFd(&self.notify_fd()?).deregister(registry)
+    }
+}
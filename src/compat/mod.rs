@@ -0,0 +1,19 @@
+// Integrations of `IoUring` with event loops and async runtimes, each behind a feature.
+
+#[cfg(feature = "async-io")]
+mod async_io;
+
+#[cfg(feature = "mio")]
+mod mio;
+
+#[cfg(feature = "polling")]
+mod polling;
+
+#[cfg(feature = "tokio")]
+mod tokio;
+
+#[cfg(feature = "async-io")]
+pub use self::async_io::SmolIoUring;
+
+#[cfg(feature = "tokio")]
+pub use self::tokio::AsyncIoUring;
@@ -0,0 +1,80 @@
+use std::io;
+use std::os::unix::io::BorrowedFd;
+
+use ::polling::{Event, Poller};
+
+use crate::{CQE, IoUring};
+
+/// With the `polling` feature enabled, an `IoUring` can be watched by a `polling::Poller`, which
+/// reports it readable when there are completions to reap.
+///
+/// The ring is watched through its [`notify_fd`](IoUring::notify_fd): the registered owned
+/// eventfd if there is one, or the file descriptor of the ring. Like every source of a `Poller`,
+/// the ring is watched in oneshot mode, and must be re-armed after each event;
+/// [`drain_ready`](IoUring::drain_ready) reaps the ready CQEs and re-arms it.
+///
+/// ```
+/// # use std::io;
+/// # use polling::{Events, Poller};
+/// # fn main() -> io::Result<()> {
+/// let mut ring = iou::IoUring::new(2)?;
+/// let poller = Poller::new()?;
+/// unsafe { ring.add_to_poller(&poller, 7)?; }
+///
+/// unsafe { ring.prepare_sqe().unwrap().prep_nop(); }
+/// ring.submit_sqes()?;
+///
+/// let mut events = Events::new();
+/// poller.wait(&mut events, None)?;
+/// assert_eq!(events.iter().next().unwrap().key, 7);
+/// assert_eq!(ring.drain_ready(&poller, 7, |_| ())?, 1);
+///
+/// ring.delete_from_poller(&poller)?;
+/// # Ok(())
+/// # }
+/// ```
+impl IoUring {
+    /// Add the ring to `poller`, to be reported readable with `key`.
+    ///
+    /// # Safety
+    ///
+    /// The ring must be [deleted](IoUring::delete_from_poller) from the poller before it is
+    /// dropped, or before the eventfd it is watched through is unregistered.
+    pub unsafe fn add_to_poller(&self, poller: &Poller, key: usize) -> io::Result<()> {
+        poller.add(self.notify_fd()?, Event::readable(key))
+    }
+
+    /// Re-arm the ring in `poller` after it was reported readable.
+    pub fn rearm_in_poller(&self, poller: &Poller, key: usize) -> io::Result<()> {
+        poller.modify(self.notify_source()?, Event::readable(key))
+    }
+
+    /// Stop watching the ring with `poller`.
+    pub fn delete_from_poller(&self, poller: &Poller) -> io::Result<()> {
+        poller.delete(self.notify_source()?)
+    }
+
+    /// Reap every ready CQE, passing each to `f`, then re-arm the ring in `poller`. Call this
+    /// when the poller reports the ring readable. Returns the number of CQEs reaped.
+    ///
+    /// The registered eventfd, if any, is drained before the CQEs are reaped, so the ring is
+    /// reported again for every completion which arrives afterwards.
+    pub fn drain_ready(&mut self, poller: &Poller, key: usize, mut f: impl FnMut(CQE)) -> io::Result<usize> {
+        let eventfd = self.registered_eventfd.lock().unwrap().clone();
+        if let Some(eventfd) = eventfd {
+            eventfd.read_events()?;
+        }
+        let mut count = 0;
+        while let Some(cqe) = self.peek_for_cqe() {
+            f(cqe);
+            count += 1;
+        }
+        self.rearm_in_poller(poller, key)?;
+        Ok(count)
+    }
+
+    fn notify_source(&self) -> io::Result<BorrowedFd<'_>> {
+        // the eventfd stays open while it is registered, and the ring while it lives
+        self.notify_fd().map(|fd| unsafe { BorrowedFd::borrow_raw(fd) })
+    }
+}
@@ -0,0 +1,98 @@
+use std::fmt;
+use std::io;
+
+use ::tokio::io::unix::AsyncFd;
+
+use crate::{CQE, IoUring};
+use crate::registrar::EventFd;
+
+/// An [`IoUring`] whose completions can be awaited on a tokio runtime, available with the
+/// `tokio` feature.
+///
+/// The adapter registers an [`EventFd`] with the ring and watches it with tokio's reactor, so
+/// awaiting a completion does not block a thread. Events are prepared and submitted through the
+/// ring as usual, with [`get_mut`](AsyncIoUring::get_mut).
+///
+/// ```
+/// # use std::io;
+/// # use iou::{AsyncIoUring, IoUring};
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> io::Result<()> {
+/// let mut ring = AsyncIoUring::new(IoUring::new(8)?)?;
+/// unsafe {
+///     let mut sqe = ring.get_mut().prepare_sqe().unwrap();
+///     sqe.prep_nop();
+///     sqe.set_user_data(1);
+/// }
+/// ring.get_mut().submit_sqes()?;
+/// assert_eq!(ring.next_cqe().await?.user_data(), 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct AsyncIoUring {
+    ring: IoUring,
+    eventfd: AsyncFd<EventFd>,
+}
+
+impl AsyncIoUring {
+    /// Register a new eventfd with `ring` and watch it with the reactor of the current tokio
+    /// runtime, replacing any eventfd already registered with the ring.
+    ///
+    /// # Panics
+    ///
+    /// Panics when not called from within a tokio runtime with IO enabled.
+    pub fn new(ring: IoUring) -> io::Result<AsyncIoUring> {
+        let eventfd = EventFd::new()?;
+        let registrar = ring.registrar();
+        if registrar.register_owned_eventfd(&eventfd).is_err() {
+            // only one eventfd can be registered at a time
+            registrar.unregister_eventfd()?;
+            registrar.register_owned_eventfd(&eventfd)?;
+        }
+        Ok(AsyncIoUring { eventfd: AsyncFd::new(eventfd)?, ring })
+    }
+
+    pub fn get_ref(&self) -> &IoUring {
+        &self.ring
+    }
+
+    pub fn get_mut(&mut self) -> &mut IoUring {
+        &mut self.ring
+    }
+
+    /// Wait for the next [`CQE`] without blocking the thread.
+    pub async fn next_cqe(&mut self) -> io::Result<CQE> {
+        loop {
+            if let Some(cqe) = self.ring.peek_for_cqe() {
+                return Ok(cqe);
+            }
+            let mut guard = self.eventfd.readable().await?;
+            guard.get_inner().read_events()?;
+            guard.clear_ready();
+        }
+    }
+
+    /// Wait for the next `count` [`CQE`]s without blocking the thread.
+    pub async fn wait_for(&mut self, count: usize) -> io::Result<Vec<CQE>> {
+        let mut cqes = Vec::with_capacity(count);
+        while cqes.len() < count {
+            cqes.push(self.next_cqe().await?);
+        }
+        Ok(cqes)
+    }
+
+    /// Unregister the eventfd and return the ring.
+    pub fn into_inner(self) -> io::Result<IoUring> {
+        self.ring.registrar().unregister_eventfd()?;
+        Ok(self.ring)
+    }
+}
+
+impl fmt::Debug for AsyncIoUring {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AsyncIoUring")
+            .field("ring", &self.ring)
+            .field("eventfd", self.eventfd.get_ref())
+            .finish()
+    }
+}
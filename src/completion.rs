@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use crate::{CQE, IoUring, SQE, sys};
+
+// the user data of the events submitted with a handle all have the top bit set
+const KEY_TAG: u64 = 1 << 63;
+
+/// The events awaited through [`CompletionFuture`]s, available with the `completion` feature.
+///
+/// This is not a runtime: nothing reaps the CQEs of the ring by itself. Whatever reaps them,
+/// such as a thread blocking in [`wait_for_cqe`](IoUring::wait_for_cqe) or an event loop
+/// watching the ring, passes each CQE to [`dispatch`](Completions::dispatch), which wakes the
+/// task awaiting it.
+///
+/// The events are told apart by their user data, which always has its top bit set. Other
+/// events submitted to the same ring must use user data without that bit.
+///
+/// ```
+/// # use std::{io, thread};
+/// # use iou::{Completions, IoUring};
+/// # fn main() -> io::Result<()> {
+/// let mut ring = IoUring::new(2)?;
+/// let completions = Completions::new();
+/// let nop = unsafe { ring.submit_with_handle(&completions, |sqe| sqe.prep_nop())? };
+///
+/// let driver = completions.clone();
+/// thread::spawn(move || -> io::Result<()> {
+///     let cqe = ring.wait_for_cqe()?;
+///     assert!(driver.dispatch(cqe).is_none());
+///     Ok(())
+/// });
+///
+/// assert_eq!(async_io::block_on(nop)?, 0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct Completions {
+    inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Default)]
+struct Inner {
+    next_key: u64,
+    slots: HashMap<u64, Slot>,
+}
+
+enum Slot {
+    Waiting(Option<Waker>),
+    Done(io::Result<u32>),
+    Abandoned,
+}
+
+impl Completions {
+    pub fn new() -> Completions {
+        Completions::default()
+    }
+
+    /// Complete the handle the event of `cqe` was submitted with, waking the task awaiting it.
+    /// Returns the `CQE` back if it does not belong to a handle of this set.
+    ///
+    /// A handle completes with the first CQE of its event, so it should not be used to await
+    /// multishot events.
+    pub fn dispatch(&self, cqe: CQE) -> Option<CQE> {
+        let mut inner = self.inner.lock().unwrap();
+        let slot = match inner.slots.get_mut(&cqe.user_data()) {
+            Some(slot)  => slot,
+            None        => return Some(cqe),
+        };
+        let waker = match slot {
+            Slot::Waiting(waker)    => waker.take(),
+            Slot::Done(_)           => return None,
+            Slot::Abandoned         => {
+                inner.slots.remove(&cqe.user_data());
+                return None;
+            }
+        };
+        *slot = Slot::Done(cqe.result());
+        drop(inner);
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        None
+    }
+
+    /// The number of handles whose events have not completed yet, including those of dropped
+    /// handles.
+    pub fn pending(&self) -> usize {
+        let inner = self.inner.lock().unwrap();
+        inner.slots.values().filter(|slot| !matches!(slot, Slot::Done(_))).count()
+    }
+
+    fn insert(&self) -> u64 {
+        let mut inner = self.inner.lock().unwrap();
+        loop {
+            let key = KEY_TAG | inner.next_key;
+            inner.next_key = (inner.next_key + 1) & !KEY_TAG;
+            if key != sys::LIBURING_UDATA_TIMEOUT && !inner.slots.contains_key(&key) {
+                inner.slots.insert(key, Slot::Waiting(None));
+                return key;
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Completions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Completions").field("pending", &self.pending()).finish()
+    }
+}
+
+/// A handle to an event submitted with [`IoUring::submit_with_handle`], which resolves to the
+/// [result](CQE::result) of the event once its CQE is [dispatched](Completions::dispatch).
+///
+/// Dropping the handle does not cancel the event.
+pub struct CompletionFuture {
+    key: u64,
+    completions: Completions,
+}
+
+impl CompletionFuture {
+    /// The user data the event was submitted with.
+    pub fn user_data(&self) -> u64 {
+        self.key
+    }
+}
+
+impl Future for CompletionFuture {
+    type Output = io::Result<u32>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u32>> {
+        let mut inner = self.completions.inner.lock().unwrap();
+        match inner.slots.get_mut(&self.key) {
+            Some(Slot::Waiting(waker)) => {
+                *waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+            Some(Slot::Done(_)) => match inner.slots.remove(&self.key) {
+                Some(Slot::Done(result))    => Poll::Ready(result),
+                _                           => unreachable!(),
+            },
+            _ => panic!("polled a completion after it returned"),
+        }
+    }
+}
+
+impl Drop for CompletionFuture {
+    fn drop(&mut self) {
+        let mut inner = self.completions.inner.lock().unwrap();
+        match inner.slots.get_mut(&self.key) {
+            Some(slot @ Slot::Waiting(_))   => *slot = Slot::Abandoned,
+            Some(Slot::Done(_))             => { inner.slots.remove(&self.key); }
+            _                               => {}
+        }
+    }
+}
+
+impl fmt::Debug for CompletionFuture {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CompletionFuture").field("user_data", &format_args!("{:#x}", self.key)).finish()
+    }
+}
+
+impl IoUring {
+    /// Prepare an event with `setup` and submit it, returning a handle which resolves once the
+    /// CQE of the event is [dispatched](Completions::dispatch) to `completions`. Available with
+    /// the `completion` feature.
+    ///
+    /// `setup` should prepare the event, but its user data is overwritten with the key of the
+    /// handle. If the submission queue is full, the events already in it are submitted first.
+    ///
+    /// # Safety
+    ///
+    /// Any buffer or other resource the event refers to must live until the event completes,
+    /// even if the handle is dropped.
+    pub unsafe fn submit_with_handle(
+        &mut self,
+        completions: &Completions,
+        setup: impl FnOnce(&mut SQE<'_>),
+    ) -> io::Result<CompletionFuture> {
+        let mut sq = self.sq();
+        let (mut sqe, _) = sq.prepare_or_submit()?;
+        setup(&mut sqe);
+        let key = completions.insert();
+        sqe.set_user_data(key);
+        let handle = CompletionFuture { key, completions: completions.clone() };
+        sq.submit()?;
+        Ok(handle)
+    }
+}
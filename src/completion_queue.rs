@@ -1,10 +1,15 @@
 use std::fmt;
+use std::hint;
 use std::io;
+use std::iter;
 use std::marker::PhantomData;
-use std::mem::MaybeUninit;
-use std::ptr::{self, NonNull};
+use std::ptr::NonNull;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
 
-use super::{IoUring, CQE, CQEs, CQEsBlocking, resultify};
+use super::{EnterArg, IoUring, SetupFeatures, SetupFlags, CQE, CQEBatch, CQEs, CQEsBlocking, Timespec, cqe_shift, raw_ring, submission_queue, sys};
+use crate::cqe::CQERef;
 
 /// The queue of completed IO events.
 ///
@@ -12,29 +17,49 @@ use super::{IoUring, CQE, CQEs, CQEsBlocking, resultify};
 ///
 /// Completion does not imply success. Completed events may be [timeouts](crate::cqe::CQE::is_iou_timeout).
 pub struct CompletionQueue<'ring> {
-    pub(crate) ring: NonNull<uring_sys::io_uring>,
+    pub(crate) ring: NonNull<IoUring>,
+    // whether waits submit the prepared SQEs as well, which only a queue borrowed from an
+    // `IoUring` borrowed mutably may do, as it owns the submission queue too
+    submits: bool,
+    // keeps the ring alive when the queue was split off with `IoUring::into_parts`
+    _owner: Option<Arc<IoUring>>,
     _marker: PhantomData<&'ring mut IoUring>,
 }
 
 impl<'ring> CompletionQueue<'ring> {
     pub(crate) fn new(ring: &'ring IoUring) -> CompletionQueue<'ring> {
         CompletionQueue {
-            ring: NonNull::from(&ring.ring),
+            ring: NonNull::from(ring),
+            submits: false,
+            _owner: None,
+            _marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn submitting(ring: &'ring mut IoUring) -> CompletionQueue<'ring> {
+        CompletionQueue {
+            ring: NonNull::from(&*ring),
+            submits: true,
+            _owner: None,
+            _marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn owned(ring: Arc<IoUring>) -> CompletionQueue<'static> {
+        CompletionQueue {
+            ring: NonNull::from(&*ring),
+            submits: false,
+            _owner: Some(ring),
             _marker: PhantomData,
         }
     }
 
     /// Returns the next CQE if any are available.
+    #[inline]
     pub fn peek_for_cqe(&mut self) -> Option<CQE> {
         unsafe {
-            let mut cqe = MaybeUninit::uninit();
-            uring_sys::io_uring_peek_cqe(self.ring.as_ptr(), cqe.as_mut_ptr());
-            let cqe = cqe.assume_init();
-            if !cqe.is_null() {
-                Some(CQE::new(self.ring, &mut *cqe))
-            } else {
-                None
-            }
+            let ring = NonNull::new_unchecked(raw_ring(self.ring));
+            peek_cqe(&mut *ring.as_ptr()).ok().flatten().map(|cqe| CQE::new(ring, cqe))
         }
     }
 
@@ -45,7 +70,7 @@ impl<'ring> CompletionQueue<'ring> {
 
     #[inline(always)]
     pub(crate) fn wait_for_cqes(&mut self, count: u32) -> io::Result<CQE> {
-        let ring = self.ring;
+        let ring = unsafe { NonNull::new_unchecked(raw_ring(self.ring)) };
         self.wait_inner(count).map(|cqe| CQE::new(ring, cqe))
     }
 
@@ -56,21 +81,107 @@ impl<'ring> CompletionQueue<'ring> {
         self.wait_inner(count).map(|_| ())
     }
 
-    #[inline(always)]
-    fn wait_inner(&mut self, count: u32) -> io::Result<&mut uring_sys::io_uring_cqe> {
-        unsafe {
-            let mut cqe = MaybeUninit::uninit();
+    /// Block the thread until at least `count` CQEs are ready, and return all ready CQEs. There
+    /// may be more than `count` of them.
+    pub fn wait_for_cqes_batch(&mut self, count: u32) -> io::Result<Vec<CQE>> {
+        let mut cqes = Vec::with_capacity(count as usize);
+        self.wait_for_cqes_into(count, &mut cqes)?;
+        Ok(cqes)
+    }
 
-            resultify(uring_sys::io_uring_wait_cqes(
-                self.ring.as_ptr(),
-                cqe.as_mut_ptr(),
-                count as _,
-                ptr::null(),
-                ptr::null(),
-            ))?;
+    /// Block the thread until at least `count` CQEs are ready, and append all ready CQEs to
+    /// `cqes`. Returns the number of CQEs appended, which may be more than `count`.
+    ///
+    /// Reusing `cqes` across calls avoids allocating for every batch.
+    pub fn wait_for_cqes_into(&mut self, count: u32, cqes: &mut Vec<CQE>) -> io::Result<usize> {
+        if count > 0 {
+            self.wait(count)?;
+        }
+        let len = cqes.len();
+        cqes.extend(self.cqes());
+        Ok(cqes.len() - len)
+    }
+
+    /// Returns the next CQE, blocking the thread until one is ready if necessary, with the
+    /// thread's signal mask replaced by `sigmask` while it is blocked.
+    ///
+    /// Like `ppoll`, this makes it possible to unblock signals only while waiting, without
+    /// racing their delivery. If a signal is delivered while waiting, an `EINTR` error is
+    /// returned.
+    pub fn wait_for_cqe_with_sigmask(&mut self, sigmask: &libc::sigset_t) -> io::Result<CQE> {
+        let ring = unsafe { NonNull::new_unchecked(raw_ring(self.ring)) };
+        let cqe = unsafe { get_cqe(self.ring, 0, 1, Some(EnterArg::Sigmask(sigmask)))? };
+        Ok(CQE::new(ring, cqe))
+    }
 
-            Ok(&mut *cqe.assume_init())
+    /// Block the thread until at least `count` CQEs are ready, with the thread's signal mask
+    /// replaced by `sigmask` while it is blocked. See
+    /// [`wait_for_cqe_with_sigmask`](CompletionQueue::wait_for_cqe_with_sigmask).
+    ///
+    /// If `sigmask` is `None`, the signal mask is left as it is, like [`wait`](CompletionQueue::wait).
+    pub fn wait_with_sigmask(&mut self, count: u32, sigmask: Option<&libc::sigset_t>) -> io::Result<()> {
+        unsafe { get_cqe(self.ring, 0, count, sigmask.map(EnterArg::Sigmask))?; }
+        Ok(())
+    }
+
+    /// Block the thread until at least `count` CQEs are ready or `deadline` has passed, in which
+    /// case an `ETIME` error is returned.
+    ///
+    /// The timeout is passed to the kernel along with the wait, which requires
+    /// [`SetupFeatures::EXT_ARG`]; without it an `EOPNOTSUPP` error is returned. Prepared events
+    /// are not submitted. [`IoUring::wait_for_cqes_until`] submits them, and on kernels without
+    /// `EXT_ARG` submits the timeout as an event instead.
+    pub fn wait_until(&mut self, count: u32, deadline: Instant) -> io::Result<()> {
+        let ts = Timespec::from(deadline.saturating_duration_since(Instant::now()));
+        unsafe { wait_for_cqes(self.ring, count, Some(&ts), self.submits)?; }
+        Ok(())
+    }
+
+    /// Block the thread until at least `count` CQEs are ready or the `CLOCK_MONOTONIC` clock has
+    /// reached `deadline`, in which case an `ETIME` error is returned.
+    ///
+    /// Unlike [`wait_until`](CompletionQueue::wait_until), the deadline is passed to the kernel as
+    /// an absolute time, so it does not drift however late the kernel is entered. This requires
+    /// Linux 6.12 or newer; older kernels return an `EINVAL` error.
+    pub fn wait_until_monotonic(&mut self, count: u32, deadline: &Timespec) -> io::Result<()> {
+        let arg = sys::io_uring_getevents_arg { ts: deadline as *const _ as u64, ..Default::default() };
+        unsafe { get_cqe(self.ring, 0, count, Some(EnterArg::ExtAbs(&arg)))?; }
+        Ok(())
+    }
+
+    /// Block the thread until at least `count` CQEs are ready, but return as soon as `min_wait`
+    /// has passed if at least one CQE is ready by then. If `timeout` is set and passes without any
+    /// CQE being ready, an `ETIME` error is returned.
+    ///
+    /// This batches completions: a busy ring returns with up to `count` CQEs after waiting at most
+    /// `min_wait`, without the latency of a quiet ring waiting for all of them. This requires
+    /// [`SetupFeatures::MIN_TIMEOUT`]; without it an `EOPNOTSUPP` error is returned. Prepared
+    /// events are only submitted by [`IoUring::wait_for_cqes_with_min_timeout`].
+    pub fn wait_with_min_timeout(&mut self, count: u32, min_wait: Duration, timeout: Option<Duration>)
+        -> io::Result<()>
+    {
+        if unsafe { !(*self.ring.as_ptr()).has_feature(SetupFeatures::MIN_TIMEOUT) } {
+            return Err(io::Error::from_raw_os_error(libc::EOPNOTSUPP));
         }
+        let ts = timeout.map(Timespec::from);
+        let arg = sys::io_uring_getevents_arg {
+            min_wait_usec: min_wait.as_micros().min(u32::MAX as u128) as u32,
+            ts: ts.as_ref().map_or(0, |ts| ts as *const _ as u64),
+            ..Default::default()
+        };
+        unsafe {
+            let submit = match self.submits {
+                true    => submission_queue::flush(&mut *raw_ring(self.ring)),
+                false   => 0,
+            };
+            get_cqe(self.ring, submit, count, Some(EnterArg::Ext(&arg)))?;
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn wait_inner(&mut self, count: u32) -> io::Result<&mut sys::io_uring_cqe> {
+        unsafe { wait_for_cqes(self.ring, count, None, false) }
     }
 
     /// Returns an iterator of ready CQEs.
@@ -78,7 +189,45 @@ impl<'ring> CompletionQueue<'ring> {
     /// When there are no CQEs ready to process, the iterator will end. It will never
     /// block the thread to wait for CQEs to be completed.
     pub fn cqes(&mut self) -> CQEs<'_> {
-        CQEs::new(self.ring)
+        CQEs::new(unsafe { NonNull::new_unchecked(raw_ring(self.ring)) })
+    }
+
+    /// Returns the CQEs which are ready, to be read in place. They are all marked as seen at once
+    /// when the batch is dropped. See [`CQEBatch`].
+    ///
+    /// Like [`cqes`](CompletionQueue::cqes), this never enters the kernel.
+    pub fn iter(&mut self) -> CQEBatch<'_> {
+        CQEBatch::new(unsafe { NonNull::new_unchecked(raw_ring(self.ring)) })
+    }
+
+    /// Pass every ready CQE to `f` in place, without copying it out of the queue, then mark them
+    /// all as seen at once. Returns the number of CQEs passed to `f`.
+    ///
+    /// This is the cheapest way to reap completions, like liburing's `io_uring_for_each_cqe`. It
+    /// never enters the kernel.
+    ///
+    /// ```
+    /// # use std::io;
+    /// # fn main() -> io::Result<()> {
+    /// # let mut ring = iou::IoUring::new(4)?;
+    /// # for _ in 0..2 { unsafe { ring.prepare_sqe().unwrap().prep_nop(); } }
+    /// # ring.submit_sqes_and_wait(2)?;
+    /// let (_, mut cq, _) = ring.queues();
+    /// let mut failed = 0;
+    /// let reaped = cq.for_each(|cqe| if cqe.result().is_err() { failed += 1 });
+    /// assert_eq!((reaped, failed), (2, 0));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn for_each(&mut self, mut f: impl FnMut(CQERef<'_>)) -> usize {
+        let batch = self.iter();
+        let mut count = 0;
+        for cqe in &batch {
+            f(cqe);
+            count += 1;
+        }
+        count
     }
 
     /// Returns an iterator of ready CQEs, blocking when there are none ready.
@@ -89,26 +238,286 @@ impl<'ring> CompletionQueue<'ring> {
         CQEsBlocking::new(self.ring, wait_for)
     }
 
+    /// Returns an iterator of ready CQEs, blocking for at most `timeout` at a time when there are
+    /// none ready.
+    ///
+    /// Whenever a wait times out, the iterator yields an `ETIME` error; it still never ends. Like
+    /// [`wait_until`](CompletionQueue::wait_until), the waits require
+    /// [`SetupFeatures::EXT_ARG`], and yield an `EOPNOTSUPP` error without it.
+    pub fn cqes_blocking_with_timeout(&mut self, wait_for: u32, timeout: Duration) -> CQEsBlocking<'_> {
+        CQEsBlocking::with_timeout(self.ring, wait_for, timeout, self.submits)
+    }
+
+    /// Poll for completions once and return an iterator of at most `budget` ready CQEs.
+    ///
+    /// On an `IOPOLL` ring, completions are only found when the kernel is entered to poll for
+    /// them, and the ring must never be waited on without polling. This polls once without
+    /// blocking, which makes it suitable for a loop which reaps a bounded number of completions
+    /// per iteration. On other rings, it also flushes any overflowed CQEs.
+    pub fn reap(&mut self, budget: usize) -> io::Result<iter::Take<CQEs<'_>>> {
+        self.poll()?;
+        Ok(self.cqes().take(budget))
+    }
+
+    /// Poll for completions until at least `count` CQEs are ready or `deadline` has passed,
+    /// returning the number of ready CQEs.
+    ///
+    /// This busy polls without ever sleeping in the kernel, which is how `IOPOLL` rings are
+    /// meant to be driven; the CQEs can then be processed with `peek_for_cqe` or `cqes`.
+    pub fn poll_until(&mut self, count: u32, deadline: Instant) -> io::Result<u32> {
+        loop {
+            let ready = self.ready();
+            if ready >= count || Instant::now() >= deadline {
+                return Ok(ready);
+            }
+            self.poll()?;
+        }
+    }
+
+    /// Busy poll for up to `spin_budget` iterations until a CQE is ready, then block the thread
+    /// for at most `fallback_timeout` if none is. Returns the number of ready CQEs, or an `ETIME`
+    /// error if the timeout passes without any.
+    ///
+    /// Spinning avoids the latency of sleeping and being woken up when completions arrive within
+    /// microseconds, and the fallback bounds the CPU burnt when they don't. On an `IOPOLL` ring
+    /// each iteration enters the kernel to poll for completions; on other rings it only checks the
+    /// completion queue, with a [spin loop hint](std::hint::spin_loop) in between. Completions
+    /// which take entering the kernel to be posted, like those of `DEFER_TASKRUN` rings, are only
+    /// found by the fallback wait. An `IOPOLL` ring can't sleep waiting for completions, so it
+    /// keeps polling until the timeout passes instead. On other rings, the fallback wait is like
+    /// [`wait_until`](CompletionQueue::wait_until), and requires [`SetupFeatures::EXT_ARG`].
+    pub fn poll_adaptive(&mut self, spin_budget: u32, fallback_timeout: Duration) -> io::Result<u32> {
+        let iopoll = unsafe { (*raw_ring(self.ring)).flags } & SetupFlags::IOPOLL.bits() != 0;
+        for _ in 0..spin_budget {
+            let ready = self.ready();
+            if ready > 0 {
+                return Ok(ready);
+            }
+            match iopoll {
+                true    => self.poll()?,
+                false   => hint::spin_loop(),
+            }
+        }
+
+        if self.ready() > 0 {
+            return Ok(self.ready());
+        }
+        if iopoll {
+            // the kernel never sleeps waiting for completions of an IOPOLL ring
+            let start = Instant::now();
+            loop {
+                self.poll()?;
+                let ready = self.ready();
+                if ready > 0 {
+                    return Ok(ready);
+                }
+                if start.elapsed() >= fallback_timeout {
+                    return Err(io::Error::from_raw_os_error(libc::ETIME));
+                }
+            }
+        }
+        let ts = Timespec::from(fallback_timeout);
+        unsafe { wait_for_cqes(self.ring, 1, Some(&ts), self.submits)?; }
+        Ok(self.ready())
+    }
+
+    // enter the kernel to reap completions, without waiting for any
+    pub(crate) fn poll(&mut self) -> io::Result<()> {
+        unsafe { crate::enter(self.ring, 0, 0, sys::IORING_ENTER_GETEVENTS)?; }
+        Ok(())
+    }
+
     pub fn ready(&self) -> u32 {
-        unsafe { uring_sys::io_uring_cq_ready(self.ring.as_ptr()) }
+        unsafe { ready(raw_ring(self.ring)) }
     }
 
     pub fn eventfd_enabled(&self) -> bool {
-        unsafe { uring_sys::io_uring_cq_eventfd_enabled(self.ring.as_ptr()) }
+        match unsafe { self.kflags() } {
+            Some(kflags)    => kflags.load(Ordering::Relaxed) & sys::IORING_CQ_EVENTFD_DISABLED == 0,
+            None            => true,
+        }
     }
 
     pub fn eventfd_toggle(&mut self, enabled: bool) -> io::Result<()> {
-        resultify(unsafe { uring_sys::io_uring_cq_eventfd_toggle(self.ring.as_ptr(), enabled) })?;
+        if enabled == self.eventfd_enabled() {
+            return Ok(());
+        }
+        let kflags = unsafe { self.kflags() }.ok_or_else(|| io::Error::from_raw_os_error(libc::EOPNOTSUPP))?;
+        match enabled {
+            true    => kflags.fetch_and(!sys::IORING_CQ_EVENTFD_DISABLED, Ordering::Relaxed),
+            false   => kflags.fetch_or(sys::IORING_CQ_EVENTFD_DISABLED, Ordering::Relaxed),
+        };
         Ok(())
     }
+
+    // the flags of the completion queue ring, which kernels before 5.8 don't have
+    unsafe fn kflags(&self) -> Option<&AtomicU32> {
+        ((*raw_ring(self.ring)).cq.kflags as *const AtomicU32).as_ref()
+    }
 }
 
 impl fmt::Debug for CompletionQueue<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let fd = unsafe { self.ring.as_ref().ring_fd };
+        let fd = unsafe { (*raw_ring(self.ring)).ring_fd };
         f.debug_struct(std::any::type_name::<Self>()).field("fd", &fd).finish()
     }
 }
 
 unsafe impl<'ring> Send for CompletionQueue<'ring> { }
 unsafe impl<'ring> Sync for CompletionQueue<'ring> { }
+
+/// Wait until `count` CQEs are ready and return the first of them, giving up after `ts` if it is
+/// set.
+///
+/// The timeout is passed to the kernel when waiting, which requires `EXT_ARG`. If `submit` is
+/// set, prepared SQEs are submitted as well, and on older kernels a timeout SQE is submitted
+/// instead. Only a caller which owns the submission queue may set it, as it writes to the queue.
+pub(crate) unsafe fn wait_for_cqes<'a>(
+    ring: NonNull<IoUring>,
+    count: u32,
+    ts: Option<&Timespec>,
+    submit: bool,
+) -> io::Result<&'a mut sys::io_uring_cqe> {
+    let raw = raw_ring(ring);
+    let mut to_submit = 0;
+
+    if let Some(ts) = ts {
+        if (*ring.as_ptr()).has_feature(SetupFeatures::EXT_ARG) {
+            let arg = sys::io_uring_getevents_arg { ts: ts as *const _ as u64, ..Default::default() };
+            let to_submit = match submit {
+                true    => submission_queue::flush(&mut *raw),
+                false   => 0,
+            };
+            return get_cqe(ring, to_submit, count, Some(EnterArg::Ext(&arg)));
+        }
+        if !submit {
+            return Err(io::Error::from_raw_os_error(libc::EOPNOTSUPP));
+        }
+
+        // If the SQ ring is full, we may need to submit IO first
+        let mut sqe = match submission_queue::prepare_sqe(&mut *raw) {
+            Some(sqe) => sqe,
+            None => {
+                submission_queue::submit(ring, 0)?;
+                submission_queue::prepare_sqe(&mut *raw)
+                    .ok_or_else(|| io::Error::from_raw_os_error(libc::EAGAIN))?
+            }
+        };
+        sqe.prep_timeout(ts, count, crate::sqe::TimeoutFlags::empty());
+        sqe.set_user_data(sys::LIBURING_UDATA_TIMEOUT);
+        to_submit = submission_queue::flush(&mut *raw);
+    }
+
+    get_cqe(ring, to_submit, count, None)
+}
+
+/// Submit `submit` SQEs and wait until `wait_for` CQEs are ready, returning the first of them.
+/// `arg` is passed to the kernel whenever it is entered.
+pub(crate) unsafe fn get_cqe<'a>(
+    ring: NonNull<IoUring>,
+    mut submit: u32,
+    mut wait_for: u32,
+    arg: Option<EnterArg<'_>>,
+) -> io::Result<&'a mut sys::io_uring_cqe> {
+    let raw = raw_ring(ring);
+    let to_wait = wait_for;
+    // whether the kernel has already waited for a timeout in `arg`
+    let mut waited = false;
+
+    loop {
+        let mut flags = 0;
+        let mut overflow_flush = false;
+
+        let cqe = peek_cqe(&mut *raw)?;
+        if cqe.is_none() && waited {
+            return Err(io::Error::from_raw_os_error(libc::ETIME));
+        }
+        if cqe.is_none() && to_wait == 0 && submit == 0 {
+            if !needs_flush(&*raw) {
+                return Err(io::Error::from_raw_os_error(libc::EAGAIN));
+            }
+            overflow_flush = true;
+        }
+        if wait_for > 0 && cqe.is_some() {
+            wait_for -= 1;
+        }
+        if wait_for > 0 || overflow_flush {
+            flags = sys::IORING_ENTER_GETEVENTS;
+        }
+        if submit > 0 {
+            submission_queue::needs_enter(&*raw, submit, &mut flags);
+        }
+        if wait_for > 0 || submit > 0 || overflow_flush {
+            let ret = match arg {
+                Some(arg)   => {
+                    waited = matches!(arg, EnterArg::Ext(_) | EnterArg::ExtAbs(_))
+                        && flags & sys::IORING_ENTER_GETEVENTS != 0;
+                    crate::enter_with_arg(ring, submit, wait_for, flags, arg)?
+                }
+                None        => crate::enter(ring, submit, wait_for, flags)?,
+            };
+            if ret == submit {
+                submit = 0;
+                // With IOPOLL the kernel must be entered to reap new completions, so keep
+                // waiting even once everything has been submitted.
+                if (*raw).flags & SetupFlags::IOPOLL.bits() == 0 {
+                    wait_for = 0;
+                }
+            } else {
+                submit -= ret;
+            }
+        }
+        if let Some(cqe) = cqe {
+            return Ok(cqe);
+        }
+    }
+}
+
+/// Returns the first ready CQE without entering the kernel, skipping the CQEs of internal
+/// timeouts.
+#[inline]
+pub(crate) unsafe fn peek_cqe<'a>(ring: &mut sys::io_uring)
+    -> io::Result<Option<&'a mut sys::io_uring_cqe>>
+{
+    loop {
+        let head = *ring.cq.khead;
+        let tail = (*(ring.cq.ktail as *const AtomicU32)).load(Ordering::Acquire);
+        if head == tail {
+            return Ok(None);
+        }
+
+        let index = (head & *ring.cq.kring_mask) << cqe_shift(ring.flags);
+        let cqe = &mut *ring.cq.cqes.add(index as usize);
+        if cqe.user_data != sys::LIBURING_UDATA_TIMEOUT {
+            return Ok(Some(cqe));
+        }
+
+        let res = cqe.res;
+        advance(ring, 1);
+        crate::counters(ring).completed(1);
+        if res < 0 {
+            return Err(io::Error::from_raw_os_error(-res));
+        }
+    }
+}
+
+/// The number of CQEs ready to be reaped.
+#[inline(always)]
+pub(crate) unsafe fn ready(ring: *const sys::io_uring) -> u32 {
+    let cq = &(*ring).cq;
+    (*(cq.ktail as *const AtomicU32)).load(Ordering::Acquire).wrapping_sub(*cq.khead)
+}
+
+/// Hand `count` reaped CQEs back to the kernel, once they are no longer read.
+#[inline(always)]
+pub(crate) unsafe fn advance(ring: *mut sys::io_uring, count: u32) {
+    if count > 0 {
+        let khead = &*((*ring).cq.khead as *const AtomicU32);
+        khead.store(khead.load(Ordering::Relaxed).wrapping_add(count), Ordering::Release);
+    }
+}
+
+unsafe fn needs_flush(ring: &sys::io_uring) -> bool {
+    (*(ring.sq.kflags as *const AtomicU32)).load(Ordering::Relaxed) & sys::IORING_SQ_CQ_OVERFLOW != 0
+}
+
@@ -1,49 +1,77 @@
+use std::collections::HashSet;
+use std::fmt;
 use std::io;
 use std::marker::PhantomData;
-use std::mem::MaybeUninit;
-use std::ptr::{self, NonNull};
+use std::ops::Deref;
+use std::os::unix::io::RawFd;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
 
-use super::{IoUring, resultify};
+use super::{IoUring, Timespec, completion_queue, cqe_shift, raw_ring, resultify, sys};
+use crate::registrar::{DirectSlot, RegisteredFd, PLACEHOLDER_FD};
+use crate::sqe::{FileIndex, PollFlags};
 
 /// A completed IO event.
-#[derive(Debug)]
 pub struct CQE {
     user_data: u64,
     res: i32,
     flags: CompletionFlags,
+    big_cqe: Option<[u64; 2]>,
 }
 
 impl CQE {
-    pub fn from_raw(cqe: uring_sys::io_uring_cqe) -> CQE {
+    pub fn from_raw(cqe: sys::io_uring_cqe) -> CQE {
         CQE {
             user_data: cqe.user_data,
             res: cqe.res,
-            flags: CompletionFlags::from_bits_truncate(cqe.flags),
+            flags: unsafe { CompletionFlags::from_bits_unchecked(cqe.flags) },
+            big_cqe: None,
         }
     }
 
     pub fn from_raw_parts(user_data: u64, res: i32, flags: CompletionFlags) -> CQE {
         CQE {
-            user_data, res, flags,
+            user_data, res, flags, big_cqe: None,
         }
     }
 
-    pub(crate) fn new(ring: NonNull<uring_sys::io_uring>, cqe: &mut uring_sys::io_uring_cqe) -> CQE {
+    #[inline]
+    pub(crate) fn new(ring: NonNull<sys::io_uring>, cqe: &mut sys::io_uring_cqe) -> CQE {
         let user_data = cqe.user_data;
         let res = cqe.res;
-        let flags = CompletionFlags::from_bits_truncate(cqe.flags);
+        let flags = unsafe { CompletionFlags::from_bits_unchecked(cqe.flags) };
+        let big_cqe = match cqe_shift(unsafe { ring.as_ref().flags }) {
+            0 => None,
+            // the extra data directly follows the CQE
+            _ => unsafe { Some(*((cqe as *mut sys::io_uring_cqe).add(1) as *const [u64; 2])) },
+        };
 
         unsafe {
-            uring_sys::io_uring_cqe_seen(ring.as_ptr(), cqe);
+            completion_queue::advance(ring.as_ptr(), 1);
+            crate::counters(ring.as_ptr()).completed(1);
         }
 
-        CQE::from_raw_parts(user_data, res, flags)
+        crate::trace::complete(user_data, res, flags.bits());
+        CQE { user_data, res, flags, big_cqe }
     }
 
     pub fn user_data(&self) -> u64 {
         self.user_data as u64
     }
 
+    /// Reclaim the box leaked into the user data of this event by
+    /// [`SQE::set_user_data_ptr`](crate::SQE::set_user_data_ptr).
+    ///
+    /// # Safety
+    ///
+    /// The event must have been prepared with `set_user_data_ptr` with a `Box<T>` of the same
+    /// `T`, and the box must not have been reclaimed already. Multishot events post many CQEs
+    /// with the same user data, but only the final one may reclaim the box.
+    pub unsafe fn user_data_as<T>(&self) -> Box<T> {
+        Box::from_raw(self.user_data as usize as *mut T)
+    }
+
     pub fn result(&self) -> io::Result<u32> {
         resultify(self.res)
     }
@@ -52,6 +80,98 @@ impl CQE {
         self.flags
     }
 
+    /// Interpret the result of an event which created a file descriptor, like an accept, socket
+    /// or openat event. The caller owns the returned file descriptor and is responsible for
+    /// closing it.
+    ///
+    /// Events which installed a direct descriptor should use [`direct_slot`](CQE::direct_slot)
+    /// instead.
+    pub fn result_as_fd(&self) -> io::Result<RawFd> {
+        self.result().map(|fd| fd as RawFd)
+    }
+
+    /// Interpret the result of a [poll event](crate::SQE::prep_poll_add) as the events which
+    /// are ready.
+    pub fn result_as_poll_flags(&self) -> io::Result<PollFlags> {
+        self.result().map(|mask| PollFlags::from_bits_truncate(mask as libc::c_short))
+    }
+
+    /// Interpret the result of an event which transferred data, like a read or a write, as the
+    /// number of bytes transferred.
+    pub fn result_as_size(&self) -> io::Result<usize> {
+        self.result().map(|n| n as usize)
+    }
+
+    /// The id of the provided buffer the kernel selected for this event, if it selected one.
+    pub fn buffer_id(&self) -> Option<u16> {
+        match self.flags.contains(CompletionFlags::BUFFER) {
+            true    => Some((self.flags.bits() >> BUFFER_ID_SHIFT) as u16),
+            false   => None,
+        }
+    }
+
+    /// Whether the event will post more CQEs. A multishot event which completes without this
+    /// flag has stopped, and must be prepared again to continue.
+    pub fn more(&self) -> bool {
+        self.flags.contains(CompletionFlags::MORE)
+    }
+
+    /// Whether the socket of a receive event still had data to receive after it completed.
+    pub fn sock_nonempty(&self) -> bool {
+        self.flags.contains(CompletionFlags::SOCK_NONEMPTY)
+    }
+
+    /// Whether this CQE is the notification of a zero copy send that its buffer is no longer
+    /// used by the kernel, rather than the result of the send.
+    pub fn is_notif(&self) -> bool {
+        self.flags.contains(CompletionFlags::NOTIF)
+    }
+
+    /// Interpret the result of an event which installed a direct descriptor, like
+    /// [`prep_openat_direct`](crate::SQE::prep_openat_direct), into `file_index`.
+    pub fn direct_slot(&self, file_index: FileIndex) -> io::Result<DirectSlot> {
+        let result = self.result()?;
+        let index = match file_index {
+            FileIndex::Slot(index) => index,
+            FileIndex::Alloc => result,
+        };
+        Ok(DirectSlot::new(index))
+    }
+
+    #[deprecated(note = "use `direct_slot`, direct descriptors are not registered files")]
+    pub fn direct_fd(&self, file_index: FileIndex) -> io::Result<RegisteredFd> {
+        let slot = self.direct_slot(file_index)?;
+        Ok(RegisteredFd::new(slot.index(), PLACEHOLDER_FD))
+    }
+
+    /// The extra 16 bytes of completion data of a 32 byte CQE, from a ring set up with
+    /// [`SetupFlags::CQE32`](crate::SetupFlags::CQE32). `None` for regular CQEs.
+    pub fn big_cqe(&self) -> Option<[u64; 2]> {
+        self.big_cqe
+    }
+
+    /// The extra 16 bytes of completion data of a 32 byte CQE as bytes, in the order they are in
+    /// the ring, like the response of a `uring_cmd` event. `None` for regular CQEs.
+    pub fn big_payload(&self) -> Option<[u8; 16]> {
+        let [first, second] = self.big_cqe?;
+        let mut payload = [0; 16];
+        payload[..8].copy_from_slice(&first.to_ne_bytes());
+        payload[8..].copy_from_slice(&second.to_ne_bytes());
+        Some(payload)
+    }
+
+    /// The CQE as the kernel posted it, for fields and flags which have no accessor. The extra
+    /// data of a 32 byte CQE is available from [`big_cqe`](CQE::big_cqe).
+    ///
+    /// The CQE was copied out of the completion queue, so this returns a copy of it.
+    pub fn raw(&self) -> sys::io_uring_cqe {
+        sys::io_uring_cqe {
+            user_data: self.user_data,
+            res: self.res,
+            flags: self.flags.bits(),
+        }
+    }
+
     pub fn raw_result(&self) -> i32 {
         self.res
     }
@@ -61,6 +181,29 @@ impl CQE {
     }
 }
 
+/// Shows the result as a size or as the error it encodes, and the id of the selected buffer
+/// separately from the other flags.
+impl fmt::Debug for CQE {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let flags = CompletionFlags::from_bits_truncate(self.flags.bits())
+            - CompletionFlags::BUFFER;
+        let mut debug = f.debug_struct("CQE");
+        debug.field("user_data", &format_args!("{:#x}", self.user_data));
+        match self.result() {
+            Ok(n)       => debug.field("res", &n),
+            Err(err)    => debug.field("res", &format_args!("Err({})", err)),
+        };
+        debug.field("flags", &flags);
+        if let Some(buffer_id) = self.buffer_id() {
+            debug.field("buffer_id", &buffer_id);
+        }
+        if let Some(big_cqe) = &self.big_cqe {
+            debug.field("big_cqe", big_cqe);
+        }
+        debug.finish()
+    }
+}
+
 unsafe impl Send for CQE { }
 unsafe impl Sync for CQE { }
 
@@ -68,32 +211,26 @@ unsafe impl Sync for CQE { }
 ///
 /// This iterator will be exhausted when there are no `CQE`s ready, and return `None`.
 pub struct CQEs<'a> {
-    ring: NonNull<uring_sys::io_uring>,
+    ring: NonNull<sys::io_uring>,
     ready: u32,
     marker: PhantomData<&'a mut IoUring>,
 }
 
 impl<'a> CQEs<'a> {
-    pub(crate) fn new(ring: NonNull<uring_sys::io_uring>) -> CQEs<'a> {
+    pub(crate) fn new(ring: NonNull<sys::io_uring>) -> CQEs<'a> {
         CQEs { ring, ready: 0, marker: PhantomData }
     }
 
     #[inline(always)]
     fn ready(&self) -> u32 {
-        unsafe { uring_sys::io_uring_cq_ready(self.ring.as_ptr()) }
+        unsafe { completion_queue::ready(self.ring.as_ptr()) }
     }
 
     #[inline(always)]
     fn peek_for_cqe(&mut self) -> Option<CQE> {
         unsafe {
-            let mut cqe = MaybeUninit::uninit();
-            uring_sys::io_uring_peek_cqe(self.ring.as_ptr(), cqe.as_mut_ptr());
-            let cqe = cqe.assume_init();
-            if !cqe.is_null() {
-                Some(CQE::new(self.ring, &mut *cqe))
-            } else {
-                None
-            }
+            let cqe = completion_queue::peek_cqe(&mut *self.ring.as_ptr()).ok().flatten();
+            cqe.map(|cqe| CQE::new(self.ring, cqe))
         }
     }
 }
@@ -114,57 +251,353 @@ impl Iterator for CQEs<'_> {
     }
 }
 
+/// The CQEs which were ready when the batch was taken, read in place from the completion queue.
+///
+/// Unlike [`CQEs`], which copies each CQE and marks it as seen before moving on to the next, a
+/// batch borrows the CQEs where the kernel wrote them and marks all of them as seen at once when
+/// it is dropped. The CQEs can be iterated any number of times until then.
+///
+/// ```
+/// # use std::io;
+/// # fn main() -> io::Result<()> {
+/// # let mut ring = iou::IoUring::new(4)?;
+/// # for _ in 0..2 { unsafe { ring.prepare_sqe().unwrap().prep_nop(); } }
+/// # ring.submit_sqes_and_wait(2)?;
+/// let (_, mut cq, _) = ring.queues();
+/// let batch = cq.iter();
+/// for cqe in &batch {
+///     cqe.result()?;
+/// }
+/// assert_eq!(batch.len(), 2);
+/// drop(batch);
+/// assert_eq!(cq.ready(), 0);
+/// # Ok(())
+/// # }
+/// ```
+pub struct CQEBatch<'a> {
+    ring: NonNull<sys::io_uring>,
+    head: u32,
+    len: u32,
+    marker: PhantomData<&'a mut IoUring>,
+}
+
+impl<'a> CQEBatch<'a> {
+    pub(crate) fn new(ring: NonNull<sys::io_uring>) -> CQEBatch<'a> {
+        unsafe {
+            let cq = &ring.as_ref().cq;
+            let head = *cq.khead;
+            let tail = (*(cq.ktail as *const AtomicU32)).load(Ordering::Acquire);
+            CQEBatch { ring, head, len: tail.wrapping_sub(head), marker: PhantomData }
+        }
+    }
+
+    /// The number of CQEs in the batch, including the CQEs of internal timeouts which are
+    /// skipped when iterating.
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns an iterator of the CQEs in the batch.
+    pub fn iter(&self) -> CQEBatchIter<'_> {
+        CQEBatchIter {
+            ring: self.ring,
+            next: self.head,
+            end: self.head.wrapping_add(self.len),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl Drop for CQEBatch<'_> {
+    fn drop(&mut self) {
+        // each CQE is traced once, as it is marked as seen, however often the batch was iterated
+        #[cfg(feature = "tracing")]
+        for cqe in self.iter() {
+            crate::trace::complete(cqe.user_data(), cqe.raw_result(), cqe.flags().bits());
+        }
+        unsafe {
+            completion_queue::advance(self.ring.as_ptr(), self.len);
+            crate::counters(self.ring.as_ptr()).completed(self.len);
+        }
+    }
+}
+
+impl fmt::Debug for CQEBatch<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'b> IntoIterator for &'b CQEBatch<'_> {
+    type Item = CQERef<'b>;
+    type IntoIter = CQEBatchIter<'b>;
+
+    fn into_iter(self) -> CQEBatchIter<'b> {
+        self.iter()
+    }
+}
+
+/// An iterator of the CQEs of a [`CQEBatch`].
+pub struct CQEBatchIter<'b> {
+    ring: NonNull<sys::io_uring>,
+    next: u32,
+    end: u32,
+    marker: PhantomData<&'b CQEBatch<'b>>,
+}
+
+impl<'b> Iterator for CQEBatchIter<'b> {
+    type Item = CQERef<'b>;
+
+    fn next(&mut self) -> Option<CQERef<'b>> {
+        while self.next != self.end {
+            let cqe = unsafe {
+                let ring = self.ring.as_ref();
+                let index = (self.next & *ring.cq.kring_mask) << cqe_shift(ring.flags);
+                CQERef { cqe: &*ring.cq.cqes.add(index as usize), big: cqe_shift(ring.flags) != 0 }
+            };
+            self.next = self.next.wrapping_add(1);
+            if cqe.user_data() != sys::LIBURING_UDATA_TIMEOUT {
+                return Some(cqe);
+            }
+        }
+        None
+    }
+}
+
+/// A CQE borrowed in place from the completion queue, see [`CQEBatch`].
+#[derive(Clone, Copy)]
+pub struct CQERef<'b> {
+    cqe: &'b sys::io_uring_cqe,
+    big: bool,
+}
+
+impl<'b> CQERef<'b> {
+    pub fn user_data(&self) -> u64 {
+        self.cqe.user_data
+    }
+
+    pub fn result(&self) -> io::Result<u32> {
+        resultify(self.cqe.res)
+    }
+
+    pub fn raw_result(&self) -> i32 {
+        self.cqe.res
+    }
+
+    pub fn flags(&self) -> CompletionFlags {
+        unsafe { CompletionFlags::from_bits_unchecked(self.cqe.flags) }
+    }
+
+    /// The extra 16 bytes of completion data of a 32 byte CQE, see [`CQE::big_cqe`].
+    pub fn big_cqe(&self) -> Option<[u64; 2]> {
+        match self.big {
+            // the extra data directly follows the CQE
+            true    => unsafe { Some(*((self.cqe as *const sys::io_uring_cqe).add(1) as *const [u64; 2])) },
+            false   => None,
+        }
+    }
+
+    /// Get a reference to the underlying [`io_uring_cqe`](sys::io_uring_cqe) in the completion queue.
+    pub fn raw(&self) -> &'b sys::io_uring_cqe {
+        self.cqe
+    }
+
+    /// A pointer to the CQE in the completion queue. The extra data of a 32 byte CQE directly
+    /// follows it.
+    pub fn as_ptr(&self) -> *const sys::io_uring_cqe {
+        self.cqe
+    }
+
+    /// Copy the CQE out of the completion queue.
+    pub fn to_cqe(&self) -> CQE {
+        CQE {
+            user_data: self.user_data(),
+            res: self.raw_result(),
+            flags: self.flags(),
+            big_cqe: self.big_cqe(),
+        }
+    }
+}
+
+impl fmt::Debug for CQERef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CQERef")
+            .field("user_data", &format_args!("{:#x}", self.user_data()))
+            .field("res", &self.raw_result())
+            .field("flags", &self.flags())
+            .finish()
+    }
+}
+
+unsafe impl Send for CQERef<'_> { }
+unsafe impl Sync for CQERef<'_> { }
+
+/// Tracks which multishot events are still live, from the [`MORE`](CompletionFlags::MORE) flag
+/// of their CQEs.
+///
+/// A multishot event, like a [multishot recvmsg](crate::SQE::prep_recvmsg_multishot), posts a
+/// CQE with `MORE` set for every result, and stops once it posts a CQE without it. The tracker
+/// marks an event as finished when its final CQE passes through it.
+///
+/// ```
+/// # use std::io;
+/// # fn main() -> io::Result<()> {
+/// # let mut ring = iou::IoUring::new(2)?;
+/// use iou::cqe::MultishotTracker;
+///
+/// let mut tracker = MultishotTracker::new();
+/// unsafe {
+///     let mut sqe = ring.prepare_sqe().unwrap();
+///     sqe.prep_nop();
+///     sqe.set_user_data(7);
+/// }
+/// ring.submit_sqes_and_wait(1)?;
+/// tracker.track(7);
+///
+/// for cqe in tracker.cqes(ring.cqes()) {
+///     assert!(cqe.is_final());
+/// }
+/// assert!(!tracker.is_live(7));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct MultishotTracker {
+    live: HashSet<u64>,
+}
+
+impl MultishotTracker {
+    pub fn new() -> MultishotTracker {
+        MultishotTracker::default()
+    }
+
+    /// Track the event with `user_data` as live until its final CQE.
+    pub fn track(&mut self, user_data: u64) {
+        self.live.insert(user_data);
+    }
+
+    /// Whether the event with `user_data` is tracked and has not posted its final CQE.
+    pub fn is_live(&self, user_data: u64) -> bool {
+        self.live.contains(&user_data)
+    }
+
+    /// The user data of the live events, in no particular order.
+    pub fn live(&self) -> impl Iterator<Item = u64> + '_ {
+        self.live.iter().copied()
+    }
+
+    /// The number of live events.
+    pub fn live_count(&self) -> usize {
+        self.live.len()
+    }
+
+    /// Process a CQE, marking its event as finished if it is the final one.
+    ///
+    /// CQEs of events which are not tracked pass through, and are final unless `MORE` is set.
+    pub fn process(&mut self, cqe: CQE) -> MultishotCQE {
+        let is_final = !cqe.more();
+        if is_final {
+            self.live.remove(&cqe.user_data());
+        }
+        MultishotCQE { cqe, is_final }
+    }
+
+    /// Process every CQE of `cqes`, like [`CompletionQueue::cqes`](crate::CompletionQueue::cqes).
+    pub fn cqes<I: IntoIterator<Item = CQE>>(&mut self, cqes: I) -> MultishotCQEs<'_, I::IntoIter> {
+        MultishotCQEs { tracker: self, cqes: cqes.into_iter() }
+    }
+}
+
+/// A CQE which has passed through a [`MultishotTracker`].
+#[derive(Debug)]
+pub struct MultishotCQE {
+    cqe: CQE,
+    is_final: bool,
+}
+
+impl MultishotCQE {
+    /// Whether this is the last CQE of its event.
+    pub fn is_final(&self) -> bool {
+        self.is_final
+    }
+
+    pub fn into_cqe(self) -> CQE {
+        self.cqe
+    }
+}
+
+impl Deref for MultishotCQE {
+    type Target = CQE;
+
+    fn deref(&self) -> &CQE {
+        &self.cqe
+    }
+}
+
+/// An iterator of the CQEs processed by a [`MultishotTracker`].
+pub struct MultishotCQEs<'t, I> {
+    tracker: &'t mut MultishotTracker,
+    cqes: I,
+}
+
+impl<I: Iterator<Item = CQE>> Iterator for MultishotCQEs<'_, I> {
+    type Item = MultishotCQE;
+
+    fn next(&mut self) -> Option<MultishotCQE> {
+        self.cqes.next().map(|cqe| self.tracker.process(cqe))
+    }
+}
 
 /// An iterator of [`CQE`]s from the [`CompletionQueue`](crate::CompletionQueue).
 ///
 /// This iterator will never be exhausted; if there are no `CQE`s ready, it will block until there
 /// are.
+///
+/// If it was created with a timeout, each wait gives up once the timeout has passed, and the
+/// iterator yields an `ETIME` error before it continues waiting on the next call. This makes it
+/// possible to do some work periodically while waiting.
 pub struct CQEsBlocking<'a> {
-    ring: NonNull<uring_sys::io_uring>,
+    ring: NonNull<IoUring>,
     ready: u32,
     wait_for: u32,
+    timeout: Option<Timespec>,
+    // whether the waits submit the prepared SQEs as well, see `completion_queue::wait_for_cqes`
+    submit: bool,
     marker: PhantomData<&'a mut IoUring>,
 }
 
 impl<'a> CQEsBlocking<'a> {
-    pub(crate) fn new(ring: NonNull<uring_sys::io_uring>, wait_for: u32) -> CQEsBlocking<'a> {
-        CQEsBlocking { ring, ready: 0, wait_for, marker: PhantomData }
+    pub(crate) fn new(ring: NonNull<IoUring>, wait_for: u32) -> CQEsBlocking<'a> {
+        CQEsBlocking { ring, ready: 0, wait_for, timeout: None, submit: false, marker: PhantomData }
+    }
+
+    pub(crate) fn with_timeout(ring: NonNull<IoUring>, wait_for: u32, timeout: Duration, submit: bool)
+        -> CQEsBlocking<'a>
+    {
+        let timeout = Some(Timespec::from(timeout));
+        CQEsBlocking { ring, ready: 0, wait_for, timeout, submit, marker: PhantomData }
     }
 
     #[inline(always)]
     fn ready(&self) -> u32 {
-        unsafe { uring_sys::io_uring_cq_ready(self.ring.as_ptr()) }
+        unsafe { completion_queue::ready(raw_ring(self.ring)) }
     }
 
     #[inline(always)]
     fn peek_for_cqe(&mut self) -> Option<CQE> {
         unsafe {
-            let mut cqe = MaybeUninit::uninit();
-            uring_sys::io_uring_peek_cqe(self.ring.as_ptr(), cqe.as_mut_ptr());
-            let cqe = cqe.assume_init();
-            if !cqe.is_null() {
-                Some(CQE::new(self.ring, &mut *cqe))
-            } else {
-                None
-            }
+            let ring = NonNull::new_unchecked(raw_ring(self.ring));
+            completion_queue::peek_cqe(&mut *ring.as_ptr()).ok().flatten().map(|cqe| CQE::new(ring, cqe))
         }
     }
 
     #[inline(always)]
-    fn wait(&mut self) -> io::Result<&mut uring_sys::io_uring_cqe> {
-        unsafe {
-            let mut cqe = MaybeUninit::uninit();
-
-            resultify(uring_sys::io_uring_wait_cqes(
-                self.ring.as_ptr(),
-                cqe.as_mut_ptr(),
-                self.wait_for as _,
-                ptr::null(),
-                ptr::null(),
-            ))?;
-
-            Ok(&mut *cqe.assume_init())
-        }
+    fn wait(&mut self) -> io::Result<&mut sys::io_uring_cqe> {
+        unsafe { completion_queue::wait_for_cqes(self.ring, self.wait_for, self.timeout.as_ref(), self.submit) }
     }
 }
 
@@ -175,7 +608,7 @@ impl Iterator for CQEsBlocking<'_> {
         if self.ready == 0 {
             self.ready = self.ready();
             if self.ready == 0 {
-                let ring = self.ring;
+                let ring = unsafe { NonNull::new_unchecked(raw_ring(self.ring)) };
                 return Some(self.wait().map(|cqe| CQE::new(ring, cqe)))
             }
         }
@@ -187,7 +620,24 @@ impl Iterator for CQEsBlocking<'_> {
 
 bitflags::bitflags! {
     /// Flags that can be returned from the kernel on [`CQE`]s.
+    ///
+    /// The upper 16 bits are not flags: when [`BUFFER`](CompletionFlags::BUFFER) is set, they
+    /// hold the id of the buffer the kernel selected, see [`CQE::buffer_id`].
     pub struct CompletionFlags: u32 {
+        /// The event selected a provided buffer, whose id is in the upper 16 bits.
+        const BUFFER          = 1 << 0;
+        /// The same as [`BUFFER`](CompletionFlags::BUFFER).
         const BUFFER_SHIFT    = 1 << 0;
+        /// The event will post more CQEs, like a multishot event which is still active.
+        const MORE            = 1 << 1;
+        /// The socket still had data to receive when the event completed.
+        const SOCK_NONEMPTY   = 1 << 2;
+        /// This CQE is the notification of a zero copy send that its buffer can be reused.
+        const NOTIF           = 1 << 3;
+        /// The selected buffer of a buffer ring will be used for more completions.
+        const BUF_MORE        = 1 << 4;
     }
 }
+
+// the selected buffer id is stored in the upper 16 bits of the flags
+const BUFFER_ID_SHIFT: u32 = 16;
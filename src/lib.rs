@@ -1,4 +1,4 @@
-//! Idiomatic Rust bindings to liburing.
+//! Idiomatic Rust bindings to io_uring.
 //!
 //! This gives users an idiomatic Rust interface for interacting with the Linux kernel's `io_uring`
 //! interface for async IO. Despite being idiomatic Rust, this interface is still very low level
@@ -25,14 +25,22 @@
 //!
 //! # Timeouts
 //!
-//! Some APIs allow you to time out a call into the kernel. It's important to note how this works
-//! with io_uring.
+//! Some APIs allow you to time out a call into the kernel. On kernels with
+//! `SetupFeatures::EXT_ARG` (Linux 5.11 or newer), the timeout is passed to the kernel along with
+//! the wait, and nothing is submitted for it.
 //!
-//! A timeout is submitted as an additional IO event which completes after the specified time.
-//! Therefore when you create a timeout, all that happens is that a completion event will appear
-//! after that specified time. This also means that when processing completion events, you need to
-//! be prepared for the possibility that the completion represents a timeout and not a normal IO
-//! event (`CQE` has a method to check for this).
+//! On older kernels, the `IoUring` methods which take a timeout submit it as an additional IO
+//! event which completes after the specified time. That event has the reserved user data
+//! `UserData::RESERVED`, and its completion is skipped by the library rather than returned. A
+//! split `CompletionQueue` can't submit events, so its waits with a timeout return an
+//! `EOPNOTSUPP` error on these kernels.
+//!
+//! # Tracing
+//!
+//! With the `tracing` feature enabled, ring activity is reported through the `tracing` crate
+//! under the `iou` target: a `TRACE` event with the opcode and user data of every `SQE` as it is
+//! handed to the kernel, a `TRACE` event with the user data, result and flags of every `CQE` as
+//! it is reaped, and a `DEBUG` span around every call to `io_uring_enter`.
 
 /// Types related to completion queue events.
 pub mod cqe;
@@ -41,36 +49,72 @@ pub mod cqe;
 /// The most important types here are [`SQE`], which represents a single submission queue event,
 /// and [`SQEs`], which represents a sequence of events that can be prepared at once.
 ///
-/// Many of the types in this module are re-exported from the `nix` crate, and are used when
-/// preparing [`SQE`]s associated with specific Linux system operations.
+/// It also defines the flag and address types used when preparing [`SQE`]s associated with
+/// specific Linux system operations. With the `nix` feature enabled, these convert to and from
+/// the equivalent types of the `nix` crate.
 pub mod sqe;
 
 mod completion_queue;
+mod metrics;
 mod submission_queue;
 
+mod capabilities;
+mod compat;
+#[cfg(feature = "completion")]
+mod completion;
+mod params;
 mod probe;
+mod ring_memory;
+mod timespec;
+mod trace;
+mod user_data;
 
 pub mod registrar;
+/// A safe submission API, whose events own their buffers and file descriptors.
+///
+/// The main type here is [`OwnedRing`](owned::OwnedRing), which keeps the resources of each event
+/// until it completes and hands them back with its [`Completion`](owned::Completion).
+pub mod owned;
+/// The kernel's io_uring ABI: the layouts of the entries, the rings and the setup parameters
+/// which the `raw` methods of the other types return, and the opcodes of the events.
+pub mod sys;
 
+use std::any::Any;
 use std::fmt;
 use std::io;
-use std::mem::{self, MaybeUninit};
-use std::os::unix::io::RawFd;
+use std::mem;
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd};
 use std::ptr::{self, NonNull};
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicI32, AtomicU32, Ordering};
+use std::iter;
+use std::time::{Duration, Instant};
 
 #[doc(inline)]
 pub use sqe::{SQE, SQEs};
 #[doc(inline)]
-pub use cqe::{CQE, CQEs, CQEsBlocking};
+pub use cqe::{CQE, CQEBatch, CQEs, CQEsBlocking};
 
 pub use completion_queue::CompletionQueue;
-pub use submission_queue::SubmissionQueue;
+pub use submission_queue::{SharedSubmissionQueue, SQFlags, SubmissionQueue};
 
-pub use probe::Probe;
+pub use capabilities::{Capabilities, KernelVersion};
+pub use params::Params;
+pub use probe::{CapabilityReport, Probe, ProbeOp};
+pub use ring_memory::RingMemory;
+pub use metrics::RingMetrics;
+pub use timespec::Timespec;
+pub use user_data::UserData;
 #[doc(inline)]
 pub use registrar::{Registrar, Personality};
 
+#[cfg(feature = "async-io")]
+pub use compat::SmolIoUring;
+#[cfg(feature = "completion")]
+pub use completion::{CompletionFuture, Completions};
+#[cfg(feature = "tokio")]
+pub use compat::AsyncIoUring;
+
 bitflags::bitflags! {
     /// [`IoUring`] initialization flags for advanced use cases.
     ///
@@ -107,6 +151,36 @@ bitflags::bitflags! {
         const CQSIZE    = 1 << 3;
         const CLAMP     = 1 << 4;
         const ATTACH_WQ = 1 << 5;
+        /// Start the ring disabled, so that restrictions can be registered before it is
+        /// [enabled](Registrar::enable_rings).
+        const R_DISABLED = 1 << 6;
+        /// Use 128 byte SQEs, whose second half is extra command data for `uring_cmd` events.
+        /// Required for NVMe passthrough.
+        const SQE128 = 1 << 10;
+        /// Use 32 byte CQEs, which carry 16 bytes of extra completion data, see
+        /// [`CQE::big_cqe`].
+        const CQE32 = 1 << 11;
+        /// Use hybrid polling with `IOPOLL`: the kernel sleeps for part of the expected
+        /// completion time before it starts to poll, trading some latency for much less CPU time.
+        /// Requires `IOPOLL` set and Linux 6.13 or newer.
+        const HYBRID_IOPOLL = 1 << 17;
+        /// Promise the kernel that only one task submits events to the ring: the task which
+        /// set it up, or the first to submit if it starts [disabled](SetupFlags::R_DISABLED).
+        /// Submitting from any other task fails with `EEXIST`. Requires Linux 6.0 or newer.
+        const SINGLE_ISSUER = 1 << 12;
+        /// Defer the work which posts completions until the submitting task waits for them,
+        /// instead of interrupting it whenever an event completes. Completions only show up in
+        /// the completion queue once the ring is entered to wait for them. Requires
+        /// `SINGLE_ISSUER` and Linux 6.1 or newer.
+        const DEFER_TASKRUN = 1 << 13;
+        /// Use application allocated memory for the rings instead of memory mapped from the
+        /// kernel. Rings with this flag are created with [`IoUring::new_with_memory`].
+        const NO_MMAP = 1 << 14;
+        /// Don't expose the ring as a file descriptor: the ring is only reachable through its
+        /// [registered ring fd](Registrar::register_ring_fd), so it uses up no slot in the file
+        /// table. Requires `NO_MMAP` and Linux 6.5 or newer; rings with this flag are created with
+        /// [`IoUring::new_with_memory`] and can only be used from the thread which created them.
+        const REGISTERED_FD_ONLY = 1 << 15;
     }
 }
 
@@ -120,12 +194,77 @@ bitflags::bitflags! {
         const CUR_PERSONALITY   = 1 << 4;
         const FAST_POLL         = 1 << 5;
         const POLL_32BITS       = 1 << 6;
+        /// `SQPOLL` rings can submit events on files which are not registered.
+        const SQPOLL_NONFIXED   = 1 << 7;
+        /// Waits can take a timeout argument instead of submitting a timeout event.
+        const EXT_ARG           = 1 << 8;
+        /// Async work is done by native workers which are threads of the ring's task.
+        const NATIVE_WORKERS    = 1 << 9;
+        /// Registered files and buffers can be tagged.
+        const RSRC_TAGS         = 1 << 10;
+        /// Events can skip their CQE on success.
+        const CQE_SKIP          = 1 << 11;
+        /// Files of linked events are assigned when the event is started, not when it is
+        /// submitted.
+        const LINKED_FILE       = 1 << 12;
+        /// The ring fd can be registered, and registrations can go through it.
+        const REG_REG_RING      = 1 << 13;
+        /// Sends and receives can use a bundle of provided buffers at once.
+        const RECVSEND_BUNDLE   = 1 << 14;
+        /// Waits can take a minimum timeout before they wait for more than one CQE.
+        const MIN_TIMEOUT       = 1 << 15;
+        /// Reads and writes can carry extra attributes.
+        const RW_ATTR           = 1 << 16;
+        /// Waiting on the ring can be made to not count as iowait.
+        const NO_IOWAIT         = 1 << 17;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags for [`IoUring::enter`], which are passed to the `io_uring_enter` system call.
+    pub struct EnterFlags: u32 {
+        /// Wait until `min_complete` CQEs are ready.
+        const GETEVENTS         = 1 << 0;
+        /// Wake up the kernel thread of an `SQPOLL` ring.
+        const SQ_WAKEUP         = 1 << 1;
+        /// Wait until the kernel thread of an `SQPOLL` ring has made room in the submission
+        /// queue.
+        const SQ_WAIT           = 1 << 2;
+        /// The call takes an [`EnterExtArg`]. Requires [`SetupFeatures::EXT_ARG`].
+        const EXT_ARG           = 1 << 3;
+        /// The ring is entered through its [registered ring fd](Registrar::register_ring_fd).
+        const REGISTERED_RING   = 1 << 4;
+        /// The timeout of the `EnterExtArg` is an absolute `CLOCK_MONOTONIC` time. Requires
+        /// Linux 6.12 or newer.
+        const ABS_TIMER         = 1 << 5;
+    }
+}
+
+/// The extended argument of [`IoUring::enter`].
+#[derive(Clone, Copy, Default)]
+pub struct EnterExtArg<'a> {
+    /// Stop waiting once this timeout has passed, failing with `ETIME`.
+    pub timeout: Option<&'a Timespec>,
+    /// The signal mask to set while waiting.
+    pub sigmask: Option<&'a libc::sigset_t>,
+    /// Wait at most this long for more than one CQE, then return as soon as there is one.
+    /// Requires [`SetupFeatures::MIN_TIMEOUT`].
+    pub min_wait: Duration,
+}
+
+impl fmt::Debug for EnterExtArg<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("EnterExtArg")
+            .field("timeout", &self.timeout)
+            .field("sigmask", &self.sigmask.is_some())
+            .field("min_wait", &self.min_wait)
+            .finish()
     }
 }
 
 /// The main interface to kernel IO using `io_uring`.
 ///
-/// `IoUring` is a high-level wrapper around an [`io_uring`](uring_sys::io_uring) object.
+/// `IoUring` is a high-level wrapper around an [`io_uring`](sys::io_uring) object.
 ///
 /// `IoUring`s are constructed with a requested number of ring buffer entries and possibly a set of
 /// [`SetupFlags`](SetupFlags). Allocations for `IoUring` are `memlocked` and will not be paged
@@ -161,8 +300,22 @@ bitflags::bitflags! {
 /// # Ok(())
 /// # }
 /// ```
+// `repr(C)` keeps the raw ring first, so a pointer to it is also a pointer to the `IoUring`
+#[repr(C)]
 pub struct IoUring {
-    ring: uring_sys::io_uring,
+    ring: sys::io_uring,
+    params: Params,
+    registered_ring_fd: AtomicI32,
+    // the thread which registered the ring fd, whose table of registered ring fds it is in
+    registered_ring_tid: AtomicI32,
+    memory: Option<RingMemory>,
+    // shared buffers registered with the kernel, kept alive until they are unregistered
+    retained_buffers: Mutex<Vec<Box<dyn Any + Send + Sync>>>,
+    registered_eventfd: Mutex<Option<registrar::EventFd>>,
+    personalities: Mutex<Vec<(Personality, registrar::PersonalityCreds)>>,
+    counters: metrics::Counters,
+    // the kernel's SQ head as of the last time it was loaded, see `submission_queue::has_room`
+    cached_sq_head: AtomicU32,
 }
 
 impl IoUring {
@@ -182,16 +335,180 @@ impl IoUring {
     /// use cases.
     pub fn new_with_flags(entries: u32, flags: SetupFlags, features: SetupFeatures) -> io::Result<IoUring> {
         unsafe {
-            let mut params: uring_sys::io_uring_params = mem::zeroed();
+            let mut params: sys::io_uring_params = mem::zeroed();
             params.flags = flags.bits();
             params.features = features.bits();
-            let mut ring = MaybeUninit::uninit();
-            resultify(uring_sys::io_uring_queue_init_params(
-                    entries as _,
-                    ring.as_mut_ptr(),
-                    &mut params,
-            ))?;
-            Ok(IoUring { ring: ring.assume_init() })
+            IoUring::new_with_params(entries, &mut params)
+        }
+    }
+
+    /// Creates a new `IoUring` which shares the io-wq worker pool of `other`, rather than
+    /// starting its own, by setting [`SetupFlags::ATTACH_WQ`] in addition to `flags`.
+    ///
+    /// This lets per-thread rings share one pool of async workers.
+    ///
+    /// ```
+    /// # use std::io;
+    /// # use iou::{IoUring, SetupFlags};
+    /// # fn main() -> io::Result<()> {
+    /// let ring = IoUring::new(32)?;
+    /// let worker_ring = IoUring::new_attached(32, SetupFlags::empty(), &ring)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// Returns `EBADF` if `other` has no file descriptor, because it was created with
+    /// [`SetupFlags::REGISTERED_FD_ONLY`].
+    pub fn new_attached(entries: u32, flags: SetupFlags, other: &IoUring) -> io::Result<IoUring> {
+        unsafe {
+            let mut params: sys::io_uring_params = mem::zeroed();
+            params.flags = (flags | SetupFlags::ATTACH_WQ).bits();
+            params.wq_fd = other.raw_fd()? as u32;
+            IoUring::new_with_params(entries, &mut params)
+        }
+    }
+
+    unsafe fn new_with_params(entries: u32, params: &mut sys::io_uring_params) -> io::Result<IoUring> {
+        let fd = sys::io_uring_setup(entries, params)?;
+        match map_rings(fd, params) {
+            Ok(ring)    => Ok(IoUring::from_raw_parts(ring, Params::new(params), None)),
+            Err(err)    => {
+                libc::close(fd);
+                Err(err)
+            }
+        }
+    }
+
+    /// Rebuilds an `IoUring` from the file descriptor of an existing ring and the params it was
+    /// set up with, by mapping its rings into this process.
+    ///
+    /// This lets a ring be inherited across `fork` and `exec`, or received over a unix socket,
+    /// and then driven from the new process. The ring is shared with every other process that
+    /// has it mapped, so only one of them should submit and complete events at a time.
+    ///
+    /// Events are submitted through an SQ array which maps every slot to the SQE of the same
+    /// index. If the kernel has consumed every entry of the submission queue, the array is set up
+    /// that way again; otherwise it is left as it is, so that the pending entries are not
+    /// clobbered.
+    ///
+    /// # Safety
+    /// `fd` must be the file descriptor of an io_uring instance and `params` must be the
+    /// [params](IoUring::params) which the kernel returned when that instance was set up. If its
+    /// submission queue has entries the kernel has not consumed yet, its SQ array must already
+    /// map every slot to the SQE of the same index, as it does for rings set up by this library. On
+    /// success, the `IoUring` takes ownership of `fd` and closes it when it is dropped; on error,
+    /// `fd` is left open.
+    pub unsafe fn from_ring_fd(fd: RawFd, params: &Params) -> io::Result<IoUring> {
+        if params.flags().contains(SetupFlags::NO_MMAP) {
+            // the rings live in memory of the process which set the ring up
+            return Err(io::Error::from_raw_os_error(libc::EINVAL));
+        }
+
+        map_rings(fd, params.raw()).map(|ring| IoUring::from_raw_parts(ring, params.clone(), None))
+    }
+
+    /// Creates a new `IoUring` whose rings live in application allocated [`RingMemory`], rather
+    /// than in memory mapped from the kernel. The number of entries is taken from the memory.
+    ///
+    /// This sets [`SetupFlags::NO_MMAP`] in addition to `flags`. Big entries
+    /// ([`SetupFlags::SQE128`] and [`SetupFlags::CQE32`]) are not supported.
+    ///
+    /// With [`SetupFlags::REGISTERED_FD_ONLY`], the ring never has a file descriptor: it is
+    /// entered and registered through its registered ring fd, and [`raw_fd`](IoUring::raw_fd)
+    /// returns an error.
+    ///
+    /// ```no_run
+    /// # use std::io;
+    /// # use iou::{IoUring, RingMemory, SetupFlags, SetupFeatures};
+    /// # fn main() -> io::Result<()> {
+    /// let memory = RingMemory::with_hugepages(4096).or_else(|_| RingMemory::new(4096))?;
+    /// let mut ring = IoUring::new_with_memory(memory, SetupFlags::empty(), SetupFeatures::empty())?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new_with_memory(memory: RingMemory, flags: SetupFlags, features: SetupFeatures)
+        -> io::Result<IoUring>
+    {
+        if flags.intersects(SetupFlags::SQE128 | SetupFlags::CQE32) {
+            // RingMemory is sized for regular entries
+            return Err(io::Error::from_raw_os_error(libc::EINVAL));
+        }
+
+        unsafe {
+            let (rings, rings_len) = memory.rings();
+            let (sqes, sqes_len) = memory.sqes();
+
+            let mut params: sys::io_uring_params = mem::zeroed();
+            params.flags = (flags | SetupFlags::NO_MMAP).bits();
+            params.features = features.bits();
+            params.sq_off.resv2 = sqes as u64;
+            params.cq_off.resv[1] = rings as u64;
+
+            let fd = sys::io_uring_setup(memory.entries(), &mut params)?;
+
+            // with REGISTERED_FD_ONLY, the kernel returns the index of the registered ring fd
+            let (fd, registered_ring_fd) = match flags.contains(SetupFlags::REGISTERED_FD_ONLY) {
+                true    => (-1, fd),
+                false   => (fd, -1),
+            };
+
+            let rings = rings as *mut u8;
+            let ring = raw_ring_from_params(fd, &params, (rings, rings_len), (rings, rings_len), sqes as _);
+            debug_assert!(params.sq_entries as usize * mem::size_of::<sys::io_uring_sqe>() <= sqes_len);
+
+            let ring = IoUring::from_raw_parts(ring, Params::new(&params), Some(memory));
+            if registered_ring_fd != -1 {
+                ring.set_registered_ring_fd(registered_ring_fd);
+            }
+            Ok(ring)
+        }
+    }
+
+    /// An estimate of the memory, in bytes, which the kernel allocates for the rings of an
+    /// `IoUring` set up with `entries` entries and `flags`, like liburing's
+    /// `io_uring_mlock_size`. The estimate errs on the high side, and assumes the default size of
+    /// the completion queue.
+    ///
+    /// Kernels without [`SetupFeatures::NATIVE_WORKERS`] (before Linux 5.12) charge this memory
+    /// against `RLIMIT_MEMLOCK`, and fail to set up a ring which doesn't fit with `ENOMEM` or
+    /// `EPERM`. Checking the limit first lets an application report what is actually wrong:
+    ///
+    /// ```
+    /// # use iou::{IoUring, SetupFlags};
+    /// let needed = IoUring::ring_memory_size(4096, SetupFlags::empty());
+    /// let mut limit = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+    /// unsafe { libc::getrlimit(libc::RLIMIT_MEMLOCK, &mut limit); }
+    /// if (limit.rlim_cur as usize) < needed {
+    ///     eprintln!("RLIMIT_MEMLOCK is {} bytes, older kernels need {} bytes for the ring", limit.rlim_cur, needed);
+    /// }
+    /// ```
+    pub fn ring_memory_size(entries: u32, flags: SetupFlags) -> usize {
+        ring_memory::footprint(entries, flags)
+    }
+
+    /// Resize the submission and completion queues of the ring, see
+    /// [`Registrar::resize_rings`].
+    ///
+    /// Unlike the registrar's method, this is safe: borrowing the ring mutably ensures that
+    /// nothing else is using its queues.
+    pub fn resize_rings(&mut self, sq_entries: u32, cq_entries: u32) -> io::Result<()> {
+        unsafe { self.registrar().resize_rings(sq_entries, cq_entries) }
+    }
+
+    fn from_raw_parts(ring: sys::io_uring, params: Params, memory: Option<RingMemory>) -> IoUring {
+        let sq_head = unsafe { *ring.sq.khead };
+        IoUring {
+            ring,
+            params,
+            registered_ring_fd: AtomicI32::new(-1),
+            registered_ring_tid: AtomicI32::new(0),
+            memory,
+            retained_buffers: Mutex::new(Vec::new()),
+            registered_eventfd: Mutex::new(None),
+            personalities: Mutex::new(Vec::new()),
+            counters: metrics::Counters::default(),
+            cached_sq_head: AtomicU32::new(sq_head),
         }
     }
 
@@ -215,23 +532,79 @@ impl IoUring {
         (SubmissionQueue::new(&*self), CompletionQueue::new(&*self), Registrar::new(&*self))
     }
 
+    /// Split the `IoUring` into its three parts, which own the ring rather than borrowing it.
+    ///
+    /// Unlike [`queues`](IoUring::queues), the parts can be moved to different threads, for
+    /// example to submit events from one thread and reap their completions on another. The ring
+    /// is torn down once all of the parts, and anything registered through the `Registrar` which
+    /// refers to the ring, have been dropped.
+    ///
+    /// ```
+    /// # use std::io;
+    /// # use iou::IoUring;
+    /// # fn main() -> io::Result<()> {
+    /// let (mut sq, mut cq, _reg) = IoUring::new(8)?.into_parts();
+    ///
+    /// let reaper = std::thread::spawn(move || cq.wait_for_cqe().map(|cqe| cqe.user_data()));
+    ///
+    /// unsafe {
+    ///     let mut sqe = sq.prepare_sqe().unwrap();
+    ///     sqe.prep_nop();
+    ///     sqe.set_user_data(42);
+    /// }
+    /// sq.submit()?;
+    /// assert_eq!(reaper.join().unwrap()?, 42);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn into_parts(self) -> (SubmissionQueue<'static>, CompletionQueue<'static>, Registrar<'static>) {
+        let ring = Arc::new(self);
+        (
+            SubmissionQueue::owned(ring.clone()),
+            CompletionQueue::owned(ring.clone()),
+            Registrar::owned(ring),
+        )
+    }
+
     pub fn probe(&mut self) -> io::Result<Probe> {
-        Probe::for_ring(&mut self.ring)
+        Probe::for_ring(NonNull::from(&*self))
     }
 
     /// Returns the next [`SQE`] which can be prepared to submit.
+    #[inline]
     pub fn prepare_sqe(&mut self) -> Option<SQE<'_>> {
         unsafe {
             submission_queue::prepare_sqe(&mut self.ring)
         }
     }
 
+    /// Returns the next `N` [`SQE`]s as an array, or `None` if there is not enough room for all
+    /// of them. See [`SubmissionQueue::prepare_sqes_array`].
+    pub fn prepare_sqes_array<const N: usize>(&mut self) -> Option<[SQE<'_>; N]> {
+        unsafe {
+            submission_queue::prepare_sqes_array(&mut self.ring)
+        }
+    }
+
+    /// Returns the next [`SQE`], submitting the prepared [`SQE`]s first if the queue is full.
+    /// Also returns the number of [`SQE`]s that were submitted to make room.
+    ///
+    /// See [`SubmissionQueue::prepare_or_submit`].
+    pub fn prepare_or_submit(&mut self) -> io::Result<(SQE<'_>, u32)> {
+        let flushed = match self.sq_space_left() {
+            0 => self.submit_sqes()?,
+            _ => 0,
+        };
+        let sqe = self.prepare_sqe().ok_or_else(|| io::Error::from_raw_os_error(libc::EBUSY))?;
+        Ok((sqe, flushed))
+    }
+
     /// Returns the next `count` [`SQE`]s which can be prepared to submit as an iterator.
     ///
     /// See the [`SQEs`] type for more information about how these multiple SQEs can be used.
     pub fn prepare_sqes(&mut self, count: u32) -> Option<SQEs<'_>> {
         unsafe {
-            submission_queue::prepare_sqes(&mut self.ring.sq, count)
+            submission_queue::prepare_sqes(&mut self.ring, count)
         }
     }
 
@@ -257,36 +630,61 @@ impl IoUring {
 
     /// Peek for any [`CQE`] that is already completed, without blocking. This will consume that
     /// CQE.
+    #[inline]
     pub fn peek_for_cqe(&mut self) -> Option<CQE> {
-        unsafe {
-            let mut cqe = MaybeUninit::uninit();
-            let count = uring_sys::io_uring_peek_batch_cqe(&mut self.ring, cqe.as_mut_ptr(), 1);
-
-            if count > 0 {
-                Some(CQE::new(NonNull::from(&self.ring), &mut *cqe.assume_init()))
-            } else {
-                None
-            }
-        }
+        self.cq().peek_for_cqe()
     }
 
     /// Block until at least one [`CQE`] is completed. This will consume that CQE.
     pub fn wait_for_cqe(&mut self) -> io::Result<CQE> {
         let ring = NonNull::from(&self.ring);
-        self.inner_wait_for_cqes(1, ptr::null()).map(|cqe| CQE::new(ring, cqe))
+        self.inner_wait_for_cqes(1, None).map(|cqe| CQE::new(ring, cqe))
     }
 
     /// Block until a [`CQE`] is ready or timeout.
     pub fn wait_for_cqe_with_timeout(&mut self, duration: Duration)
         -> io::Result<CQE>
     {
-        let ts = uring_sys::__kernel_timespec {
-            tv_sec: duration.as_secs() as _,
-            tv_nsec: duration.subsec_nanos() as _
-        };
-
+        let ts = Timespec::from(duration);
         let ring = NonNull::from(&self.ring);
-        self.inner_wait_for_cqes(1, &ts).map(|cqe| CQE::new(ring, cqe))
+        self.inner_wait_for_cqes(1, Some(&ts)).map(|cqe| CQE::new(ring, cqe))
+    }
+
+    /// Submit all prepared [`SQE`]s and wait until `count` [`CQE`]s are ready or `deadline` has
+    /// passed. See [`CompletionQueue::wait_until`].
+    ///
+    /// Like [`wait_for_cqe_with_timeout`](IoUring::wait_for_cqe_with_timeout), this works on
+    /// kernels without [`SetupFeatures::EXT_ARG`] by submitting the timeout as an event.
+    pub fn wait_for_cqes_until(&mut self, count: u32, deadline: Instant) -> io::Result<()> {
+        CompletionQueue::submitting(self).wait_until(count, deadline)
+    }
+
+    /// Submit all prepared [`SQE`]s and wait until `count` [`CQE`]s are ready, returning early
+    /// once `min_wait` has passed if any are. See [`CompletionQueue::wait_with_min_timeout`].
+    pub fn wait_for_cqes_with_min_timeout(&mut self, count: u32, min_wait: Duration, timeout: Option<Duration>)
+        -> io::Result<()>
+    {
+        CompletionQueue::submitting(self).wait_with_min_timeout(count, min_wait, timeout)
+    }
+
+    /// Block until a [`CQE`] is completed, with the thread's signal mask replaced by `sigmask`
+    /// while it is blocked. See [`CompletionQueue::wait_for_cqe_with_sigmask`].
+    pub fn wait_for_cqe_with_sigmask(&mut self, sigmask: &libc::sigset_t) -> io::Result<CQE> {
+        self.cq().wait_for_cqe_with_sigmask(sigmask)
+    }
+
+    /// Wait until `count` [`CQE`]s are ready, with the thread's signal mask replaced by
+    /// `sigmask` while it is blocked. See [`CompletionQueue::wait_with_sigmask`].
+    pub fn wait_for_cqes_with_sigmask(&mut self, count: u32, sigmask: Option<&libc::sigset_t>) -> io::Result<()> {
+        self.cq().wait_with_sigmask(count, sigmask)
+    }
+
+    /// Submit all prepared [`SQE`]s to the kernel and wait until at least `wait_for` events have
+    /// completed, with the thread's signal mask replaced by `sigmask` while it is blocked.
+    pub fn submit_sqes_and_wait_with_sigmask(&mut self, wait_for: u32, sigmask: &libc::sigset_t)
+        -> io::Result<u32>
+    {
+        self.sq().submit_and_wait_with_sigmask(wait_for, sigmask)
     }
 
     /// Returns an iterator of [`CQE`]s which are ready from the kernel.
@@ -294,46 +692,163 @@ impl IoUring {
         CQEs::new(NonNull::from(&mut self.ring))
     }
 
+    /// Pass every ready CQE to `f` in place, then mark them all as seen at once. Returns the number
+    /// of CQEs passed to `f`. See [`CompletionQueue::for_each`].
+    #[inline]
+    pub fn for_each_cqe(&mut self, f: impl FnMut(cqe::CQERef<'_>)) -> usize {
+        self.cq().for_each(f)
+    }
+
     /// Returns an iterator of [`CQE`]s which will block when there are no CQEs ready. It will
     /// block until at least `count` are ready, and then continue iterating.
     ///
     /// This iterator will never be exhausted; every time it runs out of CQEs it will block the
     /// thread and wait for more to be ready.
     pub fn cqes_blocking(&mut self, count: u32) -> CQEsBlocking<'_> {
-        CQEsBlocking::new(NonNull::from(&mut self.ring), count)
+        CQEsBlocking::new(NonNull::from(&*self), count)
+    }
+
+    /// Returns an iterator of [`CQE`]s which blocks for at most `timeout` at a time when there
+    /// are no CQEs ready, yielding an `ETIME` error whenever it times out. See
+    /// [`CompletionQueue::cqes_blocking_with_timeout`].
+    ///
+    /// Each wait submits the prepared [`SQE`]s first, and works on kernels without
+    /// [`SetupFeatures::EXT_ARG`] by submitting the timeout as an event.
+    pub fn cqes_blocking_with_timeout(&mut self, count: u32, timeout: Duration) -> CQEsBlocking<'_> {
+        CQEsBlocking::with_timeout(NonNull::from(&*self), count, timeout, true)
+    }
+
+    /// Poll for completions once and return an iterator of at most `budget` ready [`CQE`]s. See
+    /// [`CompletionQueue::reap`].
+    pub fn reap(&mut self, budget: usize) -> io::Result<iter::Take<CQEs<'_>>> {
+        self.cq().poll()?;
+        Ok(self.cqes().take(budget))
+    }
+
+    /// Poll for completions until `count` [`CQE`]s are ready or `deadline` passes. See
+    /// [`CompletionQueue::poll_until`].
+    pub fn poll_until(&mut self, count: u32, deadline: Instant) -> io::Result<u32> {
+        self.cq().poll_until(count, deadline)
+    }
+
+    /// Spin until a [`CQE`] is ready, then fall back to waiting for one for at most
+    /// `fallback_timeout`. See [`CompletionQueue::poll_adaptive`].
+    ///
+    /// The fallback wait submits the prepared [`SQE`]s first, and works on kernels without
+    /// [`SetupFeatures::EXT_ARG`] by submitting the timeout as an event.
+    pub fn poll_adaptive(&mut self, spin_budget: u32, fallback_timeout: Duration) -> io::Result<u32> {
+        CompletionQueue::submitting(self).poll_adaptive(spin_budget, fallback_timeout)
     }
 
     /// Wait until `count` [`CQE`]s are ready, without submitting any events.
     pub fn wait_for_cqes(&mut self, count: u32) -> io::Result<()> {
-        self.inner_wait_for_cqes(count as _, ptr::null()).map(|_| ())
+        self.inner_wait_for_cqes(count as _, None).map(|_| ())
+    }
+
+    /// Wait until `count` [`CQE`]s are ready and return all ready [`CQE`]s, which may be more
+    /// than `count`. See [`CompletionQueue::wait_for_cqes_batch`].
+    pub fn wait_for_cqes_batch(&mut self, count: u32) -> io::Result<Vec<CQE>> {
+        self.cq().wait_for_cqes_batch(count)
     }
 
-    fn inner_wait_for_cqes(&mut self, count: u32, ts: *const uring_sys::__kernel_timespec)
-        -> io::Result<&mut uring_sys::io_uring_cqe>
+    /// Wait until `count` [`CQE`]s are ready and append all ready [`CQE`]s to `cqes`, returning
+    /// how many were appended. See [`CompletionQueue::wait_for_cqes_into`].
+    pub fn wait_for_cqes_into(&mut self, count: u32, cqes: &mut Vec<CQE>) -> io::Result<usize> {
+        self.cq().wait_for_cqes_into(count, cqes)
+    }
+
+    fn inner_wait_for_cqes(&mut self, count: u32, ts: Option<&Timespec>)
+        -> io::Result<&mut sys::io_uring_cqe>
     {
-        unsafe {
-            let mut cqe = MaybeUninit::uninit();
+        unsafe { completion_queue::wait_for_cqes(NonNull::from(&*self), count, ts, true) }
+    }
 
-            resultify(uring_sys::io_uring_wait_cqes(
-                &mut self.ring,
-                cqe.as_mut_ptr(),
-                count,
-                ts,
-                ptr::null(),
-            ))?;
+    /// Returns the features the kernel granted when the ring was set up.
+    pub fn features(&self) -> SetupFeatures {
+        self.params.features()
+    }
 
-            Ok(&mut *cqe.assume_init())
-        }
+    /// Whether the kernel granted all of `features` when the ring was set up.
+    ///
+    /// ```
+    /// # use std::io;
+    /// # use iou::{IoUring, SetupFeatures};
+    /// # fn main() -> io::Result<()> {
+    /// let ring = IoUring::new(8)?;
+    /// if ring.has_feature(SetupFeatures::CQE_SKIP) {
+    ///     // successful events can skip their CQE
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn has_feature(&self, features: SetupFeatures) -> bool {
+        self.features().contains(features)
+    }
+
+    /// Returns the parameters the kernel granted when the ring was set up, such as the actual
+    /// number of entries and the supported features.
+    pub fn params(&self) -> &Params {
+        &self.params
     }
 
-    pub fn raw(&self) -> &uring_sys::io_uring {
+    pub fn raw(&self) -> &sys::io_uring {
         &self.ring
     }
 
-    pub unsafe fn raw_mut(&mut self) -> &mut uring_sys::io_uring {
+    /// # Safety
+    ///
+    /// The ring must be left in a state this library can keep driving. In particular, every slot
+    /// of the SQ array must map to the SQE of the same index when the next events are submitted.
+    pub unsafe fn raw_mut(&mut self) -> &mut sys::io_uring {
         &mut self.ring
     }
 
+    /// Make the `io_uring_enter` system call directly, for patterns the other methods don't
+    /// cover. The prepared SQEs are made visible to the kernel first, so `to_submit` can count
+    /// them. Returns the number of SQEs the kernel consumed.
+    ///
+    /// With an `ext_arg`, [`EnterFlags::EXT_ARG`] is set as well. [`EnterFlags::REGISTERED_RING`]
+    /// is set if, and only if, the ring has a [registered ring fd](Registrar::register_ring_fd),
+    /// which the call then goes through.
+    ///
+    /// ```
+    /// # use std::io;
+    /// # use iou::{EnterFlags, IoUring};
+    /// # fn main() -> io::Result<()> {
+    /// let mut ring = IoUring::new(2)?;
+    /// unsafe { ring.prepare_sqe().unwrap().prep_nop(); }
+    /// assert_eq!(ring.enter(1, 1, EnterFlags::GETEVENTS, None)?, 1);
+    /// assert!(ring.peek_for_cqe().is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn enter(&mut self, to_submit: u32, min_complete: u32, flags: EnterFlags, ext_arg: Option<&EnterExtArg<'_>>)
+        -> io::Result<u32>
+    {
+        let flags = flags - EnterFlags::REGISTERED_RING;
+        unsafe {
+            submission_queue::flush(&mut self.ring);
+            let ring = NonNull::from(&*self);
+            match ext_arg {
+                Some(ext_arg)   => {
+                    let arg = sys::io_uring_getevents_arg {
+                        sigmask: ext_arg.sigmask.map_or(0, |sigmask| sigmask as *const _ as u64),
+                        // the kernel expects the size of its own sigset, which has 64 signals
+                        sigmask_sz: 64 / 8,
+                        min_wait_usec: ext_arg.min_wait.as_micros().min(u32::MAX as u128) as u32,
+                        ts: ext_arg.timeout.map_or(0, |ts| ts.raw() as *const _ as u64),
+                    };
+                    let arg = match flags.contains(EnterFlags::ABS_TIMER) {
+                        true    => EnterArg::ExtAbs(&arg),
+                        false   => EnterArg::Ext(&arg),
+                    };
+                    enter_with_arg(ring, to_submit, min_complete, flags.bits(), arg)
+                }
+                None            => enter(ring, to_submit, min_complete, flags.bits()),
+            }
+        }
+    }
+
     pub fn cq_ready(&mut self) -> u32 {
         self.cq().ready()
     }
@@ -346,6 +861,25 @@ impl IoUring {
         self.sq().space_left()
     }
 
+    /// The number of SQEs the kernel has dropped, see [`SubmissionQueue::dropped`].
+    pub fn sq_dropped(&mut self) -> u32 {
+        self.sq().dropped()
+    }
+
+    /// A snapshot of the counters of the activity of this ring, see [`RingMetrics`].
+    pub fn metrics(&self) -> RingMetrics {
+        unsafe {
+            let cq_overflow = (*(self.ring.cq.koverflow as *const AtomicU32)).load(Ordering::Relaxed);
+            let sq_dropped = (*(self.ring.sq.kdropped as *const AtomicU32)).load(Ordering::Relaxed);
+            self.counters.snapshot(cq_overflow, sq_dropped)
+        }
+    }
+
+    /// The flags the kernel has set on the submission queue ring, see [`SubmissionQueue::flags`].
+    pub fn sq_flags(&mut self) -> SQFlags {
+        self.sq().flags()
+    }
+
     pub fn cq_eventfd_enabled(&mut self) -> bool {
         self.cq().eventfd_enabled()
     }
@@ -354,8 +888,350 @@ impl IoUring {
         self.cq().eventfd_toggle(enabled)
     }
 
-    pub fn raw_fd(&self) -> RawFd {
-        self.ring.ring_fd
+    /// Returns the file descriptor of the ring.
+    ///
+    /// # Errors
+    /// Returns `EBADF` if the ring was created with [`SetupFlags::REGISTERED_FD_ONLY`], so that
+    /// it has no file descriptor.
+    pub fn raw_fd(&self) -> io::Result<RawFd> {
+        match self.ring.ring_fd {
+            -1  => Err(io::Error::from_raw_os_error(libc::EBADF)),
+            fd  => Ok(fd),
+        }
+    }
+
+    /// Returns the file descriptor an event loop should watch to learn of completions: the
+    /// [`EventFd`](registrar::EventFd) registered with
+    /// [`register_owned_eventfd`](registrar::Registrar::register_owned_eventfd) if there is one,
+    /// and the file descriptor of the ring otherwise. Both are readable when there are CQEs to
+    /// reap.
+    ///
+    /// # Errors
+    /// Returns `EBADF` if there is no eventfd and the ring has no file descriptor, as with
+    /// [`raw_fd`](IoUring::raw_fd).
+    pub fn notify_fd(&self) -> io::Result<RawFd> {
+        match &*self.registered_eventfd.lock().unwrap() {
+            Some(eventfd)   => Ok(eventfd.as_raw_fd()),
+            None            => self.raw_fd(),
+        }
+    }
+
+    /// Duplicate the file descriptor of the ring, for example to pass it to another process which
+    /// rebuilds the ring with [`from_ring_fd`](IoUring::from_ring_fd).
+    ///
+    /// # Errors
+    /// Returns `EBADF` if the ring was created with [`SetupFlags::REGISTERED_FD_ONLY`].
+    pub fn try_clone_fd(&self) -> io::Result<OwnedFd> {
+        self.raw_fd()?;
+        self.as_fd().try_clone_to_owned()
+    }
+
+    // record a registered ring fd as registered by the calling thread
+    pub(crate) fn set_registered_ring_fd(&self, index: i32) {
+        self.registered_ring_tid.store(current_tid(), Ordering::Relaxed);
+        self.registered_ring_fd.store(index, Ordering::Release);
+    }
+
+    /// Returns the index of the ring's registered ring fd, if it has one. See
+    /// [`Registrar::register_ring_fd`] and [`SetupFlags::REGISTERED_FD_ONLY`].
+    pub fn registered_fd(&self) -> Option<u32> {
+        match self.registered_ring_fd.load(Ordering::Relaxed) {
+            -1      => None,
+            index   => Some(index as u32),
+        }
+    }
+}
+
+/// How far to shift an SQ index to get the offset of its SQE, in units of `io_uring_sqe`.
+pub(crate) fn sqe_shift(flags: u32) -> u32 {
+    (flags & SetupFlags::SQE128.bits() != 0) as u32
+}
+
+/// How far to shift a CQ index to get the offset of its CQE, in units of `io_uring_cqe`.
+pub(crate) fn cqe_shift(flags: u32) -> u32 {
+    (flags & SetupFlags::CQE32.bits() != 0) as u32
+}
+
+/// Map the rings of the ring `fd`, with entries of the sizes in the flags of `params`.
+unsafe fn map_rings(fd: RawFd, params: &sys::io_uring_params) -> io::Result<sys::io_uring> {
+    let sqe_len = mem::size_of::<sys::io_uring_sqe>() << sqe_shift(params.flags);
+    let cqe_len = mem::size_of::<sys::io_uring_cqe>() << cqe_shift(params.flags);
+    let mut sq_ring_len = params.sq_off.array as usize + params.sq_entries as usize * mem::size_of::<u32>();
+    let mut cq_ring_len = params.cq_off.cqes as usize + params.cq_entries as usize * cqe_len;
+    let single_mmap = params.features & SetupFeatures::SINGLE_MMAP.bits() != 0;
+    if single_mmap {
+        sq_ring_len = sq_ring_len.max(cq_ring_len);
+        cq_ring_len = sq_ring_len;
+    }
+
+    let sq_ring = map_ring(fd, sq_ring_len, sys::IORING_OFF_SQ_RING)?;
+    let cq_ring = match single_mmap {
+        true    => sq_ring,
+        false   => match map_ring(fd, cq_ring_len, sys::IORING_OFF_CQ_RING) {
+            Ok(cq_ring) => cq_ring,
+            Err(err)    => {
+                libc::munmap(sq_ring as _, sq_ring_len);
+                return Err(err);
+            }
+        },
+    };
+    let sqes = match map_ring(fd, params.sq_entries as usize * sqe_len, sys::IORING_OFF_SQES) {
+        Ok(sqes)    => sqes,
+        Err(err)    => {
+            if !single_mmap {
+                libc::munmap(cq_ring as _, cq_ring_len);
+            }
+            libc::munmap(sq_ring as _, sq_ring_len);
+            return Err(err);
+        }
+    };
+
+    Ok(raw_ring_from_params(fd, params, (sq_ring, sq_ring_len), (cq_ring, cq_ring_len), sqes as _))
+}
+
+unsafe fn map_ring(fd: RawFd, len: usize, offset: libc::off_t) -> io::Result<*mut u8> {
+    let ptr = libc::mmap(
+        ptr::null_mut(),
+        len,
+        libc::PROT_READ | libc::PROT_WRITE,
+        libc::MAP_SHARED | libc::MAP_POPULATE,
+        fd,
+        offset,
+    );
+    match ptr == libc::MAP_FAILED {
+        true    => Err(io::Error::last_os_error()),
+        false   => Ok(ptr as *mut u8),
+    }
+}
+
+/// Build a ring from the offsets the kernel returned in `params` and the mapped ring memory.
+unsafe fn raw_ring_from_params(
+    fd: RawFd,
+    params: &sys::io_uring_params,
+    (sq_ring, sq_ring_len): (*mut u8, usize),
+    (cq_ring, cq_ring_len): (*mut u8, usize),
+    sqes: *mut sys::io_uring_sqe,
+) -> sys::io_uring {
+    let mut ring = sys::io_uring {
+        sq: sys::io_uring_sq {
+            khead: sq_ring.add(params.sq_off.head as usize) as _,
+            ktail: sq_ring.add(params.sq_off.tail as usize) as _,
+            kring_mask: sq_ring.add(params.sq_off.ring_mask as usize) as _,
+            kring_entries: sq_ring.add(params.sq_off.ring_entries as usize) as _,
+            kflags: sq_ring.add(params.sq_off.flags as usize) as _,
+            kdropped: sq_ring.add(params.sq_off.dropped as usize) as _,
+            array: sq_ring.add(params.sq_off.array as usize) as _,
+            sqes,
+            sqe_head: 0,
+            sqe_tail: 0,
+            ring_sz: sq_ring_len,
+            ring_ptr: sq_ring as _,
+        },
+        cq: sys::io_uring_cq {
+            khead: cq_ring.add(params.cq_off.head as usize) as _,
+            ktail: cq_ring.add(params.cq_off.tail as usize) as _,
+            kring_mask: cq_ring.add(params.cq_off.ring_mask as usize) as _,
+            kring_entries: cq_ring.add(params.cq_off.ring_entries as usize) as _,
+            // cq_off.flags is the low half of the first reserved word, and 0 on kernels which
+            // predate it
+            kflags: match params.cq_off.resv[0] as u32 {
+                0       => ptr::null_mut(),
+                flags   => cq_ring.add(flags as usize) as _,
+            },
+            koverflow: cq_ring.add(params.cq_off.overflow as usize) as _,
+            cqes: cq_ring.add(params.cq_off.cqes as usize) as _,
+            ring_sz: cq_ring_len,
+            ring_ptr: cq_ring as _,
+        },
+        flags: params.flags,
+        ring_fd: fd,
+    };
+
+    // Map every slot of the SQ array to the SQE of the same index once and for all, so that
+    // submitting only has to publish the new tail. A ring rebuilt from its fd may have been
+    // driven already, so the local queue starts where the kernel's tail is, and the array is
+    // left alone while it has entries the kernel has not consumed yet.
+    let sq = &mut ring.sq;
+    if (*(sq.khead as *const AtomicU32)).load(Ordering::Acquire) == *sq.ktail {
+        for index in 0..params.sq_entries {
+            *sq.array.add(index as usize) = index;
+        }
+    }
+    sq.sqe_head = *sq.ktail;
+    sq.sqe_tail = *sq.ktail;
+    ring
+}
+
+/// Unmap the rings of a ring whose memory was mapped from the kernel, and close it.
+unsafe fn unmap_rings(ring: &mut sys::io_uring) {
+    unmap_ring_memory(ring);
+    if ring.ring_fd != -1 {
+        libc::close(ring.ring_fd);
+    }
+}
+
+/// Unmap the rings of a ring whose memory was mapped from the kernel, leaving it open.
+unsafe fn unmap_ring_memory(ring: &sys::io_uring) {
+    let sqe_len = mem::size_of::<sys::io_uring_sqe>() << sqe_shift(ring.flags);
+    libc::munmap(ring.sq.sqes as _, *ring.sq.kring_entries as usize * sqe_len);
+    libc::munmap(ring.sq.ring_ptr, ring.sq.ring_sz);
+    if !ring.cq.ring_ptr.is_null() && ring.cq.ring_ptr != ring.sq.ring_ptr {
+        libc::munmap(ring.cq.ring_ptr, ring.cq.ring_sz);
+    }
+}
+
+/// Unregister the registered ring fd of a ring, if the calling thread registered it. A ring which
+/// only has a registered ring fd is closed by this.
+unsafe fn unregister_ring_fd(ring: &IoUring) {
+    if let Some(index) = registered_ring_index(NonNull::from(ring)) {
+        let update = sys::io_uring_rsrc_update { offset: index as u32, ..Default::default() };
+        let _ = register(NonNull::from(ring), sys::IORING_UNREGISTER_RING_FDS, &update as *const _ as *const _, 1);
+    }
+}
+
+/// The index of the registered ring fd of a ring, if the calling thread registered it. Each thread
+/// has its own table of registered ring fds, so on any other thread the index is meaningless, or
+/// refers to another ring.
+#[inline]
+pub(crate) unsafe fn registered_ring_index(ring: NonNull<IoUring>) -> Option<i32> {
+    // the thread is stored before the index, see `IoUring::set_registered_ring_fd`
+    match (*ring.as_ptr()).registered_ring_fd.load(Ordering::Acquire) {
+        -1      => None,
+        index   => match (*ring.as_ptr()).registered_ring_tid.load(Ordering::Relaxed) == current_tid() {
+            true    => Some(index),
+            false   => None,
+        },
+    }
+}
+
+#[inline]
+pub(crate) fn current_tid() -> i32 {
+    thread_local!(static TID: i32 = unsafe { libc::syscall(libc::SYS_gettid) as i32 });
+    TID.with(|tid| *tid)
+}
+
+/// Returns the raw ring of an `IoUring` which is only held by pointer, as the split queues are.
+#[inline(always)]
+pub(crate) fn raw_ring(ring: NonNull<IoUring>) -> *mut sys::io_uring {
+    unsafe { ptr::addr_of_mut!((*ring.as_ptr()).ring) }
+}
+
+/// Returns the counters of the `IoUring` which holds a raw ring, the reverse of [`raw_ring`].
+///
+/// # Safety
+///
+/// `raw` must point to the raw ring inside an `IoUring`, as every raw ring of this library does.
+#[inline(always)]
+pub(crate) unsafe fn counters<'a>(raw: *const sys::io_uring) -> &'a metrics::Counters {
+    &(*(raw as *const IoUring)).counters
+}
+
+/// Returns the cached SQ head of the `IoUring` which holds a raw ring, like [`counters`].
+///
+/// # Safety
+///
+/// `raw` must point to the raw ring inside an `IoUring`.
+#[inline(always)]
+pub(crate) unsafe fn cached_sq_head<'a>(raw: *const sys::io_uring) -> &'a AtomicU32 {
+    &(*(raw as *const IoUring)).cached_sq_head
+}
+
+/// Call `io_uring_enter` on this ring, through its registered ring fd if one has been registered.
+pub(crate) unsafe fn enter(ring: NonNull<IoUring>, to_submit: u32, min_complete: u32, flags: u32)
+    -> io::Result<u32>
+{
+    enter_raw(ring, to_submit, min_complete, flags, ptr::null(), 0)
+}
+
+/// The argument passed to `io_uring_enter`.
+#[derive(Clone, Copy)]
+pub(crate) enum EnterArg<'a> {
+    /// An extended argument, which requires [`SetupFeatures::EXT_ARG`].
+    Ext(&'a sys::io_uring_getevents_arg),
+    /// An extended argument whose timeout is an absolute `CLOCK_MONOTONIC` time. This requires
+    /// Linux 6.12 or newer.
+    ExtAbs(&'a sys::io_uring_getevents_arg),
+    /// A signal mask to set while waiting.
+    Sigmask(&'a libc::sigset_t),
+}
+
+/// Call `io_uring_enter` on this ring with an argument.
+pub(crate) unsafe fn enter_with_arg(
+    ring: NonNull<IoUring>,
+    to_submit: u32,
+    min_complete: u32,
+    flags: u32,
+    arg: EnterArg<'_>,
+) -> io::Result<u32> {
+    match arg {
+        EnterArg::Ext(arg)          => {
+            let flags = flags | sys::IORING_ENTER_EXT_ARG;
+            let argsz = mem::size_of::<sys::io_uring_getevents_arg>();
+            enter_raw(ring, to_submit, min_complete, flags, arg as *const _ as *const _, argsz)
+        }
+        EnterArg::ExtAbs(arg)       => {
+            let flags = flags | sys::IORING_ENTER_EXT_ARG | sys::IORING_ENTER_ABS_TIMER;
+            let argsz = mem::size_of::<sys::io_uring_getevents_arg>();
+            enter_raw(ring, to_submit, min_complete, flags, arg as *const _ as *const _, argsz)
+        }
+        // the kernel expects the size of its own sigset, which has 64 signals
+        EnterArg::Sigmask(sigmask)  => {
+            enter_raw(ring, to_submit, min_complete, flags, sigmask as *const _ as *const _, 64 / 8)
+        }
+    }
+}
+
+unsafe fn enter_raw(
+    ring: NonNull<IoUring>,
+    to_submit: u32,
+    min_complete: u32,
+    flags: u32,
+    arg: *const libc::c_void,
+    argsz: usize,
+) -> io::Result<u32> {
+    let (fd, flags) = match registered_ring_index(ring) {
+        Some(index) => (index, flags | sys::IORING_ENTER_REGISTERED_RING),
+        None        => ((*raw_ring(ring)).ring_fd, flags),
+    };
+    let _span = trace::enter(to_submit, min_complete, flags);
+    (*ring.as_ptr()).counters.entered();
+    sys::io_uring_enter(fd, to_submit, min_complete, flags, arg, argsz)
+}
+
+/// Call `io_uring_register` on this ring, through its registered ring fd if it has no other fd.
+pub(crate) unsafe fn register(ring: NonNull<IoUring>, opcode: u32, arg: *const libc::c_void, nr_args: u32)
+    -> io::Result<u32>
+{
+    let (fd, opcode) = match (*raw_ring(ring)).ring_fd {
+        -1  => match registered_ring_index(ring) {
+            Some(index) => (index, opcode | sys::IORING_REGISTER_USE_REGISTERED_RING),
+            None        => return Err(io::Error::from_raw_os_error(libc::EBADF)),
+        },
+        fd  => (fd, opcode),
+    };
+    (*ring.as_ptr()).counters.registered();
+    sys::io_uring_register(fd, opcode, arg, nr_args)
+}
+
+/// The ring fd can be registered with epoll and similar interfaces, which report it readable
+/// when there are CQEs ready.
+///
+/// # Panics
+/// Panics if the ring was created with [`SetupFlags::REGISTERED_FD_ONLY`], so that it has no file
+/// descriptor; use [`raw_fd`](IoUring::raw_fd) to handle that case.
+impl AsRawFd for IoUring {
+    fn as_raw_fd(&self) -> RawFd {
+        self.raw_fd().expect("ring has no file descriptor")
+    }
+}
+
+/// See the [`AsRawFd`] implementation.
+///
+/// # Panics
+/// Panics if the ring was created with [`SetupFlags::REGISTERED_FD_ONLY`].
+impl AsFd for IoUring {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        unsafe { BorrowedFd::borrow_raw(self.as_raw_fd()) }
     }
 }
 
@@ -367,7 +1243,15 @@ impl fmt::Debug for IoUring {
 
 impl Drop for IoUring {
     fn drop(&mut self) {
-        unsafe { uring_sys::io_uring_queue_exit(&mut self.ring) };
+        // on any other thread than the one which registered it, the registered ring fd holds on to
+        // the ring until that thread exits
+        unsafe { unregister_ring_fd(self); }
+        match self.memory {
+            // the rings are unmapped when the memory is dropped
+            Some(_) if self.ring.ring_fd == -1  => { }
+            Some(_)                             => unsafe { libc::close(self.ring.ring_fd); },
+            None                                => unsafe { unmap_rings(&mut self.ring); },
+        }
     }
 }
 
@@ -0,0 +1,81 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A snapshot of the activity of an [`IoUring`](crate::IoUring), returned by
+/// [`IoUring::metrics`](crate::IoUring::metrics).
+///
+/// Every counter starts at 0 when the ring is created and only grows, so the counters can be
+/// exported as they are to a monitoring system; rates are the difference of two snapshots.
+///
+/// ```
+/// # use std::io;
+/// # fn main() -> io::Result<()> {
+/// let mut ring = iou::IoUring::new(2)?;
+/// unsafe { ring.prepare_sqe().unwrap().prep_nop(); }
+/// ring.submit_sqes_and_wait(1)?;
+/// ring.peek_for_cqe().unwrap();
+///
+/// let metrics = ring.metrics();
+/// assert_eq!((metrics.submitted, metrics.completed), (1, 1));
+/// assert_eq!(metrics.enter_calls, 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct RingMetrics {
+    /// The number of SQEs handed to the kernel.
+    pub submitted: u64,
+    /// The number of CQEs reaped, including those of the timeouts this library submits itself.
+    pub completed: u64,
+    /// The number of CQEs the kernel lost because the completion queue was full. Kernels with
+    /// [`SetupFeatures::NODROP`](crate::SetupFeatures::NODROP) keep overflowed CQEs in a backlog
+    /// instead, and only count those they could not keep.
+    pub cq_overflow: u64,
+    /// The number of invalid SQEs the kernel dropped, see
+    /// [`SubmissionQueue::dropped`](crate::SubmissionQueue::dropped).
+    pub sq_dropped: u64,
+    /// The number of `io_uring_enter` system calls made on the ring.
+    pub enter_calls: u64,
+    /// The number of `io_uring_register` system calls made on the ring.
+    pub register_calls: u64,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    submitted: AtomicU64,
+    completed: AtomicU64,
+    enter_calls: AtomicU64,
+    register_calls: AtomicU64,
+}
+
+impl Counters {
+    #[inline]
+    pub(crate) fn submitted(&self, count: u32) {
+        self.submitted.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn completed(&self, count: u32) {
+        self.completed.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn entered(&self) {
+        self.enter_calls.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn registered(&self) {
+        self.register_calls.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self, cq_overflow: u32, sq_dropped: u32) -> RingMetrics {
+        RingMetrics {
+            submitted: self.submitted.load(Ordering::Relaxed),
+            completed: self.completed.load(Ordering::Relaxed),
+            cq_overflow: cq_overflow as u64,
+            sq_dropped: sq_dropped as u64,
+            enter_calls: self.enter_calls.load(Ordering::Relaxed),
+            register_calls: self.register_calls.load(Ordering::Relaxed),
+        }
+    }
+}
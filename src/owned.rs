@@ -0,0 +1,324 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::mem;
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd};
+
+use crate::{IoUring, SQE, CQE, resultify};
+use crate::sqe::{prep, MsgFlags, SockFlag};
+
+// the user data of cancellations submitted when the ring is dropped
+const CANCEL_USER_DATA: u64 = u64::MAX - 1;
+
+/// An [`IoUring`] whose events own their buffers and file descriptors, which makes it safe to
+/// use.
+///
+/// Each event takes ownership of the buffer and file it uses and keeps them, keyed by the user
+/// data of the event, until it completes. They are then handed back with the [`Completion`] of
+/// the event. Because the kernel can never outlive the memory it uses, none of the methods of an
+/// `OwnedRing` are unsafe, except for [wrapping](OwnedRing::from_ring) a ring which might already
+/// be in use.
+///
+/// Files can be passed by value, like a `File` or `TcpStream`, or shared with an `Arc`.
+///
+/// ```
+/// # use std::io;
+/// # use std::fs::File;
+/// # use iou::owned::OwnedRing;
+/// # fn main() -> io::Result<()> {
+/// let mut ring = OwnedRing::new(8)?;
+/// let file = File::open("Cargo.toml")?;
+///
+/// let read = ring.read(file, Vec::with_capacity(64), 0)?;
+/// ring.submit()?;
+///
+/// let completion = ring.wait()?;
+/// assert_eq!(completion.user_data(), read);
+/// let n = completion.result()?;
+/// let buf = completion.into_buf().unwrap();
+/// assert_eq!(buf.len(), n as usize);
+/// assert!(buf.starts_with(b"[package]"));
+/// # Ok(())
+/// # }
+/// ```
+///
+/// When the ring is dropped, events still in flight are cancelled, and the ring waits for them
+/// to complete before their buffers are freed.
+pub struct OwnedRing {
+    ring: IoUring,
+    inflight: HashMap<u64, Resources>,
+    next_user_data: u64,
+}
+
+#[derive(Default)]
+struct Resources {
+    buf: Option<Vec<u8>>,
+    fd: Option<Box<dyn Any + Send>>,
+    kind: Kind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Kind {
+    #[default]
+    Other,
+    Read,
+    Accept,
+}
+
+impl OwnedRing {
+    /// Creates a new `OwnedRing` with `entries` entries.
+    pub fn new(entries: u32) -> io::Result<OwnedRing> {
+        let ring = IoUring::new(entries)?;
+        // a new ring has no events in flight
+        Ok(unsafe { OwnedRing::from_ring(ring) })
+    }
+
+    /// Wraps an `IoUring` on which no events are in flight.
+    ///
+    /// # Safety
+    ///
+    /// No events may be prepared, in flight or waiting to be reaped on `ring`. The completion of
+    /// such an event would be taken for that of an event of the `OwnedRing` with the same user
+    /// data, and its result trusted, for example as the number of bytes a read initialized.
+    pub unsafe fn from_ring(ring: IoUring) -> OwnedRing {
+        OwnedRing { ring, inflight: HashMap::new(), next_user_data: 0 }
+    }
+
+    /// The underlying ring, for example to [register](IoUring::registrar) resources with it.
+    pub fn ring(&self) -> &IoUring {
+        &self.ring
+    }
+
+    /// The number of events which have been prepared but have not completed yet.
+    pub fn inflight(&self) -> usize {
+        self.inflight.len()
+    }
+
+    /// Prepare a no-op event. Returns the user data of the event.
+    pub fn nop(&mut self) -> io::Result<u64> {
+        self.prepare(Resources::default(), |sqe| unsafe { sqe.prep_nop() })
+    }
+
+    /// Prepare a read from `fd` at `offset` into the spare capacity of `buf`. On completion, the
+    /// bytes read are appended to the contents of `buf`.
+    ///
+    /// For files which don't support offsets, like pipes and sockets, `offset` is ignored.
+    pub fn read<F>(&mut self, fd: F, mut buf: Vec<u8>, offset: u64) -> io::Result<u64>
+    where
+        F: AsRawFd + Send + 'static,
+    {
+        let raw_fd = fd.as_raw_fd();
+        let spare = buf.spare_capacity_mut();
+        let (data, len) = (spare.as_mut_ptr() as *mut libc::c_void, spare.len() as u32);
+        let resources = Resources { buf: Some(buf), fd: Some(Box::new(fd)), kind: Kind::Read };
+        self.prepare(resources, |sqe| unsafe {
+            prep::read(sqe.raw_mut(), raw_fd, data, len, offset as _);
+        })
+    }
+
+    /// Prepare a write of all of `buf` to `fd` at `offset`.
+    ///
+    /// For files which don't support offsets, like pipes and sockets, `offset` is ignored.
+    pub fn write<F>(&mut self, fd: F, buf: Vec<u8>, offset: u64) -> io::Result<u64>
+    where
+        F: AsRawFd + Send + 'static,
+    {
+        let raw_fd = fd.as_raw_fd();
+        let (data, len) = (buf.as_ptr(), buf.len());
+        let resources = Resources { buf: Some(buf), fd: Some(Box::new(fd)), kind: Kind::Other };
+        self.prepare(resources, |sqe| unsafe {
+            sqe.prep_write(raw_fd, std::slice::from_raw_parts(data, len), offset);
+        })
+    }
+
+    /// Prepare a send of all of `buf` on the socket `fd`.
+    pub fn send<F>(&mut self, fd: F, buf: Vec<u8>, flags: MsgFlags) -> io::Result<u64>
+    where
+        F: AsRawFd + Send + 'static,
+    {
+        let raw_fd = fd.as_raw_fd();
+        let (data, len) = (buf.as_ptr(), buf.len());
+        let resources = Resources { buf: Some(buf), fd: Some(Box::new(fd)), kind: Kind::Other };
+        self.prepare(resources, |sqe| unsafe {
+            sqe.prep_send(raw_fd, std::slice::from_raw_parts(data, len), flags);
+        })
+    }
+
+    /// Prepare a receive from the socket `fd` into the spare capacity of `buf`. On completion,
+    /// the bytes received are appended to the contents of `buf`.
+    pub fn recv<F>(&mut self, fd: F, mut buf: Vec<u8>, flags: MsgFlags) -> io::Result<u64>
+    where
+        F: AsRawFd + Send + 'static,
+    {
+        let raw_fd = fd.as_raw_fd();
+        let spare = buf.spare_capacity_mut();
+        let (data, len) = (spare.as_mut_ptr() as *mut libc::c_void, spare.len());
+        let resources = Resources { buf: Some(buf), fd: Some(Box::new(fd)), kind: Kind::Read };
+        self.prepare(resources, |sqe| unsafe {
+            prep::recv(sqe.raw_mut(), raw_fd, data, len, flags.bits());
+        })
+    }
+
+    /// Prepare an accept of a connection on the listening socket `fd`. The accepted socket can
+    /// be taken from the completion with [`Completion::accepted`].
+    pub fn accept<F>(&mut self, fd: F, flags: SockFlag) -> io::Result<u64>
+    where
+        F: AsRawFd + Send + 'static,
+    {
+        let raw_fd = fd.as_raw_fd();
+        let resources = Resources { buf: None, fd: Some(Box::new(fd)), kind: Kind::Accept };
+        self.prepare(resources, |sqe| unsafe { sqe.prep_accept(raw_fd, None, flags) })
+    }
+
+    /// Submit all prepared events. Returns the number of submitted events.
+    pub fn submit(&mut self) -> io::Result<u32> {
+        self.ring.submit_sqes()
+    }
+
+    /// Returns the next completion, blocking the thread until one is ready if necessary. Prepared
+    /// events are not submitted.
+    pub fn wait(&mut self) -> io::Result<Completion> {
+        loop {
+            let cqe = self.ring.wait_for_cqe()?;
+            if let Some(completion) = self.complete(cqe) {
+                return Ok(completion);
+            }
+        }
+    }
+
+    /// Returns the next completion if one is ready, without blocking.
+    pub fn peek(&mut self) -> Option<Completion> {
+        while let Some(cqe) = self.ring.peek_for_cqe() {
+            if let Some(completion) = self.complete(cqe) {
+                return Some(completion);
+            }
+        }
+        None
+    }
+
+    fn prepare(&mut self, resources: Resources, prep: impl FnOnce(&mut SQE<'_>)) -> io::Result<u64> {
+        let user_data = self.next_user_data;
+        let (mut sqe, _) = self.ring.prepare_or_submit()?;
+        prep(&mut sqe);
+        unsafe { sqe.set_user_data(user_data); }
+
+        self.next_user_data = self.next_user_data.wrapping_add(1);
+        self.inflight.insert(user_data, resources);
+        Ok(user_data)
+    }
+
+    fn complete(&mut self, cqe: CQE) -> Option<Completion> {
+        let mut resources = self.inflight.remove(&cqe.user_data())?;
+        let res = cqe.raw_result();
+        if let (Kind::Read, Some(buf)) = (resources.kind, &mut resources.buf) {
+            if res > 0 {
+                // the kernel initialized this many bytes of the spare capacity
+                unsafe { buf.set_len(buf.len() + res as usize); }
+            }
+        }
+        let accepted = match resources.kind {
+            Kind::Accept if res >= 0    => Some(unsafe { OwnedFd::from_raw_fd(res) }),
+            _                           => None,
+        };
+        Some(Completion { user_data: cqe.user_data(), res, buf: resources.buf, fd: resources.fd, accepted })
+    }
+}
+
+impl Drop for OwnedRing {
+    fn drop(&mut self) {
+        // the kernel may still use the buffers of events in flight, so they have to complete
+        // before the buffers can be freed
+        let inflight: Vec<u64> = self.inflight.keys().copied().collect();
+        for user_data in inflight {
+            if self.ring.sq_space_left() == 0 && self.ring.submit_sqes().is_err() {
+                break;
+            }
+            if let Some(mut sqe) = self.ring.prepare_sqe() {
+                unsafe {
+                    sqe.prep_cancel(user_data, 0);
+                    sqe.set_user_data(CANCEL_USER_DATA);
+                }
+            }
+        }
+
+        while !self.inflight.is_empty() {
+            let cqe = match self.ring.submit_sqes_and_wait(1).and_then(|_| self.ring.wait_for_cqe()) {
+                Ok(cqe)                                             => cqe,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_)                                              => {
+                    // leak the resources rather than free memory the kernel may still use
+                    mem::forget(mem::take(&mut self.inflight));
+                    return;
+                }
+            };
+            self.complete(cqe);
+        }
+    }
+}
+
+impl fmt::Debug for OwnedRing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct(std::any::type_name::<Self>())
+            .field("ring", &self.ring)
+            .field("inflight", &self.inflight.len())
+            .finish()
+    }
+}
+
+/// A completed event of an [`OwnedRing`], which hands back the buffer and file the event owned.
+pub struct Completion {
+    user_data: u64,
+    res: i32,
+    buf: Option<Vec<u8>>,
+    fd: Option<Box<dyn Any + Send>>,
+    accepted: Option<OwnedFd>,
+}
+
+impl Completion {
+    /// The user data of the event, as returned when it was prepared.
+    pub fn user_data(&self) -> u64 {
+        self.user_data
+    }
+
+    /// The result of the event.
+    pub fn result(&self) -> io::Result<u32> {
+        resultify(self.res)
+    }
+
+    /// The buffer of the event. For reads and receives, the bytes read have been appended to it.
+    pub fn buf(&self) -> Option<&Vec<u8>> {
+        self.buf.as_ref()
+    }
+
+    /// Take the buffer of the event.
+    pub fn into_buf(self) -> Option<Vec<u8>> {
+        self.buf
+    }
+
+    /// Take back the file the event used, if it is of type `F`.
+    pub fn take_fd<F: 'static>(&mut self) -> Option<F> {
+        match self.fd.take()?.downcast::<F>() {
+            Ok(fd)  => Some(*fd),
+            Err(fd) => {
+                self.fd = Some(fd);
+                None
+            }
+        }
+    }
+
+    /// Take the socket accepted by a successful accept event.
+    pub fn accepted(&mut self) -> Option<OwnedFd> {
+        self.accepted.take()
+    }
+}
+
+impl fmt::Debug for Completion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct(std::any::type_name::<Self>())
+            .field("user_data", &self.user_data)
+            .field("res", &self.res)
+            .field("buf_len", &self.buf.as_ref().map(Vec::len))
+            .finish()
+    }
+}
@@ -0,0 +1,80 @@
+use std::fmt;
+use std::ptr;
+
+use crate::{SetupFeatures, SetupFlags, sys};
+
+/// The parameters of an [`IoUring`](crate::IoUring), as granted by the kernel when it was set up.
+///
+/// The kernel may grant more than was asked for: the number of entries is rounded up to a power
+/// of two and the features report everything the kernel supports, not only what was requested.
+pub struct Params {
+    raw: sys::io_uring_params,
+}
+
+impl Params {
+    pub(crate) fn new(raw: &sys::io_uring_params) -> Params {
+        Params { raw: unsafe { ptr::read(raw) } }
+    }
+
+    /// The number of entries in the submission queue.
+    pub fn sq_entries(&self) -> u32 {
+        self.raw.sq_entries
+    }
+
+    /// The number of entries in the completion queue.
+    pub fn cq_entries(&self) -> u32 {
+        self.raw.cq_entries
+    }
+
+    /// The flags the ring was set up with.
+    pub fn flags(&self) -> SetupFlags {
+        SetupFlags::from_bits_truncate(self.raw.flags)
+    }
+
+    /// The features the kernel granted.
+    pub fn features(&self) -> SetupFeatures {
+        SetupFeatures::from_bits_truncate(self.raw.features)
+    }
+
+    /// The CPU of the `SQPOLL` kernel thread, if it was pinned with
+    /// [`SetupFlags::SQ_AFF`].
+    pub fn sq_thread_cpu(&self) -> u32 {
+        self.raw.sq_thread_cpu
+    }
+
+    /// How long the `SQPOLL` kernel thread polls before it goes to sleep, in milliseconds.
+    pub fn sq_thread_idle(&self) -> u32 {
+        self.raw.sq_thread_idle
+    }
+
+    /// The offsets of the fields of the submission queue ring.
+    pub fn sq_offsets(&self) -> &sys::io_sqring_offsets {
+        &self.raw.sq_off
+    }
+
+    /// The offsets of the fields of the completion queue ring.
+    pub fn cq_offsets(&self) -> &sys::io_cqring_offsets {
+        &self.raw.cq_off
+    }
+
+    pub fn raw(&self) -> &sys::io_uring_params {
+        &self.raw
+    }
+}
+
+impl Clone for Params {
+    fn clone(&self) -> Params {
+        Params::new(&self.raw)
+    }
+}
+
+impl fmt::Debug for Params {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct(std::any::type_name::<Self>())
+            .field("sq_entries", &self.sq_entries())
+            .field("cq_entries", &self.cq_entries())
+            .field("flags", &self.flags())
+            .field("features", &self.features())
+            .finish()
+    }
+}
@@ -1,29 +1,77 @@
+use std::fmt;
 use std::io;
+use std::mem;
 use std::ptr::NonNull;
+use std::slice;
+
+use crate::{IoUring, SetupFeatures, sys};
 
 /// A probe of the operations supported by this kernel version's io-uring interface.
 #[derive(Debug)]
 pub struct Probe {
-    probe: NonNull<uring_sys::io_uring_probe>,
+    probe: NonNull<sys::io_uring_probe>,
 }
 
 impl Probe {
+    /// Probe the running kernel, setting up a short-lived ring to register the probe with.
     pub fn new() -> io::Result<Probe> {
+        Probe::for_ring(NonNull::from(&IoUring::new(2)?))
+    }
+
+    pub(crate) fn for_ring(ring: NonNull<IoUring>) -> io::Result<Probe> {
+        const OPS: usize = 256;
         unsafe {
-            let probe = uring_sys::io_uring_get_probe();
-            NonNull::new(probe).ok_or_else(io::Error::last_os_error).map(|probe| Probe { probe })
+            let len = mem::size_of::<sys::io_uring_probe>() + OPS * mem::size_of::<sys::io_uring_probe_op>();
+            let probe = NonNull::new(libc::calloc(1, len) as *mut sys::io_uring_probe)
+                .ok_or_else(|| io::Error::from_raw_os_error(libc::ENOMEM))?;
+            // the probe is freed on drop, including when registration fails
+            let probe = Probe { probe };
+            crate::register(ring, sys::IORING_REGISTER_PROBE, probe.probe.as_ptr() as *const _, OPS as _)?;
+            Ok(probe)
         }
     }
 
-    pub(crate) fn for_ring(ring: *mut uring_sys::io_uring) -> io::Result<Probe> {
-        unsafe {
-            let probe = uring_sys::io_uring_get_probe_ring(ring);
-            NonNull::new(probe).ok_or_else(io::Error::last_os_error).map(|probe| Probe { probe })
+    pub fn supports(&self, op: sys::IoRingOp) -> bool {
+        self.supports_raw(op as u8)
+    }
+
+    /// Whether the kernel supports the operation with the raw opcode `op`, including operations
+    /// which are newer than [`IoRingOp`](sys::IoRingOp).
+    pub fn supports_raw(&self, op: u8) -> bool {
+        self.ops().any(|probe_op| probe_op.opcode() == op && probe_op.is_supported())
+    }
+
+    /// Whether the kernel supports the operation with the given name. The name can be given with
+    /// or without its `IORING_OP_` prefix, e.g. `"IORING_OP_SEND_ZC"` or `"SEND_ZC"`.
+    ///
+    /// Returns `false` for names this library does not know.
+    pub fn supports_name(&self, name: &str) -> bool {
+        let name = name.strip_prefix("IORING_OP_").unwrap_or(name);
+        match OP_NAMES.iter().position(|&op_name| op_name == name) {
+            Some(op)    => self.supports_raw(op as u8),
+            None        => false,
         }
     }
 
-    pub fn supports(&self, op: uring_sys::IoRingOp) -> bool {
-        unsafe { uring_sys::io_uring_opcode_supported(self.probe.as_ptr(), op as _) != 0 }
+    /// The highest opcode known to the kernel.
+    pub fn last_op(&self) -> u8 {
+        self.raw().last_op
+    }
+
+    /// The number of operations the kernel supports.
+    pub fn supported_count(&self) -> usize {
+        self.ops().filter(ProbeOp::is_supported).count()
+    }
+
+    /// Iterate over every operation reported by the kernel, whether it is supported or not.
+    pub fn ops(&self) -> impl Iterator<Item = ProbeOp> + '_ {
+        let raw = self.raw();
+        let ops = unsafe { slice::from_raw_parts(raw.ops.as_ptr(), raw.ops_len as usize) };
+        ops.iter().map(|op| ProbeOp { op: op.op, flags: op.flags })
+    }
+
+    fn raw(&self) -> &sys::io_uring_probe {
+        unsafe { &*(self.probe.as_ptr() as *const sys::io_uring_probe) }
     }
 }
 
@@ -32,3 +80,208 @@ impl Drop for Probe {
         unsafe { libc::free(self.probe.as_ptr() as *mut _) }
     }
 }
+
+/// An operation reported by a [`Probe`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ProbeOp {
+    op: u8,
+    flags: u16,
+}
+
+impl ProbeOp {
+    /// The raw opcode of the operation.
+    pub fn opcode(&self) -> u8 {
+        self.op
+    }
+
+    /// The name of the operation without its `IORING_OP_` prefix, or `None` if it is newer than
+    /// this library.
+    pub fn name(&self) -> Option<&'static str> {
+        op_name(self.op)
+    }
+
+    pub fn is_supported(&self) -> bool {
+        self.flags & sys::IO_URING_OP_SUPPORTED != 0
+    }
+
+    pub fn raw_flags(&self) -> u16 {
+        self.flags
+    }
+}
+
+impl fmt::Display for ProbeOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name() {
+            Some(name)  => write!(f, "IORING_OP_{}", name),
+            None        => write!(f, "opcode {}", self.op),
+        }
+    }
+}
+
+/// A summary of what the running kernel's io-uring interface supports: the setup features it
+/// grants and the operations it reports through a [`Probe`].
+///
+/// The report implements `Display`, which is meant to be logged at startup or attached to bug
+/// reports, and `Serialize` when the `serde` feature is enabled.
+///
+/// ```
+/// # use std::io;
+/// # fn main() -> io::Result<()> {
+/// let report = iou::CapabilityReport::detect()?;
+/// println!("{}", report);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CapabilityReport {
+    features: u32,
+    last_op: u8,
+    ops: Vec<ProbeOp>,
+}
+
+impl CapabilityReport {
+    /// Build a report from a probe and the features granted to a ring.
+    pub fn new(probe: &Probe, features: SetupFeatures) -> CapabilityReport {
+        CapabilityReport::from_raw_features(probe, features.bits())
+    }
+
+    /// Build a report for the running kernel, setting up a short-lived ring to learn which
+    /// features it grants.
+    pub fn detect() -> io::Result<CapabilityReport> {
+        let features = unsafe {
+            let mut params: sys::io_uring_params = mem::zeroed();
+            libc::close(sys::io_uring_setup(1, &mut params)?);
+            params.features
+        };
+        Ok(CapabilityReport::from_raw_features(&Probe::new()?, features))
+    }
+
+    fn from_raw_features(probe: &Probe, features: u32) -> CapabilityReport {
+        CapabilityReport { features, last_op: probe.last_op(), ops: probe.ops().collect() }
+    }
+
+    /// The granted features which this library knows about.
+    pub fn features(&self) -> SetupFeatures {
+        SetupFeatures::from_bits_truncate(self.features)
+    }
+
+    /// All granted feature bits, including those newer than this library.
+    pub fn raw_features(&self) -> u32 {
+        self.features
+    }
+
+    /// The highest opcode known to the kernel.
+    pub fn last_op(&self) -> u8 {
+        self.last_op
+    }
+
+    pub fn supported_ops(&self) -> impl Iterator<Item = ProbeOp> + '_ {
+        self.ops.iter().copied().filter(ProbeOp::is_supported)
+    }
+
+    pub fn unsupported_ops(&self) -> impl Iterator<Item = ProbeOp> + '_ {
+        self.ops.iter().copied().filter(|op| !op.is_supported())
+    }
+
+    fn feature_names(&self) -> impl Iterator<Item = String> {
+        feature_names(self.features)
+    }
+}
+
+impl fmt::Display for CapabilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn list(f: &mut fmt::Formatter, items: impl Iterator<Item = impl fmt::Display>) -> fmt::Result {
+            for (i, item) in items.enumerate() {
+                if i > 0 { f.write_str(", ")?; }
+                write!(f, "{}", item)?;
+            }
+            writeln!(f)
+        }
+
+        writeln!(f, "io_uring capabilities:")?;
+        write!(f, "  features: ")?;
+        list(f, self.feature_names())?;
+        writeln!(f, "  last op: {}", self.last_op)?;
+        write!(f, "  supported ops ({}): ", self.supported_ops().count())?;
+        list(f, self.supported_ops())?;
+        write!(f, "  unsupported ops ({}): ", self.unsupported_ops().count())?;
+        list(f, self.unsupported_ops())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for CapabilityReport {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let names = |ops: &mut dyn Iterator<Item = ProbeOp>| ops.map(|op| op.to_string()).collect::<Vec<_>>();
+        let mut report = serializer.serialize_struct("CapabilityReport", 4)?;
+        report.serialize_field("features", &self.feature_names().collect::<Vec<_>>())?;
+        report.serialize_field("last_op", &self.last_op)?;
+        report.serialize_field("supported_ops", &names(&mut self.supported_ops()))?;
+        report.serialize_field("unsupported_ops", &names(&mut self.unsupported_ops()))?;
+        report.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Probe {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut probe = serializer.serialize_struct("Probe", 2)?;
+        probe.serialize_field("last_op", &self.last_op())?;
+        probe.serialize_field("ops", &self.ops().collect::<Vec<_>>())?;
+        probe.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ProbeOp {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut op = serializer.serialize_struct("ProbeOp", 3)?;
+        op.serialize_field("opcode", &self.op)?;
+        op.serialize_field("name", &self.name())?;
+        op.serialize_field("supported", &self.is_supported())?;
+        op.end()
+    }
+}
+
+/// Serialized as the list of the feature names, like `["SINGLE_MMAP", "NODROP"]`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for SetupFeatures {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(feature_names(self.bits()))
+    }
+}
+
+// the names of the features set in `features`, or the hex value of the bits this library does
+// not know
+fn feature_names(features: u32) -> impl Iterator<Item = String> {
+    (0..32).map(|bit| 1u32 << bit).filter(move |&bit| features & bit != 0).map(|bit| {
+        match SetupFeatures::from_bits(bit) {
+            Some(feature)   => format!("{:?}", feature),
+            None            => format!("{:#x}", bit),
+        }
+    })
+}
+
+// the name of an opcode without its `IORING_OP_` prefix
+pub(crate) fn op_name(op: u8) -> Option<&'static str> {
+    OP_NAMES.get(op as usize).copied()
+}
+
+// the names of the kernel's opcodes, indexed by opcode
+const OP_NAMES: &[&str] = &[
+    "NOP", "READV", "WRITEV", "FSYNC", "READ_FIXED", "WRITE_FIXED", "POLL_ADD", "POLL_REMOVE",
+    "SYNC_FILE_RANGE", "SENDMSG", "RECVMSG", "TIMEOUT", "TIMEOUT_REMOVE", "ACCEPT",
+    "ASYNC_CANCEL", "LINK_TIMEOUT", "CONNECT", "FALLOCATE", "OPENAT", "CLOSE", "FILES_UPDATE",
+    "STATX", "READ", "WRITE", "FADVISE", "MADVISE", "SEND", "RECV", "OPENAT2", "EPOLL_CTL",
+    "SPLICE", "PROVIDE_BUFFERS", "REMOVE_BUFFERS", "TEE", "SHUTDOWN", "RENAMEAT", "UNLINKAT",
+    "MKDIRAT", "SYMLINKAT", "LINKAT", "MSG_RING", "FSETXATTR", "SETXATTR", "FGETXATTR",
+    "GETXATTR", "SOCKET", "URING_CMD", "SEND_ZC", "SENDMSG_ZC", "READ_MULTISHOT", "WAITID",
+    "FUTEX_WAIT", "FUTEX_WAKE", "FUTEX_WAITV", "FIXED_FD_INSTALL", "FTRUNCATE", "BIND", "LISTEN",
+    "RECV_ZC", "EPOLL_WAIT", "READV_FIXED", "WRITEV_FIXED", "PIPE",
+];
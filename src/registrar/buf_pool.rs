@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+
+use super::{RegisteredBuf, Registrar};
+
+/// A pool of registered buffers which are lent out for fixed buffer IO.
+///
+/// The pool registers all of its buffers when it is created. A buffer is
+/// [checked out](RegisteredBufPool::checkout) to prepare an event with it, and then handed back
+/// to the pool with the user data of that event while the kernel uses it. When the event's `CQE`
+/// arrives, [`complete`](RegisteredBufPool::complete) returns the buffer so its contents can be
+/// used, after which it is [checked in](RegisteredBufPool::checkin) to be lent out again.
+///
+/// ```
+/// # use std::io;
+/// # use std::os::unix::io::AsRawFd;
+/// # use iou::{IoUring, registrar::RegisteredBufPool};
+/// # fn main() -> io::Result<()> {
+/// # let file = std::fs::File::open(env!("CARGO_MANIFEST_DIR"))?;
+/// let mut ring = IoUring::new(8)?;
+/// let mut pool = RegisteredBufPool::new(&ring.registrar(), 4, 4096)?;
+///
+/// let mut buf = pool.checkout().unwrap();
+/// let index = buf.buf_index();
+/// unsafe {
+///     let mut sqe = ring.prepare_sqe().unwrap();
+///     sqe.prep_read_fixed(file.as_raw_fd(), &mut buf[..], 0, index);
+///     sqe.set_user_data(1);
+/// }
+/// pool.submitted(1, buf);
+/// ring.submit_sqes()?;
+///
+/// let cqe = ring.wait_for_cqe()?;
+/// let buf = pool.complete(cqe.user_data()).unwrap();
+/// // -- use the buffer
+/// pool.checkin(buf);
+/// # Ok(())
+/// # }
+/// ```
+pub struct RegisteredBufPool {
+    free: Vec<RegisteredBuf>,
+    inflight: HashMap<u64, RegisteredBuf>,
+    count: u32,
+    buf_len: usize,
+}
+
+impl RegisteredBufPool {
+    /// Register `count` buffers of `buf_len` bytes each and create a pool of them. The pool uses
+    /// the whole registered buffer table of the ring.
+    pub fn new(registrar: &Registrar<'_>, count: u32, buf_len: usize) -> io::Result<RegisteredBufPool> {
+        let buffers = (0..count).map(|_| vec![0; buf_len].into_boxed_slice()).collect();
+        let mut free: Vec<_> = registrar.register_buffers(buffers)?.collect();
+        // lend out the lowest indices first
+        free.reverse();
+        Ok(RegisteredBufPool { free, inflight: HashMap::new(), count, buf_len })
+    }
+
+    /// The total number of buffers in the pool.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// The length of each buffer in the pool.
+    pub fn buf_len(&self) -> usize {
+        self.buf_len
+    }
+
+    /// The number of buffers which can currently be checked out.
+    pub fn available(&self) -> usize {
+        self.free.len()
+    }
+
+    /// The number of buffers which are in use by the kernel.
+    pub fn inflight(&self) -> usize {
+        self.inflight.len()
+    }
+
+    /// Check a buffer out of the pool, if one is available.
+    pub fn checkout(&mut self) -> Option<RegisteredBuf> {
+        self.free.pop()
+    }
+
+    /// Return a buffer to the pool, so that it can be checked out again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer does not belong to this pool.
+    pub fn checkin(&mut self, buf: RegisteredBuf) {
+        assert!(buf.index() < self.count && buf.len() == self.buf_len, "buffer does not belong to this pool");
+        self.free.push(buf);
+    }
+
+    /// Hand a buffer to the pool while the kernel uses it for the event with `user_data`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if another buffer is already in flight with the same user data.
+    pub fn submitted(&mut self, user_data: u64, buf: RegisteredBuf) {
+        let prev = self.inflight.insert(user_data, buf);
+        assert!(prev.is_none(), "a buffer is already in flight for this user data");
+    }
+
+    /// Take back the buffer which was in use by the event with `user_data`, after its `CQE` has
+    /// arrived. Returns `None` if no buffer was in flight for that event.
+    pub fn complete(&mut self, user_data: u64) -> Option<RegisteredBuf> {
+        self.inflight.remove(&user_data)
+    }
+}
+
+impl fmt::Debug for RegisteredBufPool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct(std::any::type_name::<Self>())
+            .field("count", &self.count)
+            .field("buf_len", &self.buf_len)
+            .field("available", &self.available())
+            .field("inflight", &self.inflight())
+            .finish()
+    }
+}
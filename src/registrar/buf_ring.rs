@@ -0,0 +1,382 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::Deref;
+use std::ptr::{self, NonNull};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU16, Ordering};
+
+use crate::{CQE, IoUring, sys};
+use crate::sqe::BufferGroupId;
+
+/// A ring of provided buffers registered with the kernel under a [`BufferGroupId`].
+///
+/// This is the replacement for [providing buffers](crate::SQE::prep_provide_buffers) with an
+/// event: the buffers are handed to the kernel through a ring shared with it, so adding a buffer
+/// back after it has been used is a memory write rather than another event. Events which select a
+/// buffer from the group, like [multishot recvmsg](crate::SQE::prep_recvmsg_multishot), report
+/// the id of the buffer they used in the flags of their [`CQE`](crate::CQE).
+///
+/// The `BufRing` owns `entries` buffers of `buf_len` bytes each, identified by the buffer ids
+/// `0..entries`. All of them are in the ring when it is registered. Once the kernel has selected a
+/// buffer, it stays out of the ring until it is [added](BufRing::add) back and the ring is
+/// [advanced](BufRing::advance).
+///
+/// The ring is unregistered when the `BufRing` is dropped.
+pub struct BufRing<'ring> {
+    ring: NonNull<IoUring>,
+    bufs: NonNull<sys::io_uring_buf>,
+    entries: u16,
+    tail: u16,
+    group: BufferGroupId,
+    buf_len: u32,
+    buffers: Box<[u8]>,
+    _owner: Option<Arc<IoUring>>,
+    _marker: PhantomData<&'ring IoUring>,
+}
+
+impl<'ring> BufRing<'ring> {
+    pub(crate) fn register(
+        ring: NonNull<IoUring>,
+        owner: Option<Arc<IoUring>>,
+        group: BufferGroupId,
+        entries: u16,
+        buf_len: u32,
+    ) -> io::Result<BufRing<'ring>>
+    {
+        assert!(group.id <= u16::MAX as u32, "buffer group ids are limited to 16 bits");
+        assert!(entries.is_power_of_two(), "buffer ring entries must be a power of two");
+        let total_len = (entries as usize).checked_mul(buf_len as usize)
+            .expect("buffer ring is too large");
+
+        let ring_len = entries as usize * mem::size_of::<sys::io_uring_buf>();
+        let bufs = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                ring_len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_ANONYMOUS | libc::MAP_PRIVATE,
+                -1,
+                0,
+            )
+        };
+        if bufs == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+
+        let reg = sys::io_uring_buf_reg {
+            ring_addr: bufs as u64,
+            ring_entries: entries as u32,
+            bgid: group.id as u16,
+            ..Default::default()
+        };
+        unsafe {
+            let registered = crate::register(
+                ring,
+                sys::IORING_REGISTER_PBUF_RING,
+                &reg as *const _ as *const _,
+                1,
+            );
+            if let Err(err) = registered {
+                libc::munmap(bufs, ring_len);
+                return Err(err);
+            }
+        }
+
+        let mut buf_ring = BufRing {
+            ring,
+            bufs: NonNull::new(bufs as *mut sys::io_uring_buf).unwrap(),
+            entries,
+            tail: 0,
+            group,
+            buf_len,
+            buffers: vec![0; total_len].into_boxed_slice(),
+            _owner: owner,
+            _marker: PhantomData,
+        };
+        for bid in 0..entries {
+            buf_ring.add(bid);
+        }
+        buf_ring.advance();
+        Ok(buf_ring)
+    }
+
+    /// The buffer group this ring provides buffers for.
+    pub fn group(&self) -> BufferGroupId {
+        self.group
+    }
+
+    /// The number of buffers in this ring, which are identified by the ids `0..entries`.
+    pub fn entries(&self) -> u16 {
+        self.entries
+    }
+
+    /// The length of each buffer in this ring.
+    pub fn buf_len(&self) -> u32 {
+        self.buf_len
+    }
+
+    /// Stage the buffer `bid` to be added back to the ring. The kernel will not see it until the
+    /// ring is [advanced](BufRing::advance).
+    ///
+    /// The buffer should not already be in the ring; every buffer in the ring must have been
+    /// selected by the kernel before more buffers are added than the ring has entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bid` is not a buffer id of this ring.
+    pub fn add(&mut self, bid: u16) {
+        assert!(bid < self.entries, "buffer id out of range for this buffer ring");
+        let offset = bid as usize * self.buf_len as usize;
+        unsafe {
+            let buf = self.bufs.as_ptr().add((self.tail & (self.entries - 1)) as usize);
+            // The tail of the ring overlaps the reserved field of the first entry, so the fields
+            // are written one by one.
+            (*buf).addr = self.buffers.as_ptr().add(offset) as u64;
+            (*buf).len = self.buf_len;
+            (*buf).bid = bid;
+        }
+        self.tail = self.tail.wrapping_add(1);
+    }
+
+    /// Make all the buffers which were [added](BufRing::add) since the last call visible to the
+    /// kernel.
+    pub fn advance(&mut self) {
+        unsafe {
+            let tail = ptr::addr_of!((*self.bufs.as_ptr()).resv) as *const AtomicU16;
+            (*tail).store(self.tail, Ordering::Release);
+        }
+    }
+
+    /// Returns the first `len` bytes of the buffer `bid`, after the kernel has filled it.
+    ///
+    /// # Safety
+    ///
+    /// The buffer must have been selected by the kernel, as reported by a `CQE`, and must not have
+    /// been added back to the ring since, or the kernel may write to it while it is borrowed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bid` is not a buffer id of this ring or `len` is longer than the buffers.
+    pub unsafe fn get(&self, bid: u16, len: usize) -> BufRingBuf<'_> {
+        assert!(bid < self.entries, "buffer id out of range for this buffer ring");
+        assert!(len <= self.buf_len as usize, "length out of range for this buffer ring");
+        let offset = bid as usize * self.buf_len as usize;
+        BufRingBuf { bid, data: &self.buffers[offset..offset + len] }
+    }
+
+    /// Returns the bytes the kernel filled for the event of `cqe`, from the buffer it selected
+    /// from this ring. Returns `None` if the event failed or did not select a buffer.
+    ///
+    /// # Safety
+    ///
+    /// `cqe` must have been posted by an event which selected its buffer from this ring, and the
+    /// buffer must not have been added back to the ring since, as for [`get`](BufRing::get).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer id or the length of `cqe` is out of range for this ring.
+    pub unsafe fn decode(&self, cqe: &CQE) -> Option<BufRingBuf<'_>> {
+        let len = cqe.result().ok()?;
+        Some(self.get(cqe.buffer_id()?, len as usize))
+    }
+
+    /// A cursor over the completions of events which select buffers from this ring, which adds
+    /// each buffer back to the ring once it has been read.
+    pub fn cursor(&mut self) -> BufRingCursor<'_, 'ring> {
+        let held = vec![false; self.entries as usize];
+        BufRingCursor { buf_ring: RefCell::new(self), held: RefCell::new(held) }
+    }
+}
+
+impl fmt::Debug for BufRing<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct(std::any::type_name::<Self>())
+            .field("group", &self.group)
+            .field("entries", &self.entries)
+            .field("buf_len", &self.buf_len)
+            .finish()
+    }
+}
+
+impl Drop for BufRing<'_> {
+    fn drop(&mut self) {
+        let reg = sys::io_uring_buf_reg { bgid: self.group.id as u16, ..Default::default() };
+        unsafe {
+            let _ = crate::register(
+                self.ring,
+                sys::IORING_UNREGISTER_PBUF_RING,
+                &reg as *const _ as *const _,
+                1,
+            );
+            libc::munmap(
+                self.bufs.as_ptr() as *mut _,
+                self.entries as usize * mem::size_of::<sys::io_uring_buf>(),
+            );
+        }
+    }
+}
+
+unsafe impl Send for BufRing<'_> { }
+unsafe impl Sync for BufRing<'_> { }
+
+/// Hands out the buffers of a [`BufRing`] which the kernel filled, and adds each of them back to
+/// the ring when it is dropped.
+///
+/// Without recycling, every completion of a multishot event takes a buffer out of the ring for
+/// good, and the event stops with `ENOBUFS` once the ring is empty. With a cursor, a buffer is
+/// back in the ring as soon as the [`RecyclingBuf`] for it has been dropped.
+///
+/// ```no_run
+/// # use std::io;
+/// # use iou::sqe::BufferGroupId;
+/// # fn main() -> io::Result<()> {
+/// # let mut ring = iou::IoUring::new(4)?;
+/// # let (_, mut cq, registrar) = ring.queues();
+/// let mut buf_ring = registrar.register_buf_ring(BufferGroupId { id: 0 }, 8, 4096)?;
+/// // ... prepare a multishot receive from the group of `buf_ring` ...
+/// let cursor = buf_ring.cursor();
+/// loop {
+///     let cqe = cq.wait_for_cqe()?;
+///     if let Some(buf) = unsafe { cursor.take(&cqe) } {
+///         println!("received {} bytes", buf.len());
+///     } // the buffer is back in the ring here
+/// }
+/// # }
+/// ```
+pub struct BufRingCursor<'a, 'ring> {
+    buf_ring: RefCell<&'a mut BufRing<'ring>>,
+    // the buffers which are currently handed out
+    held: RefCell<Vec<bool>>,
+}
+
+impl BufRingCursor<'_, '_> {
+    /// Take the buffer the kernel filled for the event of `cqe`. The buffer is added back to the
+    /// ring when the returned `RecyclingBuf` is dropped.
+    ///
+    /// Returns `None` if the event did not select a buffer. If it selected one but failed, the
+    /// buffer is added back to the ring immediately, and `None` is returned as well.
+    ///
+    /// # Safety
+    ///
+    /// `cqe` must have been posted by an event which selected its buffer from the ring of this
+    /// cursor, and must not have been taken before.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer of `cqe` is still handed out, or if its id or length are out of range
+    /// for the ring.
+    pub unsafe fn take(&self, cqe: &CQE) -> Option<RecyclingBuf<'_>> {
+        let bid = cqe.buffer_id()?;
+        let len = match cqe.result() {
+            Ok(len)     => len as usize,
+            Err(_)      => {
+                self.recycle(bid);
+                return None;
+            }
+        };
+
+        let buf_ring = self.buf_ring.borrow();
+        assert!(bid < buf_ring.entries, "buffer id out of range for this buffer ring");
+        assert!(len <= buf_ring.buf_len as usize, "length out of range for this buffer ring");
+        let held = mem::replace(&mut self.held.borrow_mut()[bid as usize], true);
+        assert!(!held, "buffer {} is already handed out", bid);
+
+        // the buffers are not moved or written while the cursor borrows the ring, and the kernel
+        // can't select this one again until it is recycled
+        let data = buf_ring.buffers.as_ptr().add(bid as usize * buf_ring.buf_len as usize);
+        Some(RecyclingBuf { cursor: self, bid, data: std::slice::from_raw_parts(data, len) })
+    }
+
+    /// The number of buffers which are currently handed out.
+    pub fn held(&self) -> usize {
+        self.held.borrow().iter().filter(|&&held| held).count()
+    }
+}
+
+// lets a `RecyclingBuf` add its buffer back without naming the lifetimes of the cursor
+trait Recycle {
+    fn recycle(&self, bid: u16);
+}
+
+impl Recycle for BufRingCursor<'_, '_> {
+    fn recycle(&self, bid: u16) {
+        if let Some(held) = self.held.borrow_mut().get_mut(bid as usize) {
+            *held = false;
+        }
+        let mut buf_ring = self.buf_ring.borrow_mut();
+        buf_ring.add(bid);
+        buf_ring.advance();
+    }
+}
+
+impl fmt::Debug for BufRingCursor<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct(std::any::type_name::<Self>())
+            .field("buf_ring", &self.buf_ring)
+            .field("held", &self.held())
+            .finish()
+    }
+}
+
+/// A buffer from a [`BufRingCursor`] which the kernel has filled. The buffer is added back to the
+/// ring when this is dropped.
+pub struct RecyclingBuf<'c> {
+    cursor: &'c dyn Recycle,
+    bid: u16,
+    data: &'c [u8],
+}
+
+impl RecyclingBuf<'_> {
+    /// The buffer id of this buffer.
+    pub fn id(&self) -> u16 {
+        self.bid
+    }
+}
+
+impl Deref for RecyclingBuf<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.data
+    }
+}
+
+impl Drop for RecyclingBuf<'_> {
+    fn drop(&mut self) {
+        self.cursor.recycle(self.bid);
+    }
+}
+
+impl fmt::Debug for RecyclingBuf<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct(std::any::type_name::<Self>())
+            .field("bid", &self.bid)
+            .field("len", &self.data.len())
+            .finish()
+    }
+}
+
+/// A buffer from a [`BufRing`] which the kernel has filled.
+#[derive(Debug)]
+pub struct BufRingBuf<'a> {
+    bid: u16,
+    data: &'a [u8],
+}
+
+impl BufRingBuf<'_> {
+    /// The buffer id of this buffer.
+    pub fn id(&self) -> u16 {
+        self.bid
+    }
+}
+
+impl Deref for BufRingBuf<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.data
+    }
+}
@@ -0,0 +1,63 @@
+use std::io;
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd};
+use std::sync::Arc;
+
+/// An eventfd which can be registered with an [`IoUring`](crate::IoUring) to be notified of
+/// completions.
+///
+/// Unlike registering a raw file descriptor with
+/// [`register_eventfd`](crate::registrar::Registrar::register_eventfd), registering an `EventFd`
+/// with [`register_owned_eventfd`](crate::registrar::Registrar::register_owned_eventfd) keeps the
+/// descriptor open for as long as it is registered, even if every `EventFd` handle is dropped.
+///
+/// `EventFd` is cheap to clone; all clones refer to the same descriptor.
+#[derive(Debug, Clone)]
+pub struct EventFd {
+    fd: Arc<OwnedFd>,
+}
+
+impl EventFd {
+    /// Create a new non-blocking eventfd.
+    pub fn new() -> io::Result<EventFd> {
+        let fd = unsafe { libc::eventfd(0, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK) };
+        match fd >= 0 {
+            true    => Ok(EventFd::from_owned(unsafe { OwnedFd::from_raw_fd(fd) })),
+            false   => Err(io::Error::last_os_error()),
+        }
+    }
+
+    /// Wrap an existing eventfd. It should be non-blocking, or
+    /// [`read_events`](EventFd::read_events) blocks until there are events.
+    pub fn from_owned(fd: OwnedFd) -> EventFd {
+        EventFd { fd: Arc::new(fd) }
+    }
+
+    /// Drain the eventfd, returning the number of notifications since it was last drained, or
+    /// zero if there were none.
+    pub fn read_events(&self) -> io::Result<u64> {
+        let mut count = 0u64;
+        let n = unsafe {
+            libc::read(self.as_raw_fd(), &mut count as *mut u64 as *mut _, std::mem::size_of::<u64>())
+        };
+        if n < 0 {
+            let err = io::Error::last_os_error();
+            return match err.kind() {
+                io::ErrorKind::WouldBlock   => Ok(0),
+                _                           => Err(err),
+            };
+        }
+        Ok(count)
+    }
+}
+
+impl AsRawFd for EventFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+impl AsFd for EventFd {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
@@ -0,0 +1,113 @@
+use std::fmt;
+use std::io;
+use std::ptr::NonNull;
+use std::slice;
+
+use super::{Registered, RegisteredBufMut, RegisteredBufRef, Registrar};
+use crate::ring_memory::{map, page_size, round_up, HUGE_PAGE_LEN};
+use crate::sys;
+
+/// A set of registered buffers carved out of 2MB hugepages.
+///
+/// The kernel can map fixed buffers backed by hugepages more efficiently than buffers in regular
+/// pages. If the system has no free hugepages, the buffers are allocated from regular pages
+/// instead; [`is_hugepage_backed`](HugePageBuffers::is_hugepage_backed) reports which happened.
+///
+/// Each buffer is aligned to the page size, so its length is rounded up to a multiple of it.
+///
+/// Created by [`Registrar::register_hugepage_buffers`].
+pub struct HugePageBuffers {
+    addr: NonNull<libc::c_void>,
+    len: usize,
+    count: u32,
+    buf_len: usize,
+    huge: bool,
+}
+
+impl HugePageBuffers {
+    pub(crate) fn register(registrar: &Registrar<'_>, count: u32, buf_len: usize) -> io::Result<HugePageBuffers> {
+        assert!(count > 0 && buf_len > 0, "buffers must not be empty");
+        let buf_len = round_up(buf_len, page_size());
+        let len = buf_len.checked_mul(count as usize).expect("buffers are too large");
+
+        let bufs = match map(round_up(len, HUGE_PAGE_LEN), libc::MAP_HUGETLB | libc::MAP_HUGE_2MB) {
+            Ok(addr)    => HugePageBuffers { addr, len: round_up(len, HUGE_PAGE_LEN), count, buf_len, huge: true },
+            Err(_)      => HugePageBuffers { addr: map(len, 0)?, len, count, buf_len, huge: false },
+        };
+
+        let iovecs: Vec<_> = (0..count).map(|i| libc::iovec {
+            iov_base: bufs.ptr(i) as *mut _,
+            iov_len: buf_len,
+        }).collect();
+        registrar.register(sys::IORING_REGISTER_BUFFERS, iovecs.as_ptr() as *const _, count)?;
+        Ok(bufs)
+    }
+
+    /// The number of buffers.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// The length of each buffer, rounded up to the page size.
+    pub fn buf_len(&self) -> usize {
+        self.buf_len
+    }
+
+    /// Whether the buffers are backed by hugepages, rather than regular pages.
+    pub fn is_hugepage_backed(&self) -> bool {
+        self.huge
+    }
+
+    /// The buffer registered at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn buf(&self, index: u32) -> RegisteredBufRef<'_> {
+        assert!(index < self.count, "buffer index out of bounds");
+        let data = unsafe { slice::from_raw_parts(self.ptr(index), self.buf_len) };
+        Registered::new(index, data)
+    }
+
+    /// The buffer registered at `index`, mutably.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn buf_mut(&mut self, index: u32) -> RegisteredBufMut<'_> {
+        assert!(index < self.count, "buffer index out of bounds");
+        let data = unsafe { slice::from_raw_parts_mut(self.ptr(index), self.buf_len) };
+        Registered::new(index, data)
+    }
+
+    /// All of the buffers, mutably.
+    pub fn bufs_mut(&mut self) -> impl Iterator<Item = RegisteredBufMut<'_>> {
+        let (addr, buf_len) = (self.addr.as_ptr() as *mut u8, self.buf_len);
+        (0..self.count).map(move |i| unsafe {
+            Registered::new(i, slice::from_raw_parts_mut(addr.add(i as usize * buf_len), buf_len))
+        })
+    }
+
+    fn ptr(&self, index: u32) -> *mut u8 {
+        unsafe { (self.addr.as_ptr() as *mut u8).add(index as usize * self.buf_len) }
+    }
+}
+
+impl fmt::Debug for HugePageBuffers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct(std::any::type_name::<Self>())
+            .field("count", &self.count)
+            .field("buf_len", &self.buf_len)
+            .field("huge", &self.huge)
+            .finish()
+    }
+}
+
+impl Drop for HugePageBuffers {
+    fn drop(&mut self) {
+        unsafe { libc::munmap(self.addr.as_ptr(), self.len); }
+    }
+}
+
+unsafe impl Send for HugePageBuffers { }
+unsafe impl Sync for HugePageBuffers { }
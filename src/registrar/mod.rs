@@ -9,17 +9,38 @@
 //! pre-registered resources. By passing a [`RegisteredFd`] or the correct type of registered
 //! buffer to an [`SQE`][crate::SQE]'s prep methods, the SQE will be properly prepared to use the
 //! pre-registered object.
+//!
+//! Files and buffers can also be registered with tags. Once the kernel has stopped using a tagged
+//! resource which was replaced or unregistered, it posts a [`CQE`](crate::CQE) whose user data is
+//! the resource's tag, signalling that the resource can be released.
+mod buf_pool;
+mod buf_ring;
+mod eventfd;
+mod hugepage;
 mod registered;
+mod restrictions;
+mod zcrx;
 
 use std::fmt;
 use std::io;
 use std::marker::PhantomData;
-use std::ptr::NonNull;
-use std::os::unix::io::RawFd;
-
-use crate::{IoUring, Probe, resultify};
-
+use std::mem;
+use std::ptr::{self, NonNull};
+use std::os::unix::io::{AsFd, AsRawFd, RawFd};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use crate::{IoUring, Probe, SetupFlags, raw_ring, sys};
+use crate::sqe::{BufferGroupId, CancelToken};
+
+pub use buf_pool::RegisteredBufPool;
+pub use buf_ring::{BufRing, BufRingBuf, BufRingCursor, RecyclingBuf};
+pub use eventfd::EventFd;
+pub use hugepage::HugePageBuffers;
 pub use registered::*;
+pub use restrictions::Restrictions;
+pub use zcrx::{ZcrxBuf, ZcrxIfq};
 
 /// A `Registrar` creates ahead-of-time kernel references to files and user buffers.
 ///
@@ -45,14 +66,25 @@ pub use registered::*;
 /// # }
 /// ```
 pub struct Registrar<'ring> {
-    ring: NonNull<uring_sys::io_uring>,
+    ring: NonNull<IoUring>,
+    // keeps the ring alive when the registrar was split off with `IoUring::into_parts`
+    owner: Option<Arc<IoUring>>,
     _marker: PhantomData<&'ring mut IoUring>,
 }
 
 impl<'ring> Registrar<'ring> {
     pub(crate) fn new(ring: &'ring IoUring) -> Registrar<'ring> {
         Registrar {
-            ring: NonNull::from(&ring.ring),
+            ring: NonNull::from(ring),
+            owner: None,
+            _marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn owned(ring: Arc<IoUring>) -> Registrar<'static> {
+        Registrar {
+            ring: NonNull::from(&*ring),
+            owner: Some(ring),
             _marker: PhantomData,
         }
     }
@@ -62,9 +94,7 @@ impl<'ring> Registrar<'ring> {
     {
         let len = buffers.len();
         let addr = buffers.as_ptr() as *const _;
-        resultify(unsafe {
-            uring_sys::io_uring_register_buffers(self.ring.as_ptr(), addr, len as _)
-        })?;
+        self.register(sys::IORING_REGISTER_BUFFERS, addr, len as _)?;
         Ok(buffers
             .into_iter()
             .enumerate()
@@ -72,14 +102,80 @@ impl<'ring> Registrar<'ring> {
         )
     }
 
+    /// Register a set of buffers with the kernel, tagging each of them. When a buffer with a
+    /// non-zero tag is replaced or unregistered, the kernel posts a `CQE` with that tag as its
+    /// user data once it no longer uses the buffer, after which the buffer can be freed safely.
+    ///
+    /// Tags share the user data space of the ring, so they should be chosen so that they can't
+    /// be mistaken for the user data of an event.
+    ///
+    /// # Panics
+    /// Panics if `tags` and `buffers` do not have the same length.
+    pub fn register_buffers_tagged(&self, buffers: Vec<Box<[u8]>>, tags: &[u64])
+        -> io::Result<impl Iterator<Item = RegisteredBuf>>
+    {
+        assert_eq!(buffers.len(), tags.len(), "every buffer needs a tag");
+        let iovecs = iovecs(&buffers);
+        let reg = sys::io_uring_rsrc_register {
+            nr: iovecs.len() as u32,
+            data: iovecs.as_ptr() as u64,
+            tags: tags.as_ptr() as u64,
+            ..Default::default()
+        };
+        self.register(
+            sys::IORING_REGISTER_BUFFERS2,
+            &reg as *const _ as *const _,
+            mem::size_of::<sys::io_uring_rsrc_register>() as _,
+        )?;
+        Ok(buffers
+            .into_iter()
+            .enumerate()
+            .map(|(i, buf)| RegisteredBuf::new(i as u32, buf))
+        )
+    }
+
+    /// Register a set of reference counted buffers with the kernel, such as `Arc<[u8]>` or (with
+    /// the `bytes` feature) `bytes::Bytes`.
+    ///
+    /// Unlike [`register_buffers`](Registrar::register_buffers), the caller keeps ownership of its
+    /// buffers: a clone of each buffer is retained by the ring until the buffers are
+    /// [unregistered](Registrar::unregister_buffers) or the ring is dropped, so shared buffer pools
+    /// can be registered without giving them up.
+    ///
+    /// Because the buffers are shared, their contents can only be used with fixed buffer writes.
+    pub fn register_shared_buffers<B: SharedBuf>(&self, buffers: &[B])
+        -> io::Result<impl Iterator<Item = Registered<B>>>
+    {
+        let iovecs: Vec<_> = buffers.iter().map(|buf| libc::iovec {
+            iov_base: buf.as_ptr() as *mut _,
+            iov_len: buf.len(),
+        }).collect();
+        self.register(sys::IORING_REGISTER_BUFFERS, iovecs.as_ptr() as *const _, iovecs.len() as _)?;
+
+        let retained = unsafe { &(*self.ring.as_ptr()).retained_buffers };
+        let mut retained = retained.lock().unwrap();
+        retained.extend(buffers.iter().map(|buf| Box::new(buf.clone()) as Box<_>));
+        let registered: Vec<_> = buffers
+            .iter()
+            .enumerate()
+            .map(|(i, buf)| Registered::new(i as u32, buf.clone()))
+            .collect();
+        Ok(registered.into_iter())
+    }
+
+    /// Allocate `count` buffers of at least `buf_len` bytes from 2MB hugepages and register
+    /// them, falling back to regular pages if no hugepages are available. See
+    /// [`HugePageBuffers`].
+    pub fn register_hugepage_buffers(&self, count: u32, buf_len: usize) -> io::Result<HugePageBuffers> {
+        HugePageBuffers::register(self, count, buf_len)
+    }
+
     pub fn register_buffers_by_ref<'a>(&self, buffers: &'a [&'a [u8]])
         -> io::Result<impl Iterator<Item = RegisteredBufRef<'a>> + 'a>
     {
         let len = buffers.len();
         let addr = buffers.as_ptr() as *const _;
-        resultify(unsafe {
-            uring_sys::io_uring_register_buffers(self.ring.as_ptr(), addr, len as _)
-        })?;
+        self.register(sys::IORING_REGISTER_BUFFERS, addr, len as _)?;
         Ok(buffers
             .iter()
             .enumerate()
@@ -92,9 +188,7 @@ impl<'ring> Registrar<'ring> {
     {
         let len = buffers.len();
         let addr = buffers.as_ptr() as *const _;
-        resultify(unsafe {
-            uring_sys::io_uring_register_buffers(self.ring.as_ptr(), addr, len as _)
-        })?;
+        self.register(sys::IORING_REGISTER_BUFFERS, addr, len as _)?;
         Ok(buffers
             .iter_mut()
             .enumerate()
@@ -102,12 +196,124 @@ impl<'ring> Registrar<'ring> {
         )
     }
 
+    /// Reserve a buffer table of `count` empty slots with the kernel. Buffers can be placed in
+    /// these slots later, by [`RegisteredBufIndex`], without registering every buffer up front.
+    ///
+    /// Fixed buffer events against an empty slot fail with `EFAULT`.
+    ///
+    /// # Errors
+    /// Returns an error if
+    /// * there is a preexisting buffer table,
+    /// * `count` is zero or larger than the kernel's buffer table limit,
+    /// * the kernel does not support sparse registration
+    pub fn register_buffers_sparse(&self, count: u32) -> io::Result<()> {
+        let reg = sys::io_uring_rsrc_register {
+            nr: count,
+            flags: sys::IORING_RSRC_REGISTER_SPARSE,
+            ..Default::default()
+        };
+        self.register(
+            sys::IORING_REGISTER_BUFFERS2,
+            &reg as *const _ as *const _,
+            mem::size_of::<sys::io_uring_rsrc_register>() as _,
+        )?;
+        Ok(())
+    }
+
+    /// Replace the buffers in the registered buffer table starting at `offset`. This can fill
+    /// the slots of a [sparse](Registrar::register_buffers_sparse) table, or swap out buffers
+    /// which are no longer in use, without unregistering the whole table.
+    ///
+    /// # Errors
+    /// Returns an error if
+    /// * there isn't a registered buffer table,
+    /// * `offset` is out of bounds, or the `buffers` don't fit in the table,
+    /// * the kernel does not support buffer updates
+    pub fn update_registered_buffers(&mut self, offset: u32, buffers: Vec<Box<[u8]>>)
+        -> io::Result<impl Iterator<Item = RegisteredBuf>>
+    {
+        self.update_buffers(offset, buffers, ptr::null())
+    }
+
+    /// Replace the buffers in the registered buffer table starting at `offset`, tagging each of
+    /// them. When a buffer with a non-zero tag is later replaced or unregistered, the kernel posts
+    /// a `CQE` with that tag as its user data once it no longer uses the buffer.
+    ///
+    /// # Panics
+    /// Panics if `tags` and `buffers` do not have the same length.
+    pub fn update_registered_buffers_tagged(&mut self, offset: u32, buffers: Vec<Box<[u8]>>, tags: &[u64])
+        -> io::Result<impl Iterator<Item = RegisteredBuf>>
+    {
+        assert_eq!(buffers.len(), tags.len(), "every buffer needs a tag");
+        self.update_buffers(offset, buffers, tags.as_ptr())
+    }
+
+    fn update_buffers(&mut self, offset: u32, buffers: Vec<Box<[u8]>>, tags: *const u64)
+        -> io::Result<impl Iterator<Item = RegisteredBuf>>
+    {
+        assert!(buffers.len() + offset as usize <= u32::MAX as usize);
+        let iovecs = iovecs(&buffers);
+        let update = sys::io_uring_rsrc_update2 {
+            offset,
+            data: iovecs.as_ptr() as u64,
+            tags: tags as u64,
+            nr: iovecs.len() as u32,
+            ..Default::default()
+        };
+        self.register(
+            sys::IORING_REGISTER_BUFFERS_UPDATE,
+            &update as *const _ as *const _,
+            mem::size_of::<sys::io_uring_rsrc_update2>() as _,
+        )?;
+        Ok(buffers
+            .into_iter()
+            .enumerate()
+            .map(move |(i, buf)| RegisteredBuf::new(offset + i as u32, buf))
+        )
+    }
+
+    /// Register the buffer table of another ring with this ring as well, without pinning and
+    /// mapping the buffers again. Both rings then share the same registered buffers, which keep
+    /// the same indices.
+    ///
+    /// # Errors
+    /// Returns an error if
+    /// * this ring already has registered buffers,
+    /// * `src` has no registered buffers,
+    /// * the kernel does not support cloning buffers
+    pub fn clone_buffers(&self, src: &IoUring) -> io::Result<()> {
+        self.clone_buffers_with(sys::io_uring_clone_buffers {
+            src_fd: src.ring.ring_fd as u32,
+            ..Default::default()
+        })
+    }
+
+    /// Clone `count` buffers starting at `src_offset` in the buffer table of another ring into
+    /// this ring's buffer table, starting at `dst_offset`. Buffers already registered in those
+    /// slots of this ring are replaced.
+    pub fn clone_buffers_range(&self, src: &IoUring, src_offset: u32, dst_offset: u32, count: u32)
+        -> io::Result<()>
+    {
+        self.clone_buffers_with(sys::io_uring_clone_buffers {
+            src_fd: src.ring.ring_fd as u32,
+            flags: sys::IORING_REGISTER_DST_REPLACE,
+            src_off: src_offset,
+            dst_off: dst_offset,
+            nr: count,
+            ..Default::default()
+        })
+    }
+
+    fn clone_buffers_with(&self, clone: sys::io_uring_clone_buffers) -> io::Result<()> {
+        self.register(sys::IORING_REGISTER_CLONE_BUFFERS, &clone as *const _ as *const _, 1)?;
+        Ok(())
+    }
+
     /// Unregister all currently registered buffers. An explicit call to this method is often unecessary,
     /// because all buffers will be unregistered automatically when the ring is dropped.
     pub fn unregister_buffers(&self) -> io::Result<()> {
-        resultify(unsafe {
-            uring_sys::io_uring_unregister_buffers(self.ring.as_ptr())
-        })?;
+        self.register(sys::IORING_UNREGISTER_BUFFERS, ptr::null(), 0)?;
+        unsafe { (*self.ring.as_ptr()).retained_buffers.lock().unwrap().clear(); }
         Ok(())
     }
 
@@ -118,7 +324,7 @@ impl<'ring> Registrar<'ring> {
     /// Returns an error if
     /// * there is a preexisting set of registered files,
     /// * the `files` slice was empty,
-    /// * the inner [`io_uring_register_files`](uring_sys::io_uring_register_files) call failed for
+    /// * the inner `io_uring_register` call failed for
     ///   another reason
     /// ```no_run
     /// # use iou::IoUring;
@@ -136,13 +342,7 @@ impl<'ring> Registrar<'ring> {
     /// ```
     pub fn register_files<'a>(&self, files: &'a [RawFd]) -> io::Result<impl Iterator<Item = RegisteredFd> + 'a> {
         assert!(files.len() <= u32::MAX as usize);
-        resultify(unsafe {
-            uring_sys::io_uring_register_files(
-                self.ring.as_ptr(), 
-                files.as_ptr() as *const _, 
-                files.len() as _
-            )
-        })?;
+        self.register(sys::IORING_REGISTER_FILES, files.as_ptr() as *const _, files.len() as _)?;
         Ok(files
             .iter()
             .enumerate()
@@ -150,6 +350,100 @@ impl<'ring> Registrar<'ring> {
         )
     }
 
+    /// Register a set of files with the kernel from any collection of file-like objects, such as a
+    /// `&Vec<File>` or a slice of `&TcpListener`s. Like
+    /// [`register_files`](Registrar::register_files), this does not take ownership of the files,
+    /// which must stay open while they are registered.
+    pub fn register_files_from<I>(&self, files: I) -> io::Result<impl Iterator<Item = RegisteredFd>>
+    where
+        I: IntoIterator,
+        I::Item: AsFd,
+    {
+        let fds: Vec<RawFd> = files.into_iter().map(|file| file.as_fd().as_raw_fd()).collect();
+        let registered: Vec<_> = self.register_files(&fds)?.collect();
+        Ok(registered.into_iter())
+    }
+
+    /// Register a set of files with the kernel, tagging each of them. When a file with a non-zero
+    /// tag is replaced or unregistered, the kernel posts a `CQE` with that tag as its user data
+    /// once it no longer uses the file.
+    ///
+    /// Tags share the user data space of the ring, so they should be chosen so that they can't
+    /// be mistaken for the user data of an event.
+    ///
+    /// # Panics
+    /// Panics if `tags` and `files` do not have the same length.
+    pub fn register_files_tagged<'a>(&self, files: &'a [RawFd], tags: &[u64])
+        -> io::Result<impl Iterator<Item = RegisteredFd> + 'a>
+    {
+        assert!(files.len() <= u32::MAX as usize);
+        assert_eq!(files.len(), tags.len(), "every file needs a tag");
+        let reg = sys::io_uring_rsrc_register {
+            nr: files.len() as u32,
+            data: files.as_ptr() as u64,
+            tags: tags.as_ptr() as u64,
+            ..Default::default()
+        };
+        self.register(
+            sys::IORING_REGISTER_FILES2,
+            &reg as *const _ as *const _,
+            mem::size_of::<sys::io_uring_rsrc_register>() as _,
+        )?;
+        Ok(files
+            .iter()
+            .enumerate()
+            .map(|(i, &fd)| RegisteredFd::new(i as u32, fd))
+        )
+    }
+
+    /// Register a fileset of `count` empty slots with the kernel, without passing a slice of
+    /// placeholder descriptors. The slots can later be filled with
+    /// [`update_registered_files`](Registrar::update_registered_files) or by events which install
+    /// direct descriptors.
+    ///
+    /// # Errors
+    /// Returns an error if
+    /// * there is a preexisting set of registered files,
+    /// * `count` is zero or larger than the kernel's fileset limit,
+    /// * the kernel does not support sparse registration
+    /// ```
+    /// # use iou::IoUring;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let mut ring = IoUring::new(2)?;
+    /// let fileset: Vec<_> = ring.registrar().register_files_sparse(1024)?.collect();
+    /// assert!(fileset.iter().all(|fd| fd.is_placeholder()));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn register_files_sparse(&self, count: u32) -> io::Result<impl Iterator<Item = RegisteredFd>> {
+        let reg = sys::io_uring_rsrc_register {
+            nr: count,
+            flags: sys::IORING_RSRC_REGISTER_SPARSE,
+            ..Default::default()
+        };
+        self.register(
+            sys::IORING_REGISTER_FILES2,
+            &reg as *const _ as *const _,
+            mem::size_of::<sys::io_uring_rsrc_register>() as _,
+        )?;
+        Ok((0..count).map(|i| RegisteredFd::new(i, PLACEHOLDER_FD)))
+    }
+
+    /// Restrict the slots which the kernel picks for direct descriptors installed with
+    /// [`FileIndex::Alloc`](crate::sqe::FileIndex::Alloc) to the `len` slots starting at `offset`.
+    /// This keeps the rest of the registered fileset free to be managed by the application.
+    ///
+    /// # Errors
+    /// Returns an error if
+    /// * there isn't a registered fileset,
+    /// * the range does not fit within the registered fileset,
+    /// * the kernel does not support allocation ranges
+    pub fn register_file_alloc_range(&self, offset: u32, len: u32) -> io::Result<()> {
+        let range = sys::io_uring_file_index_range { off: offset, len, resv: 0 };
+        self.register(sys::IORING_REGISTER_FILE_ALLOC_RANGE, &range as *const _ as *const _, 0)?;
+        Ok(())
+    }
+
     /// Update the currently registered kernel fileset. It is usually more efficient to reserve space
     /// for files before submitting events, because `IoUring` will wait until the submission queue is
     /// empty before registering files.
@@ -159,18 +453,16 @@ impl<'ring> Registrar<'ring> {
     /// * the `files` slice was empty,
     /// * `offset` is out of bounds, 
     /// * the `files` slice was too large,
-    /// * the inner [`io_uring_register_files_update`](uring_sys::io_uring_register_files_update) call
+    /// * the inner `io_uring_register` call
     ///   failed for another reason
     pub fn update_registered_files<'a>(&mut self, offset: usize, files: &'a [RawFd]) -> io::Result<impl Iterator<Item = RegisteredFd> + 'a> {
         assert!(files.len() + offset <= u32::MAX as usize);
-        resultify(unsafe {
-            uring_sys::io_uring_register_files_update(
-                self.ring.as_ptr(),
-                offset as _,
-                files.as_ptr() as *const _,
-                files.len() as _,
-            )
-        })?;
+        let update = sys::io_uring_rsrc_update {
+            offset: offset as u32,
+            data: files.as_ptr() as u64,
+            ..Default::default()
+        };
+        self.register(sys::IORING_REGISTER_FILES_UPDATE, &update as *const _ as *const _, files.len() as _)?;
         Ok(files
             .iter()
             .enumerate()
@@ -178,13 +470,55 @@ impl<'ring> Registrar<'ring> {
         )
     }
 
+    /// Update the currently registered kernel fileset from any collection of file-like objects. See
+    /// [`register_files_from`](Registrar::register_files_from).
+    pub fn update_registered_files_from<I>(&mut self, offset: usize, files: I)
+        -> io::Result<impl Iterator<Item = RegisteredFd>>
+    where
+        I: IntoIterator,
+        I::Item: AsFd,
+    {
+        let fds: Vec<RawFd> = files.into_iter().map(|file| file.as_fd().as_raw_fd()).collect();
+        let registered: Vec<_> = self.update_registered_files(offset, &fds)?.collect();
+        Ok(registered.into_iter())
+    }
+
+    /// Update the currently registered kernel fileset, tagging each of the new files. See
+    /// [`register_files_tagged`](Registrar::register_files_tagged) for how tags are used.
+    ///
+    /// # Panics
+    /// Panics if `tags` and `files` do not have the same length.
+    pub fn update_registered_files_tagged<'a>(&mut self, offset: u32, files: &'a [RawFd], tags: &[u64])
+        -> io::Result<impl Iterator<Item = RegisteredFd> + 'a>
+    {
+        assert!(files.len() + offset as usize <= u32::MAX as usize);
+        assert_eq!(files.len(), tags.len(), "every file needs a tag");
+        let update = sys::io_uring_rsrc_update2 {
+            offset,
+            data: files.as_ptr() as u64,
+            tags: tags.as_ptr() as u64,
+            nr: files.len() as u32,
+            ..Default::default()
+        };
+        self.register(
+            sys::IORING_REGISTER_FILES_UPDATE2,
+            &update as *const _ as *const _,
+            mem::size_of::<sys::io_uring_rsrc_update2>() as _,
+        )?;
+        Ok(files
+            .iter()
+            .enumerate()
+            .map(move |(i, &fd)| RegisteredFd::new(offset + i as u32, fd))
+        )
+    }
+
     /// Unregister all currently registered files. An explicit call to this method is often unecessary,
     /// because all files will be unregistered automatically when the ring is dropped.
     ///
     /// # Errors
     /// Returns an error if
     /// * there isn't a registered fileset,
-    /// * the inner [`io_uring_unregister_files`](uring_sys::io_uring_unregister_files) call
+    /// * the inner `io_uring_register` call
     /// failed for another reason
     ///
     /// You can use this method to replace an existing fileset:
@@ -206,52 +540,327 @@ impl<'ring> Registrar<'ring> {
     /// # }
     /// ```
     pub fn unregister_files(&self) -> io::Result<()> {
-        resultify(unsafe { uring_sys::io_uring_unregister_files(self.ring.as_ptr()) })?;
+        self.register(sys::IORING_UNREGISTER_FILES, ptr::null(), 0)?;
         Ok(())
     }
 
     pub fn register_eventfd(&self, eventfd: RawFd) -> io::Result<()> {
-        resultify(unsafe {
-            uring_sys::io_uring_register_eventfd(self.ring.as_ptr(), eventfd)
-        })?;
+        self.register(sys::IORING_REGISTER_EVENTFD, &eventfd as *const _ as *const _, 1)?;
         Ok(())
     }
 
     pub fn register_eventfd_async(&self, eventfd: RawFd) -> io::Result<()> {
-        resultify(unsafe {
-            uring_sys::io_uring_register_eventfd_async(self.ring.as_ptr(), eventfd)
-        })?;
+        self.register(sys::IORING_REGISTER_EVENTFD_ASYNC, &eventfd as *const _ as *const _, 1)?;
+        Ok(())
+    }
+
+    /// Register an [`EventFd`] to be notified of completions. The ring keeps the eventfd open
+    /// until it is unregistered or the ring is dropped.
+    pub fn register_owned_eventfd(&self, eventfd: &EventFd) -> io::Result<()> {
+        self.register_eventfd(eventfd.as_raw_fd())?;
+        self.retain_eventfd(eventfd);
+        Ok(())
+    }
+
+    /// Register an [`EventFd`] to be notified only of completions which did not complete inline.
+    /// The ring keeps the eventfd open until it is unregistered or the ring is dropped.
+    pub fn register_owned_eventfd_async(&self, eventfd: &EventFd) -> io::Result<()> {
+        self.register_eventfd_async(eventfd.as_raw_fd())?;
+        self.retain_eventfd(eventfd);
         Ok(())
     }
 
     pub fn unregister_eventfd(&self) -> io::Result<()> {
-        resultify(unsafe {
-            uring_sys::io_uring_unregister_eventfd(self.ring.as_ptr())
-        })?;
+        self.register(sys::IORING_UNREGISTER_EVENTFD, ptr::null(), 0)?;
+        unsafe { *(*self.ring.as_ptr()).registered_eventfd.lock().unwrap() = None; }
         Ok(())
     }
 
+    /// Limit the number of io-wq workers the kernel may start for this ring, for bounded work
+    /// (like regular file IO) and unbounded work (like socket IO) respectively, returning the
+    /// previous limits. A limit of zero leaves that limit unchanged, so
+    /// `set_iowq_max_workers(0, 0)` only reads the current limits.
+    pub fn set_iowq_max_workers(&self, bounded: u32, unbounded: u32) -> io::Result<(u32, u32)> {
+        let mut values = [bounded, unbounded];
+        self.register(sys::IORING_REGISTER_IOWQ_MAX_WORKERS, values.as_mut_ptr() as *const _, 2)?;
+        Ok((values[0], values[1]))
+    }
+
+    /// Cancel the events matched by `token` and wait until they have completed, giving up with an
+    /// `ETIME` error once `timeout` has passed. Requires Linux 6.0 or newer.
+    ///
+    /// Returns an `ENOENT` error if no event matched. The CQEs of the cancelled events are still
+    /// posted to the completion queue.
+    pub fn cancel_sync(&self, token: &CancelToken, timeout: Option<Duration>) -> io::Result<()> {
+        let (user_data, fd, flags) = token.raw_parts();
+        let (timeout_sec, timeout_nsec) = match timeout {
+            Some(timeout)   => (timeout.as_secs() as i64, timeout.subsec_nanos() as i64),
+            None            => (-1, -1),
+        };
+        let arg = sys::io_uring_sync_cancel_reg {
+            addr: user_data, fd, flags, timeout_sec, timeout_nsec, ..Default::default()
+        };
+        self.register(sys::IORING_REGISTER_SYNC_CANCEL, &arg as *const _ as *const _, 1)?;
+        Ok(())
+    }
+
+    /// Register a [`BufRing`] of `entries` buffers of `buf_len` bytes each, which provides buffers
+    /// for the buffer group `group`.
+    ///
+    /// # Panics
+    /// Panics if `entries` is not a power of two, or `group` doesn't fit in 16 bits.
+    ///
+    /// # Errors
+    /// Returns an error if
+    /// * `entries` is larger than the kernel's limit,
+    /// * the group already has provided buffers,
+    /// * the kernel does not support buffer rings
+    pub fn register_buf_ring(&self, group: BufferGroupId, entries: u16, buf_len: u32)
+        -> io::Result<BufRing<'ring>>
+    {
+        BufRing::register(self.ring, self.owner.clone(), group, entries, buf_len)
+    }
+
+    /// Register the receive queue `if_rxq` of the network interface with index `if_idx` for
+    /// zero-copy receive, with a refill ring of `rq_entries` entries and an area of `area_len`
+    /// bytes the payloads are received into.
+    ///
+    /// # Panics
+    /// Panics if `rq_entries` or `area_len` is zero.
+    ///
+    /// # Errors
+    /// Returns an error if
+    /// * the ring was not set up with `DEFER_TASKRUN` and `CQE32`,
+    /// * the interface or the queue does not exist, or does not support zero-copy receive,
+    /// * the thread lacks `CAP_NET_ADMIN`,
+    /// * the kernel does not support zero-copy receive
+    pub fn register_ifq(&self, if_idx: u32, if_rxq: u32, rq_entries: u32, area_len: usize)
+        -> io::Result<ZcrxIfq<'ring>>
+    {
+        ZcrxIfq::register(self.ring, self.owner.clone(), if_idx, if_rxq, rq_entries, area_len)
+    }
+
+    /// Register the credentials of the current thread as a personality, which can be used to
+    /// submit events with these credentials after they have changed.
     pub fn register_personality(&self) -> io::Result<Personality> {
-        let id = resultify(unsafe { uring_sys::io_uring_register_personality(self.ring.as_ptr()) })?;
+        let id = self.register(sys::IORING_REGISTER_PERSONALITY, ptr::null(), 0)?;
         debug_assert!(id < u16::MAX as u32);
-        Ok(Personality { id: id as u16 })
+        let personality = Personality { id: id as u16 };
+        let creds = PersonalityCreds::current();
+        unsafe { (*self.ring.as_ptr()).personalities.lock().unwrap().push((personality, creds)); }
+        Ok(personality)
+    }
+
+    /// Register a personality like [`register_personality`](Registrar::register_personality),
+    /// returning a guard which unregisters it when dropped.
+    pub fn register_personality_guard(&self) -> io::Result<PersonalityGuard<'ring>> {
+        let personality = self.register_personality()?;
+        let registrar = Registrar { ring: self.ring, owner: self.owner.clone(), _marker: PhantomData };
+        Ok(PersonalityGuard { registrar, personality })
     }
 
     pub fn unregister_personality(&self, personality: Personality) -> io::Result<()> {
-        resultify(unsafe {
-            uring_sys::io_uring_unregister_personality(self.ring.as_ptr(), personality.id as _)
-        })?;
+        self.register(sys::IORING_UNREGISTER_PERSONALITY, ptr::null(), personality.id as _)?;
+        let personalities = unsafe { &(*self.ring.as_ptr()).personalities };
+        personalities.lock().unwrap().retain(|&(p, _)| p != personality);
+        Ok(())
+    }
+
+    /// The personalities currently registered through this ring's `Registrar`, in the order they
+    /// were registered.
+    ///
+    /// The kernel offers no way to list personalities, so they are tracked as they are
+    /// registered and unregistered; personalities registered on the ring fd directly are not
+    /// included.
+    pub fn personalities(&self) -> Vec<Personality> {
+        let personalities = unsafe { &(*self.ring.as_ptr()).personalities };
+        personalities.lock().unwrap().iter().map(|&(p, _)| p).collect()
+    }
+
+    /// The credentials a personality was registered with, or `None` if it is not one of the
+    /// [`personalities`](Registrar::personalities) of this ring.
+    pub fn personality_creds(&self, personality: Personality) -> Option<PersonalityCreds> {
+        let personalities = unsafe { &(*self.ring.as_ptr()).personalities };
+        let personalities = personalities.lock().unwrap();
+        personalities.iter().find(|&&(p, _)| p == personality).map(|&(_, creds)| creds)
+    }
+
+    /// Register the ring's own file descriptor with the kernel. Until it is unregistered, the
+    /// submit and wait paths of the ring enter the kernel through the registered descriptor,
+    /// which avoids looking up the ring fd on every call.
+    ///
+    /// Each thread has its own table of registered ring fds, so the registered descriptor is only
+    /// used on the calling thread. Other threads keep entering the ring through its normal file
+    /// descriptor. The registration is undone when the ring is dropped on the calling thread; if
+    /// it is dropped on another thread, the kernel holds on to the ring until the calling thread
+    /// exits.
+    ///
+    /// # Errors
+    /// Returns an error if
+    /// * the ring fd is already registered,
+    /// * the kernel does not support registered ring fds,
+    /// * the calling thread has no free registered ring slots.
+    pub fn register_ring_fd(&self) -> io::Result<()> {
+        let registered = unsafe { &(*self.ring.as_ptr()).registered_ring_fd };
+        if registered.load(Ordering::Relaxed) != -1 {
+            return Err(io::Error::from_raw_os_error(libc::EEXIST));
+        }
+
+        let mut update = sys::io_uring_rsrc_update {
+            offset: !0,
+            data: unsafe { (*raw_ring(self.ring)).ring_fd } as u64,
+            ..Default::default()
+        };
+        self.register(sys::IORING_REGISTER_RING_FDS, &mut update as *mut _ as *const _, 1)?;
+        unsafe { (*self.ring.as_ptr()).set_registered_ring_fd(update.offset as i32); }
+        Ok(())
+    }
+
+    /// Unregister the ring's file descriptor, which was registered with
+    /// [`register_ring_fd`](Registrar::register_ring_fd). The ring will enter the kernel through
+    /// its normal file descriptor again.
+    ///
+    /// # Errors
+    /// Returns `EINVAL` if the ring fd was not registered by the calling thread, or if the ring
+    /// was created with [`SetupFlags::REGISTERED_FD_ONLY`](crate::SetupFlags::REGISTERED_FD_ONLY)
+    /// and so has no other file descriptor.
+    pub fn unregister_ring_fd(&self) -> io::Result<()> {
+        let registered = unsafe { &(*self.ring.as_ptr()).registered_ring_fd };
+        let index = match unsafe { crate::registered_ring_index(self.ring) } {
+            Some(index) if unsafe { (*raw_ring(self.ring)).ring_fd } != -1  => index,
+            _                                                               => {
+                return Err(io::Error::from_raw_os_error(libc::EINVAL));
+            }
+        };
+
+        let update = sys::io_uring_rsrc_update { offset: index as u32, ..Default::default() };
+        self.register(sys::IORING_UNREGISTER_RING_FDS, &update as *const _ as *const _, 1)?;
+        registered.store(-1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Returns true if the ring's file descriptor is currently registered.
+    pub fn is_ring_fd_registered(&self) -> bool {
+        unsafe { (*self.ring.as_ptr()).registered_ring_fd.load(Ordering::Relaxed) != -1 }
+    }
+
+    /// Register a set of [`Restrictions`] on what can be done with this ring.
+    ///
+    /// # Errors
+    /// Returns an error if
+    /// * the ring is not [disabled](crate::SetupFlags::R_DISABLED),
+    /// * restrictions have already been registered for this ring,
+    /// * the restrictions are invalid
+    pub fn register_restrictions(&self, restrictions: &Restrictions) -> io::Result<()> {
+        let raw = restrictions.as_raw();
+        self.register(sys::IORING_REGISTER_RESTRICTIONS, raw.as_ptr() as *const _, raw.len() as _)?;
+        Ok(())
+    }
+
+    /// Enable a ring which was created with [`SetupFlags::R_DISABLED`](crate::SetupFlags::R_DISABLED).
+    /// Events can't be submitted to the ring until it has been enabled.
+    pub fn enable_rings(&self) -> io::Result<()> {
+        self.register(sys::IORING_REGISTER_ENABLE_RINGS, ptr::null(), 0)?;
         Ok(())
     }
 
     pub fn probe(&self) -> io::Result<Probe> {
-        Probe::for_ring(self.ring.as_ptr())
+        Probe::for_ring(self.ring)
+    }
+
+    /// Resize the submission and completion queues of a live ring to `sq_entries` and
+    /// `cq_entries` entries, rounded up to powers of two. The queues are remapped in place, and
+    /// events which were submitted but haven't completed yet carry on unaffected.
+    ///
+    /// A long running server can use this to grow its completion queue once it starts to see
+    /// [overflows](crate::SQFlags::CQ_OVERFLOW), instead of setting up every ring for the worst
+    /// case. This requires a ring set up with [`SetupFlags::DEFER_TASKRUN`] and Linux 6.13 or
+    /// newer.
+    ///
+    /// # Safety
+    ///
+    /// Nothing else may use the ring while it is resized: no [`SubmissionQueue`],
+    /// [`CompletionQueue`], prepared [`SQE`] or borrowed CQE of the ring may be in use, on this
+    /// thread or any other, and no [`Params`](crate::Params) of the ring may be borrowed.
+    /// [`IoUring::resize_rings`] ensures this through its mutable borrow.
+    ///
+    /// # Errors
+    /// Returns an error if
+    /// * there are prepared SQEs which haven't been submitted, or submitted SQEs which the kernel
+    ///   hasn't consumed yet (`EBUSY`),
+    /// * the ring lives in [application memory](crate::RingMemory) (`EOPNOTSUPP`),
+    /// * the ring was not set up with `DEFER_TASKRUN` (`EINVAL`),
+    /// * the pending submissions or completions don't fit in the new queues (`EOVERFLOW`).
+    ///
+    /// If the rings are resized but the new rings can't be mapped, the error is returned and the
+    /// ring can't be used anymore.
+    ///
+    /// [`SubmissionQueue`]: crate::SubmissionQueue
+    /// [`CompletionQueue`]: crate::CompletionQueue
+    /// [`SQE`]: crate::SQE
+    /// [`SetupFlags::DEFER_TASKRUN`]: crate::SetupFlags::DEFER_TASKRUN
+    pub unsafe fn resize_rings(&self, sq_entries: u32, cq_entries: u32) -> io::Result<()> {
+        let ring = &mut *self.ring.as_ptr();
+        if ring.memory.is_some() {
+            return Err(io::Error::from_raw_os_error(libc::EOPNOTSUPP));
+        }
+        // the kernel only moves the SQEs which were submitted to the new ring, and the SQ array
+        // of the new ring is only set up while the kernel has consumed every entry
+        let sq = &ring.ring.sq;
+        let khead = (*(sq.khead as *const AtomicU32)).load(Ordering::Acquire);
+        if sq.sqe_head != sq.sqe_tail || khead != *sq.ktail {
+            return Err(io::Error::from_raw_os_error(libc::EBUSY));
+        }
+
+        let mut resize: sys::io_uring_params = mem::zeroed();
+        resize.flags = SetupFlags::CQSIZE.bits();
+        resize.sq_entries = sq_entries;
+        resize.cq_entries = cq_entries;
+        self.register(sys::IORING_REGISTER_RESIZE_RINGS, &mut resize as *mut _ as *const _, 1)?;
+
+        // the kernel returns the sizes and offsets of the new rings, everything else stays
+        let mut params = ptr::read(ring.params.raw());
+        params.sq_entries = resize.sq_entries;
+        params.cq_entries = resize.cq_entries;
+        params.sq_off = resize.sq_off;
+        params.cq_off = resize.cq_off;
+        if params.sq_off.array == 0 {
+            // the kernel doesn't report where the new SQ array is: it follows the CQEs, aligned
+            // to a cache line
+            let cqe_len = mem::size_of::<sys::io_uring_cqe>() << crate::cqe_shift(params.flags);
+            let cqes_end = params.cq_off.cqes as usize + params.cq_entries as usize * cqe_len;
+            params.sq_off.array = crate::ring_memory::round_up(cqes_end, 64) as u32;
+        }
+
+        let resized = crate::map_rings(ring.ring.ring_fd, &params)?;
+        crate::unmap_ring_memory(&ring.ring);
+        ring.ring = resized;
+        ring.cached_sq_head.store(*ring.ring.sq.khead, Ordering::Relaxed);
+        ring.params = crate::Params::new(&params);
+        Ok(())
     }
 }
 
+impl Registrar<'_> {
+    fn register(&self, opcode: u32, arg: *const libc::c_void, nr_args: u32) -> io::Result<u32> {
+        unsafe { crate::register(self.ring, opcode, arg, nr_args) }
+    }
+
+    fn retain_eventfd(&self, eventfd: &EventFd) {
+        unsafe { *(*self.ring.as_ptr()).registered_eventfd.lock().unwrap() = Some(eventfd.clone()); }
+    }
+}
+
+fn iovecs(buffers: &[Box<[u8]>]) -> Vec<libc::iovec> {
+    buffers.iter().map(|buf| libc::iovec {
+        iov_base: buf.as_ptr() as *mut _,
+        iov_len: buf.len(),
+    }).collect()
+}
+
 impl fmt::Debug for Registrar<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let fd = unsafe { self.ring.as_ref().ring_fd };
+        let fd = unsafe { (*raw_ring(self.ring)).ring_fd };
         f.debug_struct(std::any::type_name::<Self>()).field("fd", &fd).finish()
     }
 }
@@ -264,12 +873,60 @@ pub struct Personality {
     pub(crate) id: u16,
 }
 
+impl Personality {
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+}
+
 impl From<u16> for Personality {
     fn from(id: u16) -> Personality {
         Personality { id }
     }
 }
 
+/// The effective user and group ids that a [`Personality`] was registered with.
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+pub struct PersonalityCreds {
+    uid: libc::uid_t,
+    gid: libc::gid_t,
+}
+
+impl PersonalityCreds {
+    fn current() -> PersonalityCreds {
+        unsafe { PersonalityCreds { uid: libc::geteuid(), gid: libc::getegid() } }
+    }
+
+    pub fn uid(&self) -> libc::uid_t {
+        self.uid
+    }
+
+    pub fn gid(&self) -> libc::gid_t {
+        self.gid
+    }
+}
+
+/// A registered [`Personality`] which is unregistered when the guard is dropped.
+///
+/// Created by [`Registrar::register_personality_guard`].
+#[derive(Debug)]
+pub struct PersonalityGuard<'ring> {
+    registrar: Registrar<'ring>,
+    personality: Personality,
+}
+
+impl PersonalityGuard<'_> {
+    pub fn personality(&self) -> Personality {
+        self.personality
+    }
+}
+
+impl Drop for PersonalityGuard<'_> {
+    fn drop(&mut self) {
+        let _ = self.registrar.unregister_personality(self.personality);
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -1,8 +1,10 @@
 use std::io;
 use std::ops::*;
 use std::os::unix::io::{AsRawFd, RawFd};
+use std::sync::Arc;
 
 use crate::SQE;
+use crate::sqe::prep;
 
 pub const PLACEHOLDER_FD: RawFd = -1;
 
@@ -25,22 +27,113 @@ pub type RegisteredBufMut<'a>   = Registered<&'a mut [u8]>;
 pub struct Registered<T> {
     data: T,
     index: u32,
+    offset: usize,
 }
 
 impl<T> Registered<T> {
     pub fn new(index: u32, data: T) -> Registered<T> {
-        Registered { data, index }
+        Registered { data, index, offset: 0 }
+    }
+
+    fn at(index: u32, offset: usize, data: T) -> Registered<T> {
+        Registered { data, index, offset }
     }
 
     pub fn index(&self) -> u32 {
         self.index
     }
 
+    /// The offset of this view from the start of the registered buffer it was sliced from, or 0
+    /// for anything which is not a slice of a registered buffer.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
     pub fn into_inner(self) -> T {
         self.data
     }
 }
 
+/// A slot of the kernel's registered fileset which holds a direct descriptor.
+///
+/// Events like [`prep_accept_direct`](crate::SQE::prep_accept_direct) and
+/// [`prep_openat_direct`](crate::SQE::prep_openat_direct) install files into the registered
+/// fileset without ever creating a regular file descriptor for them. A `DirectSlot` refers to
+/// such a file only by its slot; unlike a [`RegisteredFd`], there is no file descriptor owned by
+/// the process behind it.
+///
+/// A direct descriptor is closed with [`prep_close_direct`](crate::SQE::prep_close_direct), and
+/// can be turned into a regular file descriptor with
+/// [`prep_fixed_fd_install`](crate::SQE::prep_fixed_fd_install).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct DirectSlot {
+    index: u32,
+}
+
+impl DirectSlot {
+    pub fn new(index: u32) -> DirectSlot {
+        DirectSlot { index }
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+}
+
+impl From<RegisteredFd> for DirectSlot {
+    fn from(fd: RegisteredFd) -> DirectSlot {
+        DirectSlot::new(fd.index)
+    }
+}
+
+/// The index of a buffer in the kernel's registered buffer table.
+///
+/// This can be obtained from any of the registered buffer types, and is accepted by the fixed
+/// buffer prep methods on [`SQE`](crate::SQE).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct RegisteredBufIndex {
+    index: u32,
+}
+
+impl RegisteredBufIndex {
+    pub(crate) fn new(index: u32) -> RegisteredBufIndex {
+        RegisteredBufIndex { index }
+    }
+
+    /// Refer to the slot `index` of the registered buffer table, without a buffer to take it
+    /// from.
+    ///
+    /// # Safety
+    ///
+    /// The slot must belong to a buffer table the caller controls: fixed buffer events against
+    /// it read from or write into whatever buffer is in the slot.
+    pub unsafe fn from_raw(index: u32) -> RegisteredBufIndex {
+        RegisteredBufIndex { index }
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+}
+
+impl<'a> From<&'a RegisteredBuf> for RegisteredBufIndex {
+    fn from(buf: &'a RegisteredBuf) -> RegisteredBufIndex {
+        buf.buf_index()
+    }
+}
+
+impl<'a> From<&'a RegisteredBufRef<'_>> for RegisteredBufIndex {
+    fn from(buf: &'a RegisteredBufRef<'_>) -> RegisteredBufIndex {
+        buf.buf_index()
+    }
+}
+
+impl<'a> From<&'a RegisteredBufMut<'_>> for RegisteredBufIndex {
+    fn from(buf: &'a RegisteredBufMut<'_>) -> RegisteredBufIndex {
+        buf.buf_index()
+    }
+}
+
 impl RegisteredFd {
     pub fn is_placeholder(&self) -> bool {
         self.data == PLACEHOLDER_FD
@@ -53,89 +146,122 @@ impl AsRawFd for RegisteredFd {
     }
 }
 
+impl<B: SharedBuf> Registered<B> {
+    pub fn buf_index(&self) -> RegisteredBufIndex {
+        RegisteredBufIndex::new(self.index)
+    }
+
+    pub fn as_ref(&self) -> RegisteredBufRef<'_> {
+        Registered::at(self.index, self.offset, &self.data[..])
+    }
+
+    pub fn slice(&self, range: Range<usize>) -> RegisteredBufRef<'_> {
+        Registered::at(self.index, self.offset + range.start, &self.data[range])
+    }
+}
+
 impl RegisteredBuf {
+    pub fn buf_index(&self) -> RegisteredBufIndex {
+        RegisteredBufIndex::new(self.index)
+    }
+
     pub fn as_ref(&self) -> RegisteredBufRef<'_> {
-        Registered::new(self.index, &self.data[..])
+        Registered::at(self.index, self.offset, &self.data[..])
     }
 
     pub fn as_mut(&mut self) -> RegisteredBufMut<'_> {
-        Registered::new(self.index, &mut self.data[..])
+        Registered::at(self.index, self.offset, &mut self.data[..])
     }
 
+    /// A view of `range` of the buffer. The view keeps the index of the buffer, so fixed buffer
+    /// IO can read from or write to the middle of a registered buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds of the buffer.
     pub fn slice(&self, range: Range<usize>) -> RegisteredBufRef<'_> {
-        Registered::new(self.index, &self.data[range])
+        Registered::at(self.index, self.offset + range.start, &self.data[range])
     }
 
+    /// A mutable view of `range` of the buffer, see [`slice`](Registered::slice).
     pub fn slice_mut(&mut self, range: Range<usize>) -> RegisteredBufMut<'_> {
-        Registered::new(self.index, &mut self.data[range])
+        Registered::at(self.index, self.offset + range.start, &mut self.data[range])
     }
 
     pub fn slice_to(&self, index: usize) -> RegisteredBufRef<'_> {
-        Registered::new(self.index, &self.data[..index])
+        Registered::at(self.index, self.offset, &self.data[..index])
     }
 
     pub fn slice_to_mut(&mut self, index: usize) -> RegisteredBufMut<'_> {
-        Registered::new(self.index, &mut self.data[..index])
+        Registered::at(self.index, self.offset, &mut self.data[..index])
     }
 
     pub fn slice_from(&self, index: usize) -> RegisteredBufRef<'_> {
-        Registered::new(self.index, &self.data[index..])
+        Registered::at(self.index, self.offset + index, &self.data[index..])
     }
 
     pub fn slice_from_mut(&mut self, index: usize) -> RegisteredBufMut<'_> {
-        Registered::new(self.index, &mut self.data[index..])
+        Registered::at(self.index, self.offset + index, &mut self.data[index..])
     }
 }
 
 impl<'a> RegisteredBufRef<'a> {
+    pub fn buf_index(&self) -> RegisteredBufIndex {
+        RegisteredBufIndex::new(self.index)
+    }
+
     pub fn as_ref(&self) -> RegisteredBufRef<'_> {
-        Registered::new(self.index, &self.data[..])
+        Registered::at(self.index, self.offset, &self.data[..])
     }
 
     pub fn slice(self, range: Range<usize>) -> RegisteredBufRef<'a> {
-        Registered::new(self.index, &self.data[range])
+        Registered::at(self.index, self.offset + range.start, &self.data[range])
     }
 
     pub fn slice_to(&self, index: usize) -> RegisteredBufRef<'_> {
-        Registered::new(self.index, &self.data[..index])
+        Registered::at(self.index, self.offset, &self.data[..index])
     }
 
     pub fn slice_from(&self, index: usize) -> RegisteredBufRef<'_> {
-        Registered::new(self.index, &self.data[index..])
+        Registered::at(self.index, self.offset + index, &self.data[index..])
     }
 }
 
 impl<'a> RegisteredBufMut<'a> {
+    pub fn buf_index(&self) -> RegisteredBufIndex {
+        RegisteredBufIndex::new(self.index)
+    }
+
     pub fn as_ref(&self) -> RegisteredBufRef<'_> {
-        Registered::new(self.index, &self.data[..])
+        Registered::at(self.index, self.offset, &self.data[..])
     }
 
     pub fn as_mut(&mut self) -> RegisteredBufMut<'_> {
-        Registered::new(self.index, &mut self.data[..])
+        Registered::at(self.index, self.offset, &mut self.data[..])
     }
 
     pub fn slice(self, range: Range<usize>) -> RegisteredBufRef<'a> {
-        Registered::new(self.index, &self.data[range])
+        Registered::at(self.index, self.offset + range.start, &self.data[range])
     }
 
     pub fn slice_mut(self, range: Range<usize>) -> RegisteredBufMut<'a> {
-        Registered::new(self.index, &mut self.data[range])
+        Registered::at(self.index, self.offset + range.start, &mut self.data[range])
     }
 
     pub fn slice_to(&self, index: usize) -> RegisteredBufRef<'_> {
-        Registered::new(self.index, &self.data[..index])
+        Registered::at(self.index, self.offset, &self.data[..index])
     }
 
     pub fn slice_to_mut(&mut self, index: usize) -> RegisteredBufMut<'_> {
-        Registered::new(self.index, &mut self.data[..index])
+        Registered::at(self.index, self.offset, &mut self.data[..index])
     }
 
     pub fn slice_from(&self, index: usize) -> RegisteredBufRef<'_> {
-        Registered::new(self.index, &self.data[index..])
+        Registered::at(self.index, self.offset + index, &self.data[index..])
     }
 
     pub fn slice_from_mut(&mut self, index: usize) -> RegisteredBufMut<'_> {
-        Registered::new(self.index, &mut self.data[index..])
+        Registered::at(self.index, self.offset + index, &mut self.data[index..])
     }
 }
 
@@ -147,6 +273,14 @@ impl Deref for RegisteredBuf {
     }
 }
 
+impl<B: SharedBuf> Deref for Registered<B> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.data[..]
+    }
+}
+
 impl Deref for RegisteredBufRef<'_> {
     type Target = [u8];
 
@@ -174,6 +308,27 @@ impl DerefMut for RegisteredBufMut<'_> {
         &mut self.data[..]
     }
 }
+
+/// Reference counted storage which can be registered as a buffer with
+/// [`register_shared_buffers`](crate::registrar::Registrar::register_shared_buffers).
+///
+/// The `Registrar` keeps a clone of each registered buffer alive until the buffers are
+/// unregistered, so the storage stays valid for the kernel even after all other handles to it are
+/// dropped.
+///
+/// This is implemented for `Arc<[u8]>`, and for `bytes::Bytes` with the `bytes` feature enabled.
+///
+/// # Safety
+///
+/// Every clone of a value must dereference to the same memory, and that memory must not move or
+/// be freed while any clone is alive.
+pub unsafe trait SharedBuf: Clone + Deref<Target = [u8]> + Send + Sync + 'static { }
+
+unsafe impl SharedBuf for Arc<[u8]> { }
+
+#[cfg(feature = "bytes")]
+unsafe impl SharedBuf for bytes::Bytes { }
+
 /// A file descriptor that can be used to prepare SQEs.
 ///
 /// The standard library's [`RawFd`] type implements this trait, but so does [`RegisteredFd`], a
@@ -202,59 +357,144 @@ impl UringFd for RegisteredFd {
     }
 }
 
-/// A buffer that can be used to prepare read events.
-pub trait UringReadBuf {
-    unsafe fn prep_read(self, fd: impl UringFd, sqe: &mut SQE<'_>, offset: u64);
+impl UringFd for DirectSlot {
+    fn as_raw_fd(&self) -> RawFd {
+        self.index as RawFd
+    }
+
+    fn update_sqe(&self, sqe: &mut SQE<'_>) {
+        unsafe { sqe.raw_mut().fd = self.index as RawFd; }
+        sqe.set_fixed_file();
+    }
 }
 
-/// A buffer that can be used to prepare write events.
-pub trait UringWriteBuf {
-    unsafe fn prep_write(self, fd: impl UringFd, sqe: &mut SQE<'_>, offset: u64);
+/// A buffer that events can take data from, like the data of a send or write event.
+///
+/// This is implemented for byte slices and [`io::IoSlice`], and for views of registered buffers.
+/// Events which support fixed buffers use the registered buffer of a view directly, instead of
+/// mapping its memory again for every event.
+pub trait UringBuf {
+    fn as_buf(&self) -> &[u8];
+
+    /// The registered buffer this buffer lies within, if any.
+    fn registered_index(&self) -> Option<RegisteredBufIndex> {
+        None
+    }
 }
 
-impl UringReadBuf for RegisteredBufMut<'_> {
-    unsafe fn prep_read(self, fd: impl UringFd, sqe: &mut SQE<'_>, offset: u64) {
-        uring_sys::io_uring_prep_read_fixed(
-            sqe.raw_mut(),
-            fd.as_raw_fd(),
-            self.data.as_mut_ptr() as _,
-            self.data.len() as _,
-            offset as _,
-            self.index() as _
-        );
-        fd.update_sqe(sqe);
+/// A buffer that events can fill with data, like the buffer of a recv or read event.
+///
+/// This is implemented for mutable byte slices and [`io::IoSliceMut`], and for mutable views of
+/// registered buffers. See [`UringBuf`].
+pub trait UringBufMut {
+    fn as_buf_mut(&mut self) -> &mut [u8];
+
+    /// The registered buffer this buffer lies within, if any.
+    fn registered_index(&self) -> Option<RegisteredBufIndex> {
+        None
     }
 }
 
-impl UringReadBuf for &'_ mut [u8] {
-    unsafe fn prep_read(self, fd: impl UringFd, sqe: &mut SQE<'_>, offset: u64) {
-        uring_sys::io_uring_prep_read(
-            sqe.raw_mut(),
-            fd.as_raw_fd(),
-            self.as_mut_ptr() as _,
-            self.len() as _,
-            offset as _,
-        );
-        fd.update_sqe(sqe);
+impl UringBuf for &'_ [u8] {
+    fn as_buf(&self) -> &[u8] {
+        self
+    }
+}
+
+impl<const N: usize> UringBuf for &'_ [u8; N] {
+    fn as_buf(&self) -> &[u8] {
+        &self[..]
+    }
+}
+
+impl UringBuf for io::IoSlice<'_> {
+    fn as_buf(&self) -> &[u8] {
+        self
     }
 }
 
-impl UringReadBuf for io::IoSliceMut<'_> {
+impl UringBuf for RegisteredBufRef<'_> {
+    fn as_buf(&self) -> &[u8] {
+        self.data
+    }
+
+    fn registered_index(&self) -> Option<RegisteredBufIndex> {
+        Some(self.buf_index())
+    }
+}
+
+impl UringBufMut for &'_ mut [u8] {
+    fn as_buf_mut(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+impl<const N: usize> UringBufMut for &'_ mut [u8; N] {
+    fn as_buf_mut(&mut self) -> &mut [u8] {
+        &mut self[..]
+    }
+}
+
+impl UringBufMut for io::IoSliceMut<'_> {
+    fn as_buf_mut(&mut self) -> &mut [u8] {
+        self
+    }
+}
+
+impl UringBufMut for RegisteredBufMut<'_> {
+    fn as_buf_mut(&mut self) -> &mut [u8] {
+        self.data
+    }
+
+    fn registered_index(&self) -> Option<RegisteredBufIndex> {
+        Some(self.buf_index())
+    }
+}
+
+/// A buffer that can be used to prepare read events.
+///
+/// This is implemented for every [`UringBufMut`], and for slices of buffers, which prepare
+/// vectored reads.
+pub trait UringReadBuf {
+    unsafe fn prep_read(self, fd: impl UringFd, sqe: &mut SQE<'_>, offset: u64);
+}
+
+/// A buffer that can be used to prepare write events.
+///
+/// This is implemented for every [`UringBuf`], and for slices of buffers, which prepare
+/// vectored writes.
+pub trait UringWriteBuf {
+    unsafe fn prep_write(self, fd: impl UringFd, sqe: &mut SQE<'_>, offset: u64);
+}
+
+impl<B: UringBufMut> UringReadBuf for B {
     unsafe fn prep_read(mut self, fd: impl UringFd, sqe: &mut SQE<'_>, offset: u64) {
-        uring_sys::io_uring_prep_read(
-            sqe.raw_mut(),
-            fd.as_raw_fd(),
-            self.as_mut_ptr() as _,
-            self.len() as _,
-            offset as _,
-        );
+        let index = self.registered_index();
+        let buf = self.as_buf_mut();
+        match index {
+            Some(index) => prep::read_fixed(
+                sqe.raw_mut(),
+                fd.as_raw_fd(),
+                buf.as_mut_ptr() as _,
+                buf.len() as _,
+                offset as _,
+                index.index() as _
+            ),
+            None => prep::read(
+                sqe.raw_mut(),
+                fd.as_raw_fd(),
+                buf.as_mut_ptr() as _,
+                buf.len() as _,
+                offset as _,
+            ),
+        }
         fd.update_sqe(sqe);
     }
 }
 
 impl UringReadBuf for &'_ mut [&'_ mut [u8]] {
     unsafe fn prep_read(self, fd: impl UringFd, sqe: &mut SQE<'_>, offset: u64) {
-        uring_sys::io_uring_prep_readv(
+        prep::readv(
             sqe.raw_mut(),
             fd.as_raw_fd(),
             self.as_mut_ptr() as _,
@@ -267,7 +507,7 @@ impl UringReadBuf for &'_ mut [&'_ mut [u8]] {
 
 impl UringReadBuf for &'_ mut [io::IoSliceMut<'_>] {
     unsafe fn prep_read(self, fd: impl UringFd, sqe: &mut SQE<'_>, offset: u64) {
-        uring_sys::io_uring_prep_readv(
+        prep::readv(
             sqe.raw_mut(),
             fd.as_raw_fd(),
             self.as_mut_ptr() as _,
@@ -278,49 +518,33 @@ impl UringReadBuf for &'_ mut [io::IoSliceMut<'_>] {
     }
 }
 
-impl UringWriteBuf for RegisteredBufRef<'_> {
+impl<B: UringBuf> UringWriteBuf for B {
     unsafe fn prep_write(self, fd: impl UringFd, sqe: &mut SQE<'_>, offset: u64) {
-        uring_sys::io_uring_prep_write_fixed(
-            sqe.raw_mut(),
-            fd.as_raw_fd(),
-            self.data.as_ptr() as _,
-            self.data.len() as _,
-            offset as _,
-            self.index() as _
-        );
-        fd.update_sqe(sqe);
-    }
-}
-
-impl UringWriteBuf for &'_ [u8] {
-    unsafe fn prep_write(self, fd: impl UringFd, sqe: &mut SQE<'_>, offset: u64) {
-        uring_sys::io_uring_prep_write(
-            sqe.raw_mut(),
-            fd.as_raw_fd(),
-            self.as_ptr() as _,
-            self.len() as _,
-            offset as _,
-        );
-        fd.update_sqe(sqe);
-    }
-}
-
-impl UringWriteBuf for io::IoSlice<'_> {
-    unsafe fn prep_write(self, fd: impl UringFd, sqe: &mut SQE<'_>, offset: u64) {
-        uring_sys::io_uring_prep_write(
-            sqe.raw_mut(),
-            fd.as_raw_fd(),
-            self.as_ptr() as _,
-            self.len() as _,
-            offset as _,
-        );
+        let buf = self.as_buf();
+        match self.registered_index() {
+            Some(index) => prep::write_fixed(
+                sqe.raw_mut(),
+                fd.as_raw_fd(),
+                buf.as_ptr() as _,
+                buf.len() as _,
+                offset as _,
+                index.index() as _
+            ),
+            None => prep::write(
+                sqe.raw_mut(),
+                fd.as_raw_fd(),
+                buf.as_ptr() as _,
+                buf.len() as _,
+                offset as _,
+            ),
+        }
         fd.update_sqe(sqe);
     }
 }
 
 impl UringWriteBuf for &'_ [io::IoSlice<'_>] {
     unsafe fn prep_write(self, fd: impl UringFd, sqe: &mut SQE<'_>, offset: u64) {
-        uring_sys::io_uring_prep_writev(
+        prep::writev(
             sqe.raw_mut(),
             fd.as_raw_fd(),
             self.as_ptr() as _,
@@ -333,7 +557,7 @@ impl UringWriteBuf for &'_ [io::IoSlice<'_>] {
 
 impl UringWriteBuf for &'_ [&'_ [u8]] {
     unsafe fn prep_write(self, fd: impl UringFd, sqe: &mut SQE<'_>, offset: u64) {
-        uring_sys::io_uring_prep_writev(
+        prep::writev(
             sqe.raw_mut(),
             fd.as_raw_fd(),
             self.as_ptr() as _,
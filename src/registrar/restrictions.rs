@@ -0,0 +1,75 @@
+use crate::sqe::SubmissionFlags;
+use crate::sys;
+
+/// A set of restrictions on what can be done with a ring, registered with
+/// [`Registrar::register_restrictions`](crate::Registrar::register_restrictions).
+///
+/// Restrictions are an allow list: once they are registered, only the events, registration
+/// operations and `SQE` flags they allow will be accepted by the ring. They can only be
+/// registered while the ring is disabled, which makes it possible to set up a ring before handing
+/// it to less trusted code.
+///
+/// ```
+/// # use iou::registrar::Restrictions;
+/// # use iou::sqe::SubmissionFlags;
+/// use iou::sys::IoRingOp;
+///
+/// let restrictions = Restrictions::new()
+///     .allow_op(IoRingOp::IORING_OP_READ)
+///     .allow_op(IoRingOp::IORING_OP_WRITE)
+///     .allow_register_op(iou::sys::IORING_REGISTER_FILES_UPDATE)
+///     .allow_sqe_flags(SubmissionFlags::IO_LINK)
+///     .require_sqe_flags(SubmissionFlags::FIXED_FILE);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Restrictions {
+    restrictions: Vec<sys::io_uring_restriction>,
+}
+
+impl Restrictions {
+    /// Create an empty set of restrictions, which allows nothing.
+    pub fn new() -> Restrictions {
+        Restrictions::default()
+    }
+
+    /// Allow events with this opcode to be submitted.
+    pub fn allow_op(self, op: sys::IoRingOp) -> Restrictions {
+        self.allow_raw_op(op as u8)
+    }
+
+    /// Allow events with this raw opcode to be submitted, for opcodes which are newer than
+    /// [`IoRingOp`](sys::IoRingOp).
+    pub fn allow_raw_op(self, op: u8) -> Restrictions {
+        self.push(sys::IORING_RESTRICTION_SQE_OP, op)
+    }
+
+    /// Allow this `io_uring_register` opcode, such as
+    /// [`IORING_REGISTER_BUFFERS`](sys::IORING_REGISTER_BUFFERS), to be used with the ring.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `op` is not a valid register opcode.
+    pub fn allow_register_op(self, op: u32) -> Restrictions {
+        assert!(op <= u8::MAX as u32, "register opcodes are limited to 8 bits");
+        self.push(sys::IORING_RESTRICTION_REGISTER_OP, op as u8)
+    }
+
+    /// Allow these flags to be set on submitted events.
+    pub fn allow_sqe_flags(self, flags: SubmissionFlags) -> Restrictions {
+        self.push(sys::IORING_RESTRICTION_SQE_FLAGS_ALLOWED, flags.bits())
+    }
+
+    /// Require these flags to be set on all submitted events.
+    pub fn require_sqe_flags(self, flags: SubmissionFlags) -> Restrictions {
+        self.push(sys::IORING_RESTRICTION_SQE_FLAGS_REQUIRED, flags.bits())
+    }
+
+    pub(crate) fn as_raw(&self) -> &[sys::io_uring_restriction] {
+        &self.restrictions
+    }
+
+    fn push(mut self, opcode: u16, arg: u8) -> Restrictions {
+        self.restrictions.push(sys::io_uring_restriction { opcode, arg, ..Default::default() });
+        self
+    }
+}
@@ -0,0 +1,249 @@
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::Deref;
+use std::ptr::NonNull;
+use std::slice;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::{CQE, IoUring, sys};
+use crate::ring_memory::{map, page_size, round_up};
+
+// the area offset of a zero-copy receive, without the area id in its top bits
+const AREA_OFFSET_MASK: u64 = (1 << sys::IORING_ZCRX_AREA_SHIFT) - 1;
+
+/// An interface queue registered for zero-copy receive, which lets the network card write the
+/// payloads of the packets it receives on one of its hardware receive queues directly into user
+/// memory.
+///
+/// The `ZcrxIfq` owns the memory area the payloads are written to, and the refill ring through
+/// which the parts of the area the kernel filled are handed back to it. A [zero-copy
+/// receive](crate::SQE::prep_recv_zc) reports where in the area the data of each completion is
+/// in its 32 byte [`CQE`], which [`decode`](ZcrxIfq::decode) turns into a slice of the area.
+/// Once it has been read, the data is [added](ZcrxIfq::add) back to the refill ring, and the
+/// ring is [advanced](ZcrxIfq::advance).
+///
+/// This needs a network card which supports header splitting and flow steering, configured so
+/// that the traffic of the socket arrives on the registered receive queue, and a ring set up
+/// with [`SetupFlags::DEFER_TASKRUN`](crate::SetupFlags::DEFER_TASKRUN) and
+/// [`SetupFlags::CQE32`](crate::SetupFlags::CQE32). Requires `CAP_NET_ADMIN` and Linux 6.15 or
+/// newer.
+///
+/// The kernel can't unregister an interface queue, so it stays registered until the ring is
+/// dropped. Dropping the `ZcrxIfq` only unmaps its memory.
+pub struct ZcrxIfq<'ring> {
+    id: u32,
+    area: NonNull<libc::c_void>,
+    area_len: usize,
+    area_token: u64,
+    region: NonNull<libc::c_void>,
+    region_len: usize,
+    rq_head: NonNull<AtomicU32>,
+    rq_tail: NonNull<AtomicU32>,
+    rqes: NonNull<sys::io_uring_zcrx_rqe>,
+    rq_entries: u32,
+    tail: u32,
+    _owner: Option<Arc<IoUring>>,
+    _marker: PhantomData<&'ring IoUring>,
+}
+
+impl<'ring> ZcrxIfq<'ring> {
+    pub(crate) fn register(
+        ring: NonNull<IoUring>,
+        owner: Option<Arc<IoUring>>,
+        if_idx: u32,
+        if_rxq: u32,
+        rq_entries: u32,
+        area_len: usize,
+    ) -> io::Result<ZcrxIfq<'ring>>
+    {
+        assert!(rq_entries > 0 && area_len > 0, "the refill ring and the area must not be empty");
+        let area_len = round_up(area_len, page_size());
+        // the head and tail of the refill ring fit in its first page
+        let region_len = round_up(
+            page_size() + rq_entries.next_power_of_two() as usize * mem::size_of::<sys::io_uring_zcrx_rqe>(),
+            page_size(),
+        );
+
+        let area = map(area_len, 0)?;
+        let region = match map(region_len, 0) {
+            Ok(region)  => region,
+            Err(err)    => {
+                unsafe { libc::munmap(area.as_ptr(), area_len); }
+                return Err(err);
+            }
+        };
+
+        let mut area_reg = sys::io_uring_zcrx_area_reg {
+            addr: area.as_ptr() as u64,
+            len: area_len as u64,
+            ..Default::default()
+        };
+        let region_desc = sys::io_uring_region_desc {
+            user_addr: region.as_ptr() as u64,
+            size: region_len as u64,
+            flags: sys::IORING_MEM_REGION_TYPE_USER,
+            ..Default::default()
+        };
+        let mut reg = sys::io_uring_zcrx_ifq_reg {
+            if_idx,
+            if_rxq,
+            rq_entries,
+            area_ptr: &mut area_reg as *mut _ as u64,
+            region_ptr: &region_desc as *const _ as u64,
+            ..Default::default()
+        };
+        unsafe {
+            let registered = crate::register(
+                ring,
+                sys::IORING_REGISTER_ZCRX_IFQ,
+                &mut reg as *mut _ as *const _,
+                1,
+            );
+            if let Err(err) = registered {
+                libc::munmap(area.as_ptr(), area_len);
+                libc::munmap(region.as_ptr(), region_len);
+                return Err(err);
+            }
+        }
+
+        // the kernel rounds the entries up to a power of two, and reports where the head, the
+        // tail and the entries are in the region
+        let at = |offset: u32| unsafe { NonNull::new_unchecked(region.as_ptr().add(offset as usize)) };
+        Ok(ZcrxIfq {
+            id: reg.zcrx_id,
+            area,
+            area_len,
+            area_token: area_reg.rq_area_token,
+            region,
+            region_len,
+            rq_head: at(reg.offsets.head).cast(),
+            rq_tail: at(reg.offsets.tail).cast(),
+            rqes: at(reg.offsets.rqes).cast(),
+            rq_entries: reg.rq_entries,
+            tail: 0,
+            _owner: owner,
+            _marker: PhantomData,
+        })
+    }
+
+    /// The id of this interface queue, which [zero-copy receives](crate::SQE::prep_recv_zc)
+    /// receive through.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// The number of entries of the refill ring.
+    pub fn rq_entries(&self) -> u32 {
+        self.rq_entries
+    }
+
+    /// The length of the memory area the payloads are written to.
+    pub fn area_len(&self) -> usize {
+        self.area_len
+    }
+
+    /// Stage the `len` bytes at `offset` in the area to be handed back to the kernel. The kernel
+    /// will not see them until the ring is [advanced](ZcrxIfq::advance).
+    ///
+    /// The bytes should be exactly those of a [`ZcrxBuf`] which has been read; no more than
+    /// [`rq_entries`](ZcrxIfq::rq_entries) of them can be staged before the kernel has consumed
+    /// them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bytes are out of range for the area.
+    pub fn add(&mut self, offset: u64, len: u32) {
+        assert!(offset + len as u64 <= self.area_len as u64, "offset out of range for this area");
+        unsafe {
+            let rqe = self.rqes.as_ptr().add((self.tail & (self.rq_entries - 1)) as usize);
+            rqe.write(sys::io_uring_zcrx_rqe { off: self.area_token | offset, len, pad: 0 });
+        }
+        self.tail = self.tail.wrapping_add(1);
+    }
+
+    /// Make all the bytes which were [added](ZcrxIfq::add) since the last call visible to the
+    /// kernel.
+    pub fn advance(&mut self) {
+        unsafe { self.rq_tail.as_ref().store(self.tail, Ordering::Release); }
+    }
+
+    /// The number of entries of the refill ring the kernel has not consumed yet.
+    pub fn pending(&self) -> u32 {
+        let head = unsafe { self.rq_head.as_ref().load(Ordering::Acquire) };
+        self.tail.wrapping_sub(head)
+    }
+
+    /// Returns the bytes the kernel wrote into the area for the completion `cqe` of a zero-copy
+    /// receive. Returns `None` if the event failed, reached the end of the stream, or `cqe` is
+    /// not a 32 byte CQE.
+    ///
+    /// # Safety
+    ///
+    /// `cqe` must have been posted by a zero-copy receive through this interface queue, and its
+    /// bytes must not have been added back to the refill ring since, or the kernel may write to
+    /// them while they are borrowed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the offset or the length of `cqe` are out of range for the area.
+    pub unsafe fn decode(&self, cqe: &CQE) -> Option<ZcrxBuf<'_>> {
+        let len = cqe.result().ok()? as usize;
+        let [off, _] = cqe.big_cqe()?;
+        if len == 0 {
+            return None;
+        }
+        let offset = off & AREA_OFFSET_MASK;
+        assert!(offset as usize + len <= self.area_len, "offset out of range for this area");
+        let data = (self.area.as_ptr() as *const u8).add(offset as usize);
+        Some(ZcrxBuf { offset, data: slice::from_raw_parts(data, len) })
+    }
+}
+
+impl fmt::Debug for ZcrxIfq<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct(std::any::type_name::<Self>())
+            .field("id", &self.id)
+            .field("rq_entries", &self.rq_entries)
+            .field("area_len", &self.area_len)
+            .finish()
+    }
+}
+
+impl Drop for ZcrxIfq<'_> {
+    fn drop(&mut self) {
+        // the kernel keeps its own references to the pages of both until the ring is dropped
+        unsafe {
+            libc::munmap(self.area.as_ptr(), self.area_len);
+            libc::munmap(self.region.as_ptr(), self.region_len);
+        }
+    }
+}
+
+unsafe impl Send for ZcrxIfq<'_> { }
+unsafe impl Sync for ZcrxIfq<'_> { }
+
+/// Bytes the kernel wrote into the area of a [`ZcrxIfq`] for a zero-copy receive.
+#[derive(Debug)]
+pub struct ZcrxBuf<'a> {
+    offset: u64,
+    data: &'a [u8],
+}
+
+impl ZcrxBuf<'_> {
+    /// The offset of these bytes in the area, to [add](ZcrxIfq::add) them back to the refill
+    /// ring with.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+}
+
+impl Deref for ZcrxBuf<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.data
+    }
+}
@@ -0,0 +1,150 @@
+use std::fmt;
+use std::io;
+use std::mem;
+use std::ptr::{self, NonNull};
+
+use crate::{SetupFlags, sys};
+
+// An upper bound on the size of the kernel's ring header which precedes the CQEs.
+const RINGS_HEADER_LEN: usize = 1024;
+pub(crate) const HUGE_PAGE_LEN: usize = 2 << 20;
+// The most entries a submission queue can have; larger rings are clamped or rejected.
+const MAX_ENTRIES: u32 = 32768;
+
+/// Memory for the rings of an [`IoUring`](crate::IoUring), allocated by the application instead of
+/// by the kernel.
+///
+/// An `IoUring` created with [`IoUring::new_with_memory`](crate::IoUring::new_with_memory) is set
+/// up with [`SetupFlags::NO_MMAP`](crate::SetupFlags::NO_MMAP), so the kernel uses this memory
+/// for the submission and completion queues instead of mapping its own. This makes it possible to
+/// place the rings in hugepages, which reduces TLB pressure for very large rings.
+///
+/// The memory is sized for a ring with `entries` submission queue entries (rounded up to a power
+/// of two, as the kernel does) and twice as many completion queue entries.
+///
+/// Some kernels require each ring larger than a single page to be physically contiguous, which is
+/// only guaranteed by hugepages; setting up a ring in regular pages then fails with `EINVAL`.
+pub struct RingMemory {
+    rings: NonNull<libc::c_void>,
+    rings_len: usize,
+    sqes: NonNull<libc::c_void>,
+    sqes_len: usize,
+    entries: u32,
+}
+
+impl RingMemory {
+    /// Allocate ring memory for `entries` entries from regular pages.
+    pub fn new(entries: u32) -> io::Result<RingMemory> {
+        RingMemory::allocate(entries, page_size(), 0)
+    }
+
+    /// Allocate ring memory for `entries` entries from 2MB hugepages. This fails if the system has
+    /// no free hugepages.
+    pub fn with_hugepages(entries: u32) -> io::Result<RingMemory> {
+        RingMemory::allocate(entries, HUGE_PAGE_LEN, libc::MAP_HUGETLB | libc::MAP_HUGE_2MB)
+    }
+
+    fn allocate(entries: u32, page_len: usize, flags: libc::c_int) -> io::Result<RingMemory> {
+        assert!(entries > 0, "rings must have at least one entry");
+        let entries = entries.checked_next_power_of_two().expect("too many ring entries");
+        let cq_entries = entries as usize * 2;
+
+        let rings_len = RINGS_HEADER_LEN
+            + cq_entries * mem::size_of::<sys::io_uring_cqe>()
+            + entries as usize * mem::size_of::<u32>();
+        let rings_len = round_up(rings_len, page_len);
+        let sqes_len = round_up(entries as usize * mem::size_of::<sys::io_uring_sqe>(), page_len);
+
+        let rings = map(rings_len, flags)?;
+        let sqes = match map(sqes_len, flags) {
+            Ok(sqes) => sqes,
+            Err(err) => {
+                unsafe { libc::munmap(rings.as_ptr(), rings_len); }
+                return Err(err);
+            }
+        };
+
+        Ok(RingMemory { rings, rings_len, sqes, sqes_len, entries })
+    }
+
+    /// The number of submission queue entries this memory was sized for.
+    pub fn entries(&self) -> u32 {
+        self.entries
+    }
+
+    /// The total number of bytes allocated for the rings.
+    pub fn total_len(&self) -> usize {
+        self.rings_len + self.sqes_len
+    }
+
+    pub(crate) fn rings(&self) -> (*mut libc::c_void, usize) {
+        (self.rings.as_ptr(), self.rings_len)
+    }
+
+    pub(crate) fn sqes(&self) -> (*mut libc::c_void, usize) {
+        (self.sqes.as_ptr(), self.sqes_len)
+    }
+}
+
+impl fmt::Debug for RingMemory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct(std::any::type_name::<Self>())
+            .field("entries", &self.entries)
+            .field("total_len", &self.total_len())
+            .finish()
+    }
+}
+
+impl Drop for RingMemory {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.rings.as_ptr(), self.rings_len);
+            libc::munmap(self.sqes.as_ptr(), self.sqes_len);
+        }
+    }
+}
+
+unsafe impl Send for RingMemory { }
+unsafe impl Sync for RingMemory { }
+
+/// An estimate of the memory the kernel allocates for the rings of a ring set up with `entries`
+/// entries and `flags`, see [`IoUring::ring_memory_size`](crate::IoUring::ring_memory_size).
+pub(crate) fn footprint(entries: u32, flags: SetupFlags) -> usize {
+    let entries = entries.clamp(1, MAX_ENTRIES).next_power_of_two() as usize;
+    let sqe_len = mem::size_of::<sys::io_uring_sqe>() << crate::sqe_shift(flags.bits());
+    let cqe_len = mem::size_of::<sys::io_uring_cqe>() << crate::cqe_shift(flags.bits());
+
+    // the header and CQEs are cache line aligned, and followed by the SQ array
+    let rings_len = round_up(RINGS_HEADER_LEN + entries * 2 * cqe_len, 64) + entries * mem::size_of::<u32>();
+    let sqes_len = entries * sqe_len;
+
+    // each is allocated as a power of two number of pages
+    let page_len = page_size();
+    let pages = |len: usize| round_up(len, page_len) / page_len;
+    (pages(rings_len).next_power_of_two() + pages(sqes_len).next_power_of_two()) * page_len
+}
+
+pub(crate) fn map(len: usize, flags: libc::c_int) -> io::Result<NonNull<libc::c_void>> {
+    let addr = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_ANONYMOUS | libc::MAP_PRIVATE | libc::MAP_POPULATE | flags,
+            -1,
+            0,
+        )
+    };
+    match addr == libc::MAP_FAILED {
+        true    => Err(io::Error::last_os_error()),
+        false   => Ok(NonNull::new(addr).unwrap()),
+    }
+}
+
+pub(crate) fn page_size() -> usize {
+    unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize }
+}
+
+pub(crate) fn round_up(len: usize, align: usize) -> usize {
+    (len + align - 1) & !(align - 1)
+}
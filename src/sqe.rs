@@ -1,33 +1,91 @@
+use std::fmt;
 use std::io;
+use std::marker::PhantomData;
 use std::mem;
 use std::ffi::CStr;
 use std::ops::{Deref, DerefMut};
 use std::os::unix::io::RawFd;
 use std::ptr;
-use std::slice;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use crate::registrar::{UringFd, UringReadBuf, UringWriteBuf};
+use crate::registrar::{DirectSlot, RegisteredBufIndex, UringBuf, UringBufMut, UringFd, UringReadBuf, UringWriteBuf};
 
-pub use nix::fcntl::{OFlag, FallocateFlags, PosixFadviseAdvice};
-pub use nix::poll::PollFlags;
-pub use nix::sys::epoll::{EpollOp, EpollEvent};
-pub use nix::sys::mman::MmapAdvise;
-pub use nix::sys::stat::Mode;
-pub use nix::sys::socket::{SockAddr, SockFlag, MsgFlags};
+mod addr;
+mod flags;
+pub(crate) mod prep;
+mod provided;
 
-use crate::Personality;
+pub use addr::SockAddr;
+pub use provided::ProvidedBufferPool;
+pub use flags::{OFlag, FallocateFlags, PosixFadviseAdvice, PollFlags, MmapAdvise, Mode, SFlag};
+pub use flags::{EpollOp, EpollEvent, EpollFlags};
+pub use flags::{AddressFamily, SockFlag, SockProtocol, SockType, MsgFlags};
+
+use crate::{CQE, Personality, Registrar, SubmissionQueue, Timespec, UserData};
+use crate::sys;
 
 /// A pending IO event.
 ///
 /// Can be configured with a set of [`SubmissionFlags`](crate::sqe::SubmissionFlags).
 ///
 pub struct SQE<'a> {
-    sqe: &'a mut uring_sys::io_uring_sqe,
+    sqe: &'a mut sys::io_uring_sqe,
+    big: bool,
 }
 
+// the offset of the `cmd` field of an SQE, and the length of the command data of a big SQE
+const CMD_OFFSET: usize = 48;
+const BIG_CMD_LEN: usize = 2 * mem::size_of::<sys::io_uring_sqe>() - CMD_OFFSET;
+
 impl<'a> SQE<'a> {
-    pub(crate) fn new(sqe: &'a mut uring_sys::io_uring_sqe) -> SQE<'a> {
-        SQE { sqe }
+    #[inline]
+    pub(crate) fn new(sqe: &'a mut sys::io_uring_sqe, big: bool) -> SQE<'a> {
+        SQE { sqe, big }
+    }
+
+    /// Whether this is a 128 byte SQE, from a ring set up with
+    /// [`SetupFlags::SQE128`](crate::SetupFlags::SQE128). The second half of a big SQE holds
+    /// extra command data for `uring_cmd` events.
+    #[inline]
+    pub fn is_big(&self) -> bool {
+        self.big
+    }
+
+    /// The command data of a big SQE: the 80 bytes from the `cmd` field to the end of the
+    /// second half. Returns `None` unless the ring was set up with
+    /// [`SetupFlags::SQE128`](crate::SetupFlags::SQE128).
+    #[inline]
+    pub fn cmd_bytes(&self) -> Option<&[u8]> {
+        if !self.big {
+            return None;
+        }
+        unsafe {
+            let cmd = (&*self.sqe as *const sys::io_uring_sqe as *const u8).add(CMD_OFFSET);
+            Some(std::slice::from_raw_parts(cmd, BIG_CMD_LEN))
+        }
+    }
+
+    /// The command data of a big SQE, to be filled in for a `uring_cmd` event. Returns `None`
+    /// unless the ring was set up with [`SetupFlags::SQE128`](crate::SetupFlags::SQE128).
+    ///
+    /// The command data is zeroed whenever an SQE is handed out. The prep methods reset its first
+    /// 16 bytes, which share their place with other fields of the SQE, so it must be filled in
+    /// after the event has been prepared.
+    #[inline]
+    pub fn cmd_bytes_mut(&mut self) -> Option<&mut [u8]> {
+        if !self.big {
+            return None;
+        }
+        unsafe {
+            let cmd = (self.sqe as *mut sys::io_uring_sqe as *mut u8).add(CMD_OFFSET);
+            Some(std::slice::from_raw_parts_mut(cmd, BIG_CMD_LEN))
+        }
+    }
+
+    /// The raw opcode of the event this SQE was prepared for.
+    #[inline]
+    pub fn opcode(&self) -> u8 {
+        self.sqe.opcode
     }
 
     /// Get this event's user data.
@@ -74,15 +132,75 @@ impl<'a> SQE<'a> {
         self.sqe.user_data = user_data as _;
     }
 
+    /// Set this event's user data to a value which is known not to be reserved by the library.
+    ///
+    /// # Safety
+    ///
+    /// The same as [`set_user_data`](SQE::set_user_data).
+    pub unsafe fn set_typed_user_data(&mut self, user_data: UserData) {
+        self.set_user_data(user_data.get());
+    }
+
+    /// Leak `data` into this event's user data. Once the event completes, the box can be
+    /// reclaimed from its CQE with [`CQE::user_data_as`].
+    ///
+    /// ```
+    /// # use iou::IoUring;
+    /// # fn main() -> std::io::Result<()> {
+    /// # let mut ring = IoUring::new(2)?;
+    /// let mut sqe = ring.prepare_sqe().unwrap();
+    /// unsafe {
+    ///     sqe.prep_nop();
+    ///     sqe.set_user_data_ptr(Box::new(String::from("request 1")));
+    /// }
+    /// ring.submit_sqes()?;
+    ///
+    /// let cqe = ring.wait_for_cqe()?;
+    /// let request = unsafe { cqe.user_data_as::<String>() };
+    /// assert_eq!(*request, "request 1");
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// The same as [`set_user_data`](SQE::set_user_data). If the event is never submitted, or its
+    /// completion is never reclaimed, the box is leaked.
+    pub unsafe fn set_user_data_ptr<T>(&mut self, data: Box<T>) {
+        self.set_typed_user_data(UserData::from_box(data));
+    }
+
     /// Get this event's flags.
     #[inline]
     pub fn flags(&self) -> SubmissionFlags {
         unsafe { SubmissionFlags::from_bits_unchecked(self.sqe.flags as _) }
     }
 
-    /// Overwrite this event's flags.
+    /// Overwrite this event's flags. This is the same as [`replace_flags`](SQE::replace_flags).
     pub fn overwrite_flags(&mut self, flags: SubmissionFlags) {
-        self.sqe.flags = flags.bits() as _;
+        self.replace_flags(flags);
+    }
+
+    /// Set these flags for this event, leaving any flags already set as they are.
+    ///
+    /// The prep methods reset the flags of the event, except for the flags they set
+    /// themselves, like [`FIXED_FILE`](SubmissionFlags::FIXED_FILE) for registered files. Flags
+    /// must therefore be added after the event has been prepared.
+    #[inline]
+    pub fn add_flags(&mut self, flags: SubmissionFlags) {
+        self.sqe.flags |= flags.bits();
+    }
+
+    /// Clear these flags for this event, leaving any other flags as they are.
+    #[inline]
+    pub fn clear_flags(&mut self, flags: SubmissionFlags) {
+        self.sqe.flags &= !flags.bits();
+    }
+
+    /// Replace all flags of this event with `flags`, including flags set by the prep method.
+    #[inline]
+    pub fn replace_flags(&mut self, flags: SubmissionFlags) {
+        self.sqe.flags = flags.bits();
     }
 
     // must be called after any prep methods to properly complete mapped kernel IO
@@ -91,10 +209,34 @@ impl<'a> SQE<'a> {
         self.set_flags(SubmissionFlags::FIXED_FILE);
     }
 
-    /// Set these flags for this event (any flags already set will still be set).
+    /// Set these flags for this event (any flags already set will still be set). This is the
+    /// same as [`add_flags`](SQE::add_flags).
     #[inline]
     pub fn set_flags(&mut self, flags: SubmissionFlags) {
-        self.sqe.flags |= flags.bits();
+        self.add_flags(flags);
+    }
+
+    /// Have the kernel select the buffer of this event from the provided buffers of `group`.
+    ///
+    /// This sets [`BUFFER_SELECT`](SubmissionFlags::BUFFER_SELECT) and stores the group in the
+    /// event, so it must be called after the event has been prepared, and only for events which
+    /// support selecting a buffer.
+    #[inline]
+    pub fn set_buffer_group(&mut self, group: BufferGroupId) {
+        self.sqe.buf_index.buf_index.index_or_group = group.id as _;
+        self.add_flags(SubmissionFlags::BUFFER_SELECT);
+    }
+
+    /// Hint that the socket of this send or receive event is probably not ready, so that the
+    /// kernel waits for it to become ready right away instead of first attempting the operation.
+    /// This saves a failed attempt on connections which are mostly idle. Requires Linux 5.19 or
+    /// newer.
+    ///
+    /// This must be called after the event has been prepared, and only for send, receive,
+    /// sendmsg and recvmsg events.
+    #[inline]
+    pub fn set_poll_first(&mut self) {
+        self.sqe.ioprio |= sys::IORING_RECVSEND_POLL_FIRST;
     }
 
     /// Set the [`Personality`] associated with this submission.
@@ -103,6 +245,21 @@ impl<'a> SQE<'a> {
         self.sqe.buf_index.buf_index.personality = personality.id;
     }
 
+    /// Get this event's IO priority.
+    #[inline]
+    pub fn ioprio(&self) -> IoPrio {
+        IoPrio::from_raw(self.sqe.ioprio)
+    }
+
+    /// Set the IO priority of this event, as with `ioprio_set(2)`.
+    ///
+    /// This must be called after the event has been prepared, and is only meaningful for read and
+    /// write events; other events may use this field for their own flags.
+    #[inline]
+    pub fn set_ioprio(&mut self, prio: IoPrio) {
+        self.sqe.ioprio = prio.to_raw();
+    }
+
     /// Prepare a read on a file descriptor.
     ///
     /// Both the file descriptor and the buffer can be pre-registered. See the
@@ -117,6 +274,21 @@ impl<'a> SQE<'a> {
         buf.prep_read(fd, self, offset);
     }
 
+    /// Prepare a read on a file descriptor into a buffer the kernel selects from `group`, reading
+    /// at most `len` bytes.
+    ///
+    /// The id of the selected buffer is reported in the flags of the [`CQE`](crate::CQE).
+    ///
+    /// # Safety
+    ///
+    /// The buffers provided to `group` must remain valid until the event completes.
+    #[inline]
+    pub unsafe fn prep_read_buffer_select(&mut self, fd: impl UringFd, group: BufferGroupId, len: u32, offset: u64) {
+        prep::read(self.sqe, fd.as_raw_fd(), ptr::null_mut(), len, offset as _);
+        fd.update_sqe(self);
+        self.set_buffer_group(group);
+    }
+
     /// Prepare a vectored read on a file descriptor.
     #[inline]
     pub unsafe fn prep_read_vectored(
@@ -127,27 +299,29 @@ impl<'a> SQE<'a> {
     ) {
         let len = bufs.len();
         let addr = bufs.as_mut_ptr();
-        uring_sys::io_uring_prep_readv(self.sqe, fd.as_raw_fd(), addr as _, len as _, offset as _);
+        prep::readv(self.sqe, fd.as_raw_fd(), addr as _, len as _, offset as _);
         fd.update_sqe(self);
     }
 
     /// Prepare a read into a fixed, pre-registered buffer on a file descriptor.
+    ///
+    /// `buf` must lie within the registered buffer identified by `buf_index`.
     #[inline]
     pub unsafe fn prep_read_fixed(
         &mut self,
         fd: impl UringFd,
         buf: &mut [u8],
         offset: u64,
-        buf_index: u32,
+        buf_index: RegisteredBufIndex,
     ) {
         let len = buf.len();
         let addr = buf.as_mut_ptr();
-        uring_sys::io_uring_prep_read_fixed(self.sqe,
-                                      fd.as_raw_fd(),
-                                      addr as _,
-                                      len as _,
-                                      offset as _,
-                                      buf_index as _);
+        prep::read_fixed(self.sqe,
+                         fd.as_raw_fd(),
+                         addr as _,
+                         len as _,
+                         offset as _,
+                         buf_index.index() as _);
         fd.update_sqe(self);
     }
 
@@ -175,82 +349,196 @@ impl<'a> SQE<'a> {
     ) {
         let len = bufs.len();
         let addr = bufs.as_ptr();
-        uring_sys::io_uring_prep_writev(self.sqe,
-                                    fd.as_raw_fd(),
-                                    addr as _,
-                                    len as _,
-                                    offset as _);
+        prep::writev(self.sqe,
+                     fd.as_raw_fd(),
+                     addr as _,
+                     len as _,
+                     offset as _);
         fd.update_sqe(self);
     }
 
     /// Prepare a write on a file descriptor from a fixed, pre-registered buffer.
+    ///
+    /// `buf` must lie within the registered buffer identified by `buf_index`.
     #[inline]
     pub unsafe fn prep_write_fixed(
         &mut self,
         fd: impl UringFd,
         buf: &[u8],
         offset: u64,
-        buf_index: usize,
+        buf_index: RegisteredBufIndex,
     ) {
         let len = buf.len();
         let addr = buf.as_ptr();
-        uring_sys::io_uring_prep_write_fixed(self.sqe,
-                                       fd.as_raw_fd(),
-                                       addr as _,
-                                       len as _,
-                                       offset as _,
-                                       buf_index as _);
+        prep::write_fixed(self.sqe,
+                          fd.as_raw_fd(),
+                          addr as _,
+                          len as _,
+                          offset as _,
+                          buf_index.index() as _);
         fd.update_sqe(self);
     }
 
     /// Prepare an fsync on a file descriptor.
     #[inline]
     pub unsafe fn prep_fsync(&mut self, fd: impl UringFd, flags: FsyncFlags) {
-        uring_sys::io_uring_prep_fsync(self.sqe, fd.as_raw_fd(), flags.bits() as _);
+        prep::fsync(self.sqe, fd.as_raw_fd(), flags.bits() as _);
         fd.update_sqe(self);
     }
 
     /// Prepare a splice, copying data from one file descriptor to another.
+    ///
+    /// An offset of `None` uses (and advances) the current file position, and must be used when
+    /// the corresponding file descriptor is a pipe.
     #[inline]
     pub unsafe fn prep_splice(
         &mut self,
         fd_in: RawFd,
-        off_in: i64,
+        off_in: Option<u64>,
         fd_out: RawFd,
-        off_out: i64,
+        off_out: Option<u64>,
         count: u32,
         flags: SpliceFlags,
     ) {
-        uring_sys::io_uring_prep_splice(self.sqe, fd_in, off_in, fd_out, off_out, count, flags.bits());
+        prep::rw(sys::IoRingOp::IORING_OP_SPLICE as _,
+                 self.sqe,
+                 fd_out,
+                 ptr::null(),
+                 count,
+                 splice_offset(off_out) as _);
+        // splice_off_in shares its slot with addr
+        self.sqe.addr = splice_offset(off_in) as _;
+        self.sqe.buf_index.buf_index.splice_fd_in = fd_in;
+        self.sqe.cmd_flags.splice_flags = flags.bits();
     }
 
     /// Prepare a recv event on a file descriptor.
+    ///
+    /// Receiving does not support fixed buffers, so the kernel maps the memory of registered
+    /// buffers like that of any other buffer.
     #[inline]
-    pub unsafe fn prep_recv(&mut self, fd: impl UringFd, buf: &mut [u8], flags: MsgFlags) {
+    pub unsafe fn prep_recv(&mut self, fd: impl UringFd, mut buf: impl UringBufMut, flags: MsgFlags) {
+        let buf = buf.as_buf_mut();
         let data = buf.as_mut_ptr() as *mut libc::c_void;
         let len = buf.len();
-        uring_sys::io_uring_prep_recv(self.sqe, fd.as_raw_fd(), data, len, flags.bits());
+        prep::recv(self.sqe, fd.as_raw_fd(), data, len, flags.bits());
+        fd.update_sqe(self);
+    }
+
+    /// Prepare a recv event on a file descriptor into a buffer the kernel selects from `group`,
+    /// receiving at most `len` bytes.
+    ///
+    /// The id of the selected buffer is reported in the flags of the [`CQE`](crate::CQE).
+    ///
+    /// # Safety
+    ///
+    /// The buffers provided to `group` must remain valid until the event completes.
+    #[inline]
+    pub unsafe fn prep_recv_buffer_select(&mut self, fd: impl UringFd, group: BufferGroupId, len: usize, flags: MsgFlags) {
+        prep::recv(self.sqe, fd.as_raw_fd(), ptr::null_mut(), len, flags.bits());
+        fd.update_sqe(self);
+        self.set_buffer_group(group);
+    }
+
+    /// Prepare a multishot zero-copy receive on a socket, through the interface queue `ifq_id`
+    /// of a [`ZcrxIfq`](crate::registrar::ZcrxIfq). Each completion reports bytes the network
+    /// card wrote into the area of the queue, which [`ZcrxIfq::decode`] reads from its CQE. If
+    /// `len` is not zero, the event stops after receiving `len` bytes.
+    ///
+    /// The event keeps posting completions flagged with
+    /// [`CompletionFlags::MORE`](crate::cqe::CompletionFlags::MORE) until it fails or the
+    /// stream ends. Requires Linux 6.15 or newer.
+    ///
+    /// [`ZcrxIfq::decode`]: crate::registrar::ZcrxIfq::decode
+    ///
+    /// # Safety
+    ///
+    /// `ifq_id` must be the id of a `ZcrxIfq` of this ring, which must not be dropped until the
+    /// last completion of the event: the kernel writes the received data into its area, and takes
+    /// the parts of the area it may write to again from its refill ring. `fd` must remain open
+    /// until then.
+    #[inline]
+    pub unsafe fn prep_recv_zc(&mut self, fd: impl UringFd, ifq_id: u32, len: u32) {
+        prep::rw(sys::IORING_OP_RECV_ZC, self.sqe, fd.as_raw_fd(), ptr::null(), len, 0);
+        self.sqe.ioprio = sys::IORING_RECV_MULTISHOT;
+        self.sqe.buf_index.buf_index.splice_fd_in = ifq_id as _;
         fd.update_sqe(self);
     }
 
     /// Prepare a send event on a file descriptor.
+    ///
+    /// Like receiving, sending does not support fixed buffers.
     #[inline]
-    pub unsafe fn prep_send(&mut self, fd: impl UringFd, buf: &[u8], flags: MsgFlags) {
+    pub unsafe fn prep_send(&mut self, fd: impl UringFd, buf: impl UringBuf, flags: MsgFlags) {
+        let buf = buf.as_buf();
         let data = buf.as_ptr() as *const libc::c_void as *mut libc::c_void;
         let len = buf.len();
-        uring_sys::io_uring_prep_send(self.sqe, fd.as_raw_fd(), data, len, flags.bits());
+        prep::send(self.sqe, fd.as_raw_fd(), data, len, flags.bits());
         fd.update_sqe(self);
     }
 
+    /// Prepare a send event to a specific destination address, like `sendto(2)`. Requires Linux 6.0
+    /// or newer.
+    ///
+    /// # Safety
+    ///
+    /// `buf` and `addr` must remain valid until the event completes.
+    #[inline]
+    pub unsafe fn prep_send_to(&mut self, fd: impl UringFd, buf: impl UringBuf, addr: &SockAddr, flags: MsgFlags) {
+        self.prep_send(fd, buf, flags);
+        let (addr, len) = addr.as_ffi_pair();
+        self.sqe.off_addr2.addr2 = addr as *const _ as _;
+        self.set_addr_len(len as _);
+    }
+
+    /// Prepare a receive event which also records the address of the sender, like `recvfrom(2)`.
+    ///
+    /// This is a recvmsg event; `from` holds the message header and the sender's address. Once the
+    /// event completes, the address can be read with [`RecvFrom::socket_addr`].
+    ///
+    /// # Safety
+    ///
+    /// `buf` and `from` must remain valid and must not be moved until the event completes.
+    #[inline]
+    pub unsafe fn prep_recv_from(&mut self, fd: impl UringFd, mut buf: impl UringBufMut, from: &mut RecvFrom, flags: MsgFlags) {
+        let buf = buf.as_buf_mut();
+        from.iov = libc::iovec { iov_base: buf.as_mut_ptr() as _, iov_len: buf.len() };
+        from.msg = mem::zeroed();
+        from.msg.msg_name = from.addr.storage.as_mut_ptr() as *mut _;
+        from.msg.msg_namelen = mem::size_of::<libc::sockaddr_storage>() as _;
+        from.msg.msg_iov = &mut from.iov;
+        from.msg.msg_iovlen = 1;
+        self.prep_recvmsg(fd, &mut from.msg, flags);
+    }
+
     /// Prepare a recvmsg event on a file descriptor.
     pub unsafe fn prep_recvmsg(&mut self, fd: impl UringFd, msg: *mut libc::msghdr, flags: MsgFlags) {
-        uring_sys::io_uring_prep_recvmsg(self.sqe, fd.as_raw_fd(), msg, flags.bits() as _);
+        prep::recvmsg(self.sqe, fd.as_raw_fd(), msg, flags.bits() as _);
+        fd.update_sqe(self);
+    }
+
+    /// Prepare a multishot recvmsg event on a file descriptor. Requires Linux 6.0 or newer.
+    ///
+    /// The event keeps receiving messages into buffers selected from `group` until it is
+    /// cancelled or fails. Each completion fills one buffer with an `io_uring_recvmsg_out` header,
+    /// followed by the sender's name, control data and the payload; [`RecvMsgOut`] parses that
+    /// layout. Only the `msg_namelen` and `msg_controllen` fields of `msg` are used, to size the
+    /// name and control areas of each buffer.
+    ///
+    /// # Safety
+    ///
+    /// `msg` must remain valid until the last completion of the event.
+    #[inline]
+    pub unsafe fn prep_recvmsg_multishot(&mut self, fd: impl UringFd, msg: &libc::msghdr, group: BufferGroupId, flags: MsgFlags) {
+        prep::recvmsg(self.sqe, fd.as_raw_fd(), msg as *const _ as *mut _, flags.bits() as _);
+        self.sqe.ioprio |= sys::IORING_RECV_MULTISHOT;
+        self.set_buffer_group(group);
         fd.update_sqe(self);
     }
 
     /// Prepare a sendmsg event on a file descriptor.
     pub unsafe fn prep_sendmsg(&mut self, fd: impl UringFd, msg: *mut libc::msghdr, flags: MsgFlags) {
-        uring_sys::io_uring_prep_sendmsg(self.sqe, fd.as_raw_fd(), msg, flags.bits() as _);
+        prep::sendmsg(self.sqe, fd.as_raw_fd(), msg, flags.bits() as _);
         fd.update_sqe(self);
     }
 
@@ -259,14 +547,17 @@ impl<'a> SQE<'a> {
     pub unsafe fn prep_fallocate(&mut self, fd: impl UringFd,
                                  offset: u64, size: u64,
                                  flags: FallocateFlags) {
-        uring_sys::io_uring_prep_fallocate(self.sqe, fd.as_raw_fd(),
-                                        flags.bits() as _,
-                                        offset as _,
-                                        size as _);
+        prep::fallocate(self.sqe, fd.as_raw_fd(),
+                        flags.bits() as _,
+                        offset as _,
+                        size as _);
         fd.update_sqe(self);
     }
 
     /// Prepare a statx event.
+    ///
+    /// The kernel fills `buf` with the fields requested by `mask`; use the accessors on [`Statx`]
+    /// to read them back once the event has completed.
     #[inline]
     pub unsafe fn prep_statx(
         &mut self,
@@ -274,11 +565,11 @@ impl<'a> SQE<'a> {
         path: &CStr,
         flags: StatxFlags,
         mask: StatxMode,
-        buf: &mut libc::statx,
+        buf: &mut Statx,
     ) {
-        uring_sys::io_uring_prep_statx(self.sqe, dirfd.as_raw_fd(), path.as_ptr() as _,
-                                       flags.bits() as _, mask.bits() as _,
-                                       buf as _);
+        prep::statx(self.sqe, dirfd.as_raw_fd(), path.as_ptr() as _,
+                    flags.bits() as _, mask.bits() as _,
+                    buf.as_raw_mut());
     }
 
     /// Prepare an openat event.
@@ -290,32 +581,117 @@ impl<'a> SQE<'a> {
         flags: OFlag,
         mode: Mode,
     ) {
-        uring_sys::io_uring_prep_openat(self.sqe, fd.as_raw_fd(), path.as_ptr() as _, flags.bits(), mode.bits());
+        prep::openat(self.sqe, fd.as_raw_fd(), path.as_ptr() as _, flags.bits(), mode.bits());
+    }
+
+    /// Prepare an openat event which installs the opened file directly into the registered
+    /// fileset instead of the process's file descriptor table. Requires Linux 5.15 or newer.
+    ///
+    /// Use [`CQE::direct_slot`](crate::CQE::direct_slot) with the same `file_index` to get the
+    /// slot of the opened file from the completion.
+    ///
+    /// # Safety
+    ///
+    /// `path` must remain valid until the event completes.
+    #[inline]
+    pub unsafe fn prep_openat_direct(
+        &mut self,
+        fd: impl UringFd,
+        path: &CStr,
+        flags: OFlag,
+        mode: Mode,
+        file_index: FileIndex,
+    ) {
+        self.prep_openat(fd, path, flags, mode);
+        self.set_file_index(file_index);
+    }
+
+    /// Prepare an openat2 event.
+    ///
+    /// # Safety
+    ///
+    /// `path` and `how` must remain valid until the event completes.
+    #[inline]
+    pub unsafe fn prep_openat2(
+        &mut self,
+        fd: impl UringFd,
+        path: &CStr,
+        how: &OpenHow,
+    ) {
+        prep::openat2(self.sqe, fd.as_raw_fd(), path.as_ptr() as _, how as *const _ as *mut _);
     }
 
-    // TODO openat2
+    /// Prepare an openat2 event which installs the opened file directly into the registered
+    /// fileset. Requires Linux 5.15 or newer.
+    ///
+    /// # Safety
+    ///
+    /// `path` and `how` must remain valid until the event completes.
+    #[inline]
+    pub unsafe fn prep_openat2_direct(
+        &mut self,
+        fd: impl UringFd,
+        path: &CStr,
+        how: &OpenHow,
+        file_index: FileIndex,
+    ) {
+        self.prep_openat2(fd, path, how);
+        self.set_file_index(file_index);
+    }
 
     /// Prepare a close event on a file descriptor.
     #[inline]
     pub unsafe fn prep_close(&mut self, fd: impl UringFd) {
-        uring_sys::io_uring_prep_close(self.sqe, fd.as_raw_fd());
+        prep::close(self.sqe, fd.as_raw_fd());
+    }
+
+    /// Prepare an event which closes a direct descriptor, freeing its slot in the registered
+    /// fileset.
+    ///
+    /// # Safety
+    ///
+    /// Events which are still in flight with the file in `slot` keep their own reference to it,
+    /// but the slot may be reused by later events as soon as this completes.
+    #[inline]
+    pub unsafe fn prep_close_direct(&mut self, slot: DirectSlot) {
+        prep::close(self.sqe, 0);
+        self.set_file_index(FileIndex::Slot(slot.index()));
     }
 
+    /// Prepare an event which installs a direct descriptor into the process's file descriptor
+    /// table. The result of the event is the new file descriptor, which is owned by the caller.
+    /// Requires Linux 6.8 or newer.
+    ///
+    /// The new descriptor is close-on-exec unless [`FixedFdInstallFlags::NO_CLOEXEC`] is set.
+    ///
+    /// # Safety
+    ///
+    /// The caller takes ownership of the descriptor in the result, and must close it.
+    #[inline]
+    pub unsafe fn prep_fixed_fd_install(&mut self, slot: DirectSlot, flags: FixedFdInstallFlags) {
+        prep::rw(sys::IORING_OP_FIXED_FD_INSTALL,
+                 self.sqe,
+                 slot.index() as _,
+                 ptr::null(),
+                 0,
+                 0);
+        self.sqe.cmd_flags.rw_flags = flags.bits() as _;
+        self.set_fixed_file();
+    }
 
     /// Prepare a timeout event.
     ///
     /// ```
     /// # use iou::IoUring;
+    /// # use std::time::Duration;
+    /// # use iou::Timespec;
     /// # use iou::sqe::TimeoutFlags;
     /// # fn main() -> std::io::Result<()> {
     /// # let mut ring = IoUring::new(1)?;
     /// # let mut sqe = ring.prepare_sqe().unwrap();
     /// #
     /// // make a one-second timeout
-    /// let timeout_spec: _ = uring_sys::__kernel_timespec {
-    ///     tv_sec:  1 as _,
-    ///     tv_nsec: 0 as _,
-    /// };
+    /// let timeout_spec = Timespec::from(Duration::from_secs(1));
     ///
     /// unsafe { sqe.prep_timeout(&timeout_spec, 0, TimeoutFlags::empty()); }
     ///
@@ -324,38 +700,149 @@ impl<'a> SQE<'a> {
     /// # }
     ///```
     #[inline]
-    pub unsafe fn prep_timeout(&mut self, ts: &uring_sys::__kernel_timespec, events: u32, flags: TimeoutFlags) {
-        uring_sys::io_uring_prep_timeout(self.sqe,
-                                   ts as *const _ as *mut _,
-                                   events as _,
-                                   flags.bits() as _);
+    pub unsafe fn prep_timeout(&mut self, ts: &Timespec, events: u32, flags: TimeoutFlags) {
+        prep::timeout(self.sqe,
+                      ts.raw() as *const _ as *mut _,
+                      events as _,
+                      flags.bits() as _);
+    }
+
+    /// Prepare a multishot timeout, which fires every `interval` rather than once, as a periodic
+    /// tick driven by the kernel. Requires Linux 6.4 or newer.
+    ///
+    /// Each time the interval passes, the event completes with an `ETIME` error. It fires `count`
+    /// times, or until it is [removed](SQE::prep_timeout_remove) if `count` is 0. Every CQE but
+    /// the last has the [`MORE`](crate::cqe::CompletionFlags::MORE) flag set, so
+    /// [`CQE::more`](crate::CQE::more) tells when the timeout has stopped.
+    ///
+    /// ```
+    /// # use std::io;
+    /// # use std::time::Duration;
+    /// # use iou::{IoUring, Timespec};
+    /// # fn main() -> io::Result<()> {
+    /// # let mut ring = IoUring::new(2)?;
+    /// let interval = Timespec::from(Duration::from_millis(1));
+    /// unsafe { ring.prepare_sqe().unwrap().prep_timeout_multishot(&interval, 3); }
+    /// ring.submit_sqes()?;
+    ///
+    /// let mut ticks = 0;
+    /// loop {
+    ///     let cqe = ring.wait_for_cqe()?;
+    ///     ticks += 1;
+    ///     if !cqe.more() {
+    ///         break;
+    ///     }
+    /// }
+    /// assert_eq!(ticks, 3);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// `interval` must remain valid until the event is submitted, when the kernel reads it.
+    #[inline]
+    pub unsafe fn prep_timeout_multishot(&mut self, interval: &Timespec, count: u32) {
+        self.prep_timeout(interval, count, TimeoutFlags::MULTISHOT);
     }
 
     #[inline]
     pub unsafe fn prep_timeout_remove(&mut self, user_data: u64) {
-        uring_sys::io_uring_prep_timeout_remove(self.sqe, user_data as _, 0);
+        prep::timeout_remove(self.sqe, user_data as _, 0);
+    }
+
+    /// Prepare an update of the timeout of the event with `user_data`, which changes its
+    /// timeout to `ts` as measured with `flags`. The update completes with an `ENOENT` error if
+    /// the timeout has already fired or was removed.
+    ///
+    /// This updates a timeout event, or with [`TimeoutFlags::LINK_TIMEOUT_UPDATE`], a linked
+    /// timeout. [`TimeoutFlags::UPDATE`] is always set.
+    ///
+    /// # Safety
+    ///
+    /// `ts` must remain valid until the event is submitted, when the kernel reads it.
+    #[inline]
+    pub unsafe fn prep_timeout_update(&mut self, user_data: u64, ts: &Timespec, flags: TimeoutFlags) {
+        prep::timeout_remove(self.sqe, user_data as _, (flags | TimeoutFlags::UPDATE).bits());
+        self.sqe.off_addr2.off = ts.raw() as *const _ as u64;
     }
 
     #[inline]
-    pub unsafe fn prep_link_timeout(&mut self, ts: &uring_sys::__kernel_timespec) {
-        uring_sys::io_uring_prep_link_timeout(self.sqe, ts as *const _ as *mut _, 0);
+    pub unsafe fn prep_link_timeout(&mut self, ts: &Timespec) {
+        prep::link_timeout(self.sqe, ts.raw() as *const _ as *mut _, 0);
     }
 
     #[inline]
     pub unsafe fn prep_poll_add(&mut self, fd: impl UringFd, poll_flags: PollFlags) {
-        uring_sys::io_uring_prep_poll_add(self.sqe, fd.as_raw_fd(), poll_flags.bits());
+        prep::poll_add(self.sqe, fd.as_raw_fd(), poll_flags.bits());
         fd.update_sqe(self);
     }
 
+    /// Prepare a poll event like [`prep_poll_add`](SQE::prep_poll_add), with `add_flags` to choose
+    /// how it reports readiness.
+    ///
+    /// By default a poll completes once. [`PollAddFlags::MULTI`] keeps it armed after each event,
+    /// posting a CQE with [`MORE`](crate::cqe::CompletionFlags::MORE) set every time the file
+    /// becomes ready, until it is removed or fails. A multishot poll is edge triggered, like
+    /// `epoll` with `EPOLLET`. A poll which completes once checks whether the file is ready when
+    /// it is armed, so arming it again after each event gives level triggered polling.
+    ///
+    /// ```
+    /// # use std::io::{self, Write};
+    /// # use std::os::unix::{io::AsRawFd, net::UnixStream};
+    /// # use iou::sqe::{PollAddFlags, PollFlags};
+    /// # fn main() -> io::Result<()> {
+    /// # let mut ring = iou::IoUring::new(2)?;
+    /// let (read, mut write) = UnixStream::pair()?;
+    /// unsafe {
+    ///     let mut sqe = ring.prepare_sqe().unwrap();
+    ///     sqe.prep_poll_add_with_flags(read.as_raw_fd(), PollFlags::POLLIN, PollAddFlags::MULTI);
+    /// }
+    /// ring.submit_sqes()?;
+    ///
+    /// write.write_all(b"ping")?;
+    /// assert!(ring.wait_for_cqe()?.more());
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// With [`PollAddFlags::MULTI`], the event posts any number of completions with its user data.
+    /// Whatever reaps them must not take the first one to mean that the event is done, and free
+    /// what the user data refers to.
+    #[inline]
+    pub unsafe fn prep_poll_add_with_flags(&mut self, fd: impl UringFd, poll_flags: PollFlags, add_flags: PollAddFlags) {
+        self.prep_poll_add(fd, poll_flags);
+        self.sqe.len = add_flags.bits();
+    }
+
+    /// Prepare an update of the poll event with `old_user_data`, in place of removing and adding
+    /// it again. [`PollAddFlags::UPDATE_EVENTS`] changes the events it polls for to `poll_flags`,
+    /// and [`PollAddFlags::UPDATE_USER_DATA`] changes its user data to `new_user_data`; the other
+    /// flags apply to the updated event.
+    ///
+    /// The update completes with an `ENOENT` error if the poll event has already completed.
+    ///
+    /// # Safety
+    ///
+    /// With [`PollAddFlags::UPDATE_USER_DATA`], the later completions of the poll event carry
+    /// `new_user_data`. It must not be that of an event whose result other code trusts, like a
+    /// read whose result is taken as the number of bytes it initialized.
+    #[inline]
+    pub unsafe fn prep_poll_update(&mut self, old_user_data: u64, new_user_data: u64, poll_flags: PollFlags, flags: PollAddFlags) {
+        prep::poll_update(self.sqe, old_user_data, new_user_data, poll_flags.bits(), flags.bits());
+    }
+
     #[inline]
     pub unsafe fn prep_poll_remove(&mut self, user_data: u64) {
-        uring_sys::io_uring_prep_poll_remove(self.sqe, user_data as _)
+        prep::poll_remove(self.sqe, user_data as _)
     }
 
     #[inline]
     pub unsafe fn prep_connect(&mut self, fd: impl UringFd, socket_addr: &SockAddr) {
         let (addr, len) = socket_addr.as_ffi_pair();
-        uring_sys::io_uring_prep_connect(self.sqe, fd.as_raw_fd(), addr as *const _ as *mut _, len);
+        prep::connect(self.sqe, fd.as_raw_fd(), addr as *const _ as *mut _, len);
         fd.update_sqe(self);
     }
 
@@ -365,88 +852,249 @@ impl<'a> SQE<'a> {
             Some(accept) => (accept.storage.as_mut_ptr() as *mut _, &mut accept.len as *mut _ as *mut _),
             None => (std::ptr::null_mut(), std::ptr::null_mut())
         };
-        uring_sys::io_uring_prep_accept(self.sqe, fd.as_raw_fd(), addr, len, flags.bits());
+        prep::accept(self.sqe, fd.as_raw_fd(), addr, len, flags.bits());
         fd.update_sqe(self);
     }
 
+    /// Prepare an accept event which installs the accepted socket directly into the registered
+    /// fileset instead of the process's file descriptor table. Requires Linux 5.19 or newer.
+    ///
+    /// If `file_index` is [`FileIndex::Alloc`], the result of the event is the index of the slot
+    /// the kernel chose; otherwise it is 0. `SOCK_CLOEXEC` cannot be used with direct descriptors.
+    ///
+    /// # Safety
+    ///
+    /// `accept` must remain valid until the event completes.
     #[inline]
-    pub unsafe fn prep_fadvise(&mut self, fd: impl UringFd, off: u64, len: u64, advice: PosixFadviseAdvice) {
-        use PosixFadviseAdvice::*;
-        let advice = match advice {
-            POSIX_FADV_NORMAL       => libc::POSIX_FADV_NORMAL,
-            POSIX_FADV_SEQUENTIAL   => libc::POSIX_FADV_SEQUENTIAL,
-            POSIX_FADV_RANDOM       => libc::POSIX_FADV_RANDOM,
-            POSIX_FADV_NOREUSE      => libc::POSIX_FADV_NOREUSE,
-            POSIX_FADV_WILLNEED     => libc::POSIX_FADV_WILLNEED,
-            POSIX_FADV_DONTNEED     => libc::POSIX_FADV_DONTNEED,
-        };
-        uring_sys::io_uring_prep_fadvise(self.sqe, fd.as_raw_fd(), off as _, len as _, advice);
-        fd.update_sqe(self);
+    pub unsafe fn prep_accept_direct(
+        &mut self,
+        fd: impl UringFd,
+        accept: Option<&mut SockAddrStorage>,
+        flags: SockFlag,
+        file_index: FileIndex,
+    ) {
+        self.prep_accept(fd, accept, flags);
+        self.set_file_index(file_index);
     }
 
+    /// Prepare an event which creates a socket, like `socket(2)`. Requires Linux 5.19 or newer.
+    ///
+    /// The result of the event is the new file descriptor, which whoever reaps the completion
+    /// has to close.
     #[inline]
-    pub unsafe fn prep_madvise(&mut self, data: &mut [u8], advice: MmapAdvise) {
-        use MmapAdvise::*;
-        let advice = match advice {
-            MADV_NORMAL         => libc::MADV_NORMAL,
-            MADV_RANDOM         => libc::MADV_RANDOM,
-            MADV_SEQUENTIAL     => libc::MADV_SEQUENTIAL,
-            MADV_WILLNEED       => libc::MADV_WILLNEED,
-            MADV_DONTNEED       => libc::MADV_DONTNEED,
-            MADV_REMOVE         => libc::MADV_REMOVE,
-            MADV_DONTFORK       => libc::MADV_DONTFORK,
-            MADV_DOFORK         => libc::MADV_DOFORK,
-            MADV_HWPOISON       => libc::MADV_HWPOISON,
-            MADV_MERGEABLE      => libc::MADV_MERGEABLE,
-            MADV_UNMERGEABLE    => libc::MADV_UNMERGEABLE,
-            MADV_SOFT_OFFLINE   => libc::MADV_SOFT_OFFLINE,
-            MADV_HUGEPAGE       => libc::MADV_HUGEPAGE,
-            MADV_NOHUGEPAGE     => libc::MADV_NOHUGEPAGE,
-            MADV_DONTDUMP       => libc::MADV_DONTDUMP,
-            MADV_DODUMP         => libc::MADV_DODUMP,
-            MADV_FREE           => libc::MADV_FREE,
-        };
-        uring_sys::io_uring_prep_madvise(self.sqe, data.as_mut_ptr() as *mut _, data.len() as _, advice);
+    pub fn prep_socket(
+        &mut self,
+        domain: AddressFamily,
+        ty: SockType,
+        flags: SockFlag,
+        protocol: Option<SockProtocol>,
+    ) {
+        let protocol = protocol.map_or(0, |protocol| protocol as libc::c_int);
+        prep::rw(sys::IORING_OP_SOCKET,
+                 self.sqe,
+                 domain as _,
+                 ptr::null(),
+                 protocol as _,
+                 (ty as libc::c_int | flags.bits()) as _);
     }
 
+    /// Prepare an event which creates a socket directly in the registered fileset, without ever
+    /// installing it in the process's file descriptor table. Requires Linux 5.19 or newer.
+    ///
+    /// Use [`CQE::direct_slot`](crate::CQE::direct_slot) with the same `file_index` to get the
+    /// slot of the socket from the completion. `SOCK_CLOEXEC` cannot be used with direct
+    /// descriptors.
+    ///
+    /// # Safety
+    ///
+    /// If `file_index` is a slot, the socket replaces the file in it. The slot must not hold a
+    /// file which is still used through a [`RegisteredFd`](crate::registrar::RegisteredFd) or
+    /// [`DirectSlot`].
     #[inline]
-    pub unsafe fn prep_epoll_ctl(&mut self, epoll_fd: RawFd, op: EpollOp, fd: RawFd, event: Option<&mut EpollEvent>) {
-        let op = match op {
-            EpollOp::EpollCtlAdd    => libc::EPOLL_CTL_ADD,
-            EpollOp::EpollCtlDel    => libc::EPOLL_CTL_DEL,
-            EpollOp::EpollCtlMod    => libc::EPOLL_CTL_MOD,
-        };
-        let event = event.map_or(ptr::null_mut(), |event| event as *mut EpollEvent as *mut _);
-        uring_sys::io_uring_prep_epoll_ctl(self.sqe, epoll_fd, fd, op, event);
+    pub unsafe fn prep_socket_direct(
+        &mut self,
+        domain: AddressFamily,
+        ty: SockType,
+        flags: SockFlag,
+        protocol: Option<SockProtocol>,
+        file_index: FileIndex,
+    ) {
+        self.prep_socket(domain, ty, flags, protocol);
+        self.set_file_index(file_index);
     }
 
+    /// Prepare an event which posts a completion to another ring, identified by its file
+    /// descriptor. Requires Linux 5.18 or newer.
+    ///
+    /// The completion posted to the target ring has `user_data` and `result` as its user data and
+    /// result. [`MsgRingFlags::CQE_SKIP`] is not valid for this event.
+    ///
+    /// # Safety
+    ///
+    /// `ring_fd` must remain open until the event completes.
     #[inline]
-    pub unsafe fn prep_files_update(&mut self, files: &[RawFd], offset: u32) {
-        let addr = files.as_ptr() as *mut RawFd;
-        let len = files.len() as u32;
-        uring_sys::io_uring_prep_files_update(self.sqe, addr, len, offset as _);
+    pub unsafe fn prep_msg_ring(&mut self, ring_fd: impl UringFd, result: i32, user_data: u64, flags: MsgRingFlags) {
+        prep::rw(sys::IORING_OP_MSG_RING,
+                 self.sqe,
+                 ring_fd.as_raw_fd(),
+                 sys::IORING_MSG_DATA as _,
+                 result as _,
+                 user_data);
+        self.sqe.cmd_flags.rw_flags = flags.bits() as _;
+        ring_fd.update_sqe(self);
+    }
+
+    /// Prepare an event like [`prep_msg_ring`](SQE::prep_msg_ring) which also sets the flags of
+    /// the completion posted to the target ring to `cqe_flags`. Requires Linux 6.3 or newer.
+    ///
+    /// # Safety
+    ///
+    /// `ring_fd` must remain open until the event completes.
+    #[inline]
+    pub unsafe fn prep_msg_ring_cqe_flags(
+        &mut self,
+        ring_fd: impl UringFd,
+        result: i32,
+        user_data: u64,
+        flags: MsgRingFlags,
+        cqe_flags: u32,
+    ) {
+        self.prep_msg_ring(ring_fd, result, user_data, flags | MsgRingFlags::FLAGS_PASS);
+        // the flags to pass share their slot with file_index
+        self.sqe.buf_index.buf_index.splice_fd_in = cqe_flags as _;
     }
 
-    pub unsafe fn prep_provide_buffers(&mut self,
-        buffers: &mut [u8],
-        count: u32,
-        group: BufferGroupId,
-        index: u32,
+    /// Prepare an event which installs a file from this ring's registered fileset into the
+    /// registered fileset of another ring. Requires Linux 6.0 or newer.
+    ///
+    /// Unless [`MsgRingFlags::CQE_SKIP`] is set, a completion with `user_data` as its user data is
+    /// posted to the target ring; its result is the slot the file was installed in.
+    ///
+    /// # Safety
+    ///
+    /// `ring_fd` must remain open until the event completes.
+    #[inline]
+    pub unsafe fn prep_msg_ring_fd(
+        &mut self,
+        ring_fd: impl UringFd,
+        fd: impl Into<DirectSlot>,
+        file_index: FileIndex,
+        user_data: u64,
+        flags: MsgRingFlags,
     ) {
-        let addr = buffers.as_mut_ptr() as *mut libc::c_void;
-        let len = buffers.len() as u32 / count;
-        uring_sys::io_uring_prep_provide_buffers(self.sqe, addr, len as _, count as _, group.id as _, index as _);
+        prep::rw(sys::IORING_OP_MSG_RING,
+                 self.sqe,
+                 ring_fd.as_raw_fd(),
+                 sys::IORING_MSG_SEND_FD as _,
+                 0,
+                 user_data);
+        self.sqe.cmd_flags.rw_flags = flags.bits() as _;
+        // the source fd is stored in addr3
+        self.sqe.buf_index.__pad2[1] = fd.into().index() as _;
+        self.set_file_index(file_index);
+        ring_fd.update_sqe(self);
     }
 
-    pub unsafe fn prep_remove_buffers(&mut self, count: u32, id: BufferGroupId) {
-        uring_sys::io_uring_prep_remove_buffers(self.sqe, count as _, id.id as _);
+    #[inline]
+    pub unsafe fn prep_fadvise(&mut self, fd: impl UringFd, off: u64, len: u64, advice: PosixFadviseAdvice) {
+        let advice = advice as libc::c_int;
+        prep::fadvise(self.sqe, fd.as_raw_fd(), off as _, len as _, advice);
+        fd.update_sqe(self);
     }
 
     #[inline]
-    pub unsafe fn prep_cancel(&mut self, user_data: u64, flags: i32) {
-        uring_sys::io_uring_prep_cancel(self.sqe, user_data as _, flags);
+    pub unsafe fn prep_madvise(&mut self, data: &mut [u8], advice: MmapAdvise) {
+        let advice = advice as libc::c_int;
+        prep::madvise(self.sqe, data.as_mut_ptr() as *mut _, data.len() as _, advice);
     }
 
+    #[inline]
+    pub unsafe fn prep_epoll_ctl(&mut self, epoll_fd: RawFd, op: EpollOp, fd: RawFd, event: Option<&mut EpollEvent>) {
+        let op = op as libc::c_int;
+        let event = event.map_or(ptr::null_mut(), |event| event as *mut EpollEvent as *mut _);
+        prep::epoll_ctl(self.sqe, epoll_fd, fd, op, event);
+    }
+
+    /// Prepare an epoll_wait event, filling `events` with up to `events.len()` ready events from
+    /// the epoll instance `epoll_fd`. Requires Linux 6.15 or newer.
+    ///
+    /// The result of the event is the number of entries of `events` that were filled in.
+    ///
+    /// # Safety
+    ///
+    /// `events` must remain valid until the event completes.
+    #[inline]
+    pub unsafe fn prep_epoll_wait(&mut self, epoll_fd: RawFd, events: &mut [EpollEvent]) {
+        prep::rw(sys::IORING_OP_EPOLL_WAIT,
+                 self.sqe,
+                 epoll_fd,
+                 events.as_mut_ptr() as *const _,
+                 events.len() as _,
+                 0);
+    }
+
+    #[inline]
+    pub unsafe fn prep_files_update(&mut self, files: &[RawFd], offset: u32) {
+        let addr = files.as_ptr() as *mut RawFd;
+        let len = files.len() as u32;
+        prep::files_update(self.sqe, addr, len, offset as _);
+    }
+
+    /// Prepare a provide buffers event, handing the buffers described by `group` to the kernel
+    /// for buffer selection.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffers` is too small to hold every buffer in the group.
+    ///
+    /// # Safety
+    ///
+    /// `buffers` must remain valid until the kernel has consumed or returned every buffer in the
+    /// group.
+    #[inline]
+    pub unsafe fn prep_provide_buffers(&mut self, group: &BufferGroup, buffers: &mut [u8]) {
+        assert!(buffers.len() >= group.total_len(), "buffers are too small for the buffer group");
+        let addr = buffers.as_mut_ptr() as *mut libc::c_void;
+        prep::provide_buffers(self.sqe,
+                              addr,
+                              group.buf_len() as _,
+                              group.count() as _,
+                              group.id().id as _,
+                              group.base_id() as _);
+    }
+
+    /// Prepare a remove buffers event, taking back up to `group.count()` unconsumed buffers from
+    /// the group's id.
+    #[inline]
+    pub unsafe fn prep_remove_buffers(&mut self, group: &BufferGroup) {
+        prep::remove_buffers(self.sqe, group.count() as _, group.id().id as _);
+    }
+
+    #[inline]
+    pub unsafe fn prep_cancel(&mut self, user_data: u64, flags: i32) {
+        prep::cancel(self.sqe, user_data as _, flags);
+    }
+
+    /// Prepare an event which cancels the events matched by `token`. Tokens which match by file
+    /// descriptor require Linux 5.19 or newer.
+    ///
+    /// The result of the event is 0 if an event was cancelled, or the number of cancelled events
+    /// with [`CancelFlags::ALL`]. If no event matched, it is `ENOENT`; if a matching event could
+    /// not be cancelled because it was already running, it is `EALREADY`.
+    #[inline]
+    pub fn prep_cancel_token(&mut self, token: &CancelToken) {
+        prep::cancel(self.sqe, token.user_data as _, 0);
+        self.sqe.fd = token.fd;
+        self.sqe.cmd_flags.cancel_flags = token.flags.bits();
+    }
+
+    /// A token which cancels this event, matching it by its user data. The user data must be set
+    /// before the token is taken, and should be unique among the events in flight.
+    pub fn cancel_token(&self) -> CancelToken {
+        let fixed = self.flags().contains(SubmissionFlags::FIXED_FILE);
+        let event_fd = Some((self.sqe.fd, fixed)).filter(|&(fd, fixed)| fixed || fd >= 0);
+        CancelToken { event_fd, ..CancelToken::user_data(self.user_data()) }
+    }
 
     /// Prepare a no-op event.
     /// ```
@@ -467,7 +1115,56 @@ impl<'a> SQE<'a> {
     ///```
     #[inline]
     pub unsafe fn prep_nop(&mut self) {
-        uring_sys::io_uring_prep_nop(self.sqe);
+        prep::nop(self.sqe);
+    }
+
+    /// Prepare a no-op event with a set of [`NopFlags`].
+    ///
+    /// When `NopFlags::INJECT_RESULT` is set, the event completes with `result` as its result code
+    /// instead of 0, which makes it possible to fabricate completions for testing. Otherwise
+    /// `result` is ignored.
+    ///
+    /// ```
+    /// # use iou::{IoUring, sqe::NopFlags};
+    /// # fn main() -> std::io::Result<()> {
+    /// # let mut ring = IoUring::new(1)?;
+    /// let mut nop = ring.prepare_sqe().unwrap();
+    /// unsafe { nop.prep_nop_with_flags(NopFlags::INJECT_RESULT, -libc::EIO); }
+    ///
+    /// ring.submit_sqes()?;
+    /// let cqe = ring.wait_for_cqe()?;
+    /// assert_eq!(cqe.raw_result(), -libc::EIO);
+    /// # Ok(())
+    /// # }
+    ///```
+    ///
+    /// # Safety
+    ///
+    /// With `NopFlags::INJECT_RESULT`, the event completes with `result` no matter what it is.
+    /// Its user data must not be that of an event whose result other code trusts, like a read
+    /// whose result is taken as the number of bytes it initialized.
+    #[inline]
+    pub unsafe fn prep_nop_with_flags(&mut self, flags: NopFlags, result: i32) {
+        prep::nop(self.sqe);
+        // nop_flags shares its slot with rw_flags
+        self.sqe.cmd_flags.rw_flags = flags.bits() as _;
+        self.sqe.len = result as _;
+    }
+
+    /// Prepare a no-op event which looks up a file descriptor, completing with `-EBADF` if the
+    /// descriptor is not valid.
+    ///
+    /// # Safety
+    ///
+    /// `fd` must remain open until the event completes.
+    #[inline]
+    pub unsafe fn prep_nop_file(&mut self, fd: impl UringFd, flags: NopFlags, result: i32) {
+        self.prep_nop_with_flags(flags | NopFlags::FILE, result);
+        self.sqe.fd = fd.as_raw_fd();
+        fd.update_sqe(self);
+        if self.flags().contains(SubmissionFlags::FIXED_FILE) {
+            self.sqe.cmd_flags.rw_flags |= NopFlags::FIXED_FILE.bits() as libc::c_int;
+        }
     }
 
     /// Clear event. Clears user data, flags, and any event setup.
@@ -488,11 +1185,35 @@ impl<'a> SQE<'a> {
     /// # Ok(())
     /// # }
     /// ```
+    #[inline]
     pub fn clear(&mut self) {
         *self.sqe = unsafe { mem::zeroed() };
+        self.clear_big();
     }
 
-    /// Get a reference to the underlying [`uring_sys::io_uring_sqe`](uring_sys::io_uring_sqe) object.
+    // zero the second half of a big SQE
+    #[inline]
+    fn clear_big(&mut self) {
+        if self.big {
+            unsafe { ptr::write_bytes((self.sqe as *mut sys::io_uring_sqe).add(1), 0, 1); }
+        }
+    }
+
+    // file_index shares its slot with splice_fd_in
+    fn set_file_index(&mut self, file_index: FileIndex) {
+        self.sqe.buf_index.buf_index.splice_fd_in = file_index.to_raw() as _;
+    }
+
+    // addr_len and the padding after it share their slot with splice_fd_in
+    fn set_addr_len(&mut self, len: u16) {
+        unsafe {
+            let addr_len = &mut self.sqe.buf_index.buf_index.splice_fd_in as *mut i32 as *mut u16;
+            addr_len.write(len);
+            addr_len.add(1).write(0);
+        }
+    }
+
+    /// Get a reference to the underlying [`io_uring_sqe`](sys::io_uring_sqe) object.
     ///
     /// You can use this method to inspect the low-level details of an event.
     /// ```
@@ -511,28 +1232,57 @@ impl<'a> SQE<'a> {
     /// # }
     ///
     /// ```
-    pub fn raw(&self) -> &uring_sys::io_uring_sqe {
+    pub fn raw(&self) -> &sys::io_uring_sqe {
         &self.sqe
     }
 
-    pub unsafe fn raw_mut(&mut self) -> &mut uring_sys::io_uring_sqe {
+    pub unsafe fn raw_mut(&mut self) -> &mut sys::io_uring_sqe {
         &mut self.sqe
     }
 }
 
+fn splice_offset(offset: Option<u64>) -> i64 {
+    match offset {
+        Some(offset) => {
+            debug_assert!(offset <= i64::MAX as u64, "splice offset overflows i64");
+            offset as i64
+        }
+        None => -1,
+    }
+}
+
+/// Shows the opcode by name along with the fields common to most events. The meaning of `len`
+/// and `off` depends on the opcode.
+impl fmt::Debug for SQE<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let opcode = self.opcode();
+        let mut debug = f.debug_struct("SQE");
+        match crate::probe::op_name(opcode) {
+            Some(name)  => debug.field("opcode", &format_args!("IORING_OP_{}", name)),
+            None        => debug.field("opcode", &opcode),
+        };
+        debug.field("fd", &self.sqe.fd)
+            .field("len", &self.sqe.len)
+            .field("off", &unsafe { self.sqe.off_addr2.off })
+            .field("user_data", &format_args!("{:#x}", self.user_data()))
+            .field("flags", &self.flags())
+            .finish()
+    }
+}
+
 unsafe impl<'a> Send for SQE<'a> { }
 unsafe impl<'a> Sync for SQE<'a> { }
 
 #[derive(Debug)]
 pub struct SockAddrStorage {
-    storage: mem::MaybeUninit<nix::sys::socket::sockaddr_storage>,
+    storage: mem::MaybeUninit<libc::sockaddr_storage>,
     len: usize,
 }
 
 impl SockAddrStorage {
     pub fn uninit() -> Self {
         let storage = mem::MaybeUninit::uninit();
-        let len = mem::size_of::<nix::sys::socket::sockaddr_storage>();
+        let len = mem::size_of::<libc::sockaddr_storage>();
         SockAddrStorage {
             storage,
             len
@@ -540,22 +1290,533 @@ impl SockAddrStorage {
     }
 
     pub unsafe fn as_socket_addr(&self) -> io::Result<SockAddr> {
-        let storage = &*self.storage.as_ptr();
-        nix::sys::socket::sockaddr_storage_to_addr(storage, self.len).map_err(|e| {
-            let err_no = e.as_errno();
-            match err_no {
-                Some(err_no) => io::Error::from_raw_os_error(err_no as _),
-                None => io::Error::new(io::ErrorKind::Other, "Unknown error")
-            }
+        // the kernel reports the full length of addresses which did not fit
+        if self.len > mem::size_of::<libc::sockaddr_storage>() {
+            return Err(io::Error::from_raw_os_error(libc::EINVAL));
+        }
+        let storage = std::slice::from_raw_parts(self.storage.as_ptr() as *const u8, self.len);
+        SockAddr::from_bytes(storage).ok_or_else(|| io::Error::from_raw_os_error(libc::EINVAL))
+    }
+}
+
+/// The message header and sender address of a [`prep_recv_from`](SQE::prep_recv_from) event.
+pub struct RecvFrom {
+    msg: libc::msghdr,
+    iov: libc::iovec,
+    addr: SockAddrStorage,
+}
+
+impl RecvFrom {
+    pub fn new() -> RecvFrom {
+        RecvFrom {
+            msg: unsafe { mem::zeroed() },
+            iov: libc::iovec { iov_base: ptr::null_mut(), iov_len: 0 },
+            addr: SockAddrStorage::uninit(),
+        }
+    }
+
+    /// The address of the sender.
+    ///
+    /// # Safety
+    ///
+    /// The receive event prepared with this `RecvFrom` must have completed successfully.
+    pub unsafe fn socket_addr(&mut self) -> io::Result<SockAddr> {
+        self.addr.len = self.msg.msg_namelen as usize;
+        self.addr.as_socket_addr()
+    }
+}
+
+impl Default for RecvFrom {
+    fn default() -> RecvFrom {
+        RecvFrom::new()
+    }
+}
+
+impl fmt::Debug for RecvFrom {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RecvFrom").field("addr", &self.addr).finish()
+    }
+}
+
+/// A message received by a [multishot recvmsg](SQE::prep_recvmsg_multishot) event, parsed from
+/// the buffer the kernel selected for it.
+#[derive(Debug, Clone, Copy)]
+pub struct RecvMsgOut<'buf> {
+    header: sys::io_uring_recvmsg_out,
+    name: &'buf [u8],
+    control: &'buf [u8],
+    payload: &'buf [u8],
+}
+
+impl<'buf> RecvMsgOut<'buf> {
+    /// Parse the filled part of a selected buffer, using the same `msg` that the event was
+    /// prepared with. `buf` should be exactly as long as the result of the completion.
+    ///
+    /// Returns `None` if `buf` is too short to contain the layout described by `msg`.
+    pub fn parse(buf: &'buf [u8], msg: &libc::msghdr) -> Option<RecvMsgOut<'buf>> {
+        let header_len = mem::size_of::<sys::io_uring_recvmsg_out>();
+        let name_len = msg.msg_namelen as usize;
+        let control_len = msg.msg_controllen;
+        if buf.len() < header_len + name_len + control_len {
+            return None;
+        }
+
+        let header = unsafe { ptr::read_unaligned(buf.as_ptr() as *const sys::io_uring_recvmsg_out) };
+        let (name, rest) = buf[header_len..].split_at(name_len);
+        let (control, payload) = rest.split_at(control_len);
+        Some(RecvMsgOut {
+            name: &name[..name_len.min(header.namelen as usize)],
+            control: &control[..control_len.min(header.controllen as usize)],
+            header,
+            payload,
         })
     }
+
+    /// The raw socket address of the sender.
+    pub fn name(&self) -> &'buf [u8] {
+        self.name
+    }
+
+    /// Returns true if the sender's address did not fit in the space reserved for it.
+    pub fn is_name_truncated(&self) -> bool {
+        self.header.namelen as usize > self.name.len()
+    }
+
+    /// The sender's address, if it was received in full.
+    pub fn socket_addr(&self) -> Option<SockAddr> {
+        if self.is_name_truncated() || self.name.is_empty() {
+            return None;
+        }
+        SockAddr::from_bytes(self.name)
+    }
+
+    /// The control data (ancillary messages) received with the message.
+    pub fn control(&self) -> &'buf [u8] {
+        self.control
+    }
+
+    /// The part of the payload which fit into the buffer.
+    pub fn payload(&self) -> &'buf [u8] {
+        self.payload
+    }
+
+    /// The full length of the received payload, which may be longer than [`payload`](Self::payload)
+    /// if the message was truncated.
+    pub fn payload_len(&self) -> u32 {
+        self.header.payloadlen
+    }
+
+    /// The flags of the received message, like the `msg_flags` field of a `msghdr`.
+    pub fn flags(&self) -> MsgFlags {
+        MsgFlags::from_bits_truncate(self.header.flags as _)
+    }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+/// A buffer for the result of a [statx](SQE::prep_statx) event.
+///
+/// The kernel only fills in the fields that were requested and that the filesystem supports, so
+/// each accessor returns `None` unless the corresponding [`StatxMode`] bit is set in
+/// [`mask`](Statx::mask).
+pub struct Statx {
+    statx: libc::statx,
+}
+
+impl Statx {
+    /// Create an empty buffer to pass to [`SQE::prep_statx`].
+    pub fn new() -> Statx {
+        Statx { statx: unsafe { mem::zeroed() } }
+    }
+
+    /// The set of fields the kernel filled in.
+    pub fn mask(&self) -> StatxMode {
+        StatxMode::from_bits_truncate(self.statx.stx_mask as _)
+    }
+
+    fn field<T>(&self, mode: StatxMode, value: T) -> Option<T> {
+        if self.mask().contains(mode) { Some(value) } else { None }
+    }
+
+    /// The size of the file in bytes.
+    pub fn size(&self) -> Option<u64> {
+        self.field(StatxMode::STATX_SIZE, self.statx.stx_size)
+    }
+
+    /// The permission bits of the file.
+    pub fn mode(&self) -> Option<Mode> {
+        self.field(StatxMode::STATX_MODE, Mode::from_bits_truncate(self.statx.stx_mode as _))
+    }
+
+    /// The type of the file.
+    pub fn file_type(&self) -> Option<SFlag> {
+        let file_type = SFlag::from_bits_truncate(self.statx.stx_mode as libc::mode_t & libc::S_IFMT);
+        self.field(StatxMode::STATX_TYPE, file_type)
+    }
+
+    pub fn nlink(&self) -> Option<u32> {
+        self.field(StatxMode::STATX_NLINK, self.statx.stx_nlink)
+    }
+
+    pub fn uid(&self) -> Option<u32> {
+        self.field(StatxMode::STATX_UID, self.statx.stx_uid)
+    }
+
+    pub fn gid(&self) -> Option<u32> {
+        self.field(StatxMode::STATX_GID, self.statx.stx_gid)
+    }
+
+    pub fn ino(&self) -> Option<u64> {
+        self.field(StatxMode::STATX_INO, self.statx.stx_ino)
+    }
+
+    /// The number of 512 byte blocks allocated to the file.
+    pub fn blocks(&self) -> Option<u64> {
+        self.field(StatxMode::STATX_BLOCKS, self.statx.stx_blocks)
+    }
+
+    /// The time of last access.
+    pub fn atime(&self) -> Option<SystemTime> {
+        self.field(StatxMode::STATX_ATIME, system_time(&self.statx.stx_atime))
+    }
+
+    /// The time of last modification.
+    pub fn mtime(&self) -> Option<SystemTime> {
+        self.field(StatxMode::STATX_MTIME, system_time(&self.statx.stx_mtime))
+    }
+
+    /// The time of the last status change.
+    pub fn ctime(&self) -> Option<SystemTime> {
+        self.field(StatxMode::STATX_CTIME, system_time(&self.statx.stx_ctime))
+    }
+
+    /// The time the file was created.
+    pub fn btime(&self) -> Option<SystemTime> {
+        self.field(StatxMode::STATX_BTIME, system_time(&self.statx.stx_btime))
+    }
+
+    pub fn as_raw(&self) -> &libc::statx {
+        &self.statx
+    }
+
+    pub fn as_raw_mut(&mut self) -> &mut libc::statx {
+        &mut self.statx
+    }
+}
+
+impl Default for Statx {
+    fn default() -> Statx {
+        Statx::new()
+    }
+}
+
+impl fmt::Debug for Statx {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Statx")
+            .field("mask", &self.mask())
+            .field("size", &self.size())
+            .field("mode", &self.mode())
+            .field("mtime", &self.mtime())
+            .finish()
+    }
+}
+
+fn system_time(ts: &libc::statx_timestamp) -> SystemTime {
+    if ts.tv_sec >= 0 {
+        UNIX_EPOCH + Duration::new(ts.tv_sec as u64, ts.tv_nsec)
+    } else {
+        UNIX_EPOCH - Duration::from_secs(ts.tv_sec.unsigned_abs()) + Duration::from_nanos(ts.tv_nsec as u64)
+    }
+}
+
+/// The scheduling class of an [`IoPrio`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum IoPrioClass {
+    /// No class was set; the priority is derived from the CPU niceness of the process.
+    None,
+    /// Real time IO, which is served before any other class. Requires `CAP_SYS_ADMIN`.
+    RealTime,
+    /// Best effort IO, the default class.
+    BestEffort,
+    /// Idle IO, which is only served when no other IO is pending.
+    Idle,
+}
+
+/// An IO priority, encoded as it is for `ioprio_set(2)`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct IoPrio {
+    value: u16,
+}
+
+impl IoPrio {
+    const CLASS_SHIFT: u16 = 13;
+    const LEVEL_MASK: u16 = (1 << IoPrio::CLASS_SHIFT) - 1;
+
+    /// Create a priority of `level` within `class`. Lower levels are served first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `level` is not in the range 0..8.
+    pub fn new(class: IoPrioClass, level: u8) -> IoPrio {
+        assert!(level < 8, "IO priority levels must be in the range 0..8");
+        let class = match class {
+            IoPrioClass::None       => 0,
+            IoPrioClass::RealTime   => 1,
+            IoPrioClass::BestEffort => 2,
+            IoPrioClass::Idle       => 3,
+        };
+        IoPrio { value: class << IoPrio::CLASS_SHIFT | level as u16 }
+    }
+
+    pub fn from_raw(value: u16) -> IoPrio {
+        IoPrio { value }
+    }
+
+    pub fn to_raw(self) -> u16 {
+        self.value
+    }
+
+    pub fn class(self) -> IoPrioClass {
+        match self.value >> IoPrio::CLASS_SHIFT {
+            1 => IoPrioClass::RealTime,
+            2 => IoPrioClass::BestEffort,
+            3 => IoPrioClass::Idle,
+            _ => IoPrioClass::None,
+        }
+    }
+
+    pub fn level(self) -> u8 {
+        (self.value & IoPrio::LEVEL_MASK) as u8
+    }
+}
+
+impl Default for IoPrio {
+    fn default() -> IoPrio {
+        IoPrio::from_raw(0)
+    }
+}
+
+/// The arguments of an [openat2](SQE::prep_openat2) event.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct OpenHow {
+    flags: u64,
+    mode: u64,
+    resolve: u64,
+}
+
+impl OpenHow {
+    pub fn new(flags: OFlag, mode: Mode) -> OpenHow {
+        OpenHow { flags: flags.bits() as _, mode: mode.bits() as _, resolve: 0 }
+    }
+
+    /// Restrict how the path is resolved.
+    pub fn resolve(self, resolve: ResolveFlags) -> OpenHow {
+        OpenHow { resolve: resolve.bits(), ..self }
+    }
+}
+
+/// Identifies the events a cancellation applies to, either by their user data or by their file
+/// descriptor.
+///
+/// A token is taken from an event with [`SQE::cancel_token`] after its user data is set, and can
+/// later cancel it asynchronously, with [`cancel`](CancelToken::cancel), or synchronously, with
+/// [`cancel_sync`](CancelToken::cancel_sync).
+///
+/// ```
+/// # use std::io;
+/// # use std::os::unix::{io::AsRawFd, net::UnixStream};
+/// # use iou::IoUring;
+/// # use iou::sqe::PollFlags;
+/// # fn main() -> io::Result<()> {
+/// # let mut ring = IoUring::new(2)?;
+/// let (read, _write) = UnixStream::pair()?;
+/// let mut sqe = ring.prepare_sqe().unwrap();
+/// let token = unsafe {
+///     sqe.prep_poll_add(read.as_raw_fd(), PollFlags::POLLIN);
+///     sqe.set_user_data(1);
+///     sqe.cancel_token()
+/// };
+/// ring.submit_sqes()?;
+///
+/// token.cancel_sync(&ring.registrar())?;
+/// let cqe = ring.wait_for_cqe()?;
+/// assert_eq!(cqe.result().unwrap_err().raw_os_error(), Some(libc::ECANCELED));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CancelToken {
+    user_data: u64,
+    fd: RawFd,
+    flags: CancelFlags,
+    // the file descriptor of the event the token was taken from, and whether it is fixed
+    event_fd: Option<(RawFd, bool)>,
+}
+
+impl CancelToken {
+    /// A token which matches events with `user_data`.
+    pub fn user_data(user_data: u64) -> CancelToken {
+        CancelToken { user_data, fd: -1, flags: CancelFlags::empty(), event_fd: None }
+    }
+
+    /// A token which matches events on the file descriptor `fd`.
+    pub fn fd(fd: RawFd) -> CancelToken {
+        CancelToken { user_data: 0, fd, flags: CancelFlags::FD, event_fd: None }
+    }
+
+    /// A token which matches events on a file of the registered fileset.
+    pub fn fixed_fd(fd: impl Into<DirectSlot>) -> CancelToken {
+        let fd = fd.into().index() as RawFd;
+        CancelToken { user_data: 0, fd, flags: CancelFlags::FD | CancelFlags::FD_FIXED, event_fd: None }
+    }
+
+    /// Match events on the file descriptor of the event this token was taken from, instead of by
+    /// its user data. Tokens which were not taken from an event with a file descriptor are
+    /// returned unchanged.
+    pub fn by_fd(self) -> CancelToken {
+        match self.event_fd {
+            Some((fd, true))    => CancelToken { fd, flags: self.flags | CancelFlags::FD | CancelFlags::FD_FIXED, ..self },
+            Some((fd, false))   => CancelToken { fd, flags: self.flags | CancelFlags::FD, ..self },
+            None                => self,
+        }
+    }
+
+    /// Cancel every matching event, rather than only the first. Requires Linux 5.19 or newer.
+    pub fn all(self) -> CancelToken {
+        CancelToken { flags: self.flags | CancelFlags::ALL, ..self }
+    }
+
+    pub fn flags(&self) -> CancelFlags {
+        self.flags
+    }
+
+    /// Prepare an event on `sq` which cancels the matching events, submitting the queue first if
+    /// it is full. See [`SQE::prep_cancel_token`] for the result of the event, whose user data is
+    /// 0 unless it is set.
+    pub fn cancel<'a>(&self, sq: &'a mut SubmissionQueue<'_>) -> io::Result<SQE<'a>> {
+        let (mut sqe, _) = sq.prepare_or_submit()?;
+        sqe.prep_cancel_token(self);
+        Ok(sqe)
+    }
+
+    /// Cancel the matching events and wait for them to complete, without going through the
+    /// submission queue. See [`Registrar::cancel_sync`](crate::Registrar::cancel_sync).
+    pub fn cancel_sync(&self, registrar: &Registrar<'_>) -> io::Result<()> {
+        registrar.cancel_sync(self, None)
+    }
+
+    pub(crate) fn raw_parts(&self) -> (u64, RawFd, u32) {
+        (self.user_data, self.fd, self.flags.bits())
+    }
+}
+
+/// The slot of the registered fileset that an event creating a direct descriptor installs it
+/// into.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum FileIndex {
+    /// Install the descriptor into this slot, replacing any file already registered there.
+    Slot(u32),
+    /// Let the kernel pick a free slot, and report its index as the result of the event.
+    Alloc,
+}
+
+impl FileIndex {
+    fn to_raw(self) -> u32 {
+        match self {
+            FileIndex::Slot(index) => {
+                assert!(index < sys::IORING_FILE_INDEX_ALLOC - 1, "invalid file index");
+                index + 1
+            }
+            FileIndex::Alloc => sys::IORING_FILE_INDEX_ALLOC,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct BufferGroupId {
     pub id: u32,
 }
 
+/// A set of equally sized buffers provided to the kernel under a single [`BufferGroupId`].
+///
+/// The buffers are identified by consecutive buffer ids starting at `base_id`; the kernel reports
+/// which of them it selected in the flags of the [`CQE`](crate::CQE).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct BufferGroup {
+    id: BufferGroupId,
+    buf_len: u32,
+    count: u16,
+    base_id: u16,
+}
+
+impl BufferGroup {
+    /// Describe `count` buffers of `buf_len` bytes each, numbered from `base_id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the group id does not fit in 16 bits, if `buf_len` or `count` is zero, or if the
+    /// buffer ids would run past `u16::MAX`.
+    pub fn new(id: BufferGroupId, buf_len: u32, count: u16, base_id: u16) -> BufferGroup {
+        assert!(id.id <= u16::MAX as u32, "buffer group ids are limited to 16 bits");
+        assert!(buf_len > 0 && count > 0, "buffer groups must not be empty");
+        assert!(base_id as u32 + count as u32 <= u16::MAX as u32 + 1, "buffer ids overflow u16");
+        BufferGroup { id, buf_len, count, base_id }
+    }
+
+    pub fn id(&self) -> BufferGroupId {
+        self.id
+    }
+
+    /// The size of each buffer in the group.
+    pub fn buf_len(&self) -> u32 {
+        self.buf_len
+    }
+
+    /// The number of buffers in the group.
+    pub fn count(&self) -> u16 {
+        self.count
+    }
+
+    /// The buffer id of the first buffer in the group.
+    pub fn base_id(&self) -> u16 {
+        self.base_id
+    }
+
+    /// The total number of bytes needed to back every buffer in the group.
+    pub fn total_len(&self) -> usize {
+        self.buf_len as usize * self.count as usize
+    }
+
+    /// Returns true if `buffer_id` identifies one of the buffers in this group.
+    pub fn contains(&self, buffer_id: u16) -> bool {
+        buffer_id >= self.base_id && ((buffer_id - self.base_id) as u32) < self.count as u32
+    }
+
+    /// The byte range within the backing memory of the buffer with id `buffer_id`.
+    pub fn range_of(&self, buffer_id: u16) -> Option<std::ops::Range<usize>> {
+        if self.contains(buffer_id) {
+            let start = (buffer_id - self.base_id) as usize * self.buf_len as usize;
+            Some(start..start + self.buf_len as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the bytes the kernel filled for the event of `cqe`, from the buffer it selected
+    /// from this group, where `buffers` is the memory which was
+    /// [provided](SQE::prep_provide_buffers) for the group.
+    ///
+    /// Returns `None` if the event failed, did not select a buffer of this group, or reported
+    /// more bytes than `buffers` holds for the buffer.
+    pub fn decode<'a>(&self, cqe: &CQE, buffers: &'a [u8]) -> Option<&'a [u8]> {
+        let len = cqe.result().ok()? as usize;
+        let range = self.range_of(cqe.buffer_id()?)?;
+        if len > range.len() {
+            return None;
+        }
+        buffers.get(range.start..range.start + len)
+    }
+}
+
 bitflags::bitflags! {
     /// [`SQE`](SQE) configuration flags.
     pub struct SubmissionFlags: u8 {
@@ -572,6 +1833,70 @@ bitflags::bitflags! {
         const IO_HARDLINK   = 1 << 3;
         const ASYNC         = 1 << 4;
         const BUFFER_SELECT = 1 << 5;
+        /// Don't post a CQE if this event succeeds.
+        ///
+        /// This requires [`SetupFeatures::CQE_SKIP`](crate::SetupFeatures::CQE_SKIP).
+        const CQE_SKIP_SUCCESS = 1 << 6;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags for [`SQE::prep_nop_with_flags`]. These require Linux 6.10 or newer.
+    pub struct NopFlags: u32 {
+        /// Complete with the result code passed to the prep method instead of 0.
+        const INJECT_RESULT = 1 << 0;
+        /// Look up the file descriptor of the event.
+        const FILE          = 1 << 1;
+        /// The file descriptor is an index into the registered fileset.
+        const FIXED_FILE    = 1 << 2;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags for [`SQE::prep_fixed_fd_install`].
+    pub struct FixedFdInstallFlags: u32 {
+        /// Don't set close-on-exec on the installed file descriptor.
+        const NO_CLOEXEC    = 1 << 0;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags for the msg_ring events, like [`SQE::prep_msg_ring`].
+    pub struct MsgRingFlags: u32 {
+        /// Don't post a completion to the target ring. Only valid for
+        /// [`prep_msg_ring_fd`](SQE::prep_msg_ring_fd).
+        const CQE_SKIP      = 1 << 0;
+        /// Set the flags of the completion posted to the target ring. This is set by
+        /// [`prep_msg_ring_cqe_flags`](SQE::prep_msg_ring_cqe_flags).
+        const FLAGS_PASS    = 1 << 1;
+    }
+}
+
+bitflags::bitflags! {
+    /// How a [`CancelToken`] matches the events it cancels.
+    pub struct CancelFlags: u32 {
+        /// Cancel every matching event, rather than only the first.
+        const ALL       = 1 << 0;
+        /// Match events by file descriptor rather than by user data.
+        const FD        = 1 << 1;
+        /// Match any event.
+        const ANY       = 1 << 2;
+        /// The file descriptor is an index into the registered fileset.
+        const FD_FIXED  = 1 << 3;
+    }
+}
+
+bitflags::bitflags! {
+    /// Options of [poll events](SQE::prep_poll_add_with_flags) and of
+    /// [updates](SQE::prep_poll_update) to them.
+    pub struct PollAddFlags: u32 {
+        /// Keep polling after each event, posting a CQE for every one. Requires Linux 5.13 or
+        /// newer.
+        const MULTI             = 1 << 0;
+        /// Change the events the updated poll event polls for. Only valid for updates.
+        const UPDATE_EVENTS     = 1 << 1;
+        /// Change the user data of the updated poll event. Only valid for updates.
+        const UPDATE_USER_DATA  = 1 << 2;
     }
 }
 
@@ -610,9 +1935,38 @@ bitflags::bitflags! {
     }
 }
 
+bitflags::bitflags! {
+    /// Path resolution flags for [`OpenHow`].
+    pub struct ResolveFlags: u64 {
+        const RESOLVE_NO_XDEV       = 0x01;
+        const RESOLVE_NO_MAGICLINKS = 0x02;
+        const RESOLVE_NO_SYMLINKS   = 0x04;
+        const RESOLVE_BENEATH       = 0x08;
+        const RESOLVE_IN_ROOT       = 0x10;
+        const RESOLVE_CACHED        = 0x20;
+    }
+}
+
 bitflags::bitflags! {
     pub struct TimeoutFlags: u32 {
-        const TIMEOUT_ABS   = 1 << 0;
+        const TIMEOUT_ABS           = 1 << 0;
+        /// Update a pending timeout instead of removing it, see [`SQE::prep_timeout_update`].
+        const UPDATE                = 1 << 1;
+        /// Measure the timeout with the `CLOCK_BOOTTIME` clock, which keeps counting while the
+        /// system is suspended, instead of `CLOCK_MONOTONIC`.
+        const BOOTTIME              = 1 << 2;
+        /// Measure the timeout with the `CLOCK_REALTIME` clock. Together with `TIMEOUT_ABS`,
+        /// this sets a wall clock deadline, see [`Timespec::from_system_time`].
+        const REALTIME              = 1 << 3;
+        /// Update a pending linked timeout rather than a timeout event, see
+        /// [`SQE::prep_timeout_update`].
+        const LINK_TIMEOUT_UPDATE   = 1 << 4;
+        /// An expired timeout still completes with an `ETIME` error, but it is not treated as a
+        /// failure: the events linked after it run instead of being cancelled.
+        const ETIME_SUCCESS         = 1 << 5;
+        /// Fire repeatedly at the interval of the timeout, see
+        /// [`SQE::prep_timeout_multishot`]. Requires Linux 6.4 or newer.
+        const MULTISHOT             = 1 << 6;
     }
 }
 
@@ -624,14 +1978,20 @@ bitflags::bitflags! {
 
 /// A sequence of [`SQE`]s from the [`SubmissionQueue`][crate::SubmissionQueue].
 pub struct SQEs<'ring> {
-    sqes: slice::IterMut<'ring, uring_sys::io_uring_sqe>,
+    sqes: *mut sys::io_uring_sqe,
+    mask: u32,
+    shift: u32,
+    next: u32,
+    end: u32,
+    _marker: PhantomData<&'ring mut [sys::io_uring_sqe]>,
 }
 
 impl<'ring> SQEs<'ring> {
-    pub(crate) fn new(slice: &'ring mut [uring_sys::io_uring_sqe]) -> SQEs<'ring> {
-        SQEs {
-            sqes: slice.iter_mut(),
-        }
+    // `count` SQEs of the ring starting from the SQ index `start`, wrapping around the ring
+    pub(crate) fn new(sqes: *mut sys::io_uring_sqe, mask: u32, shift: u32, start: u32, count: u32)
+        -> SQEs<'ring>
+    {
+        SQEs { sqes, mask, shift, next: start, end: start.wrapping_add(count), _marker: PhantomData }
     }
 
     /// Consumes all remaining [`SQE`]s, returning the last one. Subsequent attempts to get
@@ -647,7 +2007,7 @@ impl<'ring> SQEs<'ring> {
     /// Hard-linked SQEs will occur sequentially. All of them will be completed, even if one of the
     /// events resolves to an error.
     pub fn hard_linked(&mut self) -> HardLinked<'ring, '_> {
-        HardLinked { sqes: self }
+        HardLinked { sqes: self, skip_success: false }
     }
 
     /// An iterator of [`SoftLinkedSQE`]s. These will be [`SQE`]s that are *soft-linked* together.
@@ -655,22 +2015,118 @@ impl<'ring> SQEs<'ring> {
     /// Soft-linked SQEs will occur sequentially. If one the events errors, all events after it
     /// will be cancelled.
     pub fn soft_linked(&mut self) -> SoftLinked<'ring, '_> {
-        SoftLinked { sqes: self }
+        SoftLinked { sqes: self, skip_success: false }
+    }
+
+    /// An iterator of [`SoftLinkedSQE`]s, which sets `IO_LINK` on every [`SQE`] but the last.
+    ///
+    /// This is the same as [`soft_linked`](SQEs::soft_linked): if one of the events fails, the
+    /// events after it are cancelled with `ECANCELED`.
+    pub fn linked(&mut self) -> SoftLinked<'ring, '_> {
+        self.soft_linked()
+    }
+
+    /// An iterator of [`DrainedSQE`]s, which sets `IO_DRAIN` on the last [`SQE`].
+    ///
+    /// The last event will not start before all events submitted before it, including the
+    /// other events of this sequence, have completed. This makes it a barrier, for example for
+    /// an `fsync` which must follow all prior writes.
+    pub fn drained(&mut self) -> Drained<'ring, '_> {
+        Drained { sqes: self }
+    }
+
+    /// Prepare a connect event which is cancelled if it has not completed within `timeout`,
+    /// using the next two [`SQE`]s: the connect event, linked to a
+    /// [link timeout](SQE::prep_link_timeout).
+    ///
+    /// If the timeout expires first, the connect event completes with `ECANCELED` and the
+    /// timeout with `ETIME`; otherwise the timeout completes with `ECANCELED`. Returns the two
+    /// events, to set their user data, or `None` without preparing anything if fewer than two
+    /// `SQE`s remain.
+    ///
+    /// # Safety
+    ///
+    /// `addr` and `timeout` must remain valid until the events are submitted.
+    pub unsafe fn connect_with_timeout(&mut self, fd: impl UringFd, addr: &SockAddr, timeout: &Timespec)
+        -> Option<[SQE<'ring>; 2]>
+    {
+        let [mut connect, mut link_timeout] = self.pair()?;
+        connect.prep_connect(fd, addr);
+        connect.add_flags(SubmissionFlags::IO_LINK);
+        link_timeout.prep_link_timeout(timeout);
+        Some([connect, link_timeout])
+    }
+
+    /// Prepare a write followed by an `fsync` of the same file, using the next two [`SQE`]s.
+    ///
+    /// The fsync only runs once the write has completed in full; if the write fails or is short,
+    /// the fsync completes with `ECANCELED`. Returns the two events, to set their user data, or
+    /// `None` without preparing anything if fewer than two `SQE`s remain.
+    ///
+    /// # Safety
+    ///
+    /// `buf` must remain valid until the write completes.
+    pub unsafe fn write_then_fsync<F: UringFd + Copy>(&mut self, fd: F, buf: impl UringWriteBuf, offset: u64)
+        -> Option<[SQE<'ring>; 2]>
+    {
+        let [mut write, mut fsync] = self.pair()?;
+        write.prep_write(fd, buf, offset);
+        write.add_flags(SubmissionFlags::IO_LINK);
+        fsync.prep_fsync(fd, FsyncFlags::empty());
+        Some([write, fsync])
+    }
+
+    /// Prepare a read followed by closing the file, using the next two [`SQE`]s.
+    ///
+    /// The events are hard-linked, so the file is closed even if the read fails. Returns the two
+    /// events, to set their user data, or `None` without preparing anything if fewer than two
+    /// `SQE`s remain.
+    ///
+    /// # Safety
+    ///
+    /// `buf` must remain valid until the read completes, and `fd` must not be used after the
+    /// events are submitted.
+    pub unsafe fn read_then_close(&mut self, fd: RawFd, buf: impl UringReadBuf, offset: u64)
+        -> Option<[SQE<'ring>; 2]>
+    {
+        let [mut read, mut close] = self.pair()?;
+        read.prep_read(fd, buf, offset);
+        read.add_flags(SubmissionFlags::IO_HARDLINK);
+        close.prep_close(fd);
+        Some([read, close])
     }
 
     /// Remaining [`SQE`]s that can be modified.
     pub fn remaining(&self) -> u32 {
-        self.sqes.len() as u32
+        self.end.wrapping_sub(self.next)
+    }
+
+    fn pair(&mut self) -> Option<[SQE<'ring>; 2]> {
+        if self.remaining() < 2 {
+            return None;
+        }
+        Some([self.consume()?, self.consume()?])
     }
 
     fn consume(&mut self) -> Option<SQE<'ring>> {
-        self.sqes.next().map(|sqe| {
-            unsafe { uring_sys::io_uring_prep_nop(sqe) }
-            SQE { sqe }
-        })
+        if self.next == self.end {
+            return None;
+        }
+        let index = ((self.next & self.mask) << self.shift) as usize;
+        self.next = self.next.wrapping_add(1);
+        unsafe {
+            let sqe = &mut *self.sqes.add(index);
+            prep::nop(sqe);
+            let mut sqe = SQE::new(sqe, self.shift != 0);
+            sqe.clear_big();
+            Some(sqe)
+        }
     }
 }
 
+unsafe impl Send for SQEs<'_> { }
+unsafe impl Sync for SQEs<'_> { }
+
 impl<'ring> Iterator for SQEs<'ring> {
     type Item = SQE<'ring>;
 
@@ -682,12 +2138,31 @@ impl<'ring> Iterator for SQEs<'ring> {
 /// An Iterator of [`SQE`]s which will be hard linked together.
 pub struct HardLinked<'ring, 'a> {
     sqes: &'a mut SQEs<'ring>,
+    skip_success: bool,
 }
 
-impl<'ring> HardLinked<'ring, '_> {
+impl<'ring, 'a> HardLinked<'ring, 'a> {
+    /// Set [`CQE_SKIP_SUCCESS`](SubmissionFlags::CQE_SKIP_SUCCESS) on every event of the chain
+    /// but the last, so that only the last event posts a CQE when the chain succeeds. An event
+    /// which fails still posts its CQE.
+    ///
+    /// This requires [`SetupFeatures::CQE_SKIP`](crate::SetupFeatures::CQE_SKIP).
+    pub fn skip_success(self) -> HardLinked<'ring, 'a> {
+        HardLinked { skip_success: true, ..self }
+    }
+
     pub fn terminate(self) -> Option<SQE<'ring>> {
         self.sqes.consume()
     }
+
+    /// Terminate the chain with an event which will not start before all events submitted before
+    /// it have completed.
+    ///
+    /// The kernel drains a chain as a whole, so none of its events will start before the events
+    /// submitted before the chain have completed.
+    pub fn terminate_with_drain(self) -> Option<DrainedSQE<'ring>> {
+        self.sqes.consume().map(|sqe| DrainedSQE { sqe, is_final: true })
+    }
 }
 
 impl<'ring> Iterator for HardLinked<'ring, '_> {
@@ -695,13 +2170,15 @@ impl<'ring> Iterator for HardLinked<'ring, '_> {
 
     fn next(&mut self) -> Option<Self::Item> {
         let is_final = self.sqes.remaining() == 1;
-        self.sqes.consume().map(|sqe| HardLinkedSQE { sqe, is_final })
+        let skip_success = self.skip_success;
+        self.sqes.consume().map(|sqe| HardLinkedSQE { sqe, is_final, skip_success })
     }
 }
 
 pub struct HardLinkedSQE<'ring> {
     sqe: SQE<'ring>,
     is_final: bool,
+    skip_success: bool,
 }
 
 impl<'ring> Deref for HardLinkedSQE<'ring> {
@@ -722,6 +2199,9 @@ impl<'ring> Drop for HardLinkedSQE<'ring> {
     fn drop(&mut self) {
         if !self.is_final {
             self.sqe.set_flags(SubmissionFlags::IO_HARDLINK);
+            if self.skip_success {
+                self.sqe.set_flags(SubmissionFlags::CQE_SKIP_SUCCESS);
+            }
         }
     }
 }
@@ -729,12 +2209,31 @@ impl<'ring> Drop for HardLinkedSQE<'ring> {
 /// An Iterator of [`SQE`]s which will be soft linked together.
 pub struct SoftLinked<'ring, 'a> {
     sqes: &'a mut SQEs<'ring>,
+    skip_success: bool,
 }
 
-impl<'ring> SoftLinked<'ring, '_> {
+impl<'ring, 'a> SoftLinked<'ring, 'a> {
+    /// Set [`CQE_SKIP_SUCCESS`](SubmissionFlags::CQE_SKIP_SUCCESS) on every event of the chain
+    /// but the last, so that only the last event posts a CQE when the chain succeeds. An event
+    /// which fails still posts its CQE.
+    ///
+    /// This requires [`SetupFeatures::CQE_SKIP`](crate::SetupFeatures::CQE_SKIP).
+    pub fn skip_success(self) -> SoftLinked<'ring, 'a> {
+        SoftLinked { skip_success: true, ..self }
+    }
+
     pub fn terminate(self) -> Option<SQE<'ring>> {
         self.sqes.consume()
     }
+
+    /// Terminate the chain with an event which will not start before all events submitted before
+    /// it have completed.
+    ///
+    /// The kernel drains a chain as a whole, so none of its events will start before the events
+    /// submitted before the chain have completed.
+    pub fn terminate_with_drain(self) -> Option<DrainedSQE<'ring>> {
+        self.sqes.consume().map(|sqe| DrainedSQE { sqe, is_final: true })
+    }
 }
 
 impl<'ring> Iterator for SoftLinked<'ring, '_> {
@@ -742,13 +2241,15 @@ impl<'ring> Iterator for SoftLinked<'ring, '_> {
 
     fn next(&mut self) -> Option<Self::Item> {
         let is_final = self.sqes.remaining() == 1;
-        self.sqes.consume().map(|sqe| SoftLinkedSQE { sqe, is_final })
+        let skip_success = self.skip_success;
+        self.sqes.consume().map(|sqe| SoftLinkedSQE { sqe, is_final, skip_success })
     }
 }
 
 pub struct SoftLinkedSQE<'ring> {
     sqe: SQE<'ring>,
     is_final: bool,
+    skip_success: bool,
 }
 
 impl<'ring> Deref for SoftLinkedSQE<'ring> {
@@ -769,6 +2270,50 @@ impl<'ring> Drop for SoftLinkedSQE<'ring> {
     fn drop(&mut self) {
         if !self.is_final {
             self.sqe.set_flags(SubmissionFlags::IO_LINK);
+            if self.skip_success {
+                self.sqe.set_flags(SubmissionFlags::CQE_SKIP_SUCCESS);
+            }
+        }
+    }
+}
+
+/// An Iterator of [`SQE`]s, the last of which will drain all events submitted before it.
+pub struct Drained<'ring, 'a> {
+    sqes: &'a mut SQEs<'ring>,
+}
+
+impl<'ring> Iterator for Drained<'ring, '_> {
+    type Item = DrainedSQE<'ring>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let is_final = self.sqes.remaining() == 1;
+        self.sqes.consume().map(|sqe| DrainedSQE { sqe, is_final })
+    }
+}
+
+pub struct DrainedSQE<'ring> {
+    sqe: SQE<'ring>,
+    is_final: bool,
+}
+
+impl<'ring> Deref for DrainedSQE<'ring> {
+    type Target = SQE<'ring>;
+
+    fn deref(&self) -> &SQE<'ring> {
+        &self.sqe
+    }
+}
+
+impl<'ring> DerefMut for DrainedSQE<'ring> {
+    fn deref_mut(&mut self) -> &mut SQE<'ring> {
+        &mut self.sqe
+    }
+}
+
+impl<'ring> Drop for DrainedSQE<'ring> {
+    fn drop(&mut self) {
+        if self.is_final {
+            self.sqe.set_flags(SubmissionFlags::IO_DRAIN);
         }
     }
 }
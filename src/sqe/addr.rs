@@ -0,0 +1,194 @@
+use std::ffi::OsStr;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::mem;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::ptr;
+
+use super::AddressFamily;
+
+/// A socket address, as used by [`SQE::prep_connect`](super::SQE::prep_connect) and
+/// [`SQE::prep_send_to`](super::SQE::prep_send_to), and as returned for the peers of accepted
+/// sockets and received messages.
+///
+/// Internet addresses convert to and from the standard library's [`SocketAddr`]:
+///
+/// ```
+/// # use iou::sqe::{AddressFamily, SockAddr};
+/// let addr: std::net::SocketAddr = "127.0.0.1:8080".parse().unwrap();
+/// let sock_addr = SockAddr::from(addr);
+/// assert_eq!(sock_addr.family(), Some(AddressFamily::Inet));
+/// assert_eq!(sock_addr.to_socket_addr(), Some(addr));
+/// ```
+#[derive(Clone, Copy)]
+pub struct SockAddr {
+    storage: libc::sockaddr_storage,
+    len: libc::socklen_t,
+}
+
+impl SockAddr {
+    /// Copy a socket address from its raw bytes, such as a `sockaddr_in` or a `sockaddr_un`.
+    ///
+    /// Returns `None` if `bytes` is too short to hold an address family or too long for a
+    /// `sockaddr_storage`.
+    pub fn from_bytes(bytes: &[u8]) -> Option<SockAddr> {
+        if bytes.len() < mem::size_of::<libc::sa_family_t>()
+            || bytes.len() > mem::size_of::<libc::sockaddr_storage>()
+        {
+            return None;
+        }
+        unsafe {
+            let mut storage: libc::sockaddr_storage = mem::zeroed();
+            ptr::copy_nonoverlapping(bytes.as_ptr(), &mut storage as *mut _ as *mut u8, bytes.len());
+            Some(SockAddr { storage, len: bytes.len() as _ })
+        }
+    }
+
+    /// The address of the unix socket bound to `path`.
+    pub fn new_unix(path: impl AsRef<Path>) -> io::Result<SockAddr> {
+        let path = path.as_ref().as_os_str().as_bytes();
+        unsafe {
+            let mut addr: libc::sockaddr_un = mem::zeroed();
+            // leave room for the terminating nul byte
+            if path.len() >= addr.sun_path.len() {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "path too long for a unix socket"));
+            }
+            addr.sun_family = libc::AF_UNIX as _;
+            ptr::copy_nonoverlapping(path.as_ptr(), addr.sun_path.as_mut_ptr() as *mut u8, path.len());
+            let len = mem::size_of::<libc::sa_family_t>() + path.len() + 1;
+            Ok(SockAddr::from_raw_parts(&addr as *const _ as *const u8, len))
+        }
+    }
+
+    unsafe fn from_raw_parts(addr: *const u8, len: usize) -> SockAddr {
+        let mut storage: libc::sockaddr_storage = mem::zeroed();
+        ptr::copy_nonoverlapping(addr, &mut storage as *mut _ as *mut u8, len);
+        SockAddr { storage, len: len as _ }
+    }
+
+    /// The family of the address, if it is one of the known [`AddressFamily`]s.
+    pub fn family(&self) -> Option<AddressFamily> {
+        AddressFamily::from_raw(self.storage.ss_family as _)
+    }
+
+    /// The raw bytes of the address.
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(&self.storage as *const _ as *const u8, self.len as usize) }
+    }
+
+    /// A pointer to the address and its length, as taken by the socket system calls.
+    pub fn as_ffi_pair(&self) -> (&libc::sockaddr, libc::socklen_t) {
+        (unsafe { &*(&self.storage as *const _ as *const libc::sockaddr) }, self.len)
+    }
+
+    /// The address as a standard library `SocketAddr`, if it is an internet address.
+    pub fn to_socket_addr(&self) -> Option<SocketAddr> {
+        match self.family()? {
+            AddressFamily::Inet if self.len as usize >= mem::size_of::<libc::sockaddr_in>() => {
+                let addr = unsafe { &*(&self.storage as *const _ as *const libc::sockaddr_in) };
+                let ip = Ipv4Addr::from(addr.sin_addr.s_addr.to_ne_bytes());
+                Some(SocketAddrV4::new(ip, u16::from_be(addr.sin_port)).into())
+            }
+            AddressFamily::Inet6 if self.len as usize >= mem::size_of::<libc::sockaddr_in6>() => {
+                let addr = unsafe { &*(&self.storage as *const _ as *const libc::sockaddr_in6) };
+                let ip = Ipv6Addr::from(addr.sin6_addr.s6_addr);
+                let port = u16::from_be(addr.sin6_port);
+                Some(SocketAddrV6::new(ip, port, addr.sin6_flowinfo, addr.sin6_scope_id).into())
+            }
+            _ => None,
+        }
+    }
+
+    /// The path of the address, if it is the address of a unix socket bound to a path.
+    pub fn as_unix_path(&self) -> Option<&Path> {
+        if self.family()? != AddressFamily::Unix {
+            return None;
+        }
+        let path = self.as_bytes().get(mem::size_of::<libc::sa_family_t>()..)?;
+        // unnamed sockets have no path, and abstract sockets start with a nul byte
+        if let None | Some(0) = path.first() {
+            return None;
+        }
+        let path = &path[..path.iter().position(|&byte| byte == 0).unwrap_or(path.len())];
+        Some(Path::new(OsStr::from_bytes(path)))
+    }
+}
+
+impl From<SocketAddr> for SockAddr {
+    fn from(addr: SocketAddr) -> SockAddr {
+        unsafe {
+            match addr {
+                SocketAddr::V4(addr) => {
+                    let mut raw: libc::sockaddr_in = mem::zeroed();
+                    raw.sin_family = libc::AF_INET as _;
+                    raw.sin_port = addr.port().to_be();
+                    raw.sin_addr.s_addr = u32::from_ne_bytes(addr.ip().octets());
+                    SockAddr::from_raw_parts(&raw as *const _ as *const u8, mem::size_of_val(&raw))
+                }
+                SocketAddr::V6(addr) => {
+                    let mut raw: libc::sockaddr_in6 = mem::zeroed();
+                    raw.sin6_family = libc::AF_INET6 as _;
+                    raw.sin6_port = addr.port().to_be();
+                    raw.sin6_flowinfo = addr.flowinfo();
+                    raw.sin6_addr.s6_addr = addr.ip().octets();
+                    raw.sin6_scope_id = addr.scope_id();
+                    SockAddr::from_raw_parts(&raw as *const _ as *const u8, mem::size_of_val(&raw))
+                }
+            }
+        }
+    }
+}
+
+impl PartialEq for SockAddr {
+    fn eq(&self, other: &SockAddr) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for SockAddr { }
+
+impl Hash for SockAddr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state)
+    }
+}
+
+impl fmt::Debug for SockAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(addr) = self.to_socket_addr() {
+            return f.debug_tuple("SockAddr").field(&addr).finish();
+        }
+        if let Some(path) = self.as_unix_path() {
+            return f.debug_tuple("SockAddr").field(&path).finish();
+        }
+        f.debug_struct("SockAddr")
+            .field("family", &self.storage.ss_family)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+#[cfg(feature = "nix")]
+impl From<nix::sys::socket::SockAddr> for SockAddr {
+    fn from(addr: nix::sys::socket::SockAddr) -> SockAddr {
+        let (addr, len) = addr.as_ffi_pair();
+        unsafe { SockAddr::from_raw_parts(addr as *const _ as *const u8, len as usize) }
+    }
+}
+
+#[cfg(feature = "nix")]
+impl std::convert::TryFrom<SockAddr> for nix::sys::socket::SockAddr {
+    type Error = io::Error;
+
+    fn try_from(addr: SockAddr) -> io::Result<nix::sys::socket::SockAddr> {
+        nix::sys::socket::sockaddr_storage_to_addr(&addr.storage, addr.len as usize).map_err(|err| {
+            match err.as_errno() {
+                Some(errno) => io::Error::from_raw_os_error(errno as _),
+                None        => io::Error::other(err),
+            }
+        })
+    }
+}
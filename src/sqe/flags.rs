@@ -0,0 +1,399 @@
+use std::fmt;
+
+use libc::{c_int, c_short, mode_t};
+
+bitflags::bitflags! {
+    /// Flags for opening a file, as taken by `open(2)`.
+    pub struct OFlag: c_int {
+        const O_ACCMODE     = libc::O_ACCMODE;
+        const O_APPEND      = libc::O_APPEND;
+        const O_ASYNC       = libc::O_ASYNC;
+        const O_CLOEXEC     = libc::O_CLOEXEC;
+        const O_CREAT       = libc::O_CREAT;
+        const O_DIRECT      = libc::O_DIRECT;
+        const O_DIRECTORY   = libc::O_DIRECTORY;
+        const O_DSYNC       = libc::O_DSYNC;
+        const O_EXCL        = libc::O_EXCL;
+        const O_LARGEFILE   = libc::O_LARGEFILE;
+        const O_NOATIME     = libc::O_NOATIME;
+        const O_NOCTTY      = libc::O_NOCTTY;
+        const O_NOFOLLOW    = libc::O_NOFOLLOW;
+        const O_NONBLOCK    = libc::O_NONBLOCK;
+        const O_PATH        = libc::O_PATH;
+        const O_RDONLY      = libc::O_RDONLY;
+        const O_RDWR        = libc::O_RDWR;
+        const O_SYNC        = libc::O_SYNC;
+        const O_TMPFILE     = libc::O_TMPFILE;
+        const O_TRUNC       = libc::O_TRUNC;
+        const O_WRONLY      = libc::O_WRONLY;
+    }
+}
+
+bitflags::bitflags! {
+    /// The permission bits of a file mode.
+    pub struct Mode: mode_t {
+        const S_IRWXU = libc::S_IRWXU;
+        const S_IRUSR = libc::S_IRUSR;
+        const S_IWUSR = libc::S_IWUSR;
+        const S_IXUSR = libc::S_IXUSR;
+        const S_IRWXG = libc::S_IRWXG;
+        const S_IRGRP = libc::S_IRGRP;
+        const S_IWGRP = libc::S_IWGRP;
+        const S_IXGRP = libc::S_IXGRP;
+        const S_IRWXO = libc::S_IRWXO;
+        const S_IROTH = libc::S_IROTH;
+        const S_IWOTH = libc::S_IWOTH;
+        const S_IXOTH = libc::S_IXOTH;
+        const S_ISUID = libc::S_ISUID;
+        const S_ISGID = libc::S_ISGID;
+        const S_ISVTX = libc::S_ISVTX;
+    }
+}
+
+bitflags::bitflags! {
+    /// The file type bits of a file mode.
+    pub struct SFlag: mode_t {
+        const S_IFIFO   = libc::S_IFIFO;
+        const S_IFCHR   = libc::S_IFCHR;
+        const S_IFDIR   = libc::S_IFDIR;
+        const S_IFBLK   = libc::S_IFBLK;
+        const S_IFREG   = libc::S_IFREG;
+        const S_IFLNK   = libc::S_IFLNK;
+        const S_IFSOCK  = libc::S_IFSOCK;
+        const S_IFMT    = libc::S_IFMT;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags for [`SQE::prep_fallocate`](super::SQE::prep_fallocate), as taken by
+    /// `fallocate(2)`.
+    pub struct FallocateFlags: c_int {
+        const FALLOC_FL_KEEP_SIZE       = libc::FALLOC_FL_KEEP_SIZE;
+        const FALLOC_FL_PUNCH_HOLE      = libc::FALLOC_FL_PUNCH_HOLE;
+        const FALLOC_FL_COLLAPSE_RANGE  = libc::FALLOC_FL_COLLAPSE_RANGE;
+        const FALLOC_FL_ZERO_RANGE      = libc::FALLOC_FL_ZERO_RANGE;
+        const FALLOC_FL_INSERT_RANGE    = libc::FALLOC_FL_INSERT_RANGE;
+        const FALLOC_FL_UNSHARE_RANGE   = libc::FALLOC_FL_UNSHARE_RANGE;
+    }
+}
+
+bitflags::bitflags! {
+    /// The events of a [poll event](super::SQE::prep_poll_add), and the events reported by its
+    /// completion.
+    pub struct PollFlags: c_short {
+        const POLLIN        = libc::POLLIN;
+        const POLLPRI       = libc::POLLPRI;
+        const POLLOUT       = libc::POLLOUT;
+        const POLLRDNORM    = libc::POLLRDNORM;
+        const POLLRDBAND    = libc::POLLRDBAND;
+        const POLLWRNORM    = libc::POLLWRNORM;
+        const POLLWRBAND    = libc::POLLWRBAND;
+        const POLLERR       = libc::POLLERR;
+        const POLLHUP       = libc::POLLHUP;
+        const POLLNVAL      = libc::POLLNVAL;
+    }
+}
+
+bitflags::bitflags! {
+    /// The events of an [`EpollEvent`].
+    pub struct EpollFlags: c_int {
+        const EPOLLIN           = libc::EPOLLIN;
+        const EPOLLPRI          = libc::EPOLLPRI;
+        const EPOLLOUT          = libc::EPOLLOUT;
+        const EPOLLRDNORM       = libc::EPOLLRDNORM;
+        const EPOLLRDBAND       = libc::EPOLLRDBAND;
+        const EPOLLWRNORM       = libc::EPOLLWRNORM;
+        const EPOLLWRBAND       = libc::EPOLLWRBAND;
+        const EPOLLMSG          = libc::EPOLLMSG;
+        const EPOLLERR          = libc::EPOLLERR;
+        const EPOLLHUP          = libc::EPOLLHUP;
+        const EPOLLRDHUP        = libc::EPOLLRDHUP;
+        const EPOLLEXCLUSIVE    = libc::EPOLLEXCLUSIVE;
+        const EPOLLWAKEUP       = libc::EPOLLWAKEUP;
+        const EPOLLONESHOT      = libc::EPOLLONESHOT;
+        const EPOLLET           = libc::EPOLLET;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags for sending and receiving messages on sockets, as taken by `send(2)` and `recv(2)`.
+    pub struct MsgFlags: c_int {
+        const MSG_OOB           = libc::MSG_OOB;
+        const MSG_PEEK          = libc::MSG_PEEK;
+        const MSG_DONTROUTE     = libc::MSG_DONTROUTE;
+        const MSG_CTRUNC        = libc::MSG_CTRUNC;
+        const MSG_TRUNC         = libc::MSG_TRUNC;
+        const MSG_DONTWAIT      = libc::MSG_DONTWAIT;
+        const MSG_EOR           = libc::MSG_EOR;
+        const MSG_WAITALL       = libc::MSG_WAITALL;
+        const MSG_ERRQUEUE      = libc::MSG_ERRQUEUE;
+        const MSG_NOSIGNAL      = libc::MSG_NOSIGNAL;
+        const MSG_MORE          = libc::MSG_MORE;
+        const MSG_WAITFORONE    = libc::MSG_WAITFORONE;
+        const MSG_CMSG_CLOEXEC  = libc::MSG_CMSG_CLOEXEC;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags for sockets created or accepted by an event.
+    pub struct SockFlag: c_int {
+        const SOCK_NONBLOCK = libc::SOCK_NONBLOCK;
+        const SOCK_CLOEXEC  = libc::SOCK_CLOEXEC;
+    }
+}
+
+/// Advice for [`SQE::prep_fadvise`](super::SQE::prep_fadvise), as taken by `posix_fadvise(2)`.
+#[repr(i32)]
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PosixFadviseAdvice {
+    POSIX_FADV_NORMAL       = libc::POSIX_FADV_NORMAL,
+    POSIX_FADV_SEQUENTIAL   = libc::POSIX_FADV_SEQUENTIAL,
+    POSIX_FADV_RANDOM       = libc::POSIX_FADV_RANDOM,
+    POSIX_FADV_NOREUSE      = libc::POSIX_FADV_NOREUSE,
+    POSIX_FADV_WILLNEED     = libc::POSIX_FADV_WILLNEED,
+    POSIX_FADV_DONTNEED     = libc::POSIX_FADV_DONTNEED,
+}
+
+/// Advice for [`SQE::prep_madvise`](super::SQE::prep_madvise), as taken by `madvise(2)`.
+#[repr(i32)]
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MmapAdvise {
+    MADV_NORMAL         = libc::MADV_NORMAL,
+    MADV_RANDOM         = libc::MADV_RANDOM,
+    MADV_SEQUENTIAL     = libc::MADV_SEQUENTIAL,
+    MADV_WILLNEED       = libc::MADV_WILLNEED,
+    MADV_DONTNEED       = libc::MADV_DONTNEED,
+    MADV_REMOVE         = libc::MADV_REMOVE,
+    MADV_DONTFORK       = libc::MADV_DONTFORK,
+    MADV_DOFORK         = libc::MADV_DOFORK,
+    MADV_HWPOISON       = libc::MADV_HWPOISON,
+    MADV_MERGEABLE      = libc::MADV_MERGEABLE,
+    MADV_UNMERGEABLE    = libc::MADV_UNMERGEABLE,
+    MADV_SOFT_OFFLINE   = libc::MADV_SOFT_OFFLINE,
+    MADV_HUGEPAGE       = libc::MADV_HUGEPAGE,
+    MADV_NOHUGEPAGE     = libc::MADV_NOHUGEPAGE,
+    MADV_DONTDUMP       = libc::MADV_DONTDUMP,
+    MADV_DODUMP         = libc::MADV_DODUMP,
+    MADV_FREE           = libc::MADV_FREE,
+}
+
+/// The operation of an [`SQE::prep_epoll_ctl`](super::SQE::prep_epoll_ctl) event.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EpollOp {
+    EpollCtlAdd = libc::EPOLL_CTL_ADD,
+    EpollCtlDel = libc::EPOLL_CTL_DEL,
+    EpollCtlMod = libc::EPOLL_CTL_MOD,
+}
+
+/// The domain of a socket.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddressFamily {
+    Unix    = libc::AF_UNIX,
+    Inet    = libc::AF_INET,
+    Inet6   = libc::AF_INET6,
+    Netlink = libc::AF_NETLINK,
+    Packet  = libc::AF_PACKET,
+    Vsock   = libc::AF_VSOCK,
+}
+
+impl AddressFamily {
+    /// The address family with the `AF_*` constant `family`, if it is one of the known families.
+    pub fn from_raw(family: c_int) -> Option<AddressFamily> {
+        match family {
+            libc::AF_UNIX       => Some(AddressFamily::Unix),
+            libc::AF_INET       => Some(AddressFamily::Inet),
+            libc::AF_INET6      => Some(AddressFamily::Inet6),
+            libc::AF_NETLINK    => Some(AddressFamily::Netlink),
+            libc::AF_PACKET     => Some(AddressFamily::Packet),
+            libc::AF_VSOCK      => Some(AddressFamily::Vsock),
+            _                   => None,
+        }
+    }
+}
+
+/// The type of a socket.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SockType {
+    Stream      = libc::SOCK_STREAM,
+    Datagram    = libc::SOCK_DGRAM,
+    SeqPacket   = libc::SOCK_SEQPACKET,
+    Raw         = libc::SOCK_RAW,
+    Rdm         = libc::SOCK_RDM,
+}
+
+/// The protocol of a socket.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SockProtocol {
+    Tcp = libc::IPPROTO_TCP,
+    Udp = libc::IPPROTO_UDP,
+}
+
+/// An event of an epoll instance, as read by [`SQE::prep_epoll_wait`](super::SQE::prep_epoll_wait)
+/// or registered by [`SQE::prep_epoll_ctl`](super::SQE::prep_epoll_ctl).
+#[repr(transparent)]
+#[derive(Clone, Copy)]
+pub struct EpollEvent {
+    event: libc::epoll_event,
+}
+
+impl EpollEvent {
+    pub fn new(events: EpollFlags, data: u64) -> EpollEvent {
+        EpollEvent { event: libc::epoll_event { events: events.bits() as u32, u64: data } }
+    }
+
+    pub fn empty() -> EpollEvent {
+        EpollEvent::new(EpollFlags::empty(), 0)
+    }
+
+    pub fn events(&self) -> EpollFlags {
+        EpollFlags::from_bits_truncate(self.event.events as c_int)
+    }
+
+    pub fn data(&self) -> u64 {
+        self.event.u64
+    }
+}
+
+impl PartialEq for EpollEvent {
+    fn eq(&self, other: &EpollEvent) -> bool {
+        self.events() == other.events() && self.data() == other.data()
+    }
+}
+
+impl Eq for EpollEvent { }
+
+impl fmt::Debug for EpollEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("EpollEvent")
+            .field("events", &self.events())
+            .field("data", &self.data())
+            .finish()
+    }
+}
+
+#[cfg(feature = "nix")]
+mod nix_impls {
+    use super::*;
+
+    macro_rules! convert_flags {
+        ($($flags:ident => $nix:ty,)*) => {$(
+            impl From<$nix> for $flags {
+                fn from(flags: $nix) -> $flags {
+                    $flags::from_bits_truncate(flags.bits())
+                }
+            }
+
+            impl From<$flags> for $nix {
+                fn from(flags: $flags) -> $nix {
+                    <$nix>::from_bits_truncate(flags.bits())
+                }
+            }
+        )*}
+    }
+
+    convert_flags! {
+        OFlag           => nix::fcntl::OFlag,
+        Mode            => nix::sys::stat::Mode,
+        SFlag           => nix::sys::stat::SFlag,
+        FallocateFlags  => nix::fcntl::FallocateFlags,
+        PollFlags       => nix::poll::PollFlags,
+        EpollFlags      => nix::sys::epoll::EpollFlags,
+        MsgFlags        => nix::sys::socket::MsgFlags,
+        SockFlag        => nix::sys::socket::SockFlag,
+    }
+
+    impl From<nix::fcntl::PosixFadviseAdvice> for PosixFadviseAdvice {
+        fn from(advice: nix::fcntl::PosixFadviseAdvice) -> PosixFadviseAdvice {
+            use nix::fcntl::PosixFadviseAdvice::*;
+            match advice {
+                POSIX_FADV_NORMAL       => PosixFadviseAdvice::POSIX_FADV_NORMAL,
+                POSIX_FADV_SEQUENTIAL   => PosixFadviseAdvice::POSIX_FADV_SEQUENTIAL,
+                POSIX_FADV_RANDOM       => PosixFadviseAdvice::POSIX_FADV_RANDOM,
+                POSIX_FADV_NOREUSE      => PosixFadviseAdvice::POSIX_FADV_NOREUSE,
+                POSIX_FADV_WILLNEED     => PosixFadviseAdvice::POSIX_FADV_WILLNEED,
+                POSIX_FADV_DONTNEED     => PosixFadviseAdvice::POSIX_FADV_DONTNEED,
+            }
+        }
+    }
+
+    impl From<nix::sys::mman::MmapAdvise> for MmapAdvise {
+        fn from(advice: nix::sys::mman::MmapAdvise) -> MmapAdvise {
+            use nix::sys::mman::MmapAdvise::*;
+            match advice {
+                MADV_NORMAL         => MmapAdvise::MADV_NORMAL,
+                MADV_RANDOM         => MmapAdvise::MADV_RANDOM,
+                MADV_SEQUENTIAL     => MmapAdvise::MADV_SEQUENTIAL,
+                MADV_WILLNEED       => MmapAdvise::MADV_WILLNEED,
+                MADV_DONTNEED       => MmapAdvise::MADV_DONTNEED,
+                MADV_REMOVE         => MmapAdvise::MADV_REMOVE,
+                MADV_DONTFORK       => MmapAdvise::MADV_DONTFORK,
+                MADV_DOFORK         => MmapAdvise::MADV_DOFORK,
+                MADV_HWPOISON       => MmapAdvise::MADV_HWPOISON,
+                MADV_MERGEABLE      => MmapAdvise::MADV_MERGEABLE,
+                MADV_UNMERGEABLE    => MmapAdvise::MADV_UNMERGEABLE,
+                MADV_SOFT_OFFLINE   => MmapAdvise::MADV_SOFT_OFFLINE,
+                MADV_HUGEPAGE       => MmapAdvise::MADV_HUGEPAGE,
+                MADV_NOHUGEPAGE     => MmapAdvise::MADV_NOHUGEPAGE,
+                MADV_DONTDUMP       => MmapAdvise::MADV_DONTDUMP,
+                MADV_DODUMP         => MmapAdvise::MADV_DODUMP,
+                MADV_FREE           => MmapAdvise::MADV_FREE,
+            }
+        }
+    }
+
+    impl From<nix::sys::epoll::EpollOp> for EpollOp {
+        fn from(op: nix::sys::epoll::EpollOp) -> EpollOp {
+            match op {
+                nix::sys::epoll::EpollOp::EpollCtlAdd   => EpollOp::EpollCtlAdd,
+                nix::sys::epoll::EpollOp::EpollCtlDel   => EpollOp::EpollCtlDel,
+                nix::sys::epoll::EpollOp::EpollCtlMod   => EpollOp::EpollCtlMod,
+            }
+        }
+    }
+
+    impl From<AddressFamily> for nix::sys::socket::AddressFamily {
+        fn from(family: AddressFamily) -> nix::sys::socket::AddressFamily {
+            nix::sys::socket::AddressFamily::from_i32(family as c_int)
+                .expect("every address family is known to nix")
+        }
+    }
+
+    impl From<nix::sys::socket::SockType> for SockType {
+        fn from(ty: nix::sys::socket::SockType) -> SockType {
+            match ty {
+                nix::sys::socket::SockType::Stream      => SockType::Stream,
+                nix::sys::socket::SockType::Datagram    => SockType::Datagram,
+                nix::sys::socket::SockType::SeqPacket   => SockType::SeqPacket,
+                nix::sys::socket::SockType::Raw         => SockType::Raw,
+                nix::sys::socket::SockType::Rdm         => SockType::Rdm,
+            }
+        }
+    }
+
+    impl From<nix::sys::socket::SockProtocol> for SockProtocol {
+        fn from(protocol: nix::sys::socket::SockProtocol) -> SockProtocol {
+            match protocol {
+                nix::sys::socket::SockProtocol::Tcp => SockProtocol::Tcp,
+                nix::sys::socket::SockProtocol::Udp => SockProtocol::Udp,
+            }
+        }
+    }
+
+    impl From<nix::sys::epoll::EpollEvent> for EpollEvent {
+        fn from(event: nix::sys::epoll::EpollEvent) -> EpollEvent {
+            EpollEvent::new(event.events().into(), event.data())
+        }
+    }
+
+    impl From<EpollEvent> for nix::sys::epoll::EpollEvent {
+        fn from(event: EpollEvent) -> nix::sys::epoll::EpollEvent {
+            nix::sys::epoll::EpollEvent::new(event.events().into(), event.data())
+        }
+    }
+}
@@ -0,0 +1,258 @@
+// Ports of liburing's `io_uring_prep_*` helpers, which fill in an SQE for an operation. They
+// take the same arguments as the liburing functions, so that events are prepared exactly as
+// liburing prepares them without calling into C.
+
+use crate::sys::{self, io_uring_sqe};
+use crate::sys::IoRingOp::*;
+
+#[inline]
+pub(crate) fn rw(
+    op: libc::c_int,
+    sqe: &mut io_uring_sqe,
+    fd: libc::c_int,
+    addr: *const libc::c_void,
+    len: libc::c_uint,
+    offset: libc::__u64,
+) {
+    sqe.opcode = op as _;
+    sqe.flags = 0;
+    sqe.ioprio = 0;
+    sqe.fd = fd;
+    sqe.off_addr2.off = offset;
+    sqe.addr = addr as usize as u64;
+    sqe.len = len;
+    sqe.cmd_flags.rw_flags = 0;
+    sqe.user_data = 0;
+    sqe.buf_index.__pad2 = [0; 3];
+}
+
+#[inline]
+pub(crate) fn readv(sqe: &mut io_uring_sqe, fd: libc::c_int, iovecs: *const libc::iovec, nr_vecs: libc::c_uint, offset: libc::off_t) {
+    rw(IORING_OP_READV as _, sqe, fd, iovecs as _, nr_vecs, offset as _);
+}
+
+#[inline]
+pub(crate) fn read_fixed(
+    sqe: &mut io_uring_sqe,
+    fd: libc::c_int,
+    buf: *mut libc::c_void,
+    nbytes: libc::c_uint,
+    offset: libc::off_t,
+    buf_index: libc::c_int,
+) {
+    rw(IORING_OP_READ_FIXED as _, sqe, fd, buf, nbytes, offset as _);
+    sqe.buf_index.buf_index.index_or_group = buf_index as _;
+}
+
+#[inline]
+pub(crate) fn writev(sqe: &mut io_uring_sqe, fd: libc::c_int, iovecs: *const libc::iovec, nr_vecs: libc::c_uint, offset: libc::off_t) {
+    rw(IORING_OP_WRITEV as _, sqe, fd, iovecs as _, nr_vecs, offset as _);
+}
+
+#[inline]
+pub(crate) fn write_fixed(
+    sqe: &mut io_uring_sqe,
+    fd: libc::c_int,
+    buf: *const libc::c_void,
+    nbytes: libc::c_uint,
+    offset: libc::off_t,
+    buf_index: libc::c_int,
+) {
+    rw(IORING_OP_WRITE_FIXED as _, sqe, fd, buf, nbytes, offset as _);
+    sqe.buf_index.buf_index.index_or_group = buf_index as _;
+}
+
+#[inline]
+pub(crate) fn recvmsg(sqe: &mut io_uring_sqe, fd: libc::c_int, msg: *mut libc::msghdr, flags: libc::c_uint) {
+    rw(IORING_OP_RECVMSG as _, sqe, fd, msg as _, 1, 0);
+    sqe.cmd_flags.msg_flags = flags;
+}
+
+#[inline]
+pub(crate) fn sendmsg(sqe: &mut io_uring_sqe, fd: libc::c_int, msg: *const libc::msghdr, flags: libc::c_uint) {
+    rw(IORING_OP_SENDMSG as _, sqe, fd, msg as _, 1, 0);
+    sqe.cmd_flags.msg_flags = flags;
+}
+
+#[inline]
+pub(crate) fn poll_add(sqe: &mut io_uring_sqe, fd: libc::c_int, poll_mask: libc::c_short) {
+    rw(IORING_OP_POLL_ADD as _, sqe, fd, std::ptr::null(), 0, 0);
+    sqe.cmd_flags.rw_flags = poll32_events(poll_mask) as _;
+}
+
+#[inline]
+pub(crate) fn poll_update(
+    sqe: &mut io_uring_sqe,
+    old_user_data: libc::__u64,
+    new_user_data: libc::__u64,
+    poll_mask: libc::c_short,
+    flags: libc::c_uint,
+) {
+    rw(IORING_OP_POLL_REMOVE as _, sqe, -1, std::ptr::null(), flags, new_user_data);
+    sqe.addr = old_user_data;
+    sqe.cmd_flags.rw_flags = poll32_events(poll_mask) as _;
+}
+
+// poll32_events, which the kernel reads in its native byte order
+#[inline]
+fn poll32_events(poll_mask: libc::c_short) -> u32 {
+    let poll_mask = poll_mask as u16 as u32;
+    #[cfg(target_endian = "big")]
+    let poll_mask = poll_mask.rotate_left(16);
+    poll_mask
+}
+
+#[inline]
+pub(crate) fn poll_remove(sqe: &mut io_uring_sqe, user_data: *mut libc::c_void) {
+    rw(IORING_OP_POLL_REMOVE as _, sqe, -1, user_data, 0, 0);
+}
+
+#[inline]
+pub(crate) fn fsync(sqe: &mut io_uring_sqe, fd: libc::c_int, fsync_flags: libc::c_uint) {
+    rw(IORING_OP_FSYNC as _, sqe, fd, std::ptr::null(), 0, 0);
+    sqe.cmd_flags.fsync_flags = fsync_flags;
+}
+
+#[inline]
+pub(crate) fn nop(sqe: &mut io_uring_sqe) {
+    rw(IORING_OP_NOP as _, sqe, -1, std::ptr::null(), 0, 0);
+}
+
+#[inline]
+pub(crate) fn timeout(sqe: &mut io_uring_sqe, ts: *mut sys::__kernel_timespec, count: libc::c_uint, flags: libc::c_uint) {
+    rw(IORING_OP_TIMEOUT as _, sqe, -1, ts as _, 1, count as _);
+    sqe.cmd_flags.timeout_flags = flags;
+}
+
+#[inline]
+pub(crate) fn timeout_remove(sqe: &mut io_uring_sqe, user_data: libc::__u64, flags: libc::c_uint) {
+    rw(IORING_OP_TIMEOUT_REMOVE as _, sqe, -1, user_data as usize as _, 0, 0);
+    sqe.cmd_flags.timeout_flags = flags;
+}
+
+#[inline]
+pub(crate) fn accept(
+    sqe: &mut io_uring_sqe,
+    fd: libc::c_int,
+    addr: *mut libc::sockaddr,
+    addrlen: *mut libc::socklen_t,
+    flags: libc::c_int,
+) {
+    rw(IORING_OP_ACCEPT as _, sqe, fd, addr as _, 0, addrlen as usize as _);
+    sqe.cmd_flags.accept_flags = flags as _;
+}
+
+#[inline]
+pub(crate) fn cancel(sqe: &mut io_uring_sqe, user_data: *mut libc::c_void, flags: libc::c_int) {
+    rw(IORING_OP_ASYNC_CANCEL as _, sqe, -1, user_data, 0, 0);
+    sqe.cmd_flags.cancel_flags = flags as _;
+}
+
+#[inline]
+pub(crate) fn link_timeout(sqe: &mut io_uring_sqe, ts: *mut sys::__kernel_timespec, flags: libc::c_uint) {
+    rw(IORING_OP_LINK_TIMEOUT as _, sqe, -1, ts as _, 1, 0);
+    sqe.cmd_flags.timeout_flags = flags;
+}
+
+#[inline]
+pub(crate) fn connect(sqe: &mut io_uring_sqe, fd: libc::c_int, addr: *mut libc::sockaddr, addrlen: libc::socklen_t) {
+    rw(IORING_OP_CONNECT as _, sqe, fd, addr as _, 0, addrlen as _);
+}
+
+#[inline]
+pub(crate) fn files_update(sqe: &mut io_uring_sqe, fds: *mut libc::c_int, nr_fds: libc::c_uint, offset: libc::c_int) {
+    rw(IORING_OP_FILES_UPDATE as _, sqe, -1, fds as _, nr_fds, offset as _);
+}
+
+#[inline]
+pub(crate) fn fallocate(sqe: &mut io_uring_sqe, fd: libc::c_int, mode: libc::c_int, offset: libc::off_t, len: libc::off_t) {
+    rw(IORING_OP_FALLOCATE as _, sqe, fd, len as usize as _, mode as _, offset as _);
+}
+
+#[inline]
+pub(crate) fn openat(sqe: &mut io_uring_sqe, dfd: libc::c_int, path: *const libc::c_char, flags: libc::c_int, mode: libc::mode_t) {
+    rw(IORING_OP_OPENAT as _, sqe, dfd, path as _, mode, 0);
+    sqe.cmd_flags.open_flags = flags as _;
+}
+
+#[inline]
+pub(crate) fn close(sqe: &mut io_uring_sqe, fd: libc::c_int) {
+    rw(IORING_OP_CLOSE as _, sqe, fd, std::ptr::null(), 0, 0);
+}
+
+#[inline]
+pub(crate) fn read(sqe: &mut io_uring_sqe, fd: libc::c_int, buf: *mut libc::c_void, nbytes: libc::c_uint, offset: libc::off_t) {
+    rw(IORING_OP_READ as _, sqe, fd, buf, nbytes, offset as _);
+}
+
+#[inline]
+pub(crate) fn write(sqe: &mut io_uring_sqe, fd: libc::c_int, buf: *const libc::c_void, nbytes: libc::c_uint, offset: libc::off_t) {
+    rw(IORING_OP_WRITE as _, sqe, fd, buf, nbytes, offset as _);
+}
+
+#[inline]
+pub(crate) fn statx(
+    sqe: &mut io_uring_sqe,
+    dfd: libc::c_int,
+    path: *const libc::c_char,
+    flags: libc::c_int,
+    mask: libc::c_uint,
+    statxbuf: *mut libc::statx,
+) {
+    rw(IORING_OP_STATX as _, sqe, dfd, path as _, mask, statxbuf as usize as _);
+    sqe.cmd_flags.statx_flags = flags as _;
+}
+
+#[inline]
+pub(crate) fn fadvise(sqe: &mut io_uring_sqe, fd: libc::c_int, offset: libc::off_t, len: libc::off_t, advice: libc::c_int) {
+    rw(IORING_OP_FADVISE as _, sqe, fd, std::ptr::null(), len as _, offset as _);
+    sqe.cmd_flags.fadvise_advice = advice as _;
+}
+
+#[inline]
+pub(crate) fn madvise(sqe: &mut io_uring_sqe, addr: *mut libc::c_void, length: libc::off_t, advice: libc::c_int) {
+    rw(IORING_OP_MADVISE as _, sqe, -1, addr, length as _, 0);
+    sqe.cmd_flags.fadvise_advice = advice as _;
+}
+
+#[inline]
+pub(crate) fn send(sqe: &mut io_uring_sqe, sockfd: libc::c_int, buf: *const libc::c_void, len: libc::size_t, flags: libc::c_int) {
+    rw(IORING_OP_SEND as _, sqe, sockfd, buf, len as _, 0);
+    sqe.cmd_flags.msg_flags = flags as _;
+}
+
+#[inline]
+pub(crate) fn recv(sqe: &mut io_uring_sqe, sockfd: libc::c_int, buf: *mut libc::c_void, len: libc::size_t, flags: libc::c_int) {
+    rw(IORING_OP_RECV as _, sqe, sockfd, buf, len as _, 0);
+    sqe.cmd_flags.msg_flags = flags as _;
+}
+
+#[inline]
+pub(crate) fn openat2(sqe: &mut io_uring_sqe, dfd: libc::c_int, path: *const libc::c_char, how: *mut libc::c_void) {
+    // the size of struct open_how
+    rw(IORING_OP_OPENAT2 as _, sqe, dfd, path as _, 24, how as usize as _);
+}
+
+#[inline]
+pub(crate) fn epoll_ctl(sqe: &mut io_uring_sqe, epfd: libc::c_int, fd: libc::c_int, op: libc::c_int, ev: *mut libc::epoll_event) {
+    rw(IORING_OP_EPOLL_CTL as _, sqe, epfd, ev as _, op as _, fd as _);
+}
+
+#[inline]
+pub(crate) fn provide_buffers(
+    sqe: &mut io_uring_sqe,
+    addr: *mut libc::c_void,
+    len: libc::c_int,
+    nr: libc::c_int,
+    bgid: libc::c_int,
+    bid: libc::c_int,
+) {
+    rw(IORING_OP_PROVIDE_BUFFERS as _, sqe, nr, addr, len as _, bid as _);
+    sqe.buf_index.buf_index.index_or_group = bgid as _;
+}
+
+#[inline]
+pub(crate) fn remove_buffers(sqe: &mut io_uring_sqe, nr: libc::c_int, bgid: libc::c_int) {
+    rw(IORING_OP_REMOVE_BUFFERS as _, sqe, nr, std::ptr::null(), 0, 0);
+    sqe.buf_index.buf_index.index_or_group = bgid as _;
+}
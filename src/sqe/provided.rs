@@ -0,0 +1,141 @@
+use std::fmt;
+use std::io;
+
+use super::{BufferGroup, BufferGroupId};
+use crate::{CQE, SubmissionQueue};
+
+/// A group of buffers handed to the kernel with [provide buffers](super::SQE::prep_provide_buffers)
+/// events, for kernels which predate [buffer rings](crate::registrar::BufRing).
+///
+/// The pool owns `count` buffers of `buf_len` bytes each, identified by the buffer ids
+/// `0..count`. Once the kernel has selected a buffer for an event, the buffer is consumed and is
+/// not selected again until it is provided anew. [`take`](ProvidedBufferPool::take) returns the
+/// data of a completed event and marks its buffer as consumed; [`provide`](ProvidedBufferPool::provide)
+/// hands the consumed buffers back to the kernel, with one event for each run of consecutive
+/// buffer ids.
+///
+/// A new pool has not provided any of its buffers yet, so its first `provide` provides all of
+/// them:
+///
+/// ```
+/// # use std::io;
+/// # use iou::IoUring;
+/// # use iou::sqe::{BufferGroupId, ProvidedBufferPool};
+/// # fn main() -> io::Result<()> {
+/// let mut ring = IoUring::new(8)?;
+/// let mut pool = ProvidedBufferPool::new(BufferGroupId { id: 1 }, 4096, 16);
+/// pool.set_user_data(u64::MAX - 1);
+/// let prepared = unsafe { pool.provide(&mut ring.sq(), 1)? };
+/// assert_eq!(prepared, 1);
+/// ring.submit_sqes_and_wait(1)?;
+/// assert_eq!(ring.wait_for_cqe()?.user_data(), u64::MAX - 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct ProvidedBufferPool {
+    group: BufferGroup,
+    buffers: Box<[u8]>,
+    consumed: Vec<bool>,
+    pending: usize,
+    user_data: u64,
+}
+
+impl ProvidedBufferPool {
+    /// Allocate `count` buffers of `buf_len` bytes each for the buffer group `id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the group id does not fit in 16 bits, or if `buf_len` or `count` is zero.
+    pub fn new(id: BufferGroupId, buf_len: u32, count: u16) -> ProvidedBufferPool {
+        let group = BufferGroup::new(id, buf_len, count, 0);
+        ProvidedBufferPool {
+            group,
+            buffers: vec![0; group.total_len()].into_boxed_slice(),
+            consumed: vec![true; count as usize],
+            pending: count as usize,
+            user_data: 0,
+        }
+    }
+
+    /// The buffer group the pool provides, to [remove](super::SQE::prep_remove_buffers) its
+    /// buffers from the kernel.
+    pub fn group(&self) -> &BufferGroup {
+        &self.group
+    }
+
+    /// The number of consumed buffers waiting to be provided again.
+    pub fn pending(&self) -> usize {
+        self.pending
+    }
+
+    /// Set the user data of the provide buffers events prepared by the pool, so their `CQE`s
+    /// can be told apart from those of other events. It is 0 by default.
+    pub fn set_user_data(&mut self, user_data: u64) {
+        self.user_data = user_data;
+    }
+
+    /// Returns the bytes the kernel filled for the event of `cqe` and marks the buffer it
+    /// selected as consumed. The data stays borrowed from the pool until the buffer is
+    /// [provided](ProvidedBufferPool::provide) again.
+    ///
+    /// Returns `None` if the event did not select a buffer of this pool. A failed event which
+    /// selected a buffer still consumes it, and returns an empty slice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer of `cqe` is already consumed, such as when the same `CQE` is taken
+    /// twice.
+    pub fn take(&mut self, cqe: &CQE) -> Option<&[u8]> {
+        let buffer_id = cqe.buffer_id()?;
+        let range = self.group.range_of(buffer_id)?;
+        let consumed = &mut self.consumed[buffer_id as usize];
+        assert!(!*consumed, "buffer {} is already consumed", buffer_id);
+        *consumed = true;
+        self.pending += 1;
+        let len = cqe.result().map_or(0, |len| (len as usize).min(range.len()));
+        Some(&self.buffers[range.start..range.start + len])
+    }
+
+    /// Prepare provide buffers events for the consumed buffers if at least `min_batch` of them
+    /// are waiting, submitting the events already prepared if the queue fills up. Returns the
+    /// number of events prepared, which is one for each run of consecutive buffer ids.
+    ///
+    /// # Safety
+    ///
+    /// The pool must not be dropped while the kernel holds any of its buffers, that is until no
+    /// more events select buffers from the group and the remaining buffers have been
+    /// [removed](super::SQE::prep_remove_buffers).
+    pub unsafe fn provide(&mut self, sq: &mut SubmissionQueue<'_>, min_batch: usize) -> io::Result<u32> {
+        if self.pending == 0 || self.pending < min_batch {
+            return Ok(0);
+        }
+        let mut prepared = 0;
+        let mut start = 0;
+        while let Some(offset) = self.consumed[start..].iter().position(|&consumed| consumed) {
+            let run_start = start + offset;
+            let run_len = self.consumed[run_start..].iter().take_while(|&&consumed| consumed).count();
+            let run = BufferGroup::new(self.group.id(), self.group.buf_len(), run_len as u16, run_start as u16);
+            let range = run_start * run.buf_len() as usize..(run_start + run_len) * run.buf_len() as usize;
+
+            let (mut sqe, _) = sq.prepare_or_submit()?;
+            sqe.prep_provide_buffers(&run, &mut self.buffers[range]);
+            sqe.set_user_data(self.user_data);
+
+            self.consumed[run_start..run_start + run_len].iter_mut().for_each(|consumed| *consumed = false);
+            self.pending -= run_len;
+            prepared += 1;
+            start = run_start + run_len;
+        }
+        Ok(prepared)
+    }
+}
+
+impl fmt::Debug for ProvidedBufferPool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ProvidedBufferPool")
+            .field("group", &self.group)
+            .field("pending", &self.pending)
+            .field("user_data", &self.user_data)
+            .finish()
+    }
+}
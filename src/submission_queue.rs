@@ -2,11 +2,28 @@ use std::fmt;
 use std::io;
 use std::ptr::NonNull;
 use std::marker::PhantomData;
-use std::slice;
 use std::time::Duration;
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::sync::atomic::{self, Ordering};
 
-use super::{IoUring, SQE, SQEs, resultify};
+use super::{EnterArg, IoUring, SetupFeatures, SetupFlags, SQE, SQEs, Timespec, raw_ring, sqe_shift, sys};
+
+bitflags::bitflags! {
+    /// Flags the kernel sets on the submission queue ring, see [`SubmissionQueue::flags`].
+    pub struct SQFlags: u32 {
+        /// The `SQPOLL` kernel thread has gone to sleep and must be woken up to submit events.
+        /// Submitting does this automatically.
+        const NEED_WAKEUP   = 1 << 0;
+        /// The completion queue has overflowed: completions are held in a kernel backlog until
+        /// there is room in the queue, which takes entering the kernel. Without
+        /// [`SetupFeatures::NODROP`](crate::SetupFeatures::NODROP), further completions are
+        /// dropped.
+        const CQ_OVERFLOW   = 1 << 1;
+        /// There is task work pending which needs the kernel to be entered to run. Only set for
+        /// rings with `DEFER_TASKRUN` or `COOP_TASKRUN`.
+        const TASKRUN       = 1 << 2;
+    }
+}
 
 /// The queue of pending IO events.
 ///
@@ -45,14 +62,25 @@ use super::{IoUring, SQE, SQEs, resultify};
 /// # }
 /// ```
 pub struct SubmissionQueue<'ring> {
-    ring: NonNull<uring_sys::io_uring>,
+    ring: NonNull<IoUring>,
+    // keeps the ring alive when the queue was split off with `IoUring::into_parts`
+    _owner: Option<Arc<IoUring>>,
     _marker: PhantomData<&'ring mut IoUring>,
 }
 
 impl<'ring> SubmissionQueue<'ring> {
     pub(crate) fn new(ring: &'ring IoUring) -> SubmissionQueue<'ring> {
         SubmissionQueue {
-            ring: NonNull::from(&ring.ring),
+            ring: NonNull::from(ring),
+            _owner: None,
+            _marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn owned(ring: Arc<IoUring>) -> SubmissionQueue<'static> {
+        SubmissionQueue {
+            ring: NonNull::from(&*ring),
+            _owner: Some(ring),
             _marker: PhantomData,
         }
     }
@@ -76,45 +104,97 @@ impl<'ring> SubmissionQueue<'ring> {
     /// # Ok(())
     /// # }
     ///
+    #[inline]
     pub fn prepare_sqe<'a>(&'a mut self) -> Option<SQE<'a>> {
         unsafe {
-            prepare_sqe(self.ring.as_mut())
+            prepare_sqe(&mut *raw_ring(self.ring))
         }
     }
 
+    #[inline]
     pub fn prepare_sqes<'a>(&'a mut self, count: u32) -> Option<SQEs<'a>> {
         unsafe {
-            let sq: &mut uring_sys::io_uring_sq = &mut (*self.ring.as_ptr()).sq;
-            prepare_sqes(sq, count)
+            prepare_sqes(&mut *raw_ring(self.ring), count)
+        }
+    }
+
+    /// Returns the next `N` [`SQE`]s as an array, or `None` if there is not enough room for all
+    /// of them.
+    ///
+    /// This is convenient for chains of a known length, which can be destructured directly:
+    ///
+    /// ```
+    /// # use std::io;
+    /// # use iou::{IoUring, sqe::SubmissionFlags};
+    /// # fn main() -> io::Result<()> {
+    /// # let mut ring = IoUring::new(4)?;
+    /// # let (mut sq, _, _) = ring.queues();
+    /// let [mut first, mut second] = sq.prepare_sqes_array().unwrap();
+    /// unsafe {
+    ///     first.prep_nop();
+    ///     first.add_flags(SubmissionFlags::IO_LINK);
+    ///     second.prep_nop();
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn prepare_sqes_array<const N: usize>(&mut self) -> Option<[SQE<'_>; N]> {
+        unsafe {
+            prepare_sqes_array(&mut *raw_ring(self.ring))
         }
     }
 
+    /// Returns the next [`SQE`], submitting the events already prepared first if the queue is
+    /// full. Also returns the number of events that were submitted to make room.
+    ///
+    /// # Errors
+    /// Returns an error if the queue is full and submitting fails, or returns `EBUSY` if
+    /// submitting did not make room in the queue, like when an `SQPOLL` thread has not consumed
+    /// the submitted events yet.
+    pub fn prepare_or_submit(&mut self) -> io::Result<(SQE<'_>, u32)> {
+        let flushed = match self.space_left() {
+            0 => self.submit()?,
+            _ => 0,
+        };
+        let sqe = self.prepare_sqe().ok_or_else(|| io::Error::from_raw_os_error(libc::EBUSY))?;
+        Ok((sqe, flushed))
+    }
+
     /// Submit all events in the queue. Returns the number of submitted events.
     ///
     /// If this function encounters any IO errors an [`io::Error`](std::io::Result) variant is returned.
     pub fn submit(&mut self) -> io::Result<u32> {
-        resultify(unsafe { uring_sys::io_uring_submit(self.ring.as_ptr()) })
+        unsafe { submit(self.ring, 0) }
     }
 
     pub fn submit_and_wait(&mut self, wait_for: u32) -> io::Result<u32> {
-        resultify(unsafe { uring_sys::io_uring_submit_and_wait(self.ring.as_ptr(), wait_for as _) })
+        unsafe { submit(self.ring, wait_for) }
     }
 
+    /// Submit all events in the queue and wait until at least `wait_for` events have completed
+    /// or `duration` has passed. Returns the number of submitted events.
+    ///
+    /// On kernels with [`SetupFeatures::EXT_ARG`](crate::SetupFeatures::EXT_ARG), the timeout is
+    /// passed to the kernel directly, and an `ETIME` error is returned if the timeout passes
+    /// without any events having been submitted. On older kernels, a timeout event is submitted
+    /// along with the queue, which uses up one of its slots.
     pub fn submit_and_wait_with_timeout(&mut self, wait_for: u32, duration: Duration)
         -> io::Result<u32>
     {
-        let ts = uring_sys::__kernel_timespec {
-            tv_sec: duration.as_secs() as _,
-            tv_nsec: duration.subsec_nanos() as _
-        };
+        let ts = Timespec::from(duration);
+
+        if unsafe { (*self.ring.as_ptr()).has_feature(SetupFeatures::EXT_ARG) } {
+            let arg = sys::io_uring_getevents_arg { ts: &ts as *const _ as u64, ..Default::default() };
+            return unsafe { submit_with_arg(self.ring, wait_for, EnterArg::Ext(&arg)) };
+        }
 
         loop {
             if let Some(mut sqe) = self.prepare_sqe() {
                 sqe.clear();
                 unsafe {
                     sqe.prep_timeout(&ts, 0, crate::sqe::TimeoutFlags::empty());
-                    sqe.set_user_data(uring_sys::LIBURING_UDATA_TIMEOUT);
-                    return resultify(uring_sys::io_uring_submit_and_wait(self.ring.as_ptr(), wait_for as _))
+                    sqe.set_user_data(sys::LIBURING_UDATA_TIMEOUT);
+                    return submit(self.ring, wait_for)
                 }
             }
 
@@ -122,18 +202,55 @@ impl<'ring> SubmissionQueue<'ring> {
         }
     }
 
+    /// Submit all events in the queue and wait until at least `wait_for` events have completed,
+    /// with the thread's signal mask replaced by `sigmask` while it is blocked. Returns the number
+    /// of submitted events.
+    ///
+    /// If a signal is delivered while waiting, an `EINTR` error is returned.
+    pub fn submit_and_wait_with_sigmask(&mut self, wait_for: u32, sigmask: &libc::sigset_t)
+        -> io::Result<u32>
+    {
+        unsafe { submit_with_arg(self.ring, wait_for, EnterArg::Sigmask(sigmask)) }
+    }
+
     pub fn ready(&self) -> u32 {
-        unsafe { uring_sys::io_uring_sq_ready(self.ring.as_ptr()) as u32 }
+        unsafe { ready(raw_ring(self.ring)) }
+    }
+
+    /// The number of SQEs the kernel has dropped because they were invalid, rather than consuming
+    /// them. Dropped SQEs produce no CQE, so this is the only sign that they were lost.
+    pub fn dropped(&self) -> u32 {
+        unsafe { (*((*raw_ring(self.ring)).sq.kdropped as *const atomic::AtomicU32)).load(Ordering::Acquire) }
+    }
+
+    /// The flags the kernel has set on the submission queue ring. Flags it doesn't know about are
+    /// ignored.
+    ///
+    /// Checking for [`SQFlags::CQ_OVERFLOW`] lets an application notice that it is not reaping
+    /// completions fast enough.
+    pub fn flags(&self) -> SQFlags {
+        let flags = unsafe {
+            (*((*raw_ring(self.ring)).sq.kflags as *const atomic::AtomicU32)).load(Ordering::Relaxed)
+        };
+        SQFlags::from_bits_truncate(flags)
     }
 
     pub fn space_left(&self) -> u32 {
-        unsafe { uring_sys::io_uring_sq_space_left(self.ring.as_ptr()) as u32 }
+        unsafe { *(*raw_ring(self.ring)).sq.kring_entries - ready(raw_ring(self.ring)) }
+    }
+}
+
+impl<'ring> SubmissionQueue<'ring> {
+    /// Turn this queue into a [`SharedSubmissionQueue`], which many threads can prepare and
+    /// submit events on.
+    pub fn into_shared(self) -> SharedSubmissionQueue<'ring> {
+        SharedSubmissionQueue { sq: Arc::new(Mutex::new(self)) }
     }
 }
 
 impl fmt::Debug for SubmissionQueue<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let fd = unsafe { self.ring.as_ref().ring_fd };
+        let fd = unsafe { (*raw_ring(self.ring)).ring_fd };
         f.debug_struct(std::any::type_name::<Self>()).field("fd", &fd).finish()
     }
 }
@@ -141,30 +258,220 @@ impl fmt::Debug for SubmissionQueue<'_> {
 unsafe impl<'ring> Send for SubmissionQueue<'ring> { }
 unsafe impl<'ring> Sync for SubmissionQueue<'ring> { }
 
-pub(crate) unsafe fn prepare_sqe<'a>(ring: &mut uring_sys::io_uring) -> Option<SQE<'a>> {
-    let sqe = uring_sys::io_uring_get_sqe(ring);
-    if !sqe.is_null() {
-        let mut sqe = SQE::new(&mut *sqe);
-        sqe.clear();
-        Some(sqe)
-    } else {
-        None
+/// A [`SubmissionQueue`] which can be cloned and used from many threads at once.
+///
+/// Access to the queue is serialized by a mutex, so each producer prepares its events and
+/// submits them without racing the others. Together with [`IoUring::into_parts`], this lets
+/// several threads submit events to one ring while another thread reaps their completions.
+///
+/// ```
+/// # use std::io;
+/// # use iou::IoUring;
+/// # fn main() -> io::Result<()> {
+/// let (sq, mut cq, _reg) = IoUring::new(8)?.into_parts();
+/// let sq = sq.into_shared();
+///
+/// let producers: Vec<_> = (0..4).map(|n| {
+///     let sq = sq.clone();
+///     std::thread::spawn(move || -> io::Result<u32> {
+///         sq.prepare_sqe_with(|sqe| unsafe {
+///             sqe.prep_nop();
+///             sqe.set_user_data(n);
+///         })?;
+///         sq.submit()
+///     })
+/// }).collect();
+///
+/// for producer in producers {
+///     producer.join().unwrap()?;
+/// }
+/// for _ in 0..4 {
+///     cq.wait_for_cqe()?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct SharedSubmissionQueue<'ring> {
+    sq: Arc<Mutex<SubmissionQueue<'ring>>>,
+}
+
+impl<'ring> SharedSubmissionQueue<'ring> {
+    /// Lock the queue, for access to the whole [`SubmissionQueue`] API. Other threads can't
+    /// prepare or submit events until the guard is dropped.
+    pub fn lock(&self) -> MutexGuard<'_, SubmissionQueue<'ring>> {
+        // the queue is never left in an inconsistent state, so a poisoned lock can be reused
+        self.sq.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Prepare an event by passing the next [`SQE`] to `prepare`, returning its result.
+    ///
+    /// If the queue is full, the events already prepared are submitted to make room.
+    ///
+    /// # Errors
+    /// Returns an error if the queue is full and submitting fails, or returns `EBUSY` if
+    /// submitting did not make room in the queue.
+    pub fn prepare_sqe_with<R>(&self, prepare: impl FnOnce(&mut SQE<'_>) -> R) -> io::Result<R> {
+        let mut sq = self.lock();
+        let (mut sqe, _) = sq.prepare_or_submit()?;
+        Ok(prepare(&mut sqe))
+    }
+
+    /// Submit all events in the queue, including those prepared by other threads. Returns the
+    /// number of submitted events.
+    pub fn submit(&self) -> io::Result<u32> {
+        self.lock().submit()
+    }
+
+    /// Submit all events in the queue and wait until at least `wait_for` events have completed.
+    /// The queue stays locked while waiting.
+    pub fn submit_and_wait(&self, wait_for: u32) -> io::Result<u32> {
+        self.lock().submit_and_wait(wait_for)
+    }
+}
+
+impl Clone for SharedSubmissionQueue<'_> {
+    fn clone(&self) -> Self {
+        SharedSubmissionQueue { sq: self.sq.clone() }
+    }
+}
+
+impl fmt::Debug for SharedSubmissionQueue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct(std::any::type_name::<Self>()).field("sq", &self.sq).finish()
     }
 }
 
-pub(crate) unsafe fn prepare_sqes<'a>(sq: &mut uring_sys::io_uring_sq, count: u32)
+#[inline]
+pub(crate) unsafe fn prepare_sqe<'a>(ring: &mut sys::io_uring) -> Option<SQE<'a>> {
+    if !has_room(ring, 1) {
+        return None;
+    }
+
+    let shift = sqe_shift(ring.flags);
+    let sq = &mut ring.sq;
+    let index = (sq.sqe_tail & *sq.kring_mask) << shift;
+    sq.sqe_tail = sq.sqe_tail.wrapping_add(1);
+    let mut sqe = SQE::new(&mut *sq.sqes.add(index as usize), shift != 0);
+    sqe.clear();
+    Some(sqe)
+}
+
+pub(crate) unsafe fn prepare_sqes_array<'a, const N: usize>(ring: &mut sys::io_uring)
+    -> Option<[SQE<'a>; N]>
+{
+    let mut sqes = prepare_sqes(ring, N as u32)?;
+    Some(std::array::from_fn(|_| {
+        let mut sqe = sqes.next().unwrap();
+        sqe.clear();
+        sqe
+    }))
+}
+
+#[inline]
+pub(crate) unsafe fn prepare_sqes<'a>(ring: &mut sys::io_uring, count: u32)
     -> Option<SQEs<'a>>
 {
-    atomic::fence(Ordering::Acquire);
+    if !has_room(ring, count) {
+        return None;
+    }
 
-    let head: u32 = *sq.khead;
-    let next: u32 = sq.sqe_tail + count;
+    let shift = sqe_shift(ring.flags);
+    let sq = &mut ring.sq;
+    let sqes = SQEs::new(sq.sqes, *sq.kring_mask, shift, sq.sqe_tail, count);
+    sq.sqe_tail = sq.sqe_tail.wrapping_add(count);
+    Some(sqes)
+}
 
-    if next - head <= *sq.kring_entries {
-        let sqe = sq.sqes.offset((sq.sqe_tail & *sq.kring_mask) as isize);
-        sq.sqe_tail = next;
-        Some(SQEs::new(slice::from_raw_parts_mut(sqe, count as usize)))
+/// Whether `count` more SQEs fit in the queue.
+///
+/// The kernel's head is only loaded when the head cached by the previous load says they don't,
+/// so preparing a batch of SQEs doesn't keep pulling in the cache line an `SQPOLL` thread writes
+/// to. The head only moves forward, so the cached head never claims room that isn't there.
+#[inline]
+unsafe fn has_room(ring: &sys::io_uring, count: u32) -> bool {
+    let sq = &ring.sq;
+    let cached_head = crate::cached_sq_head(ring);
+    let next = sq.sqe_tail.wrapping_add(count);
+    if next.wrapping_sub(cached_head.load(Ordering::Relaxed)) <= *sq.kring_entries {
+        return true;
+    }
+
+    let head = (*(sq.khead as *const atomic::AtomicU32)).load(Ordering::Acquire);
+    cached_head.store(head, Ordering::Relaxed);
+    next.wrapping_sub(head) <= *sq.kring_entries
+}
+
+/// Make the prepared SQEs visible to the kernel, returning the number of SQEs pending in the ring.
+///
+/// The SQ array maps each slot to the SQE of the same index since the ring was set up, so the
+/// whole batch is published with a single release store of the tail.
+pub(crate) unsafe fn flush(ring: &mut sys::io_uring) -> u32 {
+    let shift = crate::sqe_shift(ring.flags);
+    let counters = crate::counters(ring);
+    let sq = &mut ring.sq;
+
+    if sq.sqe_head != sq.sqe_tail {
+        let mask = *sq.kring_mask;
+        let flushed = sq.sqe_tail.wrapping_sub(sq.sqe_head);
+        for offset in 0..flushed {
+            let index = sq.sqe_head.wrapping_add(offset) & mask;
+            crate::trace::submit(&*sq.sqes.add((index << shift) as usize));
+        }
+        sq.sqe_head = sq.sqe_tail;
+        // the kernel must see the SQEs before it sees the new tail
+        (*(sq.ktail as *const atomic::AtomicU32)).store(sq.sqe_tail, Ordering::Release);
+        counters.submitted(flushed);
+    }
+
+    (*sq.ktail).wrapping_sub((*(sq.khead as *const atomic::AtomicU32)).load(Ordering::Acquire))
+}
+
+/// The number of SQEs which have been prepared but not consumed by the kernel yet.
+#[inline]
+unsafe fn ready(ring: *const sys::io_uring) -> u32 {
+    let sq = &(*ring).sq;
+    sq.sqe_tail.wrapping_sub((*(sq.khead as *const atomic::AtomicU32)).load(Ordering::Acquire))
+}
+
+/// Whether submitting `submitted` SQEs requires a call to `io_uring_enter`. With `SQPOLL` this is
+/// only the case when the kernel thread needs to be woken up, which adds to `flags`.
+pub(crate) unsafe fn needs_enter(ring: &sys::io_uring, submitted: u32, flags: &mut u32) -> bool {
+    if ring.flags & SetupFlags::SQPOLL.bits() == 0 && submitted > 0 {
+        return true;
+    }
+
+    atomic::fence(Ordering::SeqCst);
+    if (*(ring.sq.kflags as *const atomic::AtomicU32)).load(Ordering::Relaxed) & sys::IORING_SQ_NEED_WAKEUP != 0 {
+        *flags |= sys::IORING_ENTER_SQ_WAKEUP;
+        return true;
+    }
+
+    false
+}
+
+/// Submit the prepared SQEs and wait until `wait_for` CQEs are ready, passing `arg` to the
+/// kernel.
+pub(crate) unsafe fn submit_with_arg(ring: NonNull<IoUring>, wait_for: u32, arg: EnterArg<'_>)
+    -> io::Result<u32>
+{
+    let raw = &mut *raw_ring(ring);
+    let submitted = flush(raw);
+    let mut flags = sys::IORING_ENTER_GETEVENTS;
+    needs_enter(raw, submitted, &mut flags);
+    crate::enter_with_arg(ring, submitted, wait_for, flags, arg)
+}
+
+pub(crate) unsafe fn submit(ring: NonNull<IoUring>, wait_for: u32) -> io::Result<u32> {
+    let raw = &mut *raw_ring(ring);
+    let submitted = flush(raw);
+    let mut flags = 0;
+
+    if needs_enter(raw, submitted, &mut flags) || wait_for > 0 {
+        if wait_for > 0 || raw.flags & SetupFlags::IOPOLL.bits() != 0 {
+            flags |= sys::IORING_ENTER_GETEVENTS;
+        }
+        crate::enter(ring, submitted, wait_for, flags)
     } else {
-        None
+        Ok(submitted)
     }
 }
@@ -0,0 +1,506 @@
+use std::io;
+
+/// The user data of the timeout events submitted by waits with a timeout on kernels without
+/// `EXT_ARG`, whose completions are never returned.
+pub const LIBURING_UDATA_TIMEOUT: u64 = u64::MAX;
+
+/// The opcodes of the events, stored in [`io_uring_sqe::opcode`].
+#[repr(C)]
+#[non_exhaustive]
+#[allow(nonstandard_style)]
+#[derive(Debug)]
+pub enum IoRingOp {
+    IORING_OP_NOP,
+    IORING_OP_READV,
+    IORING_OP_WRITEV,
+    IORING_OP_FSYNC,
+    IORING_OP_READ_FIXED,
+    IORING_OP_WRITE_FIXED,
+    IORING_OP_POLL_ADD,
+    IORING_OP_POLL_REMOVE,
+    IORING_OP_SYNC_FILE_RANGE,
+    IORING_OP_SENDMSG,
+    IORING_OP_RECVMSG,
+    IORING_OP_TIMEOUT,
+    IORING_OP_TIMEOUT_REMOVE,
+    IORING_OP_ACCEPT,
+    IORING_OP_ASYNC_CANCEL,
+    IORING_OP_LINK_TIMEOUT,
+    IORING_OP_CONNECT,
+    IORING_OP_FALLOCATE,
+    IORING_OP_OPENAT,
+    IORING_OP_CLOSE,
+    IORING_OP_FILES_UPDATE,
+    IORING_OP_STATX,
+    IORING_OP_READ,
+    IORING_OP_WRITE,
+    IORING_OP_FADVISE,
+    IORING_OP_MADVISE,
+    IORING_OP_SEND,
+    IORING_OP_RECV,
+    IORING_OP_OPENAT2,
+    IORING_OP_EPOLL_CTL,
+    IORING_OP_SPLICE,
+    IORING_OP_PROVIDE_BUFFERS,
+    IORING_OP_REMOVE_BUFFERS,
+    IORING_OP_TEE,
+}
+
+// io_uring_register opcodes
+pub const IORING_REGISTER_BUFFERS: libc::c_uint = 0;
+pub const IORING_UNREGISTER_BUFFERS: libc::c_uint = 1;
+pub const IORING_REGISTER_FILES: libc::c_uint = 2;
+pub const IORING_UNREGISTER_FILES: libc::c_uint = 3;
+pub const IORING_REGISTER_EVENTFD: libc::c_uint = 4;
+pub const IORING_UNREGISTER_EVENTFD: libc::c_uint = 5;
+pub const IORING_REGISTER_FILES_UPDATE: libc::c_uint = 6;
+pub const IORING_REGISTER_EVENTFD_ASYNC: libc::c_uint = 7;
+pub const IORING_REGISTER_PROBE: libc::c_uint = 8;
+pub const IORING_REGISTER_PERSONALITY: libc::c_uint = 9;
+pub const IORING_UNREGISTER_PERSONALITY: libc::c_uint = 10;
+
+/// The mapped rings of an [`IoUring`](crate::IoUring), laid out as the `struct io_uring` of
+/// liburing.
+#[derive(Debug)]
+#[repr(C)]
+pub struct io_uring {
+    pub sq: io_uring_sq,
+    pub cq: io_uring_cq,
+    pub flags: libc::c_uint,
+    pub ring_fd: libc::c_int,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct io_uring_sq {
+    pub khead: *mut libc::c_uint,
+    pub ktail: *mut libc::c_uint,
+    pub kring_mask: *mut libc::c_uint,
+    pub kring_entries: *mut libc::c_uint,
+    pub kflags: *mut libc::c_uint,
+    pub kdropped: *mut libc::c_uint,
+    pub array: *mut libc::c_uint,
+    pub sqes: *mut io_uring_sqe,
+
+    pub sqe_head: libc::c_uint,
+    pub sqe_tail: libc::c_uint,
+
+    pub ring_sz: libc::size_t,
+    pub ring_ptr: *mut libc::c_void,
+}
+
+#[derive(Debug)]
+#[repr(C)]
+pub struct io_uring_cq {
+    pub khead: *mut libc::c_uint,
+    pub ktail: *mut libc::c_uint,
+    pub kring_mask: *mut libc::c_uint,
+    pub kring_entries: *mut libc::c_uint,
+    pub kflags: *mut libc::c_uint,
+    pub koverflow: *mut libc::c_uint,
+    pub cqes: *mut io_uring_cqe,
+
+    pub ring_sz: libc::size_t,
+    pub ring_ptr: *mut libc::c_void,
+}
+
+/// A submission queue entry.
+#[repr(C)]
+pub struct io_uring_sqe {
+    pub opcode: u8,
+    pub flags: u8,
+    pub ioprio: u16,
+    pub fd: i32,
+    pub off_addr2: off_addr2,
+    pub addr: u64,
+    pub len: u32,
+    pub cmd_flags: cmd_flags,
+    pub user_data: u64,
+    pub buf_index: buf_index_padding,
+}
+
+#[repr(C)]
+pub union off_addr2 {
+    pub off: u64,
+    pub addr2: u64,
+}
+
+#[repr(C)]
+pub union cmd_flags {
+    pub rw_flags: libc::c_int,
+    pub fsync_flags: u32,
+    pub poll_events: u16,
+    pub sync_range_flags: u32,
+    pub msg_flags: u32,
+    pub timeout_flags: u32,
+    pub accept_flags: u32,
+    pub cancel_flags: u32,
+    pub open_flags: u32,
+    pub statx_flags: u32,
+    pub fadvise_advice: u32,
+    pub splice_flags: u32,
+}
+
+#[repr(C)]
+pub union buf_index_padding {
+    pub buf_index: buf_index,
+    pub __pad2: [u64; 3],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct buf_index {
+    pub index_or_group: u16,
+    pub personality: u16,
+    pub splice_fd_in: i32,
+}
+
+/// A completion queue entry.
+#[repr(C)]
+pub struct io_uring_cqe {
+    pub user_data: u64,
+    pub res: i32,
+    pub flags: u32,
+}
+
+/// The parameters of `io_uring_setup`, which the kernel fills in with the sizes, features and
+/// ring offsets of the new ring.
+#[repr(C)]
+pub struct io_uring_params {
+    pub sq_entries: u32,
+    pub cq_entries: u32,
+    pub flags: u32,
+    pub sq_thread_cpu: u32,
+    pub sq_thread_idle: u32,
+    pub features: u32,
+    pub wq_fd: u32,
+    pub resv: [u32; 3],
+    pub sq_off: io_sqring_offsets,
+    pub cq_off: io_cqring_offsets,
+}
+
+#[repr(C)]
+pub struct io_sqring_offsets {
+    pub head: u32,
+    pub tail: u32,
+    pub ring_mask: u32,
+    pub ring_entries: u32,
+    pub flags: u32,
+    pub dropped: u32,
+    pub array: u32,
+    pub resv1: u32,
+    pub resv2: u64,
+}
+
+#[repr(C)]
+pub struct io_cqring_offsets {
+    pub head: u32,
+    pub tail: u32,
+    pub ring_mask: u32,
+    pub ring_entries: u32,
+    pub overflow: u32,
+    pub cqes: u32,
+    pub resv: [u64; 2],
+}
+
+#[repr(C)]
+pub struct __kernel_timespec {
+    pub tv_sec: i64,
+    pub tv_nsec: libc::c_longlong,
+}
+
+// io_uring_sqe.opcode values beyond IoRingOp
+pub(crate) const IORING_OP_MSG_RING: libc::c_int = 40;
+pub(crate) const IORING_OP_SOCKET: libc::c_int = 45;
+pub(crate) const IORING_OP_FIXED_FD_INSTALL: libc::c_int = 54;
+pub(crate) const IORING_OP_RECV_ZC: libc::c_int = 58;
+pub(crate) const IORING_OP_EPOLL_WAIT: libc::c_int = 59;
+
+// mmap offsets of the rings
+pub(crate) const IORING_OFF_SQ_RING: libc::off_t = 0;
+pub(crate) const IORING_OFF_CQ_RING: libc::off_t = 0x8000000;
+pub(crate) const IORING_OFF_SQES: libc::off_t = 0x10000000;
+
+// io_uring_sqe.file_index value to let the kernel pick a free slot
+pub(crate) const IORING_FILE_INDEX_ALLOC: u32 = !0;
+
+// io_uring_sqe.addr commands for IORING_OP_MSG_RING
+pub(crate) const IORING_MSG_DATA: u64 = 0;
+pub(crate) const IORING_MSG_SEND_FD: u64 = 1;
+
+// io_uring_sqe.ioprio flags for send and recv events
+pub(crate) const IORING_RECVSEND_POLL_FIRST: u16 = 1 << 0;
+pub(crate) const IORING_RECV_MULTISHOT: u16 = 1 << 1;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct io_uring_recvmsg_out {
+    pub namelen: u32,
+    pub controllen: u32,
+    pub payloadlen: u32,
+    pub flags: u32,
+}
+
+// io_uring_enter flags
+pub(crate) const IORING_ENTER_GETEVENTS: libc::c_uint = 1 << 0;
+pub(crate) const IORING_ENTER_SQ_WAKEUP: libc::c_uint = 1 << 1;
+pub(crate) const IORING_ENTER_EXT_ARG: libc::c_uint = 1 << 3;
+pub(crate) const IORING_ENTER_REGISTERED_RING: libc::c_uint = 1 << 4;
+pub(crate) const IORING_ENTER_ABS_TIMER: libc::c_uint = 1 << 5;
+
+// cq_ring->flags
+pub(crate) const IORING_CQ_EVENTFD_DISABLED: libc::c_uint = 1 << 0;
+
+// sq_ring->flags
+pub(crate) const IORING_SQ_NEED_WAKEUP: libc::c_uint = 1 << 0;
+pub(crate) const IORING_SQ_CQ_OVERFLOW: libc::c_uint = 1 << 1;
+
+// io_uring_register opcodes beyond the ones above
+pub(crate) const IORING_REGISTER_RESTRICTIONS: libc::c_uint = 11;
+pub(crate) const IORING_REGISTER_ENABLE_RINGS: libc::c_uint = 12;
+pub(crate) const IORING_REGISTER_FILES2: libc::c_uint = 13;
+pub(crate) const IORING_REGISTER_FILES_UPDATE2: libc::c_uint = 14;
+pub(crate) const IORING_REGISTER_BUFFERS2: libc::c_uint = 15;
+pub(crate) const IORING_REGISTER_BUFFERS_UPDATE: libc::c_uint = 16;
+pub(crate) const IORING_REGISTER_IOWQ_MAX_WORKERS: libc::c_uint = 19;
+pub(crate) const IORING_REGISTER_RING_FDS: libc::c_uint = 20;
+pub(crate) const IORING_UNREGISTER_RING_FDS: libc::c_uint = 21;
+pub(crate) const IORING_REGISTER_PBUF_RING: libc::c_uint = 22;
+pub(crate) const IORING_UNREGISTER_PBUF_RING: libc::c_uint = 23;
+pub(crate) const IORING_REGISTER_SYNC_CANCEL: libc::c_uint = 24;
+pub(crate) const IORING_REGISTER_FILE_ALLOC_RANGE: libc::c_uint = 25;
+pub(crate) const IORING_REGISTER_CLONE_BUFFERS: libc::c_uint = 30;
+pub(crate) const IORING_REGISTER_ZCRX_IFQ: libc::c_uint = 32;
+pub(crate) const IORING_REGISTER_RESIZE_RINGS: libc::c_uint = 33;
+
+// io_uring_clone_buffers.flags
+pub(crate) const IORING_REGISTER_DST_REPLACE: u32 = 1 << 1;
+
+// io_uring_register opcode flag to pass a registered ring fd instead of the ring fd
+pub(crate) const IORING_REGISTER_USE_REGISTERED_RING: libc::c_uint = 1 << 31;
+
+// io_uring_rsrc_register.flags
+pub(crate) const IORING_RSRC_REGISTER_SPARSE: u32 = 1 << 0;
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct io_uring_rsrc_register {
+    pub nr: u32,
+    pub flags: u32,
+    pub resv2: u64,
+    pub data: u64,
+    pub tags: u64,
+}
+
+// the argument of io_uring_enter with IORING_ENTER_EXT_ARG
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct io_uring_getevents_arg {
+    pub sigmask: u64,
+    pub sigmask_sz: u32,
+    pub min_wait_usec: u32,
+    pub ts: u64,
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct io_uring_rsrc_update {
+    pub offset: u32,
+    pub resv: u32,
+    pub data: u64,
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct io_uring_rsrc_update2 {
+    pub offset: u32,
+    pub resv: u32,
+    pub data: u64,
+    pub tags: u64,
+    pub nr: u32,
+    pub resv2: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct io_uring_file_index_range {
+    pub off: u32,
+    pub len: u32,
+    pub resv: u64,
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct io_uring_sync_cancel_reg {
+    pub addr: u64,
+    pub fd: i32,
+    pub flags: u32,
+    // a __kernel_timespec, where -1 seconds and nanoseconds waits forever
+    pub timeout_sec: i64,
+    pub timeout_nsec: i64,
+    pub opcode: u8,
+    pub pad: [u8; 7],
+    pub pad2: [u64; 3],
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct io_uring_buf {
+    pub addr: u64,
+    pub len: u32,
+    pub bid: u16,
+    // the tail of the ring, in the first entry
+    pub resv: u16,
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct io_uring_buf_reg {
+    pub ring_addr: u64,
+    pub ring_entries: u32,
+    pub bgid: u16,
+    pub flags: u16,
+    pub resv: [u64; 3],
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct io_uring_clone_buffers {
+    pub src_fd: u32,
+    pub flags: u32,
+    pub src_off: u32,
+    pub dst_off: u32,
+    pub nr: u32,
+    pub pad: [u32; 3],
+}
+
+// the bits of a zero-copy receive offset above which the id of its area is encoded
+pub(crate) const IORING_ZCRX_AREA_SHIFT: u32 = 48;
+
+// io_uring_region_desc.flags
+pub(crate) const IORING_MEM_REGION_TYPE_USER: u32 = 1;
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct io_uring_zcrx_rqe {
+    pub off: u64,
+    pub len: u32,
+    pub pad: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct io_uring_zcrx_offsets {
+    pub head: u32,
+    pub tail: u32,
+    pub rqes: u32,
+    pub resv2: u32,
+    pub resv: [u64; 2],
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct io_uring_zcrx_area_reg {
+    pub addr: u64,
+    pub len: u64,
+    pub rq_area_token: u64,
+    pub flags: u32,
+    pub dmabuf_fd: u32,
+    pub resv2: [u64; 2],
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct io_uring_region_desc {
+    pub user_addr: u64,
+    pub size: u64,
+    pub flags: u32,
+    pub id: u32,
+    pub mmap_offset: u64,
+    pub resv: [u64; 4],
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct io_uring_zcrx_ifq_reg {
+    pub if_idx: u32,
+    pub if_rxq: u32,
+    pub rq_entries: u32,
+    pub flags: u32,
+    pub area_ptr: u64,
+    pub region_ptr: u64,
+    pub offsets: io_uring_zcrx_offsets,
+    pub zcrx_id: u32,
+    pub resv2: u32,
+    pub resv: [u64; 3],
+}
+
+// io_uring_restriction.opcode values
+pub(crate) const IORING_RESTRICTION_REGISTER_OP: u16 = 0;
+pub(crate) const IORING_RESTRICTION_SQE_OP: u16 = 1;
+pub(crate) const IORING_RESTRICTION_SQE_FLAGS_ALLOWED: u16 = 2;
+pub(crate) const IORING_RESTRICTION_SQE_FLAGS_REQUIRED: u16 = 3;
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct io_uring_restriction {
+    pub opcode: u16,
+    // register_op, sqe_op or sqe_flags, depending on the opcode
+    pub arg: u8,
+    pub resv: u8,
+    pub resv2: [u32; 3],
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub(crate) struct io_uring_probe {
+    pub last_op: u8,
+    pub ops_len: u8,
+    pub resv: u16,
+    pub resv2: [u32; 3],
+    pub ops: [io_uring_probe_op; 0],
+}
+
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct io_uring_probe_op {
+    pub op: u8,
+    pub resv: u8,
+    pub flags: u16,
+    pub resv2: u32,
+}
+
+pub(crate) const IO_URING_OP_SUPPORTED: u16 = 1 << 0;
+
+pub(crate) unsafe fn io_uring_setup(entries: libc::c_uint, params: *mut io_uring_params)
+    -> io::Result<libc::c_int>
+{
+    cvt(libc::syscall(libc::SYS_io_uring_setup, entries, params)).map(|fd| fd as libc::c_int)
+}
+
+pub(crate) unsafe fn io_uring_enter(
+    fd: libc::c_int,
+    to_submit: libc::c_uint,
+    min_complete: libc::c_uint,
+    flags: libc::c_uint,
+    arg: *const libc::c_void,
+    argsz: libc::size_t,
+) -> io::Result<u32> {
+    cvt(libc::syscall(libc::SYS_io_uring_enter, fd, to_submit, min_complete, flags, arg, argsz))
+}
+
+pub(crate) unsafe fn io_uring_register(
+    fd: libc::c_int,
+    opcode: libc::c_uint,
+    arg: *const libc::c_void,
+    nr_args: libc::c_uint,
+) -> io::Result<u32> {
+    cvt(libc::syscall(libc::SYS_io_uring_register, fd, opcode, arg, nr_args))
+}
+
+fn cvt(ret: libc::c_long) -> io::Result<u32> {
+    match ret >= 0 {
+        true    => Ok(ret as u32),
+        false   => Err(io::Error::last_os_error()),
+    }
+}
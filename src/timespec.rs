@@ -0,0 +1,106 @@
+use std::fmt;
+use std::io;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::sys;
+
+const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+/// A time value as the kernel takes it for timeouts, either a duration or, for absolute timeouts,
+/// a point in time of the `CLOCK_MONOTONIC` clock.
+///
+/// Durations convert into a `Timespec` with `From`, and deadlines with
+/// [`from_instant`](Timespec::from_instant):
+///
+/// ```
+/// # use std::time::{Duration, Instant};
+/// # use iou::Timespec;
+/// let relative = Timespec::from(Duration::from_millis(1500));
+/// assert_eq!((relative.secs(), relative.nanos()), (1, 500_000_000));
+///
+/// let absolute = Timespec::from_instant(Instant::now() + Duration::from_secs(1))?;
+/// assert!(absolute.to_duration() > Duration::from_secs(1));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+// laid out like `__kernel_timespec`, which is neither `Clone` nor `Copy`
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timespec {
+    tv_sec: i64,
+    tv_nsec: i64,
+}
+
+impl Timespec {
+    pub const fn new(secs: u64, nanos: u32) -> Timespec {
+        let secs = secs + nanos as u64 / NANOS_PER_SEC;
+        let nanos = nanos as u64 % NANOS_PER_SEC;
+        Timespec { tv_sec: secs as i64, tv_nsec: nanos as i64 }
+    }
+
+    /// The current time of the `CLOCK_MONOTONIC` clock.
+    pub fn now() -> io::Result<Timespec> {
+        let mut now = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+        if unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut now) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Timespec::new(now.tv_sec as u64, now.tv_nsec as u32))
+    }
+
+    /// The time of the `CLOCK_MONOTONIC` clock at `instant`, for absolute timeouts like those
+    /// prepared with [`TimeoutFlags::TIMEOUT_ABS`](crate::sqe::TimeoutFlags::TIMEOUT_ABS).
+    ///
+    /// An `instant` in the past is clamped to the start of the clock.
+    pub fn from_instant(instant: Instant) -> io::Result<Timespec> {
+        let (clock, now) = (Timespec::now()?.to_duration(), Instant::now());
+        let time = match instant.checked_duration_since(now) {
+            Some(ahead) => clock + ahead,
+            None        => clock.saturating_sub(now - instant),
+        };
+        Ok(Timespec::from(time))
+    }
+
+    /// The time of the `CLOCK_REALTIME` clock at `time`, for wall clock deadlines prepared with
+    /// [`TimeoutFlags::REALTIME`](crate::sqe::TimeoutFlags::REALTIME) and `TIMEOUT_ABS`.
+    ///
+    /// A `time` before the Unix epoch is clamped to the epoch.
+    pub fn from_system_time(time: SystemTime) -> Timespec {
+        Timespec::from(time.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default())
+    }
+
+    pub fn secs(&self) -> u64 {
+        self.tv_sec as u64
+    }
+
+    pub fn nanos(&self) -> u32 {
+        self.tv_nsec as u32
+    }
+
+    pub fn to_duration(&self) -> Duration {
+        Duration::new(self.secs(), self.nanos())
+    }
+
+    pub fn raw(&self) -> &sys::__kernel_timespec {
+        unsafe { &*(self as *const Timespec as *const sys::__kernel_timespec) }
+    }
+}
+
+impl From<Duration> for Timespec {
+    fn from(duration: Duration) -> Timespec {
+        Timespec::new(duration.as_secs(), duration.subsec_nanos())
+    }
+}
+
+impl From<sys::__kernel_timespec> for Timespec {
+    fn from(raw: sys::__kernel_timespec) -> Timespec {
+        Timespec { tv_sec: raw.tv_sec, tv_nsec: raw.tv_nsec }
+    }
+}
+
+impl fmt::Debug for Timespec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Timespec")
+            .field("secs", &self.secs())
+            .field("nanos", &self.nanos())
+            .finish()
+    }
+}
@@ -0,0 +1,48 @@
+// Instrumentation of ring activity with the `tracing` crate, when the `tracing` feature is
+// enabled. Without it, these functions do nothing.
+//
+// Events are emitted at the `TRACE` level under the `iou` target: one for every SQE as it is
+// handed to the kernel, and one for every CQE as it is reaped. Calls to `io_uring_enter` run in
+// a `DEBUG` span.
+
+use crate::sys;
+
+#[inline]
+pub(crate) fn submit(sqe: &sys::io_uring_sqe) {
+    #[cfg(feature = "tracing")]
+    tracing::trace!(
+        target: "iou",
+        opcode = crate::probe::op_name(sqe.opcode).unwrap_or("UNKNOWN"),
+        fd = sqe.fd,
+        user_data = sqe.user_data,
+        flags = sqe.flags,
+        "submit",
+    );
+    #[cfg(not(feature = "tracing"))]
+    let _ = sqe;
+}
+
+#[inline]
+pub(crate) fn complete(user_data: u64, res: i32, flags: u32) {
+    #[cfg(feature = "tracing")]
+    tracing::trace!(target: "iou", user_data, res, flags, "complete");
+    #[cfg(not(feature = "tracing"))]
+    let _ = (user_data, res, flags);
+}
+
+#[cfg(feature = "tracing")]
+pub(crate) type EnterGuard = tracing::span::EnteredSpan;
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct EnterGuard { }
+
+#[inline]
+pub(crate) fn enter(to_submit: u32, min_complete: u32, flags: u32) -> EnterGuard {
+    #[cfg(feature = "tracing")]
+    return tracing::debug_span!(target: "iou", "io_uring_enter", to_submit, min_complete, flags).entered();
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = (to_submit, min_complete, flags);
+        EnterGuard { }
+    }
+}
@@ -0,0 +1,72 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::io;
+
+use crate::sys;
+
+/// The user data of an event, which can be any value except
+/// [`LIBURING_UDATA_TIMEOUT`](sys::LIBURING_UDATA_TIMEOUT).
+///
+/// That value is reserved for the timeouts this library submits itself, and the completions of
+/// events which use it are swallowed by the library. A `UserData` can never hold it, so events
+/// prepared with [`SQE::set_typed_user_data`](crate::SQE::set_typed_user_data) are always seen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UserData(u64);
+
+impl UserData {
+    /// The value reserved for the timeouts submitted by this library.
+    pub const RESERVED: u64 = sys::LIBURING_UDATA_TIMEOUT;
+
+    /// Returns `None` if `user_data` is the [reserved](UserData::RESERVED) value.
+    pub const fn new(user_data: u64) -> Option<UserData> {
+        match user_data {
+            UserData::RESERVED  => None,
+            _                   => Some(UserData(user_data)),
+        }
+    }
+
+    /// Leak `data` into user data, to be reclaimed with [`into_box`](UserData::into_box) once
+    /// the event completes.
+    pub fn from_box<T>(data: Box<T>) -> UserData {
+        // nothing can be allocated at the last address, so this is never the reserved value
+        UserData(Box::into_raw(data) as usize as u64)
+    }
+
+    /// Reclaim the box this user data was created from.
+    ///
+    /// # Safety
+    ///
+    /// The user data must have been created by [`from_box`](UserData::from_box) with a
+    /// `Box<T>` of the same `T`, and the box must not have been reclaimed already. Multishot
+    /// events post many completions with the same user data, but only one of them may reclaim
+    /// the box, after the event has stopped.
+    pub unsafe fn into_box<T>(self) -> Box<T> {
+        Box::from_raw(self.0 as usize as *mut T)
+    }
+
+    pub fn get(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<UserData> for u64 {
+    fn from(user_data: UserData) -> u64 {
+        user_data.0
+    }
+}
+
+impl TryFrom<u64> for UserData {
+    type Error = io::Error;
+
+    fn try_from(user_data: u64) -> io::Result<UserData> {
+        UserData::new(user_data).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "user data is reserved for internal timeouts")
+        })
+    }
+}
+
+impl fmt::Display for UserData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
@@ -0,0 +1,52 @@
+use std::io::{self, Write};
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::io::AsRawFd;
+
+use iou::registrar::{DirectSlot, PLACEHOLDER_FD};
+use iou::sqe::{FileIndex, MsgFlags, SockFlag};
+
+const MESSAGE: &[u8] = b"Hello World";
+
+fn accept_direct(ring: &mut iou::IoUring, listener: &TcpListener, file_index: FileIndex) -> io::Result<u32> {
+    let mut stream = TcpStream::connect(listener.local_addr()?)?;
+    stream.write_all(MESSAGE)?;
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_accept_direct(listener.as_raw_fd(), None, SockFlag::empty(), file_index);
+    }
+    ring.submit_sqes()?;
+    ring.wait_for_cqe()?.result()
+}
+
+fn recv_message(ring: &mut iou::IoUring, slot: u32) -> io::Result<()> {
+    let mut buf = [0; MESSAGE.len()];
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_recv(DirectSlot::new(slot), &mut buf, MsgFlags::MSG_WAITALL);
+    }
+    ring.submit_sqes()?;
+    assert_eq!(ring.wait_for_cqe()?.result()? as usize, MESSAGE.len());
+    assert_eq!(buf, MESSAGE);
+    Ok(())
+}
+
+#[test]
+fn accept_direct_alloc() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(2)?;
+    let _ = ring.registrar().register_files(&[PLACEHOLDER_FD; 4])?;
+    let listener = TcpListener::bind(("127.0.0.1", 0))?;
+
+    let slot = accept_direct(&mut ring, &listener, FileIndex::Alloc)?;
+    assert!(slot < 4);
+    recv_message(&mut ring, slot)
+}
+
+#[test]
+fn accept_direct_slot() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(2)?;
+    let _ = ring.registrar().register_files(&[PLACEHOLDER_FD; 4])?;
+    let listener = TcpListener::bind(("127.0.0.1", 0))?;
+
+    assert_eq!(accept_direct(&mut ring, &listener, FileIndex::Slot(3))?, 0);
+    recv_message(&mut ring, 3)
+}
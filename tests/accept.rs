@@ -1,4 +1,3 @@
-use nix::sys::socket::InetAddr;
 use std::{
     io::{self, Read, Write},
     net::{TcpListener, TcpStream},
@@ -62,7 +61,7 @@ fn accept_with_params() -> io::Result<()> {
     assert_eq!(accept_buf, MESSAGE);
 
     let addr = unsafe { accept_params.as_socket_addr()? };
-    let connection_addr = SockAddr::Inet(InetAddr::from_std(&connection_stream.local_addr()?));
+    let connection_addr = SockAddr::from(connection_stream.local_addr()?);
     assert_eq!(addr, connection_addr);
     Ok(())
 }
@@ -0,0 +1,52 @@
+#![cfg(feature = "async-io")]
+
+use std::io;
+use std::os::unix::{io::AsRawFd, net::UnixStream};
+use std::thread;
+use std::time::Duration;
+
+use iou::{IoUring, SmolIoUring};
+use iou::sqe::PollFlags;
+
+#[test]
+fn awaits_completions() -> io::Result<()> {
+    async_io::block_on(async {
+        let mut ring = SmolIoUring::new(IoUring::new(4)?)?;
+        let (read, mut write) = UnixStream::pair()?;
+
+        unsafe {
+            let mut sqe = ring.get_mut().prepare_sqe().unwrap();
+            sqe.prep_poll_add(read.as_raw_fd(), PollFlags::POLLIN);
+            sqe.set_user_data(1);
+        }
+        ring.get_mut().submit_sqes()?;
+
+        // the poll completes only once the other thread writes
+        let writer = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            io::Write::write_all(&mut write, b"x")
+        });
+        let cqe = ring.next_cqe().await?;
+        assert_eq!(cqe.user_data(), 1);
+        assert!(cqe.result_as_poll_flags()?.contains(PollFlags::POLLIN));
+        writer.join().unwrap()?;
+
+        for user_data in 2..5 {
+            unsafe {
+                let mut sqe = ring.get_mut().prepare_sqe().unwrap();
+                sqe.prep_nop();
+                sqe.set_user_data(user_data);
+            }
+        }
+        ring.get_mut().submit_sqes()?;
+        let mut reaped = vec![];
+        while reaped.len() < 3 {
+            assert!(ring.drain(|cqe| reaped.push(cqe.user_data())).await? > 0);
+        }
+        assert_eq!(reaped, [2, 3, 4]);
+
+        let mut ring = ring.into_inner()?;
+        assert!(ring.peek_for_cqe().is_none());
+        Ok(())
+    })
+}
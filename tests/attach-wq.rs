@@ -0,0 +1,23 @@
+use std::io;
+
+use iou::{IoUring, SetupFlags};
+
+#[test]
+fn attached_rings_share_workers() -> io::Result<()> {
+    let ring = IoUring::new(4)?;
+    let mut attached = IoUring::new_attached(4, SetupFlags::empty(), &ring)?;
+    let mut attached_again = IoUring::new_attached(4, SetupFlags::empty(), &attached)?;
+
+    for ring in [&mut attached, &mut attached_again] {
+        unsafe {
+            let mut sqe = ring.prepare_sqe().unwrap();
+            sqe.prep_nop();
+            // force the nop onto an io-wq worker
+            sqe.set_flags(iou::sqe::SubmissionFlags::ASYNC);
+            sqe.set_user_data(7);
+        }
+        ring.submit_sqes_and_wait(1)?;
+        assert_eq!(ring.wait_for_cqe()?.user_data(), 7);
+    }
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use std::io;
+
+use iou::{IoUring, SetupFeatures, SetupFlags};
+
+fn nops(ring: &mut IoUring, batches: u64) -> io::Result<()> {
+    // batches of 3 in a ring of 4 wrap around the end of the SQ and CQ rings
+    for batch in 0..batches {
+        let mut sqes = ring.prepare_sqes(3).unwrap();
+        for (i, mut sqe) in (&mut sqes).enumerate() {
+            unsafe { sqe.set_user_data(batch * 3 + i as u64); }
+        }
+        ring.submit_sqes_and_wait(3)?;
+        for i in 0..3 {
+            let cqe = ring.peek_for_cqe().unwrap();
+            assert_eq!(cqe.user_data(), batch * 3 + i);
+            cqe.result()?;
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn sqe128_cqe32() -> io::Result<()> {
+    let flags = SetupFlags::SQE128 | SetupFlags::CQE32;
+    let mut ring = IoUring::new_with_flags(4, flags, SetupFeatures::empty())?;
+
+    assert!(ring.prepare_sqe().unwrap().is_big());
+    ring.submit_sqes_and_wait(1)?;
+    let cqe = ring.peek_for_cqe().unwrap();
+    assert_eq!(cqe.big_cqe(), Some([0, 0]));
+    assert_eq!(cqe.big_payload(), Some([0; 16]));
+
+    nops(&mut ring, 5)
+}
+
+#[test]
+fn sqe128_only() -> io::Result<()> {
+    let mut ring = IoUring::new_with_flags(4, SetupFlags::SQE128, SetupFeatures::empty())?;
+    nops(&mut ring, 5)?;
+    unsafe { ring.prepare_sqe().unwrap().prep_nop(); }
+    ring.submit_sqes_and_wait(1)?;
+    let cqe = ring.peek_for_cqe().unwrap();
+    assert!(cqe.big_cqe().is_none());
+    assert!(cqe.big_payload().is_none());
+    Ok(())
+}
+
+#[test]
+fn regular_entries_wrap() -> io::Result<()> {
+    let mut ring = IoUring::new(4)?;
+    assert!(!ring.prepare_sqe().unwrap().is_big());
+    ring.submit_sqes_and_wait(1)?;
+    ring.peek_for_cqe().unwrap();
+    nops(&mut ring, 5)
+}
+
+#[test]
+fn big_sqe_command_data() -> io::Result<()> {
+    let mut ring = IoUring::new_with_flags(1, SetupFlags::SQE128, SetupFeatures::empty())?;
+
+    let mut sqe = ring.prepare_sqe().unwrap();
+    unsafe { sqe.prep_nop(); }
+    let cmd = sqe.cmd_bytes_mut().unwrap();
+    assert_eq!(cmd.len(), 80);
+    assert!(cmd.iter().all(|&b| b == 0));
+    cmd[0] = 0x01;
+    cmd[79] = 0xff;
+    assert_eq!(sqe.cmd_bytes().unwrap()[79], 0xff);
+    ring.submit_sqes_and_wait(1)?;
+    ring.peek_for_cqe().unwrap().result()?;
+
+    // the next SQE uses the same slot, and its command data is zeroed again
+    let sqe = ring.prepare_sqe().unwrap();
+    assert!(sqe.cmd_bytes().unwrap().iter().all(|&b| b == 0));
+    Ok(())
+}
+
+#[test]
+fn regular_sqe_has_no_command_data() -> io::Result<()> {
+    let mut ring = IoUring::new(2)?;
+    let mut sqe = ring.prepare_sqe().unwrap();
+    assert!(sqe.cmd_bytes().is_none());
+    assert!(sqe.cmd_bytes_mut().is_none());
+    Ok(())
+}
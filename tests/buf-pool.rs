@@ -0,0 +1,46 @@
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::path::PathBuf;
+
+use iou::registrar::RegisteredBufPool;
+
+#[test]
+fn checkout_read_and_return() -> io::Result<()> {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("props");
+    path.push("text.txt");
+    let file = std::fs::File::open(&path)?;
+    let expected = std::fs::read(&path)?;
+
+    let mut ring = iou::IoUring::new(4)?;
+    let mut pool = RegisteredBufPool::new(&ring.registrar(), 2, 4096)?;
+    assert_eq!(pool.available(), 2);
+
+    let first = pool.checkout().unwrap();
+    let mut second = pool.checkout().unwrap();
+    assert_eq!(first.index(), 0);
+    assert_eq!(second.index(), 1);
+    assert!(pool.checkout().is_none());
+    pool.checkin(first);
+
+    unsafe {
+        let index = second.buf_index();
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_read_fixed(file.as_raw_fd(), &mut second[..], 0, index);
+        sqe.set_user_data(0xDEADBEEF);
+    }
+    pool.submitted(0xDEADBEEF, second);
+    assert_eq!(pool.inflight(), 1);
+    ring.submit_sqes()?;
+
+    let cqe = ring.wait_for_cqe()?;
+    let n = cqe.result()? as usize;
+    assert!(pool.complete(0).is_none());
+    let buf = pool.complete(cqe.user_data()).unwrap();
+    assert_eq!(&buf[..n], &expected[..]);
+    assert_eq!(pool.inflight(), 0);
+
+    pool.checkin(buf);
+    assert_eq!(pool.available(), 2);
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use std::io;
+use std::mem;
+use std::net::UdpSocket;
+use std::os::unix::io::AsRawFd;
+
+use iou::sqe::{BufferGroupId, MsgFlags, RecvMsgOut};
+
+#[test]
+fn buf_ring_recvmsg_multishot() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(4)?;
+    let (mut sq, mut cq, registrar) = ring.queues();
+
+    let sender = UdpSocket::bind("127.0.0.1:0")?;
+    let receiver = UdpSocket::bind("127.0.0.1:0")?;
+    sender.connect(receiver.local_addr()?)?;
+
+    let mut buf_ring = registrar.register_buf_ring(BufferGroupId { id: 3 }, 2, 256)?;
+    assert_eq!(buf_ring.entries(), 2);
+    assert_eq!(buf_ring.buf_len(), 256);
+
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_namelen = mem::size_of::<libc::sockaddr_storage>() as _;
+    unsafe {
+        let mut sqe = sq.prepare_sqe().unwrap();
+        sqe.prep_recvmsg_multishot(receiver.as_raw_fd(), &msg, buf_ring.group(), MsgFlags::empty());
+        sqe.set_user_data(0xDEADBEEF);
+    }
+    sq.submit()?;
+
+    // more messages than buffers, recycling each buffer once it has been read
+    let mut bids = vec![];
+    for message in &[&b"first"[..], &b"second"[..], &b"third"[..], &b"fourth"[..]] {
+        sender.send(message)?;
+        let cqe = cq.wait_for_cqe()?;
+        assert_eq!(cqe.user_data(), 0xDEADBEEF);
+        let len = cqe.result()? as usize;
+
+        let buf = unsafe { buf_ring.decode(&cqe) }.expect("a buffer should have been selected");
+        let bid = buf.id();
+        assert_eq!(buf.len(), len);
+        assert_eq!(RecvMsgOut::parse(&buf, &msg).unwrap().payload(), *message);
+        bids.push(bid);
+
+        buf_ring.add(bid);
+        buf_ring.advance();
+    }
+    assert_eq!(bids, [0, 1, 0, 1]);
+    Ok(())
+}
+
+#[test]
+fn buf_ring_cursor_recycles() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(4)?;
+    let (mut sq, mut cq, registrar) = ring.queues();
+
+    let sender = UdpSocket::bind("127.0.0.1:0")?;
+    let receiver = UdpSocket::bind("127.0.0.1:0")?;
+    sender.connect(receiver.local_addr()?)?;
+
+    let mut buf_ring = registrar.register_buf_ring(BufferGroupId { id: 4 }, 2, 256)?;
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_namelen = mem::size_of::<libc::sockaddr_storage>() as _;
+    unsafe {
+        let mut sqe = sq.prepare_sqe().unwrap();
+        sqe.prep_recvmsg_multishot(receiver.as_raw_fd(), &msg, buf_ring.group(), MsgFlags::empty());
+        sqe.set_user_data(0xDEADBEEF);
+    }
+    sq.submit()?;
+
+    // twice as many messages as buffers, without adding any buffer back by hand
+    let cursor = buf_ring.cursor();
+    let messages = [&b"first"[..], &b"second"[..], &b"third"[..], &b"fourth"[..]];
+    for message in &messages {
+        sender.send(message)?;
+        let cqe = cq.wait_for_cqe()?;
+        assert!(cqe.more());
+
+        let buf = unsafe { cursor.take(&cqe) }.expect("a buffer should have been selected");
+        assert_eq!(cursor.held(), 1);
+        assert_eq!(RecvMsgOut::parse(&buf, &msg).unwrap().payload(), *message);
+    }
+    assert_eq!(cursor.held(), 0);
+    Ok(())
+}
+
+#[test]
+fn buf_ring_group_in_use() -> io::Result<()> {
+    let ring = iou::IoUring::new(2)?;
+    let registrar = ring.registrar();
+
+    let first = registrar.register_buf_ring(BufferGroupId { id: 0 }, 4, 64)?;
+    let err = registrar.register_buf_ring(BufferGroupId { id: 0 }, 4, 64).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EEXIST));
+
+    drop(first);
+    let _ = registrar.register_buf_ring(BufferGroupId { id: 0 }, 4, 64)?;
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "must be a power of two")]
+fn buf_ring_entries_power_of_two() {
+    let ring = iou::IoUring::new(2).unwrap();
+    let _ = ring.registrar().register_buf_ring(BufferGroupId { id: 0 }, 3, 64);
+}
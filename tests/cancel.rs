@@ -0,0 +1,75 @@
+use std::io;
+use std::os::unix::{io::AsRawFd, net::UnixStream};
+
+use iou::IoUring;
+use iou::sqe::{CancelFlags, CancelToken, PollFlags};
+
+fn poll(ring: &mut IoUring, fd: impl iou::registrar::UringFd, user_data: u64) -> CancelToken {
+    let mut sqe = ring.prepare_sqe().unwrap();
+    unsafe {
+        sqe.prep_poll_add(fd, PollFlags::POLLIN);
+        sqe.set_user_data(user_data);
+    }
+    sqe.cancel_token()
+}
+
+fn results(ring: &mut IoUring, count: usize) -> io::Result<Vec<(u64, Option<i32>)>> {
+    let mut results = (0..count).map(|_| {
+        let cqe = ring.wait_for_cqe()?;
+        Ok((cqe.user_data(), cqe.result().err().and_then(|err| err.raw_os_error())))
+    }).collect::<io::Result<Vec<_>>>()?;
+    results.sort();
+    Ok(results)
+}
+
+#[test]
+fn cancel_by_user_data() -> io::Result<()> {
+    let mut ring = IoUring::new(4)?;
+    let (read, _write) = UnixStream::pair()?;
+    let token = poll(&mut ring, read.as_raw_fd(), 1);
+    ring.submit_sqes()?;
+
+    let mut sq = ring.sq();
+    let mut sqe = token.cancel(&mut sq)?;
+    unsafe { sqe.set_user_data(2); }
+    sq.submit()?;
+
+    assert_eq!(results(&mut ring, 2)?, vec![(1, Some(libc::ECANCELED)), (2, None)]);
+    Ok(())
+}
+
+#[test]
+fn cancel_all_by_fd() -> io::Result<()> {
+    let mut ring = IoUring::new(4)?;
+    let (read, _write) = UnixStream::pair()?;
+    let token = poll(&mut ring, read.as_raw_fd(), 1).by_fd().all();
+    poll(&mut ring, read.as_raw_fd(), 2);
+    ring.submit_sqes()?;
+    assert_eq!(token.flags(), CancelFlags::FD | CancelFlags::ALL);
+
+    token.cancel_sync(&ring.registrar())?;
+    assert_eq!(results(&mut ring, 2)?, vec![(1, Some(libc::ECANCELED)), (2, Some(libc::ECANCELED))]);
+    Ok(())
+}
+
+#[test]
+fn cancel_by_fixed_fd() -> io::Result<()> {
+    let mut ring = IoUring::new(4)?;
+    let (read, _write) = UnixStream::pair()?;
+    let fd = ring.registrar().register_files(&[read.as_raw_fd()])?.next().unwrap();
+    let token = poll(&mut ring, fd, 1).by_fd();
+    ring.submit_sqes()?;
+    assert_eq!(token.flags(), CancelFlags::FD | CancelFlags::FD_FIXED);
+
+    token.cancel_sync(&ring.registrar())?;
+    assert_eq!(results(&mut ring, 1)?, vec![(1, Some(libc::ECANCELED))]);
+    Ok(())
+}
+
+#[test]
+fn cancel_unknown_event() -> io::Result<()> {
+    let ring = IoUring::new(2)?;
+    let err = CancelToken::user_data(0xB00).cancel_sync(&ring.registrar()).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ENOENT));
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use std::io;
+
+use iou::{Capabilities, IoUring, KernelVersion, SetupFeatures};
+
+#[test]
+fn parse_kernel_versions() {
+    assert_eq!(KernelVersion::parse("6.1.0-18-amd64"), Some(KernelVersion::new(6, 1, 0)));
+    assert_eq!(KernelVersion::parse("5.15.133.1-microsoft-standard-WSL2"), Some(KernelVersion::new(5, 15, 133)));
+    assert_eq!(KernelVersion::parse("6.10-rc1"), Some(KernelVersion::new(6, 10, 0)));
+    assert_eq!(KernelVersion::parse("linux"), None);
+    assert!(KernelVersion::new(5, 19, 0) > KernelVersion::new(5, 4, 200));
+    assert_eq!(KernelVersion::new(6, 1, 7).to_string(), "6.1.7");
+}
+
+#[test]
+fn detect_capabilities() -> io::Result<()> {
+    let ring = IoUring::new(2)?;
+    let caps = Capabilities::for_ring(&ring)?;
+    assert_eq!(caps.kernel_version(), KernelVersion::current()?);
+    assert_eq!(caps.features(), ring.features());
+    assert!(caps.probe().supports_name("NOP"));
+
+    // capabilities which arrived together
+    if caps.supports_send_zc() {
+        assert!(caps.supports_msg_ring() && caps.supports_socket());
+        assert!(caps.supports_multishot_accept() && caps.supports_buf_ring());
+    }
+    Ok(())
+}
+
+#[test]
+fn capabilities_by_version() -> io::Result<()> {
+    let probe = iou::Probe::new()?;
+    let old = Capabilities::new(KernelVersion::new(5, 10, 0), probe, SetupFeatures::empty());
+    assert!(!old.supports_multishot_accept());
+    assert!(!old.supports_registered_ring_fd());
+    assert!(!old.supports_cqe_skip());
+    assert!(old.kernel_at_least(5, 10));
+    assert!(!old.kernel_at_least(5, 11));
+    Ok(())
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "completion")]
+
+use std::io;
+use std::thread;
+
+use iou::{Completions, IoUring};
+
+#[test]
+fn awaits_events_by_handle() -> io::Result<()> {
+    let mut ring = IoUring::new(8)?;
+    let completions = Completions::new();
+
+    let first = unsafe { ring.submit_with_handle(&completions, |sqe| sqe.prep_nop())? };
+    let second = unsafe { ring.submit_with_handle(&completions, |sqe| sqe.prep_nop())? };
+    assert_ne!(first.user_data(), second.user_data());
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_nop();
+        sqe.set_user_data(7);
+    }
+    ring.submit_sqes()?;
+    assert_eq!(completions.pending(), 2);
+
+    let driver = completions.clone();
+    let reaper = thread::spawn(move || -> io::Result<Vec<u64>> {
+        let mut foreign = vec![];
+        for _ in 0..3 {
+            if let Some(cqe) = driver.dispatch(ring.wait_for_cqe()?) {
+                foreign.push(cqe.user_data());
+            }
+        }
+        Ok(foreign)
+    });
+
+    // await the second handle first; the first completes in the meantime
+    assert_eq!(async_io::block_on(second)?, 0);
+    assert_eq!(async_io::block_on(first)?, 0);
+    assert_eq!(reaper.join().unwrap()?, [7]);
+    assert_eq!(completions.pending(), 0);
+    Ok(())
+}
+
+#[test]
+fn dropped_handle_is_forgotten_on_completion() -> io::Result<()> {
+    let mut ring = IoUring::new(2)?;
+    let completions = Completions::new();
+
+    drop(unsafe { ring.submit_with_handle(&completions, |sqe| sqe.prep_nop())? });
+    assert_eq!(completions.pending(), 1);
+    assert!(completions.dispatch(ring.wait_for_cqe()?).is_none());
+    assert_eq!(completions.pending(), 0);
+    Ok(())
+}
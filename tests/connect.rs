@@ -1,4 +1,4 @@
-use nix::sys::socket::{AddressFamily, SockProtocol, SockType, InetAddr, SockFlag};
+use nix::sys::socket::{AddressFamily, SockProtocol, SockType, SockFlag};
 use std::{io, net::TcpListener};
 
 #[test]
@@ -6,7 +6,7 @@ use std::{io, net::TcpListener};
 fn connect() -> io::Result<()> {
     let listener = TcpListener::bind(("0.0.0.0", 0))?;
     listener.set_nonblocking(true)?;
-    let listener_addr = iou::sqe::SockAddr::new_inet(InetAddr::from_std(&listener.local_addr()?));
+    let listener_addr = iou::sqe::SockAddr::from(listener.local_addr()?);
 
     let socket = nix::sys::socket::socket(
         AddressFamily::Inet,
@@ -0,0 +1,71 @@
+use std::io;
+
+use iou::{IoUring, SetupFeatures, SetupFlags};
+
+fn nops(ring: &mut IoUring, user_data: impl Iterator<Item = u64>) -> io::Result<()> {
+    let mut count = 0;
+    for user_data in user_data {
+        unsafe {
+            let mut sqe = ring.prepare_sqe().unwrap();
+            sqe.prep_nop();
+            sqe.set_user_data(user_data);
+        }
+        count += 1;
+    }
+    ring.submit_sqes_and_wait(count)?;
+    Ok(())
+}
+
+#[test]
+fn batch_reads_in_place_and_advances_once() -> io::Result<()> {
+    let mut ring = IoUring::new(4)?;
+
+    // each round wraps further around the ring
+    for round in 0..5 {
+        nops(&mut ring, round * 3..round * 3 + 3)?;
+        let (_, mut cq, _) = ring.queues();
+        let batch = cq.iter();
+        assert_eq!(batch.len(), 3);
+
+        let user_data: Vec<u64> = batch.iter().map(|cqe| cqe.user_data()).collect();
+        assert_eq!(user_data, [round * 3, round * 3 + 1, round * 3 + 2]);
+        // nothing is marked as seen until the batch is dropped
+        assert_eq!(batch.iter().count(), 3);
+        for cqe in &batch {
+            assert_eq!(cqe.result()?, 0);
+            assert_eq!(cqe.to_cqe().user_data(), cqe.user_data());
+        }
+        drop(batch);
+        assert_eq!(cq.ready(), 0);
+        assert!(cq.iter().is_empty());
+    }
+    Ok(())
+}
+
+#[test]
+fn batch_of_big_cqes() -> io::Result<()> {
+    let mut ring = IoUring::new_with_flags(4, SetupFlags::CQE32, SetupFeatures::empty())?;
+    nops(&mut ring, 0..2)?;
+
+    let (_, mut cq, _) = ring.queues();
+    let batch = cq.iter();
+    let cqes: Vec<_> = batch.iter().map(|cqe| (cqe.user_data(), cqe.big_cqe())).collect();
+    assert_eq!(cqes, [(0, Some([0, 0])), (1, Some([0, 0]))]);
+    Ok(())
+}
+
+#[test]
+fn for_each_visits_ready_cqes_in_order() -> io::Result<()> {
+    let mut ring = IoUring::new(4)?;
+    assert_eq!(ring.for_each_cqe(|_| panic!("no CQEs are ready")), 0);
+
+    for round in 0..3 {
+        nops(&mut ring, round * 4..round * 4 + 4)?;
+        let mut user_data = vec![];
+        assert_eq!(ring.for_each_cqe(|cqe| user_data.push(cqe.user_data())), 4);
+        assert_eq!(user_data, [round * 4, round * 4 + 1, round * 4 + 2, round * 4 + 3]);
+        assert_eq!(ring.cq().ready(), 0);
+    }
+    assert_eq!(ring.metrics().completed, 12);
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use std::io;
+
+use iou::CQE;
+use iou::cqe::CompletionFlags;
+use iou::sqe::PollFlags;
+
+#[test]
+fn typed_flag_accessors() {
+    let flags = CompletionFlags::BUFFER | CompletionFlags::MORE;
+    let cqe = CQE::from_raw_parts(1, 0, flags);
+    assert_eq!(cqe.buffer_id(), Some(0));
+    assert!(cqe.more());
+    assert!(!cqe.sock_nonempty());
+    assert!(!cqe.is_notif());
+
+    let cqe = CQE::from_raw_parts(1, 0, CompletionFlags::SOCK_NONEMPTY | CompletionFlags::NOTIF);
+    assert_eq!(cqe.buffer_id(), None);
+    assert!(!cqe.more());
+    assert!(cqe.sock_nonempty());
+    assert!(cqe.is_notif());
+}
+
+#[test]
+fn buffer_id_from_upper_bits() {
+    let raw = iou::sys::io_uring_cqe { user_data: 0, res: 0, flags: (7 << 16) | 1 };
+    assert_eq!(CQE::from_raw(raw).buffer_id(), Some(7));
+}
+
+#[test]
+fn nop_has_no_flags() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(1)?;
+    unsafe { ring.prepare_sqe().unwrap().prep_nop(); }
+    ring.submit_sqes_and_wait(1)?;
+    let cqe = ring.peek_for_cqe().unwrap();
+    assert_eq!(cqe.buffer_id(), None);
+    assert!(!cqe.more());
+    Ok(())
+}
+
+#[test]
+fn raw_cqe() -> io::Result<()> {
+    let raw = iou::sys::io_uring_cqe { user_data: 9, res: -libc::EAGAIN, flags: (3 << 16) | 3 };
+    let raw = CQE::from_raw(raw).raw();
+    assert_eq!((raw.user_data, raw.res, raw.flags), (9, -libc::EAGAIN, (3 << 16) | 3));
+
+    let mut ring = iou::IoUring::new(1)?;
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_nop();
+        sqe.set_user_data(4);
+    }
+    ring.submit_sqes_and_wait(1)?;
+    let (_, mut cq, _) = ring.queues();
+    let batch = cq.iter();
+    let cqe = batch.iter().next().unwrap();
+    assert_eq!((cqe.raw().user_data, cqe.raw().res), (4, 0));
+    assert_eq!(unsafe { (*cqe.as_ptr()).user_data }, 4);
+    Ok(())
+}
+
+#[test]
+fn typed_results() {
+    let cqe = CQE::from_raw_parts(1, 7, CompletionFlags::empty());
+    assert_eq!(cqe.result_as_fd().unwrap(), 7);
+    assert_eq!(cqe.result_as_size().unwrap(), 7);
+
+    let mask = (libc::POLLIN | libc::POLLHUP) as i32;
+    let cqe = CQE::from_raw_parts(1, mask, CompletionFlags::empty());
+    assert_eq!(cqe.result_as_poll_flags().unwrap(), PollFlags::POLLIN | PollFlags::POLLHUP);
+
+    let cqe = CQE::from_raw_parts(1, -libc::EBADF, CompletionFlags::empty());
+    assert_eq!(cqe.result_as_fd().unwrap_err().raw_os_error(), Some(libc::EBADF));
+    assert!(cqe.result_as_poll_flags().is_err());
+}
+
+#[test]
+fn debug_decodes_result_and_flags() {
+    let raw = iou::sys::io_uring_cqe { user_data: 0x2a, res: 16, flags: (7 << 16) | 1 | 2 };
+    assert_eq!(
+        format!("{:?}", CQE::from_raw(raw)),
+        "CQE { user_data: 0x2a, res: 16, flags: MORE, buffer_id: 7 }",
+    );
+
+    let cqe = CQE::from_raw_parts(1, -libc::EBADF, CompletionFlags::empty());
+    let expected = format!("CQE {{ user_data: 0x1, res: Err({}), flags: (empty) }}", io::Error::from_raw_os_error(libc::EBADF));
+    assert_eq!(format!("{:?}", cqe), expected);
+}
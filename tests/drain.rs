@@ -0,0 +1,61 @@
+use std::io;
+use std::time::Duration;
+
+use iou::Timespec;
+use iou::sqe::TimeoutFlags;
+
+fn prep_timeout(ring: &mut iou::IoUring, ts: &Timespec) {
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_timeout(ts, 0, TimeoutFlags::empty());
+        sqe.set_user_data(0);
+    }
+}
+
+fn completion_order(ring: &mut iou::IoUring, count: u32) -> io::Result<Vec<u64>> {
+    ring.submit_sqes()?;
+    (0..count).map(|_| ring.wait_for_cqe().map(|cqe| cqe.user_data())).collect()
+}
+
+#[test]
+fn drained_waits_for_prior_events() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(8)?;
+    let ts = Timespec::from(Duration::from_millis(20));
+    prep_timeout(&mut ring, &ts);
+
+    unsafe {
+        let mut sqes = ring.prepare_sqes(2).unwrap();
+        for (user_data, mut sqe) in (1..).zip(sqes.drained()) {
+            sqe.prep_nop();
+            sqe.set_user_data(user_data);
+        }
+    }
+
+    // only the final event waits for the timeout
+    assert_eq!(completion_order(&mut ring, 3)?, [1, 0, 2]);
+    Ok(())
+}
+
+#[test]
+fn linked_chain_terminated_with_drain() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(8)?;
+    let ts = Timespec::from(Duration::from_millis(20));
+    prep_timeout(&mut ring, &ts);
+
+    unsafe {
+        let mut sqes = ring.prepare_sqes(2).unwrap();
+        let mut chain = sqes.linked();
+        let mut sqe = chain.next().unwrap();
+        sqe.prep_nop();
+        sqe.set_user_data(1);
+        drop(sqe);
+
+        let mut sqe = chain.terminate_with_drain().unwrap();
+        sqe.prep_nop();
+        sqe.set_user_data(2);
+    }
+
+    // the whole chain is drained, not only its final event
+    assert_eq!(completion_order(&mut ring, 3)?, [0, 1, 2]);
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use std::io;
+use std::time::{Duration, Instant};
+
+use iou::{EnterExtArg, EnterFlags, IoUring, SetupFeatures, Timespec};
+
+#[test]
+fn enter_submits_and_waits() -> io::Result<()> {
+    let mut ring = IoUring::new(4)?;
+    for user_data in 0..2 {
+        unsafe {
+            let mut sqe = ring.prepare_sqe().unwrap();
+            sqe.prep_nop();
+            sqe.set_user_data(user_data);
+        }
+    }
+
+    // entering without GETEVENTS only submits
+    assert_eq!(ring.enter(2, 0, EnterFlags::empty(), None)?, 2);
+    assert_eq!(ring.enter(0, 2, EnterFlags::GETEVENTS, None)?, 0);
+    assert_eq!(ring.cq_ready(), 2);
+    assert_eq!(ring.metrics().submitted, 2);
+    Ok(())
+}
+
+#[test]
+fn enter_with_timeout() -> io::Result<()> {
+    let mut ring = IoUring::new(2)?;
+    if !ring.has_feature(SetupFeatures::EXT_ARG) {
+        return Ok(());
+    }
+
+    let timeout = Timespec::from(Duration::from_millis(20));
+    let arg = EnterExtArg { timeout: Some(&timeout), ..Default::default() };
+    let start = Instant::now();
+    let err = ring.enter(0, 1, EnterFlags::GETEVENTS, Some(&arg)).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ETIME));
+    assert!(start.elapsed() >= Duration::from_millis(20));
+
+    // EXT_ARG without an argument is rejected by the kernel
+    let err = ring.enter(0, 0, EnterFlags::GETEVENTS | EnterFlags::EXT_ARG, None).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EINVAL));
+    Ok(())
+}
+
+#[test]
+fn enter_through_registered_ring_fd() -> io::Result<()> {
+    let mut ring = IoUring::new(2)?;
+    if ring.registrar().register_ring_fd().is_err() {
+        return Ok(());
+    }
+
+    unsafe { ring.prepare_sqe().unwrap().prep_nop(); }
+    assert_eq!(ring.enter(1, 1, EnterFlags::GETEVENTS | EnterFlags::REGISTERED_RING, None)?, 1);
+    assert!(ring.peek_for_cqe().is_some());
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use std::io::{self, Write};
+use std::os::unix::{io::AsRawFd, net};
+
+use iou::sqe::{EpollEvent, EpollFlags};
+use nix::sys::epoll::{self, EpollCreateFlags, EpollOp};
+
+#[test]
+fn epoll_wait() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(2)?;
+    let (read, mut write) = net::UnixStream::pair()?;
+
+    let epoll_fd = epoll::epoll_create1(EpollCreateFlags::EPOLL_CLOEXEC).unwrap();
+    let mut event = epoll::EpollEvent::new(epoll::EpollFlags::EPOLLIN, 0xB00);
+    epoll::epoll_ctl(epoll_fd, EpollOp::EpollCtlAdd, read.as_raw_fd(), &mut event).unwrap();
+
+    let mut events = [EpollEvent::empty(); 4];
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_epoll_wait(epoll_fd, &mut events);
+        sqe.set_user_data(0xDEADBEEF);
+    }
+    ring.submit_sqes()?;
+
+    write.write_all(b"ping")?;
+
+    let cqe = ring.wait_for_cqe()?;
+    assert_eq!(cqe.user_data(), 0xDEADBEEF);
+    assert_eq!(cqe.result()?, 1);
+    assert_eq!(events[0].data(), 0xB00);
+    assert!(events[0].events().contains(EpollFlags::EPOLLIN));
+
+    nix::unistd::close(epoll_fd).unwrap();
+    Ok(())
+}
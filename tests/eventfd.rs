@@ -0,0 +1,43 @@
+use std::io;
+
+use iou::registrar::EventFd;
+
+#[test]
+fn owned_eventfd() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(2)?;
+    let eventfd = EventFd::new()?;
+    ring.registrar().register_owned_eventfd(&eventfd)?;
+    assert_eq!(eventfd.read_events()?, 0);
+
+    unsafe { ring.prepare_sqe().unwrap().prep_nop(); }
+    ring.submit_sqes_and_wait(1)?;
+    ring.peek_for_cqe().unwrap().result()?;
+    assert!(eventfd.read_events()? >= 1);
+    assert_eq!(eventfd.read_events()?, 0);
+
+    ring.registrar().unregister_eventfd()?;
+    Ok(())
+}
+
+#[test]
+fn toggle_eventfd_notifications() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(2)?;
+    let eventfd = EventFd::new()?;
+    ring.registrar().register_owned_eventfd(&eventfd)?;
+    assert!(ring.cq().eventfd_enabled());
+
+    ring.cq().eventfd_toggle(false)?;
+    assert!(!ring.cq().eventfd_enabled());
+    unsafe { ring.prepare_sqe().unwrap().prep_nop(); }
+    ring.submit_sqes_and_wait(1)?;
+    ring.peek_for_cqe().unwrap().result()?;
+    assert_eq!(eventfd.read_events()?, 0);
+
+    ring.cq().eventfd_toggle(true)?;
+    ring.cq().eventfd_toggle(true)?;
+    unsafe { ring.prepare_sqe().unwrap().prep_nop(); }
+    ring.submit_sqes_and_wait(1)?;
+    ring.peek_for_cqe().unwrap().result()?;
+    assert!(eventfd.read_events()? >= 1);
+    Ok(())
+}
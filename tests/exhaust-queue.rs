@@ -40,3 +40,28 @@ fn exhaust_queue_with_prepare_sqes() {
         }
     }
 }
+
+#[test]
+fn prepare_sqe_stops_at_a_full_queue() {
+    let mut io_uring = iou::IoUring::new(4).unwrap();
+
+    // the queue wraps around several times
+    for round in 0..3 {
+        let mut prepared = 0;
+        while let Some(mut sqe) = io_uring.prepare_sqe() {
+            unsafe {
+                sqe.prep_nop();
+                sqe.set_user_data(round * 4 + prepared);
+            }
+            prepared += 1;
+        }
+        assert_eq!(prepared, 4);
+        assert_eq!(io_uring.sq_space_left(), 0);
+
+        io_uring.submit_sqes_and_wait(4).unwrap();
+        assert_eq!(io_uring.sq_space_left(), 4);
+        for counter in round * 4..round * 4 + 4 {
+            assert_eq!(io_uring.peek_for_cqe().unwrap().user_data(), counter);
+        }
+    }
+}
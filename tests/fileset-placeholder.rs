@@ -45,3 +45,38 @@ fn main() -> std::io::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn sparse() -> std::io::Result<()> {
+    let mut ring = IoUring::new(2)?;
+    let mut registrar = ring.registrar();
+
+    let fileset: Vec<RegisteredFd> = registrar.register_files_sparse(1024)?.collect();
+    assert_eq!(fileset.len(), 1024);
+    assert!(fileset.iter().all(|fd| fd.is_placeholder()));
+
+    let mut path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("props");
+    path.push("tmp-fileset-sparse.txt");
+
+    let file = std::fs::File::create(&path)?;
+    let reg_file = registrar.update_registered_files(713, &[file.as_raw_fd()])?.collect::<Vec<_>>()[0];
+
+    let bufs = &[IoSlice::new(&TEXT)];
+    unsafe { ring.prepare_sqe().unwrap().prep_write_vectored(fileset[712], bufs, 0); }
+    ring.submit_sqes()?;
+    let err = ring.wait_for_cqe()?.result().unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EBADF));
+
+    unsafe { ring.prepare_sqe().unwrap().prep_write_vectored(reg_file, bufs, 0); }
+    ring.submit_sqes()?;
+    let n = ring.wait_for_cqe()?.result()? as usize;
+    assert_eq!(n, TEXT.len());
+
+    let mut buf = vec![];
+    File::open(&path)?.read_to_end(&mut buf)?;
+    assert_eq!(&buf[..], TEXT);
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
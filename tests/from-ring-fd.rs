@@ -0,0 +1,76 @@
+use std::io;
+
+use iou::{IoUring, SetupFeatures, SetupFlags};
+
+#[test]
+fn rebuild_ring_from_fd() -> io::Result<()> {
+    let mut ring = IoUring::new(4)?;
+
+    // a second mapping of the same ring sees the same queues
+    let fd = unsafe { libc::dup(ring.raw_fd()?) };
+    let mut other = unsafe { IoUring::from_ring_fd(fd, ring.params())? };
+    assert_eq!(other.raw_fd()?, fd);
+
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_nop();
+        sqe.set_user_data(0xFEED);
+    }
+    ring.submit_sqes_and_wait(1)?;
+    assert_eq!(other.wait_for_cqe()?.user_data(), 0xFEED);
+    assert!(ring.peek_for_cqe().is_none());
+    Ok(())
+}
+
+#[test]
+fn rebuild_big_ring_from_fd() -> io::Result<()> {
+    let ring = match IoUring::new_with_flags(4, SetupFlags::CQE32, SetupFeatures::empty()) {
+        Ok(ring) => ring,
+        // the kernel does not support big CQEs
+        Err(err) if err.raw_os_error() == Some(libc::EINVAL) => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    let fd = unsafe { libc::dup(ring.raw_fd()?) };
+    let mut other = unsafe { IoUring::from_ring_fd(fd, ring.params())? };
+    drop(ring);
+
+    for user_data in 0..8 {
+        unsafe {
+            let mut sqe = other.prepare_sqe().unwrap();
+            sqe.prep_nop();
+            sqe.set_user_data(user_data);
+        }
+        other.submit_sqes_and_wait(1)?;
+        let cqe = other.wait_for_cqe()?;
+        assert_eq!(cqe.user_data(), user_data);
+        assert_eq!(cqe.big_cqe(), Some([0, 0]));
+    }
+    Ok(())
+}
+
+#[test]
+fn rebuild_driven_ring_from_fd() -> io::Result<()> {
+    let mut ring = IoUring::new(4)?;
+    for _ in 0..3 {
+        unsafe { ring.prepare_sqe().unwrap().prep_nop(); }
+        ring.submit_sqes_and_wait(1)?;
+        ring.wait_for_cqe()?;
+    }
+
+    // the rebuilt ring submits from where the kernel's tail is, not from the start of the queue
+    let fd = unsafe { libc::dup(ring.raw_fd()?) };
+    let mut other = unsafe { IoUring::from_ring_fd(fd, ring.params())? };
+    drop(ring);
+    for user_data in 0..4 {
+        unsafe {
+            let mut sqe = other.prepare_sqe().unwrap();
+            sqe.prep_nop();
+            sqe.set_user_data(user_data);
+        }
+    }
+    assert_eq!(other.submit_sqes_and_wait(4)?, 4);
+    for user_data in 0..4 {
+        assert_eq!(other.peek_for_cqe().unwrap().user_data(), user_data);
+    }
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use std::io;
+use std::thread;
+
+use iou::IoUring;
+use iou::sqe::BufferGroupId;
+
+#[test]
+fn submit_and_complete_on_different_threads() -> io::Result<()> {
+    let (mut sq, mut cq, _registrar) = IoUring::new(8)?.into_parts();
+
+    let reaper = thread::spawn(move || -> io::Result<Vec<u64>> {
+        let mut user_data = Vec::new();
+        while user_data.len() < 32 {
+            user_data.push(cq.wait_for_cqe()?.user_data());
+        }
+        Ok(user_data)
+    });
+
+    for n in 0..32 {
+        unsafe {
+            let mut sqe = loop {
+                match sq.prepare_sqe() {
+                    Some(sqe) => break sqe,
+                    None => { sq.submit()?; }
+                }
+            };
+            sqe.prep_nop();
+            sqe.set_user_data(n);
+        }
+        sq.submit()?;
+    }
+
+    assert_eq!(reaper.join().unwrap()?, (0..32).collect::<Vec<_>>());
+    Ok(())
+}
+
+#[test]
+fn parts_keep_the_ring_alive() -> io::Result<()> {
+    let (sq, mut cq, registrar) = IoUring::new(2)?.into_parts();
+    let buf_ring = registrar.register_buf_ring(BufferGroupId { id: 3 }, 4, 64)?;
+    drop(registrar);
+    drop(sq);
+
+    assert!(cq.peek_for_cqe().is_none());
+    drop(cq);
+    // the buffer ring is unregistered through the ring it still keeps alive
+    drop(buf_ring);
+    Ok(())
+}
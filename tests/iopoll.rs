@@ -0,0 +1,72 @@
+use std::io;
+use std::time::{Duration, Instant};
+
+use iou::{IoUring, SetupFeatures, SetupFlags};
+
+fn submit_nops(ring: &mut IoUring, count: u64) -> io::Result<()> {
+    for i in 0..count {
+        unsafe {
+            let mut sqe = ring.prepare_sqe().unwrap();
+            sqe.prep_nop();
+            sqe.set_user_data(i);
+        }
+    }
+    ring.submit_sqes()?;
+    Ok(())
+}
+
+#[test]
+fn reap_with_budget() -> io::Result<()> {
+    let mut ring = IoUring::new_with_flags(8, SetupFlags::IOPOLL, SetupFeatures::empty())?;
+    submit_nops(&mut ring, 5)?;
+    assert_eq!(ring.poll_until(5, Instant::now() + Duration::from_secs(5))?, 5);
+
+    let first: Vec<_> = ring.reap(3)?.map(|cqe| cqe.user_data()).collect();
+    assert_eq!(first, [0, 1, 2]);
+    let rest: Vec<_> = ring.reap(3)?.map(|cqe| cqe.user_data()).collect();
+    assert_eq!(rest, [3, 4]);
+    assert_eq!(ring.reap(3)?.count(), 0);
+    Ok(())
+}
+
+#[test]
+fn poll_until_deadline() -> io::Result<()> {
+    let mut ring = IoUring::new_with_flags(8, SetupFlags::IOPOLL, SetupFeatures::empty())?;
+    let start = Instant::now();
+    assert_eq!(ring.poll_until(1, start + Duration::from_millis(20))?, 0);
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    Ok(())
+}
+
+#[test]
+fn hybrid_iopoll() -> io::Result<()> {
+    let flags = SetupFlags::IOPOLL | SetupFlags::HYBRID_IOPOLL;
+    let mut ring = IoUring::new_with_flags(8, flags, SetupFeatures::empty())?;
+    submit_nops(&mut ring, 1)?;
+    assert_eq!(ring.poll_until(1, Instant::now() + Duration::from_secs(5))?, 1);
+
+    // hybrid polling is only valid together with IOPOLL
+    let err = IoUring::new_with_flags(8, SetupFlags::HYBRID_IOPOLL, SetupFeatures::empty()).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EINVAL));
+    Ok(())
+}
+
+#[test]
+fn poll_adaptive_spins_then_waits() -> io::Result<()> {
+    let mut ring = IoUring::new_with_flags(8, SetupFlags::IOPOLL, SetupFeatures::empty())?;
+    submit_nops(&mut ring, 2)?;
+    assert_eq!(ring.poll_adaptive(10_000, Duration::from_secs(5))?, 2);
+    assert_eq!(ring.cqes().count(), 2);
+
+    // with nothing in flight, the fallback wait times out
+    let start = Instant::now();
+    let err = ring.poll_adaptive(100, Duration::from_millis(20)).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ETIME));
+    assert!(start.elapsed() >= Duration::from_millis(20));
+
+    // without IOPOLL, a completion is found by the fallback wait even with no spinning at all
+    let mut ring = IoUring::new(2)?;
+    submit_nops(&mut ring, 1)?;
+    assert_eq!(ring.poll_adaptive(0, Duration::from_secs(5))?, 1);
+    Ok(())
+}
@@ -0,0 +1,15 @@
+#[test]
+fn set_iowq_max_workers() -> std::io::Result<()> {
+    let ring = iou::IoUring::new(2)?;
+    let registrar = ring.registrar();
+
+    let (bounded, unbounded) = registrar.set_iowq_max_workers(0, 0)?;
+    assert!(bounded > 0);
+    assert!(unbounded > 0);
+
+    assert_eq!(registrar.set_iowq_max_workers(2, 3)?, (bounded, unbounded));
+    assert_eq!(registrar.set_iowq_max_workers(0, 0)?, (2, 3));
+    assert_eq!(registrar.set_iowq_max_workers(0, 5)?, (2, 3));
+    assert_eq!(registrar.set_iowq_max_workers(0, 0)?, (2, 5));
+    Ok(())
+}
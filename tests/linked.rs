@@ -0,0 +1,180 @@
+use std::io;
+
+#[test]
+fn linked_chain_is_cancelled_after_failure() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(8)?;
+    let mut buf = [0; 8];
+
+    unsafe {
+        let mut sqes = ring.prepare_sqes(3).unwrap();
+        let mut links = sqes.linked();
+
+        let mut sqe = links.next().unwrap();
+        // reading from an invalid fd fails, which cancels the rest of the chain
+        sqe.prep_read(-1, &mut buf[..], 0);
+        sqe.set_user_data(0);
+        assert!(sqe.flags().is_empty());
+        drop(sqe);
+
+        for (user_data, mut sqe) in (1..).zip(links) {
+            sqe.prep_nop();
+            sqe.set_user_data(user_data);
+        }
+    }
+
+    ring.submit_sqes_and_wait(3)?;
+    let mut results: Vec<_> = ring.cqes().map(|cqe| (cqe.user_data(), cqe.raw_result())).collect();
+    results.sort_unstable();
+    assert_eq!(results, [(0, -libc::EBADF), (1, -libc::ECANCELED), (2, -libc::ECANCELED)]);
+    Ok(())
+}
+
+#[test]
+fn linked_chain_skips_successful_cqes() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(8)?;
+    if !ring.has_feature(iou::SetupFeatures::CQE_SKIP) {
+        return Ok(());
+    }
+
+    unsafe {
+        let mut sqes = ring.prepare_sqes(3).unwrap();
+        for (user_data, mut sqe) in (0..).zip(sqes.linked().skip_success()) {
+            sqe.prep_nop();
+            sqe.set_user_data(user_data);
+        }
+    }
+
+    ring.submit_sqes_and_wait(1)?;
+    let cqes: Vec<_> = ring.cqes().map(|cqe| cqe.user_data()).collect();
+    assert_eq!(cqes, [2]);
+    Ok(())
+}
+
+#[test]
+fn skipped_chain_still_reports_failures() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(8)?;
+    if !ring.has_feature(iou::SetupFeatures::CQE_SKIP) {
+        return Ok(());
+    }
+    let mut buf = [0; 8];
+
+    unsafe {
+        let mut sqes = ring.prepare_sqes(3).unwrap();
+        for (user_data, mut sqe) in (0..).zip(sqes.linked().skip_success()) {
+            if user_data == 1 {
+                sqe.prep_read(-1, &mut buf[..], 0);
+            } else {
+                sqe.prep_nop();
+            }
+            sqe.set_user_data(user_data);
+        }
+    }
+
+    let cqe = ring.submit_sqes_and_wait(1).and_then(|_| ring.wait_for_cqe())?;
+    assert_eq!((cqe.user_data(), cqe.raw_result()), (1, -libc::EBADF));
+    Ok(())
+}
+
+#[test]
+fn write_and_fsync_from_array() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(4)?;
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("props/tmp-linked-array.txt");
+    let file = std::fs::File::create(&path)?;
+    let fd = std::os::unix::io::AsRawFd::as_raw_fd(&file);
+
+    assert!(ring.prepare_sqes_array::<5>().is_none());
+
+    unsafe {
+        let [mut write, mut fsync] = ring.prepare_sqes_array().unwrap();
+        write.prep_write(fd, &b"hello"[..], 0);
+        write.set_user_data(1);
+        write.add_flags(iou::sqe::SubmissionFlags::IO_LINK);
+        fsync.prep_fsync(fd, iou::sqe::FsyncFlags::empty());
+        fsync.set_user_data(2);
+        assert_eq!(fsync.flags(), iou::sqe::SubmissionFlags::empty());
+    }
+
+    ring.submit_sqes_and_wait(2)?;
+    let results: Vec<_> = ring.cqes().map(|cqe| (cqe.user_data(), cqe.raw_result())).collect();
+    assert_eq!(results, [(1, 5), (2, 0)]);
+    assert_eq!(file.metadata()?.len(), 5);
+    std::fs::remove_file(&path)
+}
+
+#[test]
+fn write_then_fsync_combinator() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(4)?;
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("props/tmp-linked-combinator.txt");
+    let file = std::fs::File::create(&path)?;
+    let fd = std::os::unix::io::AsRawFd::as_raw_fd(&file);
+
+    unsafe {
+        let mut sqes = ring.prepare_sqes(3).unwrap();
+        if let Some([mut write, mut fsync]) = sqes.write_then_fsync(fd, &b"hello"[..], 0) {
+            write.set_user_data(1);
+            fsync.set_user_data(2);
+            assert_eq!(write.flags(), iou::sqe::SubmissionFlags::IO_LINK);
+            assert!(fsync.flags().is_empty());
+        }
+
+        // the last SQE is not enough for another chain
+        assert!(sqes.read_then_close(fd, &mut [0; 1][..], 0).is_none());
+        assert_eq!(sqes.remaining(), 1);
+        sqes.single().unwrap().set_user_data(3);
+    }
+
+    ring.submit_sqes_and_wait(3)?;
+    let mut results: Vec<_> = ring.cqes().map(|cqe| (cqe.user_data(), cqe.raw_result())).collect();
+    results.sort_unstable();
+    assert_eq!(results, [(1, 5), (2, 0), (3, 0)]);
+    assert_eq!(file.metadata()?.len(), 5);
+    std::fs::remove_file(&path)
+}
+
+#[test]
+fn read_then_close_closes_after_failure() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(4)?;
+    let mut buf = [0; 8];
+
+    unsafe {
+        let mut sqes = ring.prepare_sqes(2).unwrap();
+        let [mut read, mut close] = sqes.read_then_close(-1, &mut buf[..], 0).unwrap();
+        read.set_user_data(1);
+        close.set_user_data(2);
+    }
+
+    ring.submit_sqes_and_wait(2)?;
+    let mut results: Vec<_> = ring.cqes().map(|cqe| (cqe.user_data(), cqe.raw_result())).collect();
+    results.sort_unstable();
+    // the close still runs, rather than being cancelled
+    assert_eq!(results, [(1, -libc::EBADF), (2, -libc::EBADF)]);
+    Ok(())
+}
+
+#[test]
+fn connect_with_timeout_combinator() -> io::Result<()> {
+    use std::net::{TcpListener, TcpStream};
+    use std::os::unix::io::{AsRawFd, FromRawFd};
+
+    let mut ring = iou::IoUring::new(4)?;
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = iou::sqe::SockAddr::from(listener.local_addr()?);
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM | libc::SOCK_CLOEXEC, 0) };
+    assert!(fd >= 0);
+    let stream = unsafe { TcpStream::from_raw_fd(fd) };
+    let timeout = iou::Timespec::new(5, 0);
+
+    unsafe {
+        let mut sqes = ring.prepare_sqes(2).unwrap();
+        let [mut connect, mut link_timeout] = sqes.connect_with_timeout(stream.as_raw_fd(), &addr, &timeout).unwrap();
+        connect.set_user_data(1);
+        link_timeout.set_user_data(2);
+    }
+
+    ring.submit_sqes_and_wait(2)?;
+    let mut results: Vec<_> = ring.cqes().map(|cqe| (cqe.user_data(), cqe.raw_result())).collect();
+    results.sort_unstable();
+    assert_eq!(results, [(1, 0), (2, -libc::ECANCELED)]);
+    assert_eq!(stream.peer_addr()?, listener.local_addr()?);
+    Ok(())
+}
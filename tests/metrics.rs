@@ -0,0 +1,31 @@
+use std::io;
+
+use iou::{IoUring, RingMetrics};
+
+#[test]
+fn counts_submissions_completions_and_syscalls() -> io::Result<()> {
+    let mut ring = IoUring::new(4)?;
+    assert_eq!(ring.metrics(), RingMetrics::default());
+
+    for user_data in 0..3 {
+        unsafe {
+            let mut sqe = ring.prepare_sqe().unwrap();
+            sqe.prep_nop();
+            sqe.set_user_data(user_data);
+        }
+    }
+    ring.submit_sqes_and_wait(3)?;
+    let metrics = ring.metrics();
+    assert_eq!((metrics.submitted, metrics.completed, metrics.enter_calls), (3, 0, 1));
+
+    ring.wait_for_cqe()?;
+    let (_, mut cq, registrar) = ring.queues();
+    assert_eq!(cq.iter().len(), 2);
+    registrar.probe()?;
+
+    let metrics = ring.metrics();
+    assert_eq!(metrics.completed, 3);
+    assert_eq!(metrics.register_calls, 1);
+    assert_eq!((metrics.cq_overflow, metrics.sq_dropped), (0, 0));
+    Ok(())
+}
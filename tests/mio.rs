@@ -0,0 +1,50 @@
+#![cfg(feature = "mio")]
+
+use std::io;
+use std::time::Duration;
+
+use iou::IoUring;
+use iou::registrar::EventFd;
+use mio::{Events, Interest, Poll, Token};
+
+fn nop(ring: &mut IoUring) -> io::Result<()> {
+    unsafe { ring.prepare_sqe().unwrap().prep_nop(); }
+    ring.submit_sqes()?;
+    Ok(())
+}
+
+#[test]
+fn ring_fd_is_readable_with_completions() -> io::Result<()> {
+    let mut poll = Poll::new()?;
+    let mut events = Events::with_capacity(4);
+    let mut ring = IoUring::new(2)?;
+    poll.registry().register(&mut ring, Token(7), Interest::READABLE)?;
+
+    nop(&mut ring)?;
+    poll.poll(&mut events, Some(Duration::from_secs(5)))?;
+    let event = events.iter().next().expect("no event for the ring");
+    assert_eq!(event.token(), Token(7));
+    assert!(event.is_readable());
+    assert!(ring.peek_for_cqe().is_some());
+
+    poll.registry().deregister(&mut ring)
+}
+
+#[test]
+fn registered_eventfd_is_watched() -> io::Result<()> {
+    let mut poll = Poll::new()?;
+    let mut events = Events::with_capacity(4);
+    let mut ring = IoUring::new(2)?;
+    let eventfd = EventFd::new()?;
+    ring.registrar().register_owned_eventfd(&eventfd)?;
+    assert_eq!(ring.notify_fd()?, std::os::unix::io::AsRawFd::as_raw_fd(&eventfd));
+    poll.registry().register(&mut ring, Token(1), Interest::READABLE)?;
+
+    nop(&mut ring)?;
+    poll.poll(&mut events, Some(Duration::from_secs(5)))?;
+    assert_eq!(events.iter().next().map(|event| event.token()), Some(Token(1)));
+    assert_eq!(eventfd.read_events()?, 1);
+    assert!(ring.peek_for_cqe().is_some());
+
+    poll.registry().deregister(&mut ring)
+}
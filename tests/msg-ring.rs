@@ -0,0 +1,44 @@
+use std::fs::File;
+use std::io;
+use std::os::unix::io::AsRawFd;
+
+use iou::registrar::PLACEHOLDER_FD;
+use iou::sqe::{FileIndex, MsgRingFlags};
+
+#[test]
+fn msg_ring_cqe_flags() -> io::Result<()> {
+    let mut source = iou::IoUring::new(2)?;
+    let mut target = iou::IoUring::new(2)?;
+
+    unsafe {
+        let mut sqe = source.prepare_sqe().unwrap();
+        sqe.prep_msg_ring_cqe_flags(target.raw_fd()?, 42, 0xB00, MsgRingFlags::empty(), 1 << 8);
+        sqe.set_user_data(1);
+    }
+    source.submit_sqes()?;
+    source.wait_for_cqe()?.result()?;
+
+    let cqe = target.wait_for_cqe()?;
+    assert_eq!(cqe.user_data(), 0xB00);
+    assert_eq!(cqe.result()?, 42);
+    assert_eq!(cqe.raw_flags(), 1 << 8);
+    Ok(())
+}
+
+#[test]
+fn msg_ring_fd_cqe_skip() -> io::Result<()> {
+    let mut source = iou::IoUring::new(2)?;
+    let mut target = iou::IoUring::new(2)?;
+    let file = File::open(env!("CARGO_MANIFEST_DIR"))?;
+    let fd = source.registrar().register_files(&[file.as_raw_fd()])?.next().unwrap();
+    let _ = target.registrar().register_files(&[PLACEHOLDER_FD; 2])?;
+
+    unsafe {
+        let mut sqe = source.prepare_sqe().unwrap();
+        sqe.prep_msg_ring_fd(target.raw_fd()?, fd, FileIndex::Slot(1), 0xB00, MsgRingFlags::CQE_SKIP);
+    }
+    source.submit_sqes()?;
+    source.wait_for_cqe()?.result()?;
+    assert!(target.peek_for_cqe().is_none());
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use std::io;
+use std::mem;
+use std::net::UdpSocket;
+use std::os::unix::io::AsRawFd;
+
+use iou::CQE;
+use iou::cqe::{CompletionFlags, MultishotTracker};
+use iou::sqe::{BufferGroup, BufferGroupId, MsgFlags};
+
+#[test]
+fn multishot_recvmsg_until_buffers_run_out() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(4)?;
+    let sender = UdpSocket::bind("127.0.0.1:0")?;
+    let receiver = UdpSocket::bind("127.0.0.1:0")?;
+    sender.connect(receiver.local_addr()?)?;
+
+    let group = BufferGroup::new(BufferGroupId { id: 2 }, 256, 2, 0);
+    let mut buffers = vec![0u8; group.total_len()];
+    unsafe { ring.prepare_sqe().unwrap().prep_provide_buffers(&group, &mut buffers); }
+    ring.submit_sqes_and_wait(1)?;
+    ring.peek_for_cqe().unwrap().result()?;
+
+    let mut tracker = MultishotTracker::new();
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_namelen = mem::size_of::<libc::sockaddr_storage>() as _;
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_recvmsg_multishot(receiver.as_raw_fd(), &msg, group.id(), MsgFlags::empty());
+        sqe.set_user_data(11);
+    }
+    ring.submit_sqes()?;
+    tracker.track(11);
+
+    // two messages fill both buffers, the third stops the event
+    let mut results = vec![];
+    for message in &[&b"one"[..], &b"two"[..], &b"three"[..]] {
+        sender.send(message)?;
+        let cqe = tracker.process(ring.wait_for_cqe()?);
+        results.push((cqe.user_data(), cqe.raw_result() >= 0, cqe.is_final()));
+        assert_eq!(tracker.is_live(11), !cqe.is_final());
+    }
+    assert_eq!(results, [(11, true, false), (11, true, false), (11, false, true)]);
+    assert_eq!(tracker.live_count(), 0);
+    Ok(())
+}
+
+#[test]
+fn untracked_cqes_pass_through() {
+    let mut tracker = MultishotTracker::new();
+    tracker.track(1);
+    tracker.track(2);
+
+    let cqes = vec![
+        CQE::from_raw_parts(1, 0, CompletionFlags::MORE),
+        CQE::from_raw_parts(3, 0, CompletionFlags::empty()),
+        CQE::from_raw_parts(2, -libc::ECANCELED, CompletionFlags::empty()),
+    ];
+    let finals: Vec<_> = tracker.cqes(cqes).map(|cqe| (cqe.user_data(), cqe.is_final())).collect();
+    assert_eq!(finals, [(1, false), (3, true), (2, true)]);
+    assert_eq!(tracker.live().collect::<Vec<_>>(), [1]);
+}
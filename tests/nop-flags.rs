@@ -0,0 +1,39 @@
+use std::fs::File;
+use std::io;
+use std::os::unix::io::AsRawFd;
+
+use iou::sqe::NopFlags;
+
+fn nop_result(ring: &mut iou::IoUring) -> io::Result<i32> {
+    ring.submit_sqes_and_wait(1)?;
+    Ok(ring.peek_for_cqe().unwrap().raw_result())
+}
+
+#[test]
+fn nop_inject_result() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(2)?;
+
+    unsafe { ring.prepare_sqe().unwrap().prep_nop_with_flags(NopFlags::INJECT_RESULT, 42); }
+    assert_eq!(nop_result(&mut ring)?, 42);
+
+    unsafe { ring.prepare_sqe().unwrap().prep_nop_with_flags(NopFlags::empty(), 42); }
+    assert_eq!(nop_result(&mut ring)?, 0);
+    Ok(())
+}
+
+#[test]
+fn nop_file() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(2)?;
+    let file = File::open(env!("CARGO_MANIFEST_DIR"))?;
+
+    unsafe { ring.prepare_sqe().unwrap().prep_nop_file(file.as_raw_fd(), NopFlags::empty(), 0); }
+    assert_eq!(nop_result(&mut ring)?, 0);
+
+    unsafe { ring.prepare_sqe().unwrap().prep_nop_file(-1, NopFlags::empty(), 0); }
+    assert_eq!(nop_result(&mut ring)?, -libc::EBADF);
+
+    let fd = ring.registrar().register_files(&[file.as_raw_fd()])?.next().unwrap();
+    unsafe { ring.prepare_sqe().unwrap().prep_nop_file(fd, NopFlags::INJECT_RESULT, 7); }
+    assert_eq!(nop_result(&mut ring)?, 7);
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use std::ffi::CString;
+use std::io;
+use std::path::PathBuf;
+
+use iou::registrar::{DirectSlot, PLACEHOLDER_FD};
+use iou::sqe::{FileIndex, FixedFdInstallFlags, Mode, OFlag, OpenHow, ResolveFlags};
+
+fn text_path() -> (PathBuf, CString) {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("props");
+    path.push("text.txt");
+    let c_path = CString::new(path.to_str().unwrap()).unwrap();
+    (path, c_path)
+}
+
+fn read_registered(ring: &mut iou::IoUring, fd: DirectSlot) -> io::Result<Vec<u8>> {
+    let mut buf = vec![0; 4096];
+    unsafe { ring.prepare_sqe().unwrap().prep_read(fd, &mut buf[..], 0); }
+    ring.submit_sqes()?;
+    let n = ring.wait_for_cqe()?.result()? as usize;
+    buf.truncate(n);
+    Ok(buf)
+}
+
+#[test]
+fn openat_direct() -> io::Result<()> {
+    let (path, c_path) = text_path();
+    let mut ring = iou::IoUring::new(2)?;
+    let _ = ring.registrar().register_files(&[PLACEHOLDER_FD; 4])?;
+
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_openat_direct(libc::AT_FDCWD, &c_path, OFlag::O_RDONLY, Mode::empty(), FileIndex::Alloc);
+    }
+    ring.submit_sqes()?;
+    let fd = ring.wait_for_cqe()?.direct_slot(FileIndex::Alloc)?;
+    assert!(fd.index() < 4);
+
+    assert_eq!(read_registered(&mut ring, fd)?, std::fs::read(&path)?);
+    Ok(())
+}
+
+#[test]
+fn openat2_direct() -> io::Result<()> {
+    let (path, c_path) = text_path();
+    let mut ring = iou::IoUring::new(2)?;
+    let _ = ring.registrar().register_files(&[PLACEHOLDER_FD; 4])?;
+
+    let how = OpenHow::new(OFlag::O_RDONLY, Mode::empty()).resolve(ResolveFlags::RESOLVE_NO_MAGICLINKS);
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_openat2_direct(libc::AT_FDCWD, &c_path, &how, FileIndex::Slot(1));
+    }
+    ring.submit_sqes()?;
+    let fd = ring.wait_for_cqe()?.direct_slot(FileIndex::Slot(1))?;
+    assert_eq!(fd.index(), 1);
+
+    assert_eq!(read_registered(&mut ring, fd)?, std::fs::read(&path)?);
+    Ok(())
+}
+
+#[test]
+fn openat_direct_alloc_range() -> io::Result<()> {
+    let (path, c_path) = text_path();
+    let mut ring = iou::IoUring::new(2)?;
+    let _ = ring.registrar().register_files_sparse(16)?;
+    ring.registrar().register_file_alloc_range(8, 2)?;
+
+    let mut fds = vec![];
+    for _ in 0..3 {
+        unsafe {
+            let mut sqe = ring.prepare_sqe().unwrap();
+            sqe.prep_openat_direct(libc::AT_FDCWD, &c_path, OFlag::O_RDONLY, Mode::empty(), FileIndex::Alloc);
+        }
+        ring.submit_sqes()?;
+        fds.push(ring.wait_for_cqe()?.direct_slot(FileIndex::Alloc));
+    }
+
+    let first = fds.remove(0)?;
+    let second = fds.remove(0)?;
+    assert!((8..10).contains(&first.index()));
+    assert!((8..10).contains(&second.index()));
+    assert_ne!(first.index(), second.index());
+    assert_eq!(fds.remove(0).err().unwrap().raw_os_error(), Some(libc::ENFILE));
+
+    assert_eq!(read_registered(&mut ring, second)?, std::fs::read(&path)?);
+
+    let err = ring.registrar().register_file_alloc_range(8, 16).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EINVAL));
+    Ok(())
+}
+
+#[test]
+fn close_direct_and_install() -> io::Result<()> {
+    use std::io::Read;
+    use std::os::unix::io::FromRawFd;
+
+    let (path, c_path) = text_path();
+    let mut ring = iou::IoUring::new(2)?;
+    let _ = ring.registrar().register_files_sparse(4)?;
+
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_openat_direct(libc::AT_FDCWD, &c_path, OFlag::O_RDONLY, Mode::empty(), FileIndex::Slot(2));
+    }
+    ring.submit_sqes()?;
+    let slot = ring.wait_for_cqe()?.direct_slot(FileIndex::Slot(2))?;
+
+    unsafe { ring.prepare_sqe().unwrap().prep_fixed_fd_install(slot, FixedFdInstallFlags::empty()); }
+    ring.submit_sqes()?;
+    let fd = ring.wait_for_cqe()?.result()?;
+    let mut file = unsafe { std::fs::File::from_raw_fd(fd as _) };
+    let mut contents = vec![];
+    file.read_to_end(&mut contents)?;
+    assert_eq!(contents, std::fs::read(&path)?);
+
+    unsafe { ring.prepare_sqe().unwrap().prep_close_direct(slot); }
+    ring.submit_sqes()?;
+    ring.wait_for_cqe()?.result()?;
+
+    // the slot is empty now
+    let err = read_registered(&mut ring, slot).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EBADF));
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::net::UnixStream;
+use std::sync::Arc;
+
+use iou::owned::OwnedRing;
+use iou::sqe::{MsgFlags, SockFlag};
+
+#[test]
+fn read_into_owned_buffer() -> io::Result<()> {
+    let mut ring = OwnedRing::new(4)?;
+    let file = Arc::new(File::open("Cargo.toml")?);
+
+    let mut buf = Vec::with_capacity(64);
+    buf.extend_from_slice(b">>");
+    let read = ring.read(file.clone(), buf, 0)?;
+    assert_eq!(ring.inflight(), 1);
+    ring.submit()?;
+
+    let mut completion = ring.wait()?;
+    assert_eq!(completion.user_data(), read);
+    assert_eq!(completion.result()?, 62);
+    assert!(completion.take_fd::<File>().is_none());
+    assert!(completion.take_fd::<Arc<File>>().is_some());
+    let buf = completion.into_buf().unwrap();
+    assert!(buf.starts_with(b">>[package]"));
+    assert_eq!(ring.inflight(), 0);
+    Ok(())
+}
+
+#[test]
+fn write_and_recv_on_socket() -> io::Result<()> {
+    let mut ring = OwnedRing::new(4)?;
+    let (a, b) = UnixStream::pair()?;
+    let (mut a2, mut b2) = (a.try_clone()?, b.try_clone()?);
+
+    ring.write(a, b"hello".to_vec(), 0)?;
+    ring.submit()?;
+    assert_eq!(ring.wait()?.result()?, 5);
+    let mut received = [0; 5];
+    b2.read_exact(&mut received)?;
+    assert_eq!(&received, b"hello");
+
+    a2.write_all(b"world")?;
+    let recv = ring.recv(b, Vec::with_capacity(16), MsgFlags::empty())?;
+    ring.submit()?;
+    let completion = ring.wait()?;
+    assert_eq!(completion.user_data(), recv);
+    assert_eq!(completion.buf().unwrap(), b"world");
+    Ok(())
+}
+
+#[test]
+fn accept_connection() -> io::Result<()> {
+    let mut ring = OwnedRing::new(4)?;
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+
+    ring.accept(listener, SockFlag::empty())?;
+    ring.submit()?;
+    let mut client = TcpStream::connect(addr)?;
+
+    let mut completion = ring.wait()?;
+    let accepted = completion.accepted().expect("no accepted socket");
+    let mut server = TcpStream::from(accepted);
+    client.write_all(b"ping")?;
+    let mut buf = [0; 4];
+    server.read_exact(&mut buf)?;
+    assert_eq!(&buf, b"ping");
+    assert!(completion.take_fd::<TcpListener>().is_some());
+    Ok(())
+}
+
+#[test]
+fn drop_with_events_in_flight() -> io::Result<()> {
+    let mut ring = OwnedRing::new(2)?;
+    let (a, _b) = UnixStream::pair()?;
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+
+    // neither of these can complete until they are cancelled
+    ring.recv(a, Vec::with_capacity(16), MsgFlags::empty())?;
+    ring.accept(listener, SockFlag::empty())?;
+    ring.nop()?;
+    assert_eq!(ring.inflight(), 3);
+    ring.submit()?;
+    drop(ring);
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use std::io;
+
+use iou::{IoUring, RingMemory, SetupFeatures, SetupFlags};
+
+#[test]
+fn granted_params() -> io::Result<()> {
+    let mut ring = IoUring::new_with_flags(100, SetupFlags::CLAMP, SetupFeatures::empty())?;
+    let params = ring.params();
+
+    // the kernel rounds the entries up to a power of two, with twice as many CQEs
+    assert_eq!(params.sq_entries(), 128);
+    assert_eq!(params.cq_entries(), 256);
+    assert_eq!(params.flags(), SetupFlags::CLAMP);
+    assert!(params.features().contains(SetupFeatures::SINGLE_MMAP));
+    assert!(params.cq_offsets().cqes > params.cq_offsets().tail);
+    assert_eq!(ring.sq_space_left(), 128);
+    Ok(())
+}
+
+#[test]
+fn params_of_ring_in_user_memory() -> io::Result<()> {
+    let memory = RingMemory::new(8)?;
+    let ring = IoUring::new_with_memory(memory, SetupFlags::empty(), SetupFeatures::empty())?;
+    assert_eq!(ring.params().sq_entries(), 8);
+    assert!(ring.params().flags().contains(SetupFlags::NO_MMAP));
+    Ok(())
+}
+
+#[test]
+fn granted_features() -> io::Result<()> {
+    let ring = IoUring::new(4)?;
+    assert_eq!(ring.features(), ring.params().features());
+    assert!(ring.has_feature(SetupFeatures::SINGLE_MMAP | SetupFeatures::NODROP));
+    assert!(ring.has_feature(SetupFeatures::empty()));
+
+    // every kernel with registered ring fds also has the features that came before them
+    if ring.has_feature(SetupFeatures::REG_REG_RING) {
+        assert!(ring.has_feature(SetupFeatures::EXT_ARG | SetupFeatures::CQE_SKIP | SetupFeatures::LINKED_FILE));
+    }
+    Ok(())
+}
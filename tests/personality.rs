@@ -0,0 +1,25 @@
+use std::io;
+
+#[test]
+fn personalities() -> io::Result<()> {
+    let ring = iou::IoUring::new(2)?;
+    let registrar = ring.registrar();
+    assert!(registrar.personalities().is_empty());
+
+    let first = registrar.register_personality()?;
+    let creds = registrar.personality_creds(first).unwrap();
+    assert_eq!(creds.uid(), unsafe { libc::geteuid() });
+    assert_eq!(creds.gid(), unsafe { libc::getegid() });
+
+    {
+        let guard = registrar.register_personality_guard()?;
+        assert_ne!(guard.personality(), first);
+        assert_eq!(registrar.personalities(), vec![first, guard.personality()]);
+    }
+    assert_eq!(registrar.personalities(), vec![first]);
+
+    registrar.unregister_personality(first)?;
+    assert!(registrar.personalities().is_empty());
+    assert!(registrar.personality_creds(first).is_none());
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::UnixStream;
+
+use iou::sqe::MsgFlags;
+
+const MESSAGE: &[u8] = b"Hello World";
+
+#[test]
+fn recv_and_send_polling_first() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(2)?;
+    let (receiver, sender) = UnixStream::pair()?;
+
+    // the receive waits for the socket instead of trying it first, and still gets the message
+    let mut buf = [0; 64];
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_recv(receiver.as_raw_fd(), &mut buf[..], MsgFlags::empty());
+        sqe.set_poll_first();
+        sqe.set_user_data(1);
+    }
+    ring.submit_sqes()?;
+    assert!(ring.peek_for_cqe().is_none());
+
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_send(sender.as_raw_fd(), MESSAGE, MsgFlags::empty());
+        sqe.set_poll_first();
+        sqe.set_user_data(2);
+    }
+    ring.submit_sqes()?;
+
+    let mut results = vec![];
+    for _ in 0..2 {
+        let cqe = ring.wait_for_cqe()?;
+        results.push((cqe.user_data(), cqe.result()? as usize));
+    }
+    results.sort();
+    assert_eq!(results, [(1, MESSAGE.len()), (2, MESSAGE.len())]);
+    assert_eq!(&buf[..MESSAGE.len()], MESSAGE);
+    Ok(())
+}
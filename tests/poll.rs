@@ -31,8 +31,7 @@ fn test_poll_add() -> io::Result<()> {
 fn test_poll_remove() -> io::Result<()> {
     let mut ring = iou::IoUring::new(2)?;
     let (read, _write) = net::UnixStream::pair()?;
-    let uname = nix::sys::utsname::uname();
-    let version = semver::Version::parse(uname.release());
+    let version = iou::KernelVersion::current()?;
     unsafe {
         let mut sqe = ring.prepare_sqe().expect("failed to get sqe");
         sqe.prep_poll_add(read.as_raw_fd(), iou::sqe::PollFlags::POLLIN);
@@ -46,7 +45,7 @@ fn test_poll_remove() -> io::Result<()> {
         for _ in 0..2 {
             let cqe = ring.wait_for_cqe()?;
             let user_data = cqe.user_data();
-            if version < semver::Version::parse("5.5.0-0") {
+            if version < iou::KernelVersion::new(5, 5, 0) {
                 let _ = cqe.result()?;
             } else if user_data == 0xDEADBEEF {
                 let err = cqe
@@ -64,3 +63,76 @@ fn test_poll_remove() -> io::Result<()> {
         Ok(())
     }
 }
+
+#[test]
+fn test_poll_multishot() -> io::Result<()> {
+    use iou::sqe::{PollAddFlags, PollFlags};
+
+    let mut ring = iou::IoUring::new(4)?;
+    let (read, mut write) = net::UnixStream::pair()?;
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_poll_add_with_flags(read.as_raw_fd(), PollFlags::POLLIN, PollAddFlags::MULTI);
+        sqe.set_user_data(1);
+    }
+    ring.submit_sqes()?;
+
+    // every time data arrives, the poll fires again without being rearmed
+    for _ in 0..2 {
+        write.write_all(MESSAGE)?;
+        let cqe = ring.wait_for_cqe()?;
+        assert_eq!(cqe.user_data(), 1);
+        assert!(cqe.result_as_poll_flags()?.contains(PollFlags::POLLIN));
+        assert!(cqe.more());
+    }
+
+    // the update moves the poll to new user data, under which it is removed
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_poll_update(1, 2, PollFlags::empty(), PollAddFlags::UPDATE_USER_DATA | PollAddFlags::MULTI);
+        sqe.set_user_data(10);
+    }
+    ring.submit_sqes_and_wait(1)?;
+    let update = ring.peek_for_cqe().unwrap();
+    assert_eq!((update.user_data(), update.result()?), (10, 0));
+
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_poll_remove(2);
+        sqe.set_user_data(11);
+    }
+    ring.submit_sqes()?;
+    // the updated poll finds the unread data and may fire again before it is removed
+    let mut finals = vec![];
+    while finals.len() < 2 {
+        let cqe = ring.wait_for_cqe()?;
+        if !cqe.more() {
+            finals.push(cqe.user_data());
+        }
+    }
+    finals.sort();
+    assert_eq!(finals, [2, 11]);
+    Ok(())
+}
+
+#[test]
+fn test_poll_rearm_is_level_triggered() -> io::Result<()> {
+    use iou::sqe::{PollAddFlags, PollFlags};
+
+    let mut ring = iou::IoUring::new(2)?;
+    let (read, mut write) = net::UnixStream::pair()?;
+    write.write_all(MESSAGE)?;
+
+    // data which was already there is reported each time the poll is armed
+    for _ in 0..2 {
+        unsafe {
+            let mut sqe = ring.prepare_sqe().unwrap();
+            sqe.prep_poll_add_with_flags(read.as_raw_fd(), PollFlags::POLLIN, PollAddFlags::empty());
+        }
+        ring.submit_sqes_and_wait(1)?;
+        let cqe = ring.peek_for_cqe().unwrap();
+        assert!(cqe.result_as_poll_flags()?.contains(PollFlags::POLLIN));
+        assert!(!cqe.more());
+    }
+    Ok(())
+}
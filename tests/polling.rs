@@ -0,0 +1,48 @@
+#![cfg(feature = "polling")]
+
+use std::io;
+use std::time::Duration;
+
+use iou::IoUring;
+use iou::registrar::EventFd;
+use polling::{Events, Poller};
+
+#[test]
+fn reports_completions_through_eventfd() -> io::Result<()> {
+    let mut ring = IoUring::new(4)?;
+    ring.registrar().register_owned_eventfd(&EventFd::new()?)?;
+    let poller = Poller::new()?;
+    unsafe { ring.add_to_poller(&poller, 3)?; }
+
+    let mut events = Events::new();
+    poller.wait(&mut events, Some(Duration::from_millis(10)))?;
+    assert!(events.is_empty());
+
+    for user_data in 0..2 {
+        unsafe {
+            let mut sqe = ring.prepare_sqe().unwrap();
+            sqe.prep_nop();
+            sqe.set_user_data(user_data);
+        }
+    }
+    ring.submit_sqes()?;
+    poller.wait(&mut events, Some(Duration::from_secs(1)))?;
+    assert_eq!(events.iter().map(|event| (event.key, event.readable)).collect::<Vec<_>>(), [(3, true)]);
+
+    let mut reaped = vec![];
+    assert_eq!(ring.drain_ready(&poller, 3, |cqe| reaped.push(cqe.user_data()))?, 2);
+    assert_eq!(reaped, [0, 1]);
+
+    // re-armed, and the eventfd was drained
+    events.clear();
+    poller.wait(&mut events, Some(Duration::from_millis(10)))?;
+    assert!(events.is_empty());
+
+    unsafe { ring.prepare_sqe().unwrap().prep_nop(); }
+    ring.submit_sqes()?;
+    poller.wait(&mut events, Some(Duration::from_secs(1)))?;
+    assert_eq!(events.len(), 1);
+
+    ring.delete_from_poller(&poller)?;
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use std::io;
+
+#[test]
+fn prepare_or_submit_flushes_full_queue() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(4)?;
+
+    let mut flushed = 0;
+    for user_data in 0..10 {
+        let (mut sqe, n) = ring.prepare_or_submit()?;
+        unsafe {
+            sqe.prep_nop();
+            sqe.set_user_data(user_data);
+        }
+        flushed += n;
+        ring.cqes().for_each(drop);
+    }
+    assert_eq!(flushed, 8);
+    assert_eq!(ring.submit_sqes()?, 2);
+    Ok(())
+}
+
+#[test]
+fn sq_prepare_or_submit() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(2)?;
+    let (mut sq, _, _) = ring.queues();
+
+    for _ in 0..2 {
+        let (mut sqe, flushed) = sq.prepare_or_submit()?;
+        unsafe { sqe.prep_nop(); }
+        assert_eq!(flushed, 0);
+    }
+    let (mut sqe, flushed) = sq.prepare_or_submit()?;
+    unsafe { sqe.prep_nop(); }
+    assert_eq!(flushed, 2);
+    assert_eq!(sq.submit()?, 1);
+    Ok(())
+}
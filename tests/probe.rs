@@ -1,8 +1,46 @@
 use iou::Probe;
-use uring_sys::IoRingOp;
+use iou::sys::IoRingOp;
 
 #[test]
 fn probe() {
     let probe = Probe::new().unwrap();
     assert!(probe.supports(IoRingOp::IORING_OP_NOP));
 }
+
+#[test]
+fn probe_ops() {
+    let probe = Probe::new().unwrap();
+    assert!(probe.last_op() >= IoRingOp::IORING_OP_TEE as u8);
+    assert!(probe.supported_count() > IoRingOp::IORING_OP_TEE as usize);
+    assert!(probe.supports_raw(IoRingOp::IORING_OP_READ as u8));
+
+    let nop = probe.ops().next().unwrap();
+    assert_eq!(nop.opcode(), 0);
+    assert_eq!(nop.name(), Some("NOP"));
+    assert_eq!(nop.to_string(), "IORING_OP_NOP");
+    assert!(nop.is_supported());
+
+    let tee = probe.ops().find(|op| op.opcode() == IoRingOp::IORING_OP_TEE as u8).unwrap();
+    assert_eq!(tee.name(), Some("TEE"));
+
+    assert!(probe.supports_name("IORING_OP_READ"));
+    assert!(probe.supports_name("READ"));
+    assert!(!probe.supports_name("IORING_OP_NOT_AN_OP"));
+}
+
+#[test]
+fn capability_report() {
+    let report = iou::CapabilityReport::detect().unwrap();
+    assert!(report.features().contains(iou::SetupFeatures::SINGLE_MMAP));
+    assert!(report.supported_ops().any(|op| op.opcode() == 0));
+
+    let text = report.to_string();
+    assert!(text.starts_with("io_uring capabilities:\n"));
+    assert!(text.contains("SINGLE_MMAP"));
+    assert!(text.contains("IORING_OP_NOP"));
+
+    let probe = Probe::new().unwrap();
+    let report = iou::CapabilityReport::new(&probe, iou::SetupFeatures::NODROP);
+    assert_eq!(report.raw_features(), iou::SetupFeatures::NODROP.bits());
+    assert_eq!(report.supported_ops().count(), probe.supported_count());
+}
@@ -0,0 +1,153 @@
+use std::io;
+
+use iou::sqe::{BufferGroup, BufferGroupId, ProvidedBufferPool};
+
+#[test]
+fn provide_and_remove_buffers() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(2)?;
+    let group = BufferGroup::new(BufferGroupId { id: 7 }, 64, 4, 10);
+    let mut buffers = vec![0u8; group.total_len()];
+
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_provide_buffers(&group, &mut buffers);
+        sqe.set_user_data(1);
+    }
+    ring.submit_sqes_and_wait(1)?;
+    let cqe = ring.peek_for_cqe().unwrap();
+    assert_eq!(cqe.user_data(), 1);
+    cqe.result()?;
+
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_remove_buffers(&group);
+        sqe.set_user_data(2);
+    }
+    ring.submit_sqes_and_wait(1)?;
+    let cqe = ring.peek_for_cqe().unwrap();
+    assert_eq!(cqe.user_data(), 2);
+    assert_eq!(cqe.result()?, 4);
+    Ok(())
+}
+
+#[test]
+fn buffer_group_ranges() {
+    let group = BufferGroup::new(BufferGroupId { id: 0 }, 16, 3, 5);
+    assert_eq!(group.total_len(), 48);
+    assert!(!group.contains(4));
+    assert_eq!(group.range_of(5), Some(0..16));
+    assert_eq!(group.range_of(7), Some(32..48));
+    assert_eq!(group.range_of(8), None);
+}
+
+#[test]
+#[should_panic(expected = "buffers are too small")]
+fn provide_buffers_too_small() {
+    let mut ring = iou::IoUring::new(1).unwrap();
+    let group = BufferGroup::new(BufferGroupId { id: 0 }, 64, 4, 0);
+    let mut buffers = vec![0u8; 100];
+    let mut sqe = ring.prepare_sqe().unwrap();
+    unsafe { sqe.prep_provide_buffers(&group, &mut buffers); }
+}
+
+#[test]
+#[should_panic(expected = "buffer ids overflow u16")]
+fn buffer_ids_overflow() {
+    BufferGroup::new(BufferGroupId { id: 0 }, 64, 2, u16::MAX);
+}
+
+#[test]
+fn read_into_selected_buffer() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(2)?;
+    let group = BufferGroup::new(BufferGroupId { id: 3 }, 16, 2, 0);
+    let mut buffers = vec![0u8; group.total_len()];
+    let file = std::fs::File::open("Cargo.toml")?;
+
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_provide_buffers(&group, &mut buffers);
+    }
+    ring.submit_sqes_and_wait(1)?;
+    ring.peek_for_cqe().unwrap().result()?;
+
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_read_buffer_select(std::os::unix::io::AsRawFd::as_raw_fd(&file), group.id(), 9, 0);
+        assert!(sqe.flags().contains(iou::sqe::SubmissionFlags::BUFFER_SELECT));
+    }
+    ring.submit_sqes_and_wait(1)?;
+    let cqe = ring.peek_for_cqe().unwrap();
+    assert_eq!(cqe.result()?, 9);
+    let bid = cqe.buffer_id().unwrap();
+    assert_eq!(&buffers[group.range_of(bid).unwrap()][..9], b"[package]");
+    assert_eq!(group.decode(&cqe, &buffers), Some(&b"[package]"[..]));
+
+    // failed events and buffers of other groups are not decoded
+    let other = BufferGroup::new(BufferGroupId { id: 3 }, 16, 2, 2);
+    assert_eq!(other.decode(&cqe, &buffers), None);
+    let failed = iou::CQE::from_raw_parts(0, -libc::ENOBUFS, cqe.flags());
+    assert_eq!(group.decode(&failed, &buffers), None);
+    Ok(())
+}
+
+#[test]
+fn provided_buffer_pool_reprovides_in_batches() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(8)?;
+    let mut pool = ProvidedBufferPool::new(BufferGroupId { id: 5 }, 4, 4);
+    pool.set_user_data(99);
+    assert_eq!(pool.pending(), 4);
+    assert_eq!(unsafe { pool.provide(&mut ring.sq(), 1)? }, 1);
+    assert_eq!(pool.pending(), 0);
+    ring.submit_sqes_and_wait(1)?;
+    let cqe = ring.peek_for_cqe().unwrap();
+    assert_eq!(cqe.user_data(), 99);
+    cqe.result()?;
+
+    let file = std::fs::File::open("Cargo.toml")?;
+    let mut cqes = vec![];
+    for offset in 0..3 {
+        unsafe {
+            let mut sqe = ring.prepare_sqe().unwrap();
+            sqe.prep_read_buffer_select(std::os::unix::io::AsRawFd::as_raw_fd(&file), pool.group().id(), 4, offset * 4);
+        }
+        ring.submit_sqes_and_wait(1)?;
+        cqes.push(ring.peek_for_cqe().unwrap());
+    }
+
+    let mut data = vec![];
+    for cqe in &cqes {
+        data.extend_from_slice(pool.take(cqe).unwrap());
+    }
+    assert_eq!(&data[..], b"[package]\nna");
+    assert_eq!(pool.pending(), 3);
+
+    // not enough buffers are consumed for a batch yet
+    assert_eq!(unsafe { pool.provide(&mut ring.sq(), 4)? }, 0);
+
+    // the consumed ids are not necessarily consecutive, but each run takes one event
+    let prepared = unsafe { pool.provide(&mut ring.sq(), 2)? };
+    assert!((1..=2).contains(&prepared));
+    assert_eq!(pool.pending(), 0);
+    ring.submit_sqes_and_wait(prepared)?;
+    for _ in 0..prepared {
+        ring.peek_for_cqe().unwrap().result()?;
+    }
+
+    // all four buffers are available again
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_remove_buffers(pool.group());
+    }
+    ring.submit_sqes_and_wait(1)?;
+    assert_eq!(ring.peek_for_cqe().unwrap().result()?, 4);
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "already consumed")]
+fn provided_buffer_pool_take_unprovided() {
+    // a new pool has not provided its buffers, so they all count as consumed
+    let mut pool = ProvidedBufferPool::new(BufferGroupId { id: 0 }, 4, 2);
+    let cqe = iou::CQE::from_raw_parts(0, 4, iou::cqe::CompletionFlags::from_bits_truncate((1 << 16) | 1));
+    pool.take(&cqe);
+}
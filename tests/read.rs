@@ -91,7 +91,7 @@ fn read_registered_buf() -> io::Result<()> {
         let mut sqe = sq.prepare_sqe().unwrap();
         sqe.prep_read(file.as_raw_fd(), buf.as_mut(), 0);
         sqe.set_user_data(0xDEADBEEF);
-        assert!(sqe.raw().opcode == uring_sys::IoRingOp::IORING_OP_READ_FIXED as u8);
+        assert!(sqe.raw().opcode == iou::sys::IoRingOp::IORING_OP_READ_FIXED as u8);
         sq.submit()?;
     }
 
@@ -126,7 +126,7 @@ fn read_registered_fd_and_buf() -> io::Result<()> {
         let mut sqe = sq.prepare_sqe().unwrap();
         sqe.prep_read(fd, buf.as_mut(), 0);
         sqe.set_user_data(0xDEADBEEF);
-        assert!(sqe.raw().opcode == uring_sys::IoRingOp::IORING_OP_READ_FIXED as u8);
+        assert!(sqe.raw().opcode == iou::sys::IoRingOp::IORING_OP_READ_FIXED as u8);
         assert!(sqe.flags().contains(iou::sqe::SubmissionFlags::FIXED_FILE));
         sq.submit()?;
     }
@@ -141,3 +141,67 @@ fn read_registered_fd_and_buf() -> io::Result<()> {
     assert_eq!(&TEXT[..n], &buf.slice_to(n)[..]);
     Ok(())
 }
+
+#[test]
+fn read_into_middle_of_registered_buf() -> io::Result<()> {
+    use iou::registrar::*;
+
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("props");
+    path.push("text.txt");
+
+    let mut io_uring = iou::IoUring::new(32)?;
+    let bufs = vec![Box::new([0u8; 4096]) as Box<[u8]>];
+    let file = File::open(&path)?;
+
+    let mut buf: RegisteredBuf = io_uring.registrar().register_buffers(bufs)?.next().unwrap();
+    let view = buf.slice_mut(100..120);
+    assert_eq!((view.index(), view.offset(), view.len()), (0, 100, 20));
+    let view = view.slice_mut(5..15);
+    assert_eq!((view.offset(), view.len()), (105, 10));
+
+    unsafe {
+        let mut sq = io_uring.sq();
+        let mut sqe = sq.prepare_sqe().unwrap();
+        sqe.prep_read(file.as_raw_fd(), view, 0);
+        assert!(sqe.raw().opcode == iou::sys::IoRingOp::IORING_OP_READ_FIXED as u8);
+        sq.submit()?;
+    }
+
+    let n = io_uring.cq().wait_for_cqe()?.result_as_size()?;
+    assert_eq!(n, 10);
+    assert_eq!(&buf[105..115], &TEXT[..10]);
+    assert!(buf[..105].iter().chain(&buf[115..]).all(|&byte| byte == 0));
+    Ok(())
+}
+
+#[test]
+fn read_with_ioprio() -> io::Result<()> {
+    use iou::sqe::{IoPrio, IoPrioClass};
+
+    let mut io_uring = iou::IoUring::new(32)?;
+
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("props");
+    path.push("text.txt");
+    let file = File::open(&path)?;
+    let mut buf = [0; 4096];
+
+    let prio = IoPrio::new(IoPrioClass::BestEffort, 4);
+    assert_eq!(prio.class(), IoPrioClass::BestEffort);
+    assert_eq!(prio.level(), 4);
+
+    unsafe {
+        let mut sq = io_uring.sq();
+        let mut sqe = sq.prepare_sqe().unwrap();
+        sqe.prep_read(file.as_raw_fd(), &mut buf[..], 0);
+        sqe.set_ioprio(prio);
+        assert_eq!(sqe.ioprio(), prio);
+        assert_eq!(sqe.raw().ioprio, 2 << 13 | 4);
+        sq.submit()?;
+    }
+
+    let n = io_uring.wait_for_cqe()?.result()? as usize;
+    assert_eq!(&TEXT[..n], &buf[..n]);
+    Ok(())
+}
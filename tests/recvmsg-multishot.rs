@@ -0,0 +1,54 @@
+use std::io;
+use std::mem;
+use std::net::UdpSocket;
+use std::os::unix::io::AsRawFd;
+
+use iou::sqe::{BufferGroup, BufferGroupId, MsgFlags, RecvMsgOut, SockAddr};
+
+#[test]
+fn recvmsg_multishot() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(4)?;
+    let sender = UdpSocket::bind("127.0.0.1:0")?;
+    let receiver = UdpSocket::bind("127.0.0.1:0")?;
+    sender.connect(receiver.local_addr()?)?;
+
+    let group = BufferGroup::new(BufferGroupId { id: 1 }, 256, 4, 0);
+    let mut buffers = vec![0u8; group.total_len()];
+    unsafe { ring.prepare_sqe().unwrap().prep_provide_buffers(&group, &mut buffers); }
+    ring.submit_sqes_and_wait(1)?;
+    ring.peek_for_cqe().unwrap().result()?;
+
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_namelen = mem::size_of::<libc::sockaddr_storage>() as _;
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_recvmsg_multishot(receiver.as_raw_fd(), &msg, group.id(), MsgFlags::empty());
+        sqe.set_user_data(0xDEADBEEF);
+    }
+    ring.submit_sqes()?;
+
+    let sender_addr = SockAddr::from(sender.local_addr()?);
+    for message in &[&b"first"[..], &b"second"[..]] {
+        sender.send(message)?;
+        let cqe = ring.wait_for_cqe()?;
+        assert_eq!(cqe.user_data(), 0xDEADBEEF);
+        let len = cqe.result()? as usize;
+        assert!(cqe.more(), "multishot recvmsg should still be armed");
+
+        let range = group.range_of(cqe.buffer_id().unwrap()).unwrap();
+        let out = RecvMsgOut::parse(&buffers[range][..len], &msg).unwrap();
+        assert_eq!(out.payload(), *message);
+        assert_eq!(out.payload_len() as usize, message.len());
+        assert!(out.control().is_empty());
+        assert_eq!(out.socket_addr(), Some(sender_addr));
+    }
+    Ok(())
+}
+
+#[test]
+fn recvmsg_out_too_short() {
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_namelen = 16;
+    assert!(RecvMsgOut::parse(&[0; 24], &msg).is_none());
+    assert!(RecvMsgOut::parse(&[0; 32], &msg).unwrap().payload().is_empty());
+}
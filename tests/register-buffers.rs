@@ -38,3 +38,156 @@ fn register_buffers_by_mut() {
     assert_eq!(bufs[0].index(), 0);
     assert_eq!(bufs[1].index(), 1);
 }
+
+#[test]
+fn register_buffers_sparse() -> std::io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let mut ring = iou::IoUring::new(8)?;
+    ring.registrar().register_buffers_sparse(64)?;
+
+    let file = std::fs::File::open(env!("CARGO_MANIFEST_DIR"))?;
+    let mut buf = vec![0; 1024];
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_read_fixed(file.as_raw_fd(), &mut buf[..], 0, iou::registrar::RegisteredBufIndex::from_raw(63));
+    }
+    ring.submit_sqes()?;
+    let err = ring.wait_for_cqe()?.result().unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EFAULT));
+
+    let err = ring.registrar().register_buffers_sparse(64).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EBUSY));
+    Ok(())
+}
+
+#[test]
+fn update_registered_buffers() -> std::io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let mut ring = iou::IoUring::new(8)?;
+    ring.registrar().register_buffers_sparse(4)?;
+
+    let buf = vec![0; 1024].into_boxed_slice();
+    let mut bufs: Vec<_> = ring.registrar().update_registered_buffers(2, vec![buf])?.collect();
+    assert_eq!(bufs[0].index(), 2);
+
+    let mut path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("props");
+    path.push("text.txt");
+    let file = std::fs::File::open(&path)?;
+
+    let buf = &mut bufs[0];
+    let buf_index = buf.buf_index();
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_read_fixed(file.as_raw_fd(), &mut buf[..], 0, buf_index);
+    }
+    ring.submit_sqes()?;
+    let n = ring.wait_for_cqe()?.result()? as usize;
+    assert_eq!(&buf[..n], &std::fs::read(&path)?[..]);
+
+    let err = ring.registrar().update_registered_buffers(4, vec![vec![0; 8].into_boxed_slice()]).err();
+    assert_eq!(err.unwrap().raw_os_error(), Some(libc::EINVAL));
+    Ok(())
+}
+
+#[test]
+fn clone_buffers() -> std::io::Result<()> {
+    use std::os::unix::io::AsRawFd;
+
+    let src = iou::IoUring::new(8)?;
+    let bufs = vec![vec![0; 64].into_boxed_slice(), vec![0; 1024].into_boxed_slice()];
+    let mut bufs: Vec<_> = src.registrar().register_buffers(bufs)?.collect();
+
+    let mut ring = iou::IoUring::new(8)?;
+    ring.registrar().clone_buffers(&src)?;
+    let err = ring.registrar().clone_buffers(&src).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EBUSY));
+
+    let mut path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("props");
+    path.push("text.txt");
+    let file = std::fs::File::open(&path)?;
+
+    // the cloned table has the same indices as the source ring's
+    let buf = &mut bufs[1];
+    let buf_index = buf.buf_index();
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_read_fixed(file.as_raw_fd(), &mut buf[..], 0, buf_index);
+    }
+    ring.submit_sqes()?;
+    let n = ring.wait_for_cqe()?.result()? as usize;
+    assert_eq!(&buf[..n], &std::fs::read(&path)?[..]);
+
+    let other = iou::IoUring::new(8)?;
+    other.registrar().register_buffers_sparse(4)?;
+    other.registrar().clone_buffers_range(&src, 1, 3, 1)?;
+    assert!(other.registrar().clone_buffers_range(&src, 2, 0, 1).is_err());
+    Ok(())
+}
+
+#[test]
+fn register_shared_buffers() -> std::io::Result<()> {
+    use std::io;
+    use std::sync::Arc;
+
+    let shared: Arc<[u8]> = Arc::from(&b"shared buffer"[..]);
+    let mut ring = iou::IoUring::new(8)?;
+    let bufs: Vec<_> = ring.registrar().register_shared_buffers(std::slice::from_ref(&shared))?.collect();
+    assert_eq!(Arc::strong_count(&shared), 3);
+
+    // the ring keeps the buffer alive after every other handle is dropped
+    let buf_index = bufs[0].buf_index();
+    let (ptr, len) = (bufs[0].as_ptr(), bufs[0].len());
+    drop(bufs);
+    drop(shared);
+
+    let (read, write) = nix::unistd::pipe().map_err(|_| io::Error::last_os_error())?;
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_write_fixed(write, std::slice::from_raw_parts(ptr, len), 0, buf_index);
+    }
+    ring.submit_sqes()?;
+    assert_eq!(ring.wait_for_cqe()?.result()? as usize, len);
+
+    let mut out = [0; 64];
+    let n = nix::unistd::read(read, &mut out).map_err(|_| io::Error::last_os_error())?;
+    assert_eq!(&out[..n], b"shared buffer");
+
+    ring.registrar().unregister_buffers()?;
+    let _ = nix::unistd::close(read);
+    let _ = nix::unistd::close(write);
+    Ok(())
+}
+
+#[test]
+fn register_hugepage_buffers() -> std::io::Result<()> {
+    let mut ring = iou::IoUring::new(8)?;
+    let mut bufs = ring.registrar().register_hugepage_buffers(3, 1000)?;
+    assert_eq!(bufs.count(), 3);
+    assert!(bufs.buf_len() >= 1000);
+    assert_eq!(bufs.bufs_mut().count(), 3);
+
+    let mut buf = bufs.buf_mut(2);
+    buf[..5].copy_from_slice(b"hello");
+    let buf = bufs.buf(2);
+    assert_eq!(buf.index(), 2);
+    assert_eq!(buf.as_ptr() as usize % 4096, 0);
+
+    let (read, write) = nix::unistd::pipe().map_err(|_| std::io::Error::last_os_error())?;
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_write_fixed(write, &buf[..5], 0, buf.buf_index());
+    }
+    ring.submit_sqes()?;
+    assert_eq!(ring.wait_for_cqe()?.result()?, 5);
+
+    let mut out = [0; 8];
+    let n = nix::unistd::read(read, &mut out).map_err(|_| std::io::Error::last_os_error())?;
+    assert_eq!(&out[..n], b"hello");
+    let _ = nix::unistd::close(read);
+    let _ = nix::unistd::close(write);
+    Ok(())
+}
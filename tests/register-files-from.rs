@@ -0,0 +1,32 @@
+use std::fs::File;
+use std::io;
+use std::net::TcpListener;
+use std::os::unix::io::AsRawFd;
+
+use iou::registrar::RegisteredFd;
+
+#[test]
+fn register_files_from() -> io::Result<()> {
+    let mut path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("props");
+    path.push("text.txt");
+    let files = vec![File::open(&path)?, File::open(&path)?];
+
+    let mut ring = iou::IoUring::new(2)?;
+    let fds: Vec<RegisteredFd> = ring.registrar().register_files_from(&files)?.collect();
+    assert_eq!(fds.len(), 2);
+    assert_eq!(fds[1].index(), 1);
+    assert_eq!(fds[1].as_raw_fd(), files[1].as_raw_fd());
+
+    let mut buf = vec![0; 4096];
+    unsafe { ring.prepare_sqe().unwrap().prep_read(fds[1], &mut buf[..], 0); }
+    ring.submit_sqes()?;
+    let n = ring.wait_for_cqe()?.result()? as usize;
+    assert_eq!(&buf[..n], &std::fs::read(&path)?[..]);
+
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let updated: Vec<_> = ring.registrar().update_registered_files_from(0, &[&listener])?.collect();
+    assert_eq!(updated[0].index(), 0);
+    assert_eq!(updated[0].as_raw_fd(), listener.as_raw_fd());
+    Ok(())
+}
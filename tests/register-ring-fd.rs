@@ -0,0 +1,84 @@
+use std::io;
+use std::time::Duration;
+
+fn submit_nops(ring: &mut iou::IoUring, count: u64) {
+    for user_data in 0..count {
+        unsafe {
+            let mut sqe = ring.prepare_sqe().unwrap();
+            sqe.prep_nop();
+            sqe.set_user_data(user_data);
+        }
+    }
+}
+
+#[test]
+fn submit_and_wait_through_registered_ring_fd() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(8)?;
+    ring.registrar().register_ring_fd()?;
+    assert!(ring.registrar().is_ring_fd_registered());
+
+    let err = ring.registrar().register_ring_fd().unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EEXIST));
+
+    submit_nops(&mut ring, 2);
+    assert_eq!(ring.submit_sqes()?, 2);
+    assert_eq!(ring.wait_for_cqe()?.user_data(), 0);
+    assert_eq!(ring.wait_for_cqe()?.user_data(), 1);
+
+    submit_nops(&mut ring, 3);
+    assert_eq!(ring.submit_sqes_and_wait(3)?, 3);
+    assert_eq!(ring.cqes().count(), 3);
+
+    submit_nops(&mut ring, 1);
+    let cqe = ring.wait_for_cqe_with_timeout(Duration::from_secs(1))?;
+    assert_eq!(cqe.user_data(), 0);
+
+    let err = ring.wait_for_cqe_with_timeout(Duration::from_millis(10)).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ETIME));
+
+    submit_nops(&mut ring, 2);
+    ring.sq().submit()?;
+    let user_data: Vec<u64> = ring.cqes_blocking(2).take(2).map(|cqe| cqe.unwrap().user_data()).collect();
+    assert_eq!(user_data, [0, 1]);
+
+    ring.registrar().unregister_ring_fd()?;
+    assert!(!ring.registrar().is_ring_fd_registered());
+    assert!(ring.registrar().unregister_ring_fd().is_err());
+
+    submit_nops(&mut ring, 1);
+    ring.submit_sqes_and_wait(1)?;
+    assert_eq!(ring.peek_for_cqe().unwrap().user_data(), 0);
+    Ok(())
+}
+
+#[test]
+fn drop_unregisters_ring_fd() -> io::Result<()> {
+    // each thread has 16 registered ring slots, which would run out if dropped rings held on to
+    // theirs
+    for _ in 0..32 {
+        let ring = iou::IoUring::new(2)?;
+        ring.registrar().register_ring_fd()?;
+    }
+    Ok(())
+}
+
+#[test]
+fn enter_from_another_thread() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(8)?;
+    ring.registrar().register_ring_fd()?;
+
+    // another thread enters through the ring fd, as the registered one is not in its table
+    let mut ring = std::thread::spawn(move || -> io::Result<iou::IoUring> {
+        assert!(ring.registrar().unregister_ring_fd().is_err());
+        submit_nops(&mut ring, 2);
+        assert_eq!(ring.submit_sqes_and_wait(2)?, 2);
+        assert_eq!(ring.cqes().count(), 2);
+        Ok(ring)
+    }).join().unwrap()?;
+
+    submit_nops(&mut ring, 1);
+    ring.submit_sqes_and_wait(1)?;
+    assert_eq!(ring.peek_for_cqe().unwrap().user_data(), 0);
+    ring.registrar().unregister_ring_fd()?;
+    Ok(())
+}
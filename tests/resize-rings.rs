@@ -0,0 +1,98 @@
+use std::io;
+
+use iou::{IoUring, SetupFeatures, SetupFlags};
+
+fn nops(ring: &mut IoUring, user_data: impl Iterator<Item = u64>) -> io::Result<u32> {
+    for user_data in user_data {
+        unsafe {
+            let mut sqe = ring.prepare_sqe().unwrap();
+            sqe.prep_nop();
+            sqe.set_user_data(user_data);
+        }
+    }
+    ring.submit_sqes()
+}
+
+#[test]
+fn grow_live_rings() -> io::Result<()> {
+    let flags = SetupFlags::SINGLE_ISSUER | SetupFlags::DEFER_TASKRUN;
+    let mut ring = match IoUring::new_with_flags(4, flags, SetupFeatures::empty()) {
+        Ok(ring) => ring,
+        // the kernel does not support deferred task work
+        Err(err) if err.raw_os_error() == Some(libc::EINVAL) => return Ok(()),
+        Err(err) => return Err(err),
+    };
+
+    // completions which are ready before the resize are still there after it
+    assert_eq!(nops(&mut ring, 0..3)?, 3);
+    match ring.resize_rings(16, 64) {
+        Ok(()) => {}
+        // the kernel can't resize rings
+        Err(err) if err.raw_os_error() == Some(libc::EINVAL) => return Ok(()),
+        Err(err) => return Err(err),
+    }
+    assert_eq!((ring.params().sq_entries(), ring.params().cq_entries()), (16, 64));
+    let user_data: Vec<u64> = ring.cqes().map(|cqe| cqe.user_data()).collect();
+    assert_eq!(user_data, [0, 1, 2]);
+
+    // the new queues are used in full
+    assert_eq!(ring.sq_space_left(), 16);
+    assert_eq!(nops(&mut ring, 0..16)?, 16);
+    ring.wait_for_cqes(16)?;
+    assert_eq!(ring.cqes().map(|cqe| cqe.user_data()).collect::<Vec<_>>(), (0..16).collect::<Vec<_>>());
+
+    // prepared events must be submitted first
+    unsafe { ring.prepare_sqe().unwrap().prep_nop(); }
+    assert_eq!(ring.resize_rings(4, 8).unwrap_err().raw_os_error(), Some(libc::EBUSY));
+    ring.submit_sqes_and_wait(1)?;
+    ring.peek_for_cqe().unwrap();
+
+    // and rings can shrink again
+    ring.resize_rings(4, 8)?;
+    assert_eq!(nops(&mut ring, 0..4)?, 4);
+    Ok(())
+}
+
+#[test]
+fn resize_with_unsubmitted_sqes() -> io::Result<()> {
+    let flags = SetupFlags::SINGLE_ISSUER | SetupFlags::DEFER_TASKRUN;
+    let mut ring = match IoUring::new_with_flags(4, flags, SetupFeatures::empty()) {
+        Ok(ring) => ring,
+        // the kernel does not support deferred task work
+        Err(err) if err.raw_os_error() == Some(libc::EINVAL) => return Ok(()),
+        Err(err) => return Err(err),
+    };
+
+    for user_data in 0..2 {
+        unsafe {
+            let mut sqe = ring.prepare_sqe().unwrap();
+            sqe.prep_nop();
+            sqe.set_user_data(user_data);
+        }
+    }
+    match ring.resize_rings(8, 16) {
+        Err(err) if err.raw_os_error() == Some(libc::EBUSY) => {}
+        // the kernel can't resize rings
+        Err(err) if err.raw_os_error() == Some(libc::EINVAL) => return Ok(()),
+        result => panic!("resized with prepared SQEs: {:?}", result),
+    }
+    assert_eq!(ring.params().sq_entries(), 4);
+
+    // the prepared events are submitted as they were, and every slot of the resized queue
+    // submits its own SQE
+    assert_eq!(ring.submit_sqes_and_wait(2)?, 2);
+    assert_eq!(ring.cqes().map(|cqe| cqe.user_data()).collect::<Vec<_>>(), [0, 1]);
+    ring.resize_rings(8, 16)?;
+    assert_eq!(nops(&mut ring, 2..10)?, 8);
+    ring.wait_for_cqes(8)?;
+    assert_eq!(ring.cqes().map(|cqe| cqe.user_data()).collect::<Vec<_>>(), (2..10).collect::<Vec<_>>());
+    Ok(())
+}
+
+#[test]
+fn resize_needs_deferred_task_work() -> io::Result<()> {
+    let mut ring = IoUring::new(4)?;
+    assert_eq!(ring.resize_rings(8, 16).unwrap_err().raw_os_error(), Some(libc::EINVAL));
+    assert_eq!(ring.params().sq_entries(), 4);
+    Ok(())
+}
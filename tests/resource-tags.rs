@@ -0,0 +1,51 @@
+use std::fs::File;
+use std::io;
+use std::os::unix::io::AsRawFd;
+
+const FILE_TAG: u64 = 1 << 63 | 1;
+const BUF_TAG: u64 = 1 << 63 | 2;
+
+#[test]
+fn file_tag_posted_on_update() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(4)?;
+    let first = File::open(env!("CARGO_MANIFEST_DIR"))?;
+    let second = File::open(env!("CARGO_MANIFEST_DIR"))?;
+
+    let fds = [first.as_raw_fd(), second.as_raw_fd()];
+    let _ = ring.registrar().register_files_tagged(&fds, &[0, FILE_TAG])?;
+    assert!(ring.peek_for_cqe().is_none());
+
+    // replacing an untagged file posts nothing
+    let _ = ring.registrar().update_registered_files_tagged(0, &[second.as_raw_fd()], &[0])?;
+    assert!(ring.peek_for_cqe().is_none());
+
+    let _ = ring.registrar().update_registered_files_tagged(1, &[first.as_raw_fd()], &[FILE_TAG + 2])?;
+    let cqe = ring.wait_for_cqe()?;
+    assert_eq!(cqe.user_data(), FILE_TAG);
+    assert_eq!(cqe.result()?, 0);
+
+    ring.registrar().unregister_files()?;
+    assert_eq!(ring.wait_for_cqe()?.user_data(), FILE_TAG + 2);
+    Ok(())
+}
+
+#[test]
+fn buffer_tag_posted_on_update() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(4)?;
+
+    let bufs = vec![vec![0; 64].into_boxed_slice()];
+    let bufs: Vec<_> = ring.registrar().register_buffers_tagged(bufs, &[BUF_TAG])?.collect();
+    assert_eq!(bufs[0].index(), 0);
+    assert!(ring.peek_for_cqe().is_none());
+
+    let new_bufs = vec![vec![0; 64].into_boxed_slice()];
+    let _ = ring.registrar().update_registered_buffers_tagged(0, new_bufs, &[BUF_TAG + 2])?;
+    assert_eq!(ring.wait_for_cqe()?.user_data(), BUF_TAG);
+
+    // the old buffer may be freed now
+    drop(bufs);
+
+    ring.registrar().unregister_buffers()?;
+    assert_eq!(ring.wait_for_cqe()?.user_data(), BUF_TAG + 2);
+    Ok(())
+}
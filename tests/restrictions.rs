@@ -0,0 +1,56 @@
+use iou::registrar::Restrictions;
+use iou::sys::IoRingOp;
+
+#[test]
+fn restrictions_require_disabled_ring() -> std::io::Result<()> {
+    let ring = iou::IoUring::new(2)?;
+    let restrictions = Restrictions::new().allow_op(IoRingOp::IORING_OP_NOP);
+
+    let err = ring.registrar().register_restrictions(&restrictions).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EBADFD));
+    Ok(())
+}
+
+#[test]
+fn restricted_ring() -> std::io::Result<()> {
+    use iou::{SetupFeatures, SetupFlags};
+    use iou::sqe::SubmissionFlags;
+
+    let mut ring = iou::IoUring::new_with_flags(2, SetupFlags::R_DISABLED, SetupFeatures::empty())?;
+
+    unsafe { ring.prepare_sqe().unwrap().prep_nop(); }
+    let err = ring.submit_sqes().unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EBADFD));
+
+    let restrictions = Restrictions::new()
+        .allow_op(IoRingOp::IORING_OP_NOP)
+        .allow_sqe_flags(SubmissionFlags::IO_LINK);
+    ring.registrar().register_restrictions(&restrictions)?;
+    ring.registrar().enable_rings()?;
+
+    ring.submit_sqes_and_wait(1)?;
+    assert_eq!(ring.peek_for_cqe().unwrap().result()?, 0);
+
+    let file = std::fs::File::open(env!("CARGO_MANIFEST_DIR"))?;
+    let mut buf = [0; 8];
+    unsafe {
+        use std::os::unix::io::AsRawFd;
+        ring.prepare_sqe().unwrap().prep_read(file.as_raw_fd(), &mut buf[..], 0);
+    }
+    ring.submit_sqes_and_wait(1)?;
+    let err = ring.peek_for_cqe().unwrap().result().unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EACCES));
+
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_nop();
+        sqe.set_flags(SubmissionFlags::IO_DRAIN);
+    }
+    ring.submit_sqes_and_wait(1)?;
+    let err = ring.peek_for_cqe().unwrap().result().unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EACCES));
+
+    let err = ring.registrar().register_personality().unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EACCES));
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use std::io;
+use std::os::unix::io::{AsFd, AsRawFd, IntoRawFd};
+
+use iou::IoUring;
+
+#[test]
+fn ring_fd_traits() -> io::Result<()> {
+    let ring = IoUring::new(2)?;
+    assert_eq!(ring.as_raw_fd(), ring.raw_fd()?);
+    assert_eq!(ring.as_fd().as_raw_fd(), ring.raw_fd()?);
+
+    let fd = ring.try_clone_fd()?;
+    assert_ne!(fd.as_raw_fd(), ring.as_raw_fd());
+    let mut clone = unsafe { IoUring::from_ring_fd(fd.into_raw_fd(), ring.params())? };
+    unsafe { clone.prepare_sqe().unwrap().prep_nop(); }
+    clone.submit_sqes_and_wait(1)?;
+    assert!(clone.peek_for_cqe().is_some());
+    Ok(())
+}
+
+#[test]
+fn ring_fd_is_readable_with_cqes() -> io::Result<()> {
+    let mut ring = IoUring::new(2)?;
+    let mut pollfd = libc::pollfd { fd: ring.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+    assert_eq!(unsafe { libc::poll(&mut pollfd, 1, 0) }, 0);
+
+    unsafe { ring.prepare_sqe().unwrap().prep_nop(); }
+    ring.submit_sqes_and_wait(1)?;
+    assert_eq!(unsafe { libc::poll(&mut pollfd, 1, 0) }, 1);
+    assert_eq!(pollfd.revents & libc::POLLIN, libc::POLLIN);
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use std::io;
+
+use iou::{IoUring, RingMemory, SetupFeatures, SetupFlags};
+
+fn run_nops(ring: &mut IoUring, count: u64) -> io::Result<()> {
+    for user_data in 0..count {
+        unsafe {
+            let mut sqe = ring.prepare_sqe().unwrap();
+            sqe.prep_nop();
+            sqe.set_user_data(user_data);
+        }
+    }
+    assert_eq!(ring.submit_sqes_and_wait(count as u32)?, count as u32);
+    let user_data: Vec<u64> = ring.cqes().map(|cqe| cqe.user_data()).collect();
+    assert_eq!(user_data, (0..count).collect::<Vec<_>>());
+    Ok(())
+}
+
+#[test]
+fn ring_in_user_memory() -> io::Result<()> {
+    let memory = RingMemory::new(100)?;
+    assert_eq!(memory.entries(), 128);
+
+    let mut ring = IoUring::new_with_memory(memory, SetupFlags::empty(), SetupFeatures::empty())?;
+    assert_eq!(ring.sq_space_left(), 128);
+
+    // go around the rings a few times
+    for _ in 0..4 {
+        run_nops(&mut ring, 100)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn ring_in_hugepages() -> io::Result<()> {
+    let memory = match RingMemory::with_hugepages(4096) {
+        Ok(memory) => memory,
+        // the system has no hugepages reserved
+        Err(err) if err.raw_os_error() == Some(libc::ENOMEM) => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    assert_eq!(memory.total_len() % (2 << 20), 0);
+
+    let mut ring = IoUring::new_with_memory(memory, SetupFlags::empty(), SetupFeatures::empty())?;
+    run_nops(&mut ring, 64)
+}
+
+#[test]
+fn ring_with_registered_fd_only() -> io::Result<()> {
+    let memory = RingMemory::new(8)?;
+    let flags = SetupFlags::REGISTERED_FD_ONLY;
+    let mut ring = match IoUring::new_with_memory(memory, flags, SetupFeatures::empty()) {
+        Ok(ring) => ring,
+        // the kernel does not support rings without an fd
+        Err(err) if err.raw_os_error() == Some(libc::EINVAL) => return Ok(()),
+        Err(err) => return Err(err),
+    };
+
+    assert_eq!(ring.raw_fd().unwrap_err().raw_os_error(), Some(libc::EBADF));
+    assert!(ring.registered_fd().is_some());
+    assert!(ring.registrar().is_ring_fd_registered());
+    assert!(ring.registrar().unregister_ring_fd().is_err());
+
+    // registration goes through the registered ring fd as well
+    assert!(ring.registrar().probe()?.supports(iou::sys::IoRingOp::IORING_OP_NOP));
+    let eventfd = iou::registrar::EventFd::new()?;
+    ring.registrar().register_owned_eventfd(&eventfd)?;
+
+    run_nops(&mut ring, 8)?;
+    assert!(eventfd.read_events()? > 0);
+    Ok(())
+}
+
+#[test]
+fn estimate_ring_memory_size() {
+    let page_len = unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize };
+    let size = IoUring::ring_memory_size(100, SetupFlags::empty());
+    assert_eq!(size % page_len, 0);
+    // entries are rounded up to a power of two, as the kernel does
+    assert_eq!(size, IoUring::ring_memory_size(128, SetupFlags::empty()));
+    assert!(size < IoUring::ring_memory_size(4096, SetupFlags::empty()));
+
+    // big entries take more memory
+    assert!(IoUring::ring_memory_size(4096, SetupFlags::SQE128) > IoUring::ring_memory_size(4096, SetupFlags::empty()));
+    assert!(IoUring::ring_memory_size(4096, SetupFlags::CQE32) > IoUring::ring_memory_size(4096, SetupFlags::empty()));
+
+    // the estimate covers memory the application allocates for the same rings
+    assert!(IoUring::ring_memory_size(4096, SetupFlags::empty()) >= RingMemory::new(4096).unwrap().total_len());
+}
@@ -0,0 +1,37 @@
+use std::io;
+use std::net::UdpSocket;
+use std::os::unix::io::AsRawFd;
+
+use iou::sqe::{MsgFlags, RecvFrom, SockAddr};
+
+const MESSAGE: &[u8] = b"Hello World";
+
+#[test]
+fn send_to_recv_from() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(2)?;
+    let sender = UdpSocket::bind("127.0.0.1:0")?;
+    let receiver = UdpSocket::bind("127.0.0.1:0")?;
+    let dest = SockAddr::from(receiver.local_addr()?);
+
+    let mut buf = [0; 64];
+    let mut from = RecvFrom::new();
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_recv_from(receiver.as_raw_fd(), &mut buf, &mut from, MsgFlags::empty());
+        sqe.set_user_data(1);
+
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_send_to(sender.as_raw_fd(), MESSAGE, &dest, MsgFlags::empty());
+        sqe.set_user_data(2);
+    }
+    ring.submit_sqes_and_wait(2)?;
+
+    for cqe in ring.cqes() {
+        assert_eq!(cqe.result()? as usize, MESSAGE.len());
+    }
+    assert_eq!(&buf[..MESSAGE.len()], MESSAGE);
+
+    let sender_addr = SockAddr::from(sender.local_addr()?);
+    assert_eq!(unsafe { from.socket_addr()? }, sender_addr);
+    Ok(())
+}
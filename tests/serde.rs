@@ -0,0 +1,36 @@
+#![cfg(feature = "serde")]
+
+use std::io;
+
+use iou::{Capabilities, CapabilityReport, IoUring, Probe, SetupFeatures};
+
+#[test]
+fn serializes_setup_features_as_names() {
+    let features = SetupFeatures::SINGLE_MMAP | SetupFeatures::NODROP;
+    assert_eq!(serde_json::to_string(&features).unwrap(), r#"["SINGLE_MMAP","NODROP"]"#);
+    assert_eq!(serde_json::to_string(&SetupFeatures::empty()).unwrap(), "[]");
+}
+
+#[test]
+fn serializes_probe_ops() -> io::Result<()> {
+    let probe = serde_json::to_value(Probe::new()?).unwrap();
+    assert!(probe["last_op"].as_u64().unwrap() > 0);
+    let nop = &probe["ops"][0];
+    assert_eq!(nop["opcode"], 0);
+    assert_eq!(nop["name"], "NOP");
+    assert_eq!(nop["supported"], true);
+    Ok(())
+}
+
+#[test]
+fn serializes_capabilities() -> io::Result<()> {
+    let ring = IoUring::new(1)?;
+    let caps = serde_json::to_value(Capabilities::for_ring(&ring)?).unwrap();
+    assert!(caps["kernel_version"]["major"].as_u64().unwrap() >= 5);
+    assert!(caps["features"].as_array().unwrap().iter().any(|feature| feature == "SINGLE_MMAP"));
+    assert!(caps["probe"]["ops"].as_array().unwrap().len() > 1);
+
+    let report = serde_json::to_value(CapabilityReport::detect()?).unwrap();
+    assert!(report["supported_ops"].as_array().unwrap().iter().any(|op| op == "IORING_OP_NOP"));
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use std::io;
+use std::thread;
+
+use iou::IoUring;
+
+#[test]
+fn many_producers() -> io::Result<()> {
+    const PRODUCERS: u64 = 4;
+    const EVENTS: u64 = 64;
+
+    let (sq, mut cq, _registrar) = IoUring::new(16)?.into_parts();
+    let sq = sq.into_shared();
+
+    let producers: Vec<_> = (0..PRODUCERS).map(|producer| {
+        let sq = sq.clone();
+        thread::spawn(move || -> io::Result<()> {
+            for n in 0..EVENTS {
+                sq.prepare_sqe_with(|sqe| unsafe {
+                    sqe.prep_nop();
+                    sqe.set_user_data(producer * EVENTS + n);
+                })?;
+                if n % 8 == 7 {
+                    sq.submit()?;
+                }
+            }
+            sq.submit()?;
+            Ok(())
+        })
+    }).collect();
+
+    let mut user_data = Vec::new();
+    while user_data.len() < (PRODUCERS * EVENTS) as usize {
+        user_data.push(cq.wait_for_cqe()?.user_data());
+    }
+    for producer in producers {
+        producer.join().unwrap()?;
+    }
+
+    user_data.sort_unstable();
+    assert_eq!(user_data, (0..PRODUCERS * EVENTS).collect::<Vec<_>>());
+    Ok(())
+}
+
+#[test]
+fn lock_gives_the_whole_queue() -> io::Result<()> {
+    let mut ring = IoUring::new(2)?;
+    let sq = ring.sq().into_shared();
+    {
+        let mut sq = sq.lock();
+        unsafe {
+            sq.prepare_sqe().unwrap().prep_nop();
+            sq.prepare_sqe().unwrap().prep_nop();
+        }
+        assert_eq!(sq.space_left(), 0);
+    }
+
+    // a full queue is submitted to make room
+    sq.prepare_sqe_with(|sqe| unsafe { sqe.prep_nop() })?;
+    assert_eq!(sq.submit_and_wait(3)?, 1);
+    drop(sq);
+    assert_eq!(ring.cqes().count(), 3);
+    Ok(())
+}
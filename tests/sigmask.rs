@@ -0,0 +1,67 @@
+use std::io;
+use std::mem;
+
+use iou::IoUring;
+
+extern "C" fn ignore(_: libc::c_int) { }
+
+fn sigset(signals: &[libc::c_int]) -> libc::sigset_t {
+    unsafe {
+        let mut set = mem::zeroed();
+        libc::sigemptyset(&mut set);
+        for &signal in signals {
+            libc::sigaddset(&mut set, signal);
+        }
+        set
+    }
+}
+
+#[test]
+fn submit_and_wait_with_sigmask() -> io::Result<()> {
+    let mut ring = IoUring::new(2)?;
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_nop();
+        sqe.set_user_data(3);
+    }
+    let mask = sigset(&[]);
+    assert_eq!(ring.submit_sqes_and_wait_with_sigmask(1, &mask)?, 1);
+    assert_eq!(ring.wait_for_cqe_with_sigmask(&mask)?.user_data(), 3);
+    Ok(())
+}
+
+#[test]
+fn signal_interrupts_wait() -> io::Result<()> {
+    let mut ring = IoUring::new(2)?;
+    unsafe {
+        let mut action: libc::sigaction = mem::zeroed();
+        action.sa_sigaction = ignore as extern "C" fn(libc::c_int) as usize;
+        libc::sigaction(libc::SIGUSR1, &action, std::ptr::null_mut());
+
+        // the signal stays pending until the wait unblocks it
+        let blocked = sigset(&[libc::SIGUSR1]);
+        libc::pthread_sigmask(libc::SIG_BLOCK, &blocked, std::ptr::null_mut());
+        libc::pthread_kill(libc::pthread_self(), libc::SIGUSR1);
+    }
+
+    let err = ring.wait_for_cqe_with_sigmask(&sigset(&[])).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EINTR));
+    Ok(())
+}
+
+#[test]
+fn completion_queue_wait_with_optional_sigmask() -> io::Result<()> {
+    let mut ring = IoUring::new(4)?;
+    for _ in 0..2 {
+        unsafe { ring.prepare_sqe().unwrap().prep_nop(); }
+    }
+    ring.submit_sqes()?;
+
+    let (_, mut cq, _) = ring.queues();
+    cq.wait_with_sigmask(2, Some(&sigset(&[libc::SIGTERM])))?;
+    cq.wait_with_sigmask(2, None)?;
+    assert_eq!(cq.ready(), 2);
+    drop(cq);
+    ring.wait_for_cqes_with_sigmask(1, None)?;
+    Ok(())
+}
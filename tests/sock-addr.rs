@@ -0,0 +1,51 @@
+use std::net::SocketAddr;
+use std::path::Path;
+
+use iou::sqe::{AddressFamily, SockAddr};
+
+#[test]
+fn inet_round_trip() {
+    for addr in &["127.0.0.1:8080", "[::1]:443"] {
+        let addr: SocketAddr = addr.parse().unwrap();
+        let sock_addr = SockAddr::from(addr);
+        assert_eq!(sock_addr.to_socket_addr(), Some(addr));
+        assert_eq!(SockAddr::from_bytes(sock_addr.as_bytes()), Some(sock_addr));
+    }
+    let v6: SocketAddr = "[::1]:443".parse().unwrap();
+    assert_eq!(SockAddr::from(v6).family(), Some(AddressFamily::Inet6));
+}
+
+#[test]
+fn unix_path() -> std::io::Result<()> {
+    let addr = SockAddr::new_unix("/tmp/iou.sock")?;
+    assert_eq!(addr.family(), Some(AddressFamily::Unix));
+    assert_eq!(addr.as_unix_path(), Some(Path::new("/tmp/iou.sock")));
+    assert_eq!(addr.to_socket_addr(), None);
+
+    assert!(SockAddr::new_unix("x".repeat(200)).is_err());
+    Ok(())
+}
+
+#[test]
+fn from_bytes_bounds() {
+    assert_eq!(SockAddr::from_bytes(&[0]), None);
+    assert_eq!(SockAddr::from_bytes(&[0; 256]), None);
+}
+
+#[test]
+#[cfg(feature = "nix")]
+fn nix_conversions() {
+    use std::convert::TryFrom;
+    use iou::sqe::{MsgFlags, OFlag};
+    use nix::sys::socket::{InetAddr, SockAddr as NixSockAddr};
+
+    let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+    let nix_addr = NixSockAddr::new_inet(InetAddr::from_std(&addr));
+    let sock_addr = SockAddr::from(nix_addr);
+    assert_eq!(sock_addr, SockAddr::from(addr));
+    assert_eq!(NixSockAddr::try_from(sock_addr).unwrap(), nix_addr);
+
+    let flags = OFlag::from(nix::fcntl::OFlag::O_CREAT | nix::fcntl::OFlag::O_TRUNC);
+    assert_eq!(flags, OFlag::O_CREAT | OFlag::O_TRUNC);
+    assert_eq!(nix::sys::socket::MsgFlags::from(MsgFlags::MSG_PEEK), nix::sys::socket::MsgFlags::MSG_PEEK);
+}
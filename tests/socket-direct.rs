@@ -0,0 +1,42 @@
+use std::io::{self, Read};
+use std::net::TcpListener;
+
+use iou::registrar::PLACEHOLDER_FD;
+use iou::sqe::{AddressFamily, FileIndex, MsgFlags, SockAddr, SockFlag, SockProtocol, SockType};
+
+const MESSAGE: &[u8] = b"Hello World";
+
+#[test]
+fn socket_direct() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(2)?;
+    let _ = ring.registrar().register_files(&[PLACEHOLDER_FD; 4])?;
+    let listener = TcpListener::bind(("127.0.0.1", 0))?;
+    let addr = SockAddr::from(listener.local_addr()?);
+
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_socket_direct(AddressFamily::Inet, SockType::Stream, SockFlag::empty(),
+                               Some(SockProtocol::Tcp), FileIndex::Alloc);
+    }
+    ring.submit_sqes()?;
+    let socket = ring.wait_for_cqe()?.direct_slot(FileIndex::Alloc)?;
+
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_connect(socket, &addr);
+    }
+    ring.submit_sqes()?;
+    ring.wait_for_cqe()?.result()?;
+
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_send(socket, MESSAGE, MsgFlags::empty());
+    }
+    ring.submit_sqes()?;
+    assert_eq!(ring.wait_for_cqe()?.result()? as usize, MESSAGE.len());
+
+    let mut buf = [0; MESSAGE.len()];
+    listener.accept()?.0.read_exact(&mut buf)?;
+    assert_eq!(buf, MESSAGE);
+    Ok(())
+}
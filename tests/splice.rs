@@ -0,0 +1,32 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::path::PathBuf;
+
+use iou::sqe::SpliceFlags;
+
+#[test]
+fn splice_from_offset() -> io::Result<()> {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("props");
+    path.push("text.txt");
+    let file = File::open(&path)?;
+    let text = std::fs::read(&path)?;
+
+    let (pipe_read, pipe_write) = nix::unistd::pipe().unwrap();
+    let mut pipe_read = unsafe { File::from_raw_fd(pipe_read) };
+    let pipe_write = unsafe { File::from_raw_fd(pipe_write) };
+
+    let mut ring = iou::IoUring::new(1)?;
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_splice(file.as_raw_fd(), Some(2), pipe_write.as_raw_fd(), None, 16, SpliceFlags::empty());
+    }
+    ring.submit_sqes()?;
+    assert_eq!(ring.wait_for_cqe()?.result()?, 16);
+
+    let mut buf = [0; 16];
+    pipe_read.read_exact(&mut buf)?;
+    assert_eq!(&buf[..], &text[2..18]);
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use std::io;
+
+use iou::{IoUring, SQFlags};
+
+#[test]
+fn detect_cq_overflow() -> io::Result<()> {
+    // 2 SQEs and 4 CQEs
+    let mut ring = IoUring::new(2)?;
+    assert_eq!(ring.sq_flags(), SQFlags::empty());
+
+    for _ in 0..2 {
+        unsafe {
+            ring.prepare_sqe().unwrap().prep_nop();
+            ring.prepare_sqe().unwrap().prep_nop();
+        }
+        ring.submit_sqes_and_wait(2)?;
+    }
+    assert!(!ring.sq_flags().contains(SQFlags::CQ_OVERFLOW));
+
+    unsafe { ring.prepare_sqe().unwrap().prep_nop(); }
+    ring.submit_sqes()?;
+    assert!(ring.sq_flags().contains(SQFlags::CQ_OVERFLOW));
+
+    // waiting flushes the backlog once there is room
+    assert_eq!(ring.cqes().count(), 4);
+    ring.wait_for_cqe()?;
+    assert!(!ring.sq().flags().contains(SQFlags::CQ_OVERFLOW));
+    Ok(())
+}
+
+#[test]
+fn count_dropped_sqes() -> io::Result<()> {
+    let mut ring = IoUring::new(2)?;
+    assert_eq!(ring.sq_dropped(), 0);
+
+    // point the next entry of the SQ array past the end of the SQEs
+    unsafe {
+        let sq = &mut ring.raw_mut().sq;
+        let tail = *sq.ktail;
+        *sq.array.add((tail & *sq.kring_mask) as usize) = 1000;
+        *sq.ktail = tail.wrapping_add(1);
+    }
+    ring.submit_sqes()?;
+    assert_eq!(ring.sq_dropped(), 1);
+    assert!(ring.peek_for_cqe().is_none());
+    Ok(())
+}
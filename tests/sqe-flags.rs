@@ -0,0 +1,50 @@
+use std::fs::File;
+use std::io;
+use std::os::unix::io::AsRawFd;
+
+use iou::sqe::SubmissionFlags;
+
+#[test]
+fn add_clear_and_replace_flags() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(2)?;
+    let file = File::open("Cargo.toml")?;
+    let fd = ring.registrar().register_files(&[file.as_raw_fd()])?.next().unwrap();
+    let mut buf = [0; 16];
+
+    let mut sqe = ring.prepare_sqe().unwrap();
+    unsafe { sqe.prep_read(fd, &mut buf[..], 0); }
+    assert_eq!(sqe.flags(), SubmissionFlags::FIXED_FILE);
+
+    sqe.add_flags(SubmissionFlags::IO_DRAIN | SubmissionFlags::ASYNC);
+    assert_eq!(sqe.flags(), SubmissionFlags::FIXED_FILE | SubmissionFlags::IO_DRAIN | SubmissionFlags::ASYNC);
+
+    sqe.clear_flags(SubmissionFlags::ASYNC | SubmissionFlags::IO_LINK);
+    assert_eq!(sqe.flags(), SubmissionFlags::FIXED_FILE | SubmissionFlags::IO_DRAIN);
+
+    sqe.replace_flags(SubmissionFlags::FIXED_FILE);
+    assert_eq!(sqe.flags(), SubmissionFlags::FIXED_FILE);
+
+    ring.submit_sqes()?;
+    assert_eq!(ring.wait_for_cqe()?.result()?, 16);
+    assert!(buf.starts_with(b"[package]"));
+    Ok(())
+}
+
+#[test]
+fn debug_decodes_opcode_and_flags() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(2)?;
+    let mut buf = [0; 16];
+
+    let mut sqe = ring.prepare_sqe().unwrap();
+    unsafe {
+        sqe.prep_read(7, &mut buf[..], 512);
+        sqe.set_user_data(0xbeef);
+    }
+    sqe.add_flags(SubmissionFlags::IO_LINK);
+    assert_eq!(sqe.opcode(), iou::sys::IoRingOp::IORING_OP_READ as u8);
+    assert_eq!(
+        format!("{:?}", sqe),
+        "SQE { opcode: IORING_OP_READ, fd: 7, len: 16, off: 512, user_data: 0xbeef, flags: IO_LINK }",
+    );
+    Ok(())
+}
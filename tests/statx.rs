@@ -0,0 +1,38 @@
+use std::ffi::CString;
+use std::io;
+use std::path::PathBuf;
+
+use iou::sqe::{SFlag, Statx, StatxFlags, StatxMode};
+
+#[test]
+fn statx() -> io::Result<()> {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("props");
+    path.push("text.txt");
+    let metadata = std::fs::metadata(&path)?;
+    let c_path = CString::new(path.to_str().unwrap()).unwrap();
+
+    let mut ring = iou::IoUring::new(1)?;
+    let mut statx = Statx::new();
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        let mask = StatxMode::STATX_SIZE | StatxMode::STATX_MTIME | StatxMode::STATX_TYPE;
+        sqe.prep_statx(libc::AT_FDCWD, &c_path, StatxFlags::empty(), mask, &mut statx);
+    }
+    ring.submit_sqes()?;
+    ring.wait_for_cqe()?.result()?;
+
+    assert_eq!(statx.size(), Some(metadata.len()));
+    assert_eq!(statx.mtime(), Some(metadata.modified()?));
+    assert_eq!(statx.file_type(), Some(SFlag::S_IFREG));
+    assert!(!statx.mask().contains(StatxMode::STATX_BTIME) || statx.btime().is_some());
+    Ok(())
+}
+
+#[test]
+fn statx_empty() {
+    let statx = Statx::new();
+    assert!(statx.mask().is_empty());
+    assert_eq!(statx.size(), None);
+    assert_eq!(statx.mtime(), None);
+}
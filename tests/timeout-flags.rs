@@ -0,0 +1,86 @@
+use std::io;
+use std::time::{Duration, Instant, SystemTime};
+
+use iou::{IoUring, Timespec};
+use iou::sqe::{SubmissionFlags, TimeoutFlags};
+
+#[test]
+fn realtime_deadline() -> io::Result<()> {
+    let mut ring = IoUring::new(2)?;
+    let start = Instant::now();
+    let deadline = Timespec::from_system_time(SystemTime::now() + Duration::from_millis(10));
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_timeout(&deadline, 0, TimeoutFlags::TIMEOUT_ABS | TimeoutFlags::REALTIME);
+    }
+    ring.submit_sqes()?;
+    let cqe = ring.wait_for_cqe()?;
+    assert_eq!(cqe.result().unwrap_err().raw_os_error(), Some(libc::ETIME));
+    assert!(start.elapsed() >= Duration::from_millis(10));
+
+    // a relative timeout on the boot time clock
+    let ts = Timespec::from(Duration::from_millis(1));
+    unsafe { ring.prepare_sqe().unwrap().prep_timeout(&ts, 0, TimeoutFlags::BOOTTIME); }
+    ring.submit_sqes()?;
+    assert_eq!(ring.wait_for_cqe()?.result().unwrap_err().raw_os_error(), Some(libc::ETIME));
+    Ok(())
+}
+
+#[test]
+fn expired_timeout_does_not_break_links() -> io::Result<()> {
+    let mut ring = IoUring::new(4)?;
+    let ts = Timespec::from(Duration::from_millis(1));
+    for flags in [TimeoutFlags::empty(), TimeoutFlags::ETIME_SUCCESS] {
+        unsafe {
+            let mut sqe = ring.prepare_sqe().unwrap();
+            sqe.prep_timeout(&ts, 0, flags);
+            sqe.set_flags(SubmissionFlags::IO_LINK);
+            let mut sqe = ring.prepare_sqe().unwrap();
+            sqe.prep_nop();
+            sqe.set_user_data(1);
+        }
+        ring.submit_sqes_and_wait(2)?;
+        let timeout = ring.peek_for_cqe().unwrap();
+        assert_eq!(timeout.result().unwrap_err().raw_os_error(), Some(libc::ETIME));
+
+        let nop = ring.peek_for_cqe().unwrap();
+        assert_eq!(nop.user_data(), 1);
+        match flags.is_empty() {
+            true    => assert_eq!(nop.result().unwrap_err().raw_os_error(), Some(libc::ECANCELED)),
+            false   => assert_eq!(nop.result()?, 0),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn update_pending_timeout() -> io::Result<()> {
+    let mut ring = IoUring::new(2)?;
+    let long = Timespec::from(Duration::from_secs(60));
+    let short = Timespec::from(Duration::from_millis(1));
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_timeout(&long, 0, TimeoutFlags::empty());
+        sqe.set_user_data(7);
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_timeout_update(7, &short, TimeoutFlags::empty());
+        sqe.set_user_data(8);
+    }
+    ring.submit_sqes()?;
+
+    let start = Instant::now();
+    let mut results = vec![];
+    for _ in 0..2 {
+        let cqe = ring.wait_for_cqe()?;
+        results.push((cqe.user_data(), cqe.raw_result()));
+    }
+    results.sort();
+    assert_eq!(results, [(7, -libc::ETIME), (8, 0)]);
+    assert!(start.elapsed() < Duration::from_secs(60));
+
+    // the timeout is gone, so there is nothing left to update
+    unsafe { ring.prepare_sqe().unwrap().prep_timeout_update(7, &short, TimeoutFlags::empty()); }
+    ring.submit_sqes_and_wait(1)?;
+    assert_eq!(ring.peek_for_cqe().unwrap().result().unwrap_err().raw_os_error(), Some(libc::ENOENT));
+    Ok(())
+}
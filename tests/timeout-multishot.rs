@@ -0,0 +1,69 @@
+use std::io;
+use std::time::{Duration, Instant};
+
+use iou::{IoUring, Timespec};
+use iou::sqe::TimeoutFlags;
+
+#[test]
+fn tick_until_count() -> io::Result<()> {
+    let mut ring = IoUring::new(2)?;
+    let interval = Timespec::from(Duration::from_millis(5));
+    let start = Instant::now();
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_timeout_multishot(&interval, 3);
+        sqe.set_user_data(9);
+    }
+    ring.submit_sqes()?;
+
+    let mut more = vec![];
+    for _ in 0..3 {
+        let cqe = ring.wait_for_cqe()?;
+        assert_eq!(cqe.user_data(), 9);
+        assert_eq!(cqe.result().unwrap_err().raw_os_error(), Some(libc::ETIME));
+        more.push(cqe.more());
+    }
+    assert_eq!(more, [true, true, false]);
+    assert!(start.elapsed() >= Duration::from_millis(15));
+    Ok(())
+}
+
+#[test]
+fn tick_until_removed() -> io::Result<()> {
+    let mut ring = IoUring::new(2)?;
+    let interval = Timespec::from(Duration::from_millis(1));
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_timeout(&interval, 0, TimeoutFlags::MULTISHOT);
+        sqe.set_user_data(9);
+    }
+    ring.submit_sqes()?;
+    for _ in 0..5 {
+        assert!(ring.wait_for_cqe()?.more());
+    }
+
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_timeout_remove(9);
+        sqe.set_user_data(10);
+    }
+    ring.submit_sqes()?;
+
+    // ticks which were already posted may come before the removal
+    let (mut removed, mut stopped) = (false, false);
+    while !(removed && stopped) {
+        let cqe = ring.wait_for_cqe()?;
+        match cqe.user_data() {
+            9 if cqe.more() => {}
+            9 => {
+                assert_eq!(cqe.result().unwrap_err().raw_os_error(), Some(libc::ECANCELED));
+                stopped = true;
+            }
+            _ => {
+                assert_eq!(cqe.result()?, 0);
+                removed = true;
+            }
+        }
+    }
+    Ok(())
+}
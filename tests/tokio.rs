@@ -0,0 +1,58 @@
+#![cfg(feature = "tokio")]
+
+use std::io;
+use std::os::unix::{io::AsRawFd, net::UnixStream};
+use std::time::Duration;
+
+use iou::{AsyncIoUring, IoUring};
+use iou::sqe::PollFlags;
+
+#[tokio::test]
+async fn awaits_completions() -> io::Result<()> {
+    let mut ring = AsyncIoUring::new(IoUring::new(4)?)?;
+    let (read, mut write) = UnixStream::pair()?;
+
+    unsafe {
+        let mut sqe = ring.get_mut().prepare_sqe().unwrap();
+        sqe.prep_poll_add(read.as_raw_fd(), PollFlags::POLLIN);
+        sqe.set_user_data(1);
+    }
+    ring.get_mut().submit_sqes()?;
+
+    // the poll completes only once the other task writes
+    let writer = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        io::Write::write_all(&mut write, b"x")
+    });
+    let cqe = ring.next_cqe().await?;
+    assert_eq!(cqe.user_data(), 1);
+    assert!(cqe.result_as_poll_flags()?.contains(PollFlags::POLLIN));
+    writer.await.unwrap()?;
+
+    for user_data in 2..4 {
+        unsafe {
+            let mut sqe = ring.get_mut().prepare_sqe().unwrap();
+            sqe.prep_nop();
+            sqe.set_user_data(user_data);
+        }
+    }
+    ring.get_mut().submit_sqes()?;
+    let cqes = ring.wait_for(2).await?;
+    assert_eq!(cqes.iter().map(|cqe| cqe.user_data()).collect::<Vec<_>>(), [2, 3]);
+
+    let mut ring = ring.into_inner()?;
+    assert!(ring.peek_for_cqe().is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn replaces_registered_eventfd() -> io::Result<()> {
+    let ring = IoUring::new(2)?;
+    ring.registrar().register_owned_eventfd(&iou::registrar::EventFd::new()?)?;
+    let mut ring = AsyncIoUring::new(ring)?;
+
+    unsafe { ring.get_mut().prepare_sqe().unwrap().prep_nop(); }
+    ring.get_mut().submit_sqes()?;
+    ring.next_cqe().await?;
+    Ok(())
+}
@@ -0,0 +1,114 @@
+#![cfg(feature = "tracing")]
+
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+// records the message and the user_data field of every event
+#[derive(Clone, Default)]
+struct Recorder {
+    events: Arc<Mutex<Vec<(String, u64)>>>,
+}
+
+#[derive(Default)]
+struct Fields {
+    message: String,
+    user_data: u64,
+}
+
+impl Visit for Fields {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        if field.name() == "user_data" {
+            self.user_data = value;
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        }
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target() == "iou"
+    }
+
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) { }
+
+    fn record_follows_from(&self, _: &Id, _: &Id) { }
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        self.events.lock().unwrap().push((fields.message, fields.user_data));
+    }
+
+    fn enter(&self, _: &Id) { }
+
+    fn exit(&self, _: &Id) { }
+}
+
+#[test]
+fn traces_submissions_and_completions() -> io::Result<()> {
+    let recorder = Recorder::default();
+    let events = recorder.events.clone();
+
+    tracing::subscriber::with_default(recorder, || {
+        let mut ring = iou::IoUring::new(2)?;
+        unsafe {
+            let mut sqe = ring.prepare_sqe().unwrap();
+            sqe.prep_nop();
+            sqe.set_user_data(42);
+        }
+        ring.submit_sqes_and_wait(1)?;
+        assert_eq!(ring.peek_for_cqe().unwrap().user_data(), 42);
+        Ok::<_, io::Error>(())
+    })?;
+
+    let events = events.lock().unwrap();
+    assert_eq!(*events, [("submit".to_owned(), 42), ("complete".to_owned(), 42)]);
+    Ok(())
+}
+
+#[test]
+fn traces_each_batched_completion_once() -> io::Result<()> {
+    let recorder = Recorder::default();
+    let events = recorder.events.clone();
+
+    tracing::subscriber::with_default(recorder, || {
+        let mut ring = iou::IoUring::new(2)?;
+        for user_data in 0..2 {
+            unsafe {
+                let mut sqe = ring.prepare_sqe().unwrap();
+                sqe.prep_nop();
+                sqe.set_user_data(user_data);
+            }
+        }
+        ring.submit_sqes_and_wait(2)?;
+
+        // iterating and formatting the batch again does not trace its CQEs again
+        let (_, mut cq, _) = ring.queues();
+        let batch = cq.iter();
+        assert_eq!(batch.iter().count(), 2);
+        assert_eq!(batch.iter().count(), 2);
+        let _ = format!("{:?}", batch);
+        Ok::<_, io::Error>(())
+    })?;
+
+    let events = events.lock().unwrap();
+    let completions: Vec<u64> = events.iter()
+        .filter(|(message, _)| message == "complete")
+        .map(|&(_, user_data)| user_data)
+        .collect();
+    assert_eq!(completions, [0, 1]);
+    Ok(())
+}
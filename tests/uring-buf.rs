@@ -0,0 +1,55 @@
+use std::io::{self, IoSlice, IoSliceMut};
+use std::os::unix::{io::AsRawFd, net::UnixStream};
+
+use iou::registrar::RegisteredBuf;
+use iou::sqe::MsgFlags;
+
+const MESSAGE: &[u8] = b"Hello World";
+
+#[test]
+fn send_and_recv_registered_bufs() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(2)?;
+    let (sender, receiver) = UnixStream::pair()?;
+    let mut bufs = ring.registrar().register_buffers(vec![vec![0; 64].into_boxed_slice()])?;
+    let mut buf: RegisteredBuf = bufs.next().unwrap();
+    buf.slice_mut(16..16 + MESSAGE.len()).copy_from_slice(MESSAGE);
+
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_send(sender.as_raw_fd(), buf.slice(16..16 + MESSAGE.len()), MsgFlags::empty());
+        sqe.set_user_data(1);
+
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_recv(receiver.as_raw_fd(), buf.slice_mut(32..64), MsgFlags::empty());
+        sqe.set_user_data(2);
+    }
+    ring.submit_sqes_and_wait(2)?;
+
+    for cqe in ring.cqes() {
+        assert_eq!(cqe.result_as_size()?, MESSAGE.len());
+    }
+    assert_eq!(&buf[32..32 + MESSAGE.len()], MESSAGE);
+    Ok(())
+}
+
+#[test]
+fn send_and_recv_io_slices() -> io::Result<()> {
+    let mut ring = iou::IoUring::new(2)?;
+    let (sender, receiver) = UnixStream::pair()?;
+
+    let mut received = vec![0; 64];
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_send(sender.as_raw_fd(), IoSlice::new(MESSAGE), MsgFlags::empty());
+
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_recv(receiver.as_raw_fd(), IoSliceMut::new(&mut received), MsgFlags::empty());
+    }
+    ring.submit_sqes_and_wait(2)?;
+
+    for cqe in ring.cqes() {
+        assert_eq!(cqe.result_as_size()?, MESSAGE.len());
+    }
+    assert_eq!(&received[..MESSAGE.len()], MESSAGE);
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use std::convert::TryFrom;
+use std::io;
+
+use iou::{IoUring, UserData};
+
+#[test]
+fn reserved_user_data() {
+    assert_eq!(UserData::new(UserData::RESERVED), None);
+    assert!(UserData::try_from(u64::MAX).is_err());
+    assert_eq!(UserData::new(0xB00).map(UserData::get), Some(0xB00));
+}
+
+#[test]
+fn typed_user_data_round_trip() -> io::Result<()> {
+    let mut ring = IoUring::new(2)?;
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_nop();
+        sqe.set_typed_user_data(UserData::new(0xB00).unwrap());
+    }
+    ring.submit_sqes()?;
+    assert_eq!(ring.wait_for_cqe()?.user_data(), 0xB00);
+    Ok(())
+}
+
+#[test]
+fn boxed_user_data_round_trip() -> io::Result<()> {
+    let mut ring = IoUring::new(4)?;
+    unsafe {
+        for (n, mut sqe) in ring.prepare_sqes(2).unwrap().enumerate() {
+            sqe.prep_nop();
+            sqe.set_user_data_ptr(Box::new(vec![n; 3]));
+        }
+    }
+    ring.submit_sqes()?;
+
+    let mut seen = Vec::new();
+    for _ in 0..2 {
+        let cqe = ring.wait_for_cqe()?;
+        assert_ne!(cqe.user_data(), UserData::RESERVED);
+        seen.push(*unsafe { cqe.user_data_as::<Vec<usize>>() });
+    }
+    seen.sort();
+    assert_eq!(seen, vec![vec![0; 3], vec![1; 3]]);
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use std::io;
+
+use iou::IoUring;
+
+fn prep_nops(ring: &mut IoUring, user_data: std::ops::Range<u64>) {
+    for user_data in user_data {
+        unsafe {
+            let mut sqe = ring.prepare_sqe().unwrap();
+            sqe.prep_nop();
+            sqe.set_user_data(user_data);
+        }
+    }
+}
+
+#[test]
+fn wait_returns_every_ready_cqe() -> io::Result<()> {
+    let mut ring = IoUring::new(8)?;
+    prep_nops(&mut ring, 0..3);
+    ring.submit_sqes()?;
+
+    let cqes = ring.wait_for_cqes_batch(3)?;
+    let user_data: Vec<u64> = cqes.iter().map(|cqe| cqe.user_data()).collect();
+    assert_eq!(user_data, [0, 1, 2]);
+    assert_eq!(ring.cq_ready(), 0);
+    Ok(())
+}
+
+#[test]
+fn wait_into_reused_vec() -> io::Result<()> {
+    let mut ring = IoUring::new(8)?;
+    let (mut sq, mut cq, _) = ring.queues();
+    let mut cqes = Vec::new();
+
+    for round in 0..3 {
+        for user_data in round * 4..round * 4 + 4 {
+            unsafe {
+                let mut sqe = sq.prepare_sqe().unwrap();
+                sqe.prep_nop();
+                sqe.set_user_data(user_data);
+            }
+        }
+        sq.submit()?;
+        assert_eq!(cq.wait_for_cqes_into(2, &mut cqes)?, 4);
+    }
+    assert_eq!(cqes.len(), 12);
+    assert!(cqes.iter().map(|cqe| cqe.user_data()).eq(0..12));
+
+    // nothing is waited for with a count of zero
+    assert_eq!(cq.wait_for_cqes_into(0, &mut cqes)?, 0);
+    Ok(())
+}
@@ -0,0 +1,181 @@
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::UnixStream;
+use std::time::{Duration, Instant};
+
+use iou::{IoUring, SetupFeatures, Timespec};
+use iou::sqe::{PollFlags, TimeoutFlags};
+
+#[test]
+fn wait_for_cqe_times_out() -> io::Result<()> {
+    let mut ring = IoUring::new(2)?;
+    let start = Instant::now();
+    let err = ring.wait_for_cqe_with_timeout(Duration::from_millis(20)).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ETIME));
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    assert!(ring.peek_for_cqe().is_none());
+    Ok(())
+}
+
+#[test]
+fn timeout_leaves_the_queues_clean() -> io::Result<()> {
+    let mut ring = IoUring::new(2)?;
+    if !ring.has_feature(SetupFeatures::EXT_ARG) {
+        return Ok(());
+    }
+
+    // a full queue can be submitted with a timeout, which takes no slot of its own
+    for user_data in 0..2 {
+        unsafe {
+            let mut sqe = ring.prepare_sqe().unwrap();
+            sqe.prep_nop();
+            sqe.set_user_data(user_data);
+        }
+    }
+    assert_eq!(ring.submit_sqes_and_wait_with_timeout(2, Duration::from_secs(1))?, 2);
+    let user_data: Vec<u64> = ring.cqes().map(|cqe| cqe.user_data()).collect();
+    assert_eq!(user_data, [0, 1]);
+
+    let err = ring.submit_sqes_and_wait_with_timeout(1, Duration::from_millis(10)).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ETIME));
+    assert_eq!(ring.sq_space_left(), 2);
+    assert_eq!(ring.cq_ready(), 0);
+    Ok(())
+}
+
+#[test]
+fn timeout_with_pending_events() -> io::Result<()> {
+    let mut ring = IoUring::new(2)?;
+    let (read, _write) = UnixStream::pair()?;
+
+    // the poll is submitted by the wait, but never completes
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_poll_add(read.as_raw_fd(), PollFlags::POLLIN);
+        sqe.set_user_data(1);
+    }
+    let err = ring.wait_for_cqe_with_timeout(Duration::from_millis(10)).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ETIME));
+    assert_eq!(ring.sq_ready(), 0);
+    Ok(())
+}
+
+#[test]
+fn wait_until_deadline() -> io::Result<()> {
+    let mut ring = IoUring::new(2)?;
+    let start = Instant::now();
+    let err = ring.wait_for_cqes_until(1, start + Duration::from_millis(20)).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ETIME));
+    assert!(start.elapsed() >= Duration::from_millis(20));
+
+    // a deadline which has passed still returns ready events
+    unsafe { ring.prepare_sqe().unwrap().prep_nop(); }
+    ring.wait_for_cqes_until(1, start)?;
+    assert_eq!(ring.cq_ready(), 1);
+    Ok(())
+}
+
+#[test]
+fn completion_queue_waits_leave_the_submission_queue_alone() -> io::Result<()> {
+    let mut ring = IoUring::new(2)?;
+    let ext_arg = ring.has_feature(SetupFeatures::EXT_ARG);
+    let (mut sq, mut cq, _) = ring.queues();
+
+    // the waits of a split completion queue don't submit what the submission queue prepared
+    unsafe { sq.prepare_sqe().unwrap().prep_nop(); }
+    let deadline = Instant::now() + Duration::from_millis(10);
+    let expected = if ext_arg { libc::ETIME } else { libc::EOPNOTSUPP };
+    let err = cq.wait_until(1, deadline).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(expected));
+    let err = cq.cqes_blocking_with_timeout(1, Duration::from_millis(10)).next().unwrap().unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(expected));
+    let err = cq.poll_adaptive(1, Duration::from_millis(10)).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(expected));
+    assert_eq!(cq.ready(), 0);
+
+    assert_eq!(sq.submit()?, 1);
+    cq.wait(1)?;
+    assert_eq!(cq.ready(), 1);
+    Ok(())
+}
+
+#[test]
+fn wait_until_monotonic_deadline() -> io::Result<()> {
+    let mut ring = IoUring::new(2)?;
+    let deadline = Timespec::from_instant(Instant::now() + Duration::from_millis(20))?;
+
+    let start = Instant::now();
+    let (_, mut cq, _) = ring.queues();
+    match cq.wait_until_monotonic(1, &deadline) {
+        Err(err) if err.raw_os_error() == Some(libc::EINVAL)    => return Ok(()),
+        Err(err)                                                => assert_eq!(err.raw_os_error(), Some(libc::ETIME)),
+        Ok(())                                                  => panic!("nothing should have completed"),
+    }
+    assert!(start.elapsed() >= Duration::from_millis(15));
+    Ok(())
+}
+
+#[test]
+fn min_timeout_returns_partial_batch() -> io::Result<()> {
+    let mut ring = IoUring::new(4)?;
+    if !ring.has_feature(SetupFeatures::MIN_TIMEOUT) {
+        let err = ring.wait_for_cqes_with_min_timeout(1, Duration::from_millis(1), None).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::EOPNOTSUPP));
+        return Ok(());
+    }
+
+    // only one of the four events completes, so the wait returns once the minimum has passed
+    unsafe { ring.prepare_sqe().unwrap().prep_nop(); }
+    let start = Instant::now();
+    ring.wait_for_cqes_with_min_timeout(4, Duration::from_millis(20), Some(Duration::from_secs(5)))?;
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(15), "{:?}", elapsed);
+    assert!(elapsed < Duration::from_secs(4), "{:?}", elapsed);
+    assert_eq!(ring.cq_ready(), 1);
+
+    ring.cqes().for_each(drop);
+    let err = ring.wait_for_cqes_with_min_timeout(1, Duration::from_millis(5), Some(Duration::from_millis(20))).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ETIME));
+    Ok(())
+}
+
+#[test]
+fn blocking_iterator_with_timeout() -> io::Result<()> {
+    let mut ring = IoUring::new(2)?;
+
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_nop();
+        sqe.set_user_data(5);
+    }
+    ring.submit_sqes()?;
+
+    let mut cqes = ring.cqes_blocking_with_timeout(1, Duration::from_millis(10));
+    assert_eq!(cqes.next().unwrap()?.user_data(), 5);
+    for _ in 0..3 {
+        let err = cqes.next().unwrap().unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(libc::ETIME));
+    }
+    Ok(())
+}
+
+#[test]
+fn absolute_timeout_event() -> io::Result<()> {
+    let mut ring = IoUring::new(2)?;
+    let deadline = Timespec::from_instant(Instant::now() + Duration::from_millis(20))?;
+    assert!(deadline > Timespec::now()?);
+
+    let start = Instant::now();
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_timeout(&deadline, 0, TimeoutFlags::TIMEOUT_ABS);
+        sqe.set_user_data(7);
+    }
+    ring.submit_sqes()?;
+
+    let cqe = ring.wait_for_cqe()?;
+    assert_eq!(cqe.user_data(), 7);
+    assert_eq!(cqe.result().unwrap_err().raw_os_error(), Some(libc::ETIME));
+    assert!(start.elapsed() >= Duration::from_millis(15));
+    Ok(())
+}
@@ -110,7 +110,7 @@ fn write_registered_buf() -> io::Result<()> {
             let mut sq = io_uring.sq();
             let mut sqe = sq.prepare_sqe().unwrap();
             sqe.prep_write(file.as_raw_fd(), buf.slice_to(TEXT.len()), 0);
-            assert!(sqe.raw().opcode == uring_sys::IoRingOp::IORING_OP_WRITE_FIXED as u8);
+            assert!(sqe.raw().opcode == iou::sys::IoRingOp::IORING_OP_WRITE_FIXED as u8);
             sqe.set_user_data(0xDEADBEEF);
             io_uring.sq().submit()?;
         }
@@ -129,3 +129,42 @@ fn write_registered_buf() -> io::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn write_fixed_by_index() -> io::Result<()> {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("props");
+    path.push("write_fixed_by_index.tmp");
+
+    let _ = fs::remove_file(&path);
+
+    let mut io_uring = iou::IoUring::new(32)?;
+    let bufs = vec![Box::new([0u8; 4096]) as Box<[u8]>, Box::new([0u8; 4096]) as Box<[u8]>];
+    let mut bufs: Vec<_> = io_uring.registrar().register_buffers(bufs)?.collect();
+    let buf = &mut bufs[1];
+    buf[..TEXT.len()].copy_from_slice(TEXT);
+    let buf_index = buf.buf_index();
+    assert_eq!(buf_index.index(), 1);
+
+    let n = {
+        let file = File::create(&path)?;
+        unsafe {
+            let mut sq = io_uring.sq();
+            let mut sqe = sq.prepare_sqe().unwrap();
+            sqe.prep_write_fixed(file.as_raw_fd(), &buf[..TEXT.len()], 0, buf_index);
+            sqe.set_user_data(0xDEADBEEF);
+            sq.submit()?;
+        }
+
+        let mut cq = io_uring.cq();
+        let cqe = cq.wait_for_cqe()?;
+        assert_eq!(cqe.user_data(), 0xDEADBEEF);
+        cqe.result()? as usize
+    };
+
+    assert_eq!(n, TEXT.len());
+    assert_eq!(&fs::read(&path)?[..], TEXT);
+    let _ = fs::remove_file(&path);
+
+    Ok(())
+}
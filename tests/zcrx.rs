@@ -0,0 +1,49 @@
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::io::AsRawFd;
+
+use iou::{IoUring, SetupFeatures, SetupFlags};
+
+const LOOPBACK: u32 = 1;
+
+fn zcrx_ring() -> io::Result<IoUring> {
+    let flags = SetupFlags::SINGLE_ISSUER | SetupFlags::DEFER_TASKRUN | SetupFlags::CQE32;
+    IoUring::new_with_flags(4, flags, SetupFeatures::empty())
+}
+
+#[test]
+fn register_ifq_errors() -> io::Result<()> {
+    // zero-copy receive needs 32 byte CQEs and deferred task work
+    let ring = IoUring::new(4)?;
+    let err = ring.registrar().register_ifq(LOOPBACK, 0, 64, 1 << 16).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EINVAL));
+
+    let ring = zcrx_ring()?;
+    let err = ring.registrar().register_ifq(!0, 0, 64, 1 << 16).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ENODEV));
+
+    // the loopback device has no hardware queues to receive into
+    let err = ring.registrar().register_ifq(LOOPBACK, 0, 64, 1 << 16).unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::EOPNOTSUPP));
+    Ok(())
+}
+
+#[test]
+fn recv_zc_without_ifq() -> io::Result<()> {
+    let mut ring = zcrx_ring()?;
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let _client = TcpStream::connect(listener.local_addr()?)?;
+    let (server, _) = listener.accept()?;
+
+    unsafe {
+        let mut sqe = ring.prepare_sqe().unwrap();
+        sqe.prep_recv_zc(server.as_raw_fd(), 0, 0);
+        sqe.set_user_data(0x5C);
+    }
+    ring.submit_sqes_and_wait(1)?;
+    let cqe = ring.peek_for_cqe().unwrap();
+    assert_eq!(cqe.user_data(), 0x5C);
+    assert_eq!(cqe.result().unwrap_err().raw_os_error(), Some(libc::EINVAL));
+    assert!(!cqe.more());
+    Ok(())
+}